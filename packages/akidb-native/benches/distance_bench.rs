@@ -3,10 +3,15 @@
 /// Run: `cargo bench --bench distance_bench --no-default-features`
 /// Quick dry run: `cargo bench --bench distance_bench --no-default-features -- --quick`
 use akidb_native::bench_support::{
-    cosine_distance, dot_product, l2_distance, normalize, HnswGraph,
+    encode_fp16_vector, kernel_sets, normalize, HnswGraph, KernelSet,
 };
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 
+const DIMS: [usize; 3] = [128, 384, 1536];
+
+type F32Kernel = fn(&[f32], &[f32]) -> f32;
+type F16Kernel = fn(&[f32], &[u8]) -> f32;
+
 fn gen_vector(dim: usize, seed: usize) -> Vec<f32> {
     (0..dim)
         .map(|i| ((seed * dim + i) as f32 * 0.0017).sin())
@@ -17,68 +22,68 @@ fn gen_normalized(dim: usize, seed: usize) -> Vec<f32> {
     normalize(&gen_vector(dim, seed))
 }
 
-fn dot_product_scalar(a: &[f32], b: &[f32]) -> f32 {
-    a.iter().zip(b).map(|(x, y)| x * y).sum()
-}
-
-fn l2_distance_scalar(a: &[f32], b: &[f32]) -> f32 {
-    a.iter()
-        .zip(b)
-        .map(|(x, y)| {
-            let d = x - y;
-            d * d
-        })
-        .sum::<f32>()
-        .sqrt()
+/// Benchmark one f32 kernel across every kernel set this CPU supports
+/// (scalar, sse, avx2, avx512).
+fn bench_f32_kernel(
+    c: &mut Criterion,
+    group_name: &str,
+    normalized: bool,
+    kernel: fn(&KernelSet) -> F32Kernel,
+) {
+    let mut group = c.benchmark_group(group_name);
+    for dim in DIMS {
+        let (a, b) = if normalized {
+            (gen_normalized(dim, 1), gen_normalized(dim, 2))
+        } else {
+            (gen_vector(dim, 1), gen_vector(dim, 2))
+        };
+        for set in kernel_sets() {
+            let f = kernel(&set);
+            group.bench_with_input(BenchmarkId::new(set.name, dim), &dim, |bench, _| {
+                bench.iter(|| black_box(f(black_box(&a), black_box(&b))))
+            });
+        }
+    }
+    group.finish();
 }
 
-fn cosine_distance_scalar(a: &[f32], b: &[f32]) -> f32 {
-    1.0 - dot_product_scalar(a, b)
+/// Benchmark one FP16 kernel (f32 query against FP16 codes) across every
+/// supported kernel set.
+fn bench_f16_kernel(
+    c: &mut Criterion,
+    group_name: &str,
+    kernel: fn(&KernelSet) -> F16Kernel,
+) {
+    let mut group = c.benchmark_group(group_name);
+    for dim in DIMS {
+        let query = gen_normalized(dim, 3);
+        let codes = encode_fp16_vector(&gen_vector(dim, 4));
+        for set in kernel_sets() {
+            let f = kernel(&set);
+            group.bench_with_input(BenchmarkId::new(set.name, dim), &dim, |bench, _| {
+                bench.iter(|| black_box(f(black_box(&query), black_box(&codes))))
+            });
+        }
+    }
+    group.finish();
 }
 
 fn bench_dot_product(c: &mut Criterion) {
-    let mut group = c.benchmark_group("dot_product");
-    for dim in [128, 384, 1536] {
-        let a = gen_vector(dim, 1);
-        let b = gen_vector(dim, 2);
-        group.bench_with_input(BenchmarkId::new("simd", dim), &dim, |bench, _| {
-            bench.iter(|| black_box(dot_product(black_box(&a), black_box(&b))))
-        });
-        group.bench_with_input(BenchmarkId::new("scalar", dim), &dim, |bench, _| {
-            bench.iter(|| black_box(dot_product_scalar(black_box(&a), black_box(&b))))
-        });
-    }
-    group.finish();
+    bench_f32_kernel(c, "dot_product", false, |set| set.dot_product);
 }
 
 fn bench_l2_distance(c: &mut Criterion) {
-    let mut group = c.benchmark_group("l2_distance");
-    for dim in [128, 384, 1536] {
-        let a = gen_vector(dim, 3);
-        let b = gen_vector(dim, 4);
-        group.bench_with_input(BenchmarkId::new("simd", dim), &dim, |bench, _| {
-            bench.iter(|| black_box(l2_distance(black_box(&a), black_box(&b))))
-        });
-        group.bench_with_input(BenchmarkId::new("scalar", dim), &dim, |bench, _| {
-            bench.iter(|| black_box(l2_distance_scalar(black_box(&a), black_box(&b))))
-        });
-    }
-    group.finish();
+    bench_f32_kernel(c, "l2_distance", false, |set| set.l2_distance);
 }
 
 fn bench_cosine_distance(c: &mut Criterion) {
-    let mut group = c.benchmark_group("cosine_distance");
-    for dim in [128, 384, 1536] {
-        let a = gen_normalized(dim, 5);
-        let b = gen_normalized(dim, 6);
-        group.bench_with_input(BenchmarkId::new("simd", dim), &dim, |bench, _| {
-            bench.iter(|| black_box(cosine_distance(black_box(&a), black_box(&b))))
-        });
-        group.bench_with_input(BenchmarkId::new("scalar", dim), &dim, |bench, _| {
-            bench.iter(|| black_box(cosine_distance_scalar(black_box(&a), black_box(&b))))
-        });
-    }
-    group.finish();
+    bench_f32_kernel(c, "cosine_distance", true, |set| set.cosine_distance);
+}
+
+fn bench_fp16_distance(c: &mut Criterion) {
+    bench_f16_kernel(c, "dot_distance_f16", |set| set.dot_distance_f16);
+    bench_f16_kernel(c, "l2_distance_f16", |set| set.l2_distance_f16);
+    bench_f16_kernel(c, "cosine_distance_f16", |set| set.cosine_distance_f16);
 }
 
 fn bench_hnsw_search(c: &mut Criterion) {
@@ -128,6 +133,7 @@ criterion_group!(
     bench_dot_product,
     bench_l2_distance,
     bench_cosine_distance,
+    bench_fp16_distance,
     bench_hnsw_search,
     bench_hnsw_search_filtered,
 );
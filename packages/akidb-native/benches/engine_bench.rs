@@ -7,7 +7,7 @@ use std::thread;
 use std::time::Duration;
 
 use akidb_native::bench_support::NativeRecord;
-use akidb_native::{EngineInner, EngineOptions, SearchMode, SearchOptions};
+use akidb_native::{EngineInner, EngineOptions, Fusion, KeywordSyntax, SearchMode, SearchOptions};
 use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
 use serde_json::json;
 use tempfile::TempDir;
@@ -29,6 +29,7 @@ fn make_record(dim: usize, index: usize) -> NativeRecord {
     NativeRecord {
         chunk_id: format!("chunk-{index:06}"),
         doc_id: format!("doc-{index:06}"),
+        ordinal: None,
         vector: gen_vector(dim, index),
         metadata: json!({
             "source_uri": format!("file://doc-{index:06}.txt"),
@@ -55,6 +56,17 @@ fn search_opts(collection_id: &str, dim: usize, seed: usize) -> SearchOptions {
         keyword_weight: 1.0,
         explain: false,
         ef_search: None,
+        ivf_num_probes: None,
+        rescore: false,
+        oversampling: None,
+        keyword_syntax: KeywordSyntax::Simple,
+        fusion: Fusion::default(),
+        include_metadata: false,
+        include_text: false,
+        include_vector: false,
+        group_by: None,
+        max_per_group: None,
+        search_after: None,
     }
 }
 
@@ -75,6 +87,7 @@ fn setup_published_engine(
     let engine = EngineInner::open(EngineOptions {
         storage_path: tempdir.path().join("storage"),
         disable_wal: true,
+        search_threads: None,
     })
     .unwrap();
     engine
@@ -211,7 +224,7 @@ fn bench_search_publish_contention(c: &mut Criterion) {
     group.sample_size(10);
     group.measurement_time(Duration::from_secs(8));
     group.bench_function("search_vs_publish_round", |bench| {
-        bench.iter(|| run_contention_round())
+        bench.iter(run_contention_round)
     });
     group.finish();
 }
@@ -7,6 +7,8 @@
 export interface EngineOptionsJs {
   storagePath: string
   disableWal?: boolean
+  /** Worker threads for per-segment vector search. Default: available parallelism. */
+  searchThreads?: number
 }
 export interface CollectionJs {
   collectionId: string
@@ -20,13 +22,21 @@ export interface CollectionJs {
   hnswM: number
   hnswEfConstruction: number
   hnswEfSearch: number
+  indexType: string
+  ivfNumClusters?: number
+  ivfNumProbes?: number
+  pqNumSubquantizers?: number
+  tokenizer: string
+  stemmer?: string
+  stopWords?: string
+  removeDiacritics: boolean
 }
 export interface CreateCollectionOptsJs {
   collectionId: string
   dimension: number
   metric: string
   embeddingModelId: string
-  /** Vector quantization: "fp16" (default) or "sq8". */
+  /** Vector quantization: "fp16" (default), "sq8", or "fp32" (lossless). */
   quantization?: string
   /** HNSW M parameter (max connections per node). Default: 16, range: 4-64. */
   hnswM?: number
@@ -34,10 +44,28 @@ export interface CreateCollectionOptsJs {
   hnswEfConstruction?: number
   /** HNSW efSearch parameter. Default: 100, range: 10-500. */
   hnswEfSearch?: number
+  /** Per-segment vector index: "hnsw" (default), "ivf_pq", or "binary" (1 bit per dimension, always re-scored). */
+  indexType?: string
+  /** IVF coarse clusters per segment. Default: √(segment record count). */
+  ivfNumClusters?: number
+  /** IVF lists probed per query. Default: 8. */
+  ivfNumProbes?: number
+  /** PQ subquantizers; must divide dimension. Default: dimension / 8. */
+  pqNumSubquantizers?: number
+  /** Keyword tokenizer: "unicode61" (default) or "cjk_bigram" (overlapping character pairs for CJK text). */
+  tokenizer?: string
+  /** Snowball stemmer for keyword search: "porter" (English) or a language such as "french". Default: none. */
+  stemmer?: string
+  /** Stop words dropped from keyword search: "english". Default: none. */
+  stopWords?: string
+  /** Fold diacritics in keyword search ("Café" matches "cafe"). Default: true. */
+  removeDiacritics?: boolean
 }
 export interface RecordJs {
   chunkId: string
   docId: string
+  /** Position of the chunk in its document, for getNeighbors. */
+  ordinal?: number
   vector: Array<number>
   metadataJson: string
   chunkText?: string
@@ -57,14 +85,112 @@ export interface SearchOptsJs {
   mode?: string
   /** Query text for keyword/hybrid search. */
   queryText?: string
-  /** Weight for vector results in hybrid RRF fusion (default 1.0). */
+  /** Weight for vector results in hybrid fusion (default 1.0). */
   vectorWeight?: number
-  /** Weight for keyword results in hybrid RRF fusion (default 1.0). */
+  /** Weight for keyword results in hybrid fusion (default 1.0). */
   keywordWeight?: number
   /** When true, include per-result scoring breakdown. */
   explain?: boolean
   /** Per-query ef_search override (range: 10-500). Overrides collection default. */
   efSearch?: number
+  /** Per-query IVF probe count override (IVF-PQ collections only). */
+  ivfNumProbes?: number
+  /**
+   * Re-score approximate IVF-PQ and sq8 candidates with full-precision vectors
+   * (default false). sq8 segments re-score against FP16 copies of their vectors.
+   */
+  rescore?: boolean
+  /** Candidates fetched per result when re-scoring, >= 1 (default 4). */
+  oversampling?: number
+  /**
+   * Keyword query syntax: "simple" (default) or "advanced" (phrases, prefix*,
+   * -exclusion, OR, NEAR).
+   */
+  keywordSyntax?: string
+  /** Hybrid fusion: "rrf" (default), "min_max", "z_score", or "dbsf" (distribution-based). */
+  fusion?: string
+  /** RRF constant k, >= 0 (default 60). Only used by "rrf" fusion. */
+  rrfK?: number
+  /** Return each hit's stored metadata as `metadataJson` (default false). */
+  includeMetadata?: boolean
+  /** Return each hit's stored chunk text (default false). */
+  includeText?: boolean
+  /**
+   * Return each hit's stored vector, decoded from the collection's
+   * quantization (default false).
+   */
+  includeVector?: boolean
+  /**
+   * Group results by "doc_id" or a top-level metadata field: topK becomes
+   * the number of groups, returned in `groups`.
+   */
+  groupBy?: string
+  /** Results kept per group, >= 1 (default 1). */
+  maxPerGroup?: number
+  /**
+   * Continue after a previous page: its response's `cursor`. Searches the
+   * manifest version and buffered records the first page read and only
+   * continues the same query and mode; topK may change. Hybrid pages split
+   * the candidates fused for the first page, then those deeper fusions add.
+   * Fails once the buffer has been flushed. Not supported with groupBy.
+   */
+  searchAfter?: string
+}
+/** Batch search: `SearchOptsJs` with one query vector and/or text per query. */
+export interface SearchBatchOptsJs {
+  collectionId: string
+  /** One query vector per query (vector/hybrid modes). */
+  queryVectors?: Array<Array<number>>
+  /** One query text per query (keyword/hybrid modes); same length as queryVectors. */
+  queryTexts?: Array<string>
+  topK: number
+  filtersJson?: string
+  manifestVersion?: number
+  includeUncommitted?: boolean
+  /** Search mode: "vector" (default), "keyword", or "hybrid". */
+  mode?: string
+  /** Weight for vector results in hybrid fusion (default 1.0). */
+  vectorWeight?: number
+  /** Weight for keyword results in hybrid fusion (default 1.0). */
+  keywordWeight?: number
+  /** When true, include per-result scoring breakdown. */
+  explain?: boolean
+  /** Per-query ef_search override (range: 10-500). Overrides collection default. */
+  efSearch?: number
+  /** Per-query IVF probe count override (IVF-PQ collections only). */
+  ivfNumProbes?: number
+  /**
+   * Re-score approximate IVF-PQ and sq8 candidates with full-precision vectors
+   * (default false). sq8 segments re-score against FP16 copies of their vectors.
+   */
+  rescore?: boolean
+  /** Candidates fetched per result when re-scoring, >= 1 (default 4). */
+  oversampling?: number
+  /**
+   * Keyword query syntax: "simple" (default) or "advanced" (phrases, prefix*,
+   * -exclusion, OR, NEAR).
+   */
+  keywordSyntax?: string
+  /** Hybrid fusion: "rrf" (default), "min_max", "z_score", or "dbsf" (distribution-based). */
+  fusion?: string
+  /** RRF constant k, >= 0 (default 60). Only used by "rrf" fusion. */
+  rrfK?: number
+  /** Return each hit's stored metadata as `metadataJson` (default false). */
+  includeMetadata?: boolean
+  /** Return each hit's stored chunk text (default false). */
+  includeText?: boolean
+  /**
+   * Return each hit's stored vector, decoded from the collection's
+   * quantization (default false).
+   */
+  includeVector?: boolean
+  /**
+   * Group results by "doc_id" or a top-level metadata field: topK becomes
+   * the number of groups, returned in `groups`.
+   */
+  groupBy?: string
+  /** Results kept per group, >= 1 (default 1). */
+  maxPerGroup?: number
 }
 export interface ExplainInfoJs {
   vectorScore?: number
@@ -73,17 +199,108 @@ export interface ExplainInfoJs {
   vectorRank?: number
   bm25Rank?: number
   chunkPreview?: string
+  /** Query terms and phrases found in the chunk text (prefixes keep `*`). */
   matchedTerms: Array<string>
+  /** Occurrences of matched terms, as character offsets into the chunk text. */
+  termMatches: Array<TermMatchJs>
+  /** Passage with the most matched terms (absent when nothing matched). */
+  snippet?: string
+  /** Character offset of `snippet` in the chunk text. */
+  snippetStart?: number
+  /** Fusion strategy, parameters and per-leg inputs (hybrid search only). */
+  fusion?: FusionInfoJs
+  /** Ranks before and after re-ranking (searchReranked only). */
+  rerank?: RerankInfoJs
+}
+export interface FusionInfoJs {
+  /** "rrf", "min_max", "z_score" or "dbsf". */
+  method: string
+  rrfK?: number
+  vectorWeight: number
+  keywordWeight: number
+  /** Unweighted vector contribution: 1 / (k + rank) for RRF, else the normalized score. */
+  vectorNormalized?: number
+  /** Unweighted keyword contribution, as for vectorNormalized. */
+  keywordNormalized?: number
+  score: number
+}
+export interface RerankInfoJs {
+  /** 1-based rank among the candidates passed to the reranker. */
+  preRerankRank: number
+  /** Search score before re-ranking; the result's score is the reranker's. */
+  preRerankScore: number
+  /** 1-based rank after re-ranking. */
+  postRerankRank: number
+}
+export interface TermMatchJs {
+  term: string
+  start: number
+  end: number
 }
 export interface SearchResultEngineJs {
   chunkId: string
   score: number
   committed?: boolean
   explain?: ExplainInfoJs
+  /** Stored metadata as JSON (with includeMetadata). */
+  metadataJson?: string
+  /** Stored chunk text (with includeText). */
+  text?: string
+  /** Stored vector (with includeVector). */
+  vector?: Array<number>
+  /** The result's groupBy value as JSON (with groupBy). */
+  groupKeyJson?: string
+}
+export interface SearchGroupJs {
+  /** The shared groupBy value as JSON; "null" when the field is missing. */
+  keyJson: string
+  /** Score of the group's best result. */
+  score: number
+  results: Array<SearchResultEngineJs>
 }
 export interface SearchResponseJs {
   results: Array<SearchResultEngineJs>
   manifestVersionUsed: number
+  /** Results grouped by groupBy value, in rank order (with groupBy). */
+  groups?: Array<SearchGroupJs>
+  /** Pass as `searchAfter` for the next page; absent after the last page. */
+  cursor?: string
+}
+export interface StoredRecordJs {
+  chunkId: string
+  /** Empty for records flushed before doc IDs were stored in segments. */
+  docId: string
+  ordinal?: number
+  vector: Array<number>
+  metadataJson: string
+  chunkText?: string
+  /** False for a record still in the write buffer. */
+  committed: boolean
+}
+export interface RerankCandidateJs {
+  chunkId: string
+  /** Score the search ranked the candidate by. */
+  score: number
+  text?: string
+  metadataJson: string
+}
+export interface RerankRequestJs {
+  queryText?: string
+  candidates: Array<RerankCandidateJs>
+}
+export interface ContextPassageJs {
+  docId: string
+  /** Source chunks, in document order. */
+  chunkIds: Array<string>
+  text: string
+  /** Score of the passage's best hit. */
+  score: number
+}
+export interface AssembledContextJs {
+  passages: Array<ContextPassageJs>
+  /** Characters across all passage texts; at most the budget. */
+  totalChars: number
+  manifestVersionUsed: number
 }
 export interface PublishOptsJs {
   segmentIds: Array<string>
@@ -141,7 +358,46 @@ export declare class AkiDbEngine {
   publish(collectionId: string, opts: PublishOptsJs): ManifestJs
   autoPublish(collectionId: string, embeddingModelId: string, pipelineSignature: string): ManifestJs
   search(opts: SearchOptsJs): SearchResponseJs
+  /**
+   * Search, then re-score the best `rerankDepth` candidates (default topK)
+   * with `reranker` and keep its top topK. The search runs on the libuv
+   * thread pool; `reranker` is called on the JS thread with
+   * `(err, request)` and returns one score per candidate, or a Promise
+   * of them.
+   */
+  searchReranked(opts: SearchOptsJs, reranker: (err: Error | null, request: RerankRequestJs) => Array<number> | Promise<Array<number>>, rerankDepth?: number): Promise<SearchResponseJs>
+  /**
+   * Run many queries against one collection under a single manifest and
+   * tombstone snapshot. Responses are in query order.
+   */
+  searchBatch(opts: SearchBatchOptsJs): Array<SearchResponseJs>
+  /**
+   * Search, then assemble the hits' chunk text into passages within
+   * `budget` characters, or approximate tokens with `budgetUnit: "tokens"`.
+   * Duplicate texts are dropped and adjacent chunks of a document (by
+   * ordinal) merged; passages come back in rank order.
+   */
+  assembleContext(opts: SearchOptsJs, budget: number, budgetUnit?: string): AssembledContextJs
+  /**
+   * Fetch records by chunk ID, in request order; missing and deleted
+   * chunks are omitted. Reads the live state (including the write buffer)
+   * unless `manifestVersion` pins a published manifest.
+   */
+  getRecords(collectionId: string, chunkIds: Array<string>, manifestVersion?: number): Array<StoredRecordJs>
+  /**
+   * Fetch a chunk and up to `before` / `after` neighbors in its document,
+   * ordered by ordinal. Empty if the chunk is missing or deleted; throws if
+   * it was written without an ordinal. Reads the same state as getRecords.
+   */
+  getNeighbors(collectionId: string, chunkId: string, before: number, after: number, manifestVersion?: number): Array<StoredRecordJs>
   deleteChunks(collectionId: string, chunkIds: Array<string>, reasonCode: string): number
+  /** Delete every chunk of a document. Returns the number of chunks deleted. */
+  deleteDocument(collectionId: string, docId: string): number
+  /**
+   * Replace a document's chunks with `records` (all with `docId`): old
+   * chunks missing from `records` are deleted, the rest upserted.
+   */
+  replaceDocument(collectionId: string, docId: string, records: Array<RecordJs>): UpsertResultJs
   compact(collectionId: string): CompactResultJs
   rollback(collectionId: string, manifestId: string): ManifestJs
   getStorageSizeBytes(): number
@@ -0,0 +1,327 @@
+//! Binary quantization index: one bit per dimension.
+//!
+//! Each vector is reduced to one bit per component — set when the component
+//! exceeds the segment's per-dimension mean — and packed into u64 words.
+//! Search ranks nodes by Hamming distance between packed codes. Hamming
+//! distance is only a coarse proxy for the collection metric, so callers are
+//! expected to oversample and re-score candidates against stored vectors.
+//!
+//! Deterministic guarantees:
+//!   - Thresholds are plain means (no sampling).
+//!   - Tie-breaking: ascending node id when distances are equal.
+
+use crate::distance;
+
+/// Bits per packed code word.
+const WORD_BITS: usize = 64;
+
+/// Binary-quantized index over a single segment's vectors.
+pub struct BinaryIndex {
+    metric: String,
+    dimension: usize,
+    /// Per-dimension thresholds; a bit is set when the value is above it.
+    thresholds: Vec<f32>,
+    /// Packed codes in node order, `node_count × words_per_vector`.
+    codes: Vec<u64>,
+    node_count: usize,
+}
+
+impl BinaryIndex {
+    pub fn new(metric: &str, dimension: usize) -> Self {
+        BinaryIndex {
+            metric: metric.to_string(),
+            dimension,
+            thresholds: Vec::new(),
+            codes: Vec::new(),
+            node_count: 0,
+        }
+    }
+
+    fn words_per_vector(&self) -> usize {
+        self.dimension.div_ceil(WORD_BITS)
+    }
+
+    /// Approximate resident size in bytes (thresholds and packed codes).
+    pub fn memory_size_bytes(&self) -> usize {
+        self.thresholds.len() * 4 + self.codes.len() * 8
+    }
+
+    /// Build the index from a batch of vectors.
+    /// For cosine metric, vectors are normalized before encoding.
+    pub fn build(&mut self, vectors: &[Vec<f32>]) {
+        let processed: Vec<Vec<f32>> = if self.metric == "cosine" {
+            vectors.iter().map(|v| distance::normalize(v)).collect()
+        } else {
+            vectors.to_vec()
+        };
+
+        let mut thresholds = vec![0.0_f32; self.dimension];
+        if !processed.is_empty() {
+            for v in &processed {
+                for (t, &x) in thresholds.iter_mut().zip(v) {
+                    *t += x;
+                }
+            }
+            let n = processed.len() as f32;
+            for t in &mut thresholds {
+                *t /= n;
+            }
+        }
+        self.thresholds = thresholds;
+
+        self.codes = Vec::with_capacity(processed.len() * self.words_per_vector());
+        for v in &processed {
+            let code = self.encode(v);
+            self.codes.extend_from_slice(&code);
+        }
+        self.node_count = processed.len();
+    }
+
+    /// Search for the topK nearest codes by Hamming distance.
+    /// Returns (node_id, score) pairs sorted by descending score, where the
+    /// score is the fraction of matching bits.
+    pub fn search(&self, query: &[f32], top_k: usize) -> Vec<(u32, f32)> {
+        self.search_filtered(query, top_k, |_| true)
+    }
+
+    /// Exhaustive Hamming scan with an inline filter predicate.
+    ///
+    /// `filter` returns true for node IDs that PASS the filter.
+    pub fn search_filtered<F>(&self, query: &[f32], top_k: usize, filter: F) -> Vec<(u32, f32)>
+    where
+        F: Fn(u32) -> bool,
+    {
+        if self.node_count == 0 || top_k == 0 {
+            return Vec::new();
+        }
+
+        let processed_query = if self.metric == "cosine" {
+            distance::normalize(query)
+        } else {
+            query.to_vec()
+        };
+        let query_code = self.encode(&processed_query);
+        let words = self.words_per_vector();
+
+        let mut candidates: Vec<(u32, u32)> = (0..self.node_count)
+            .filter(|&i| filter(i as u32))
+            .map(|i| {
+                let code = &self.codes[i * words..(i + 1) * words];
+                (i as u32, hamming_distance(&query_code, code))
+            })
+            .collect();
+
+        // Deterministic order: ascending distance, ascending id for ties.
+        let cmp = |a: &(u32, u32), b: &(u32, u32)| a.1.cmp(&b.1).then(a.0.cmp(&b.0));
+        if candidates.len() > top_k {
+            candidates.select_nth_unstable_by(top_k - 1, cmp);
+            candidates.truncate(top_k);
+        }
+        candidates.sort_by(cmp);
+
+        let dimension = self.dimension.max(1) as f32;
+        candidates
+            .into_iter()
+            .map(|(id, d)| (id, 1.0 - d as f32 / dimension))
+            .collect()
+    }
+
+    /// Serialize to the binary format (v1).
+    pub fn serialize(&self) -> Vec<u8> {
+        let metric_bytes = self.metric.as_bytes();
+        let mut buf = Vec::with_capacity(20 + metric_bytes.len() + self.memory_size_bytes());
+
+        buf.extend_from_slice(b"BINQ");
+        buf.extend_from_slice(&1u32.to_le_bytes());
+        buf.extend_from_slice(&(metric_bytes.len() as u32).to_le_bytes());
+        buf.extend_from_slice(metric_bytes);
+        buf.extend_from_slice(&(self.dimension as u32).to_le_bytes());
+        buf.extend_from_slice(&(self.node_count as u32).to_le_bytes());
+
+        for &t in &self.thresholds {
+            buf.extend_from_slice(&t.to_le_bytes());
+        }
+        for &word in &self.codes {
+            buf.extend_from_slice(&word.to_le_bytes());
+        }
+
+        buf
+    }
+
+    /// Deserialize from the binary format (v1).
+    pub fn deserialize(&mut self, data: &[u8]) -> Result<(), String> {
+        let len = data.len();
+        if len < 12 {
+            return Err("Buffer too small for binary index header".into());
+        }
+        if &data[0..4] != b"BINQ" {
+            return Err("Invalid magic bytes".into());
+        }
+
+        let mut offset = 4;
+
+        macro_rules! check_bounds {
+            ($n:expr, $label:expr) => {
+                if offset + $n > len {
+                    return Err(format!(
+                        "Truncated binary index data: need {} bytes for {} at offset {}, but only {} remain",
+                        $n, $label, offset, len - offset
+                    ));
+                }
+            };
+        }
+
+        macro_rules! read_u32 {
+            ($label:expr) => {{
+                check_bounds!(4, $label);
+                let v = u32::from_le_bytes([
+                    data[offset],
+                    data[offset + 1],
+                    data[offset + 2],
+                    data[offset + 3],
+                ]);
+                offset += 4;
+                v as usize
+            }};
+        }
+
+        let version = read_u32!("version");
+        if version != 1 {
+            return Err(format!("Unsupported version: {version}"));
+        }
+
+        let metric_len = read_u32!("metric_len");
+        check_bounds!(metric_len, "metric string");
+        let metric = std::str::from_utf8(&data[offset..offset + metric_len])
+            .map_err(|e| format!("Invalid metric string: {e}"))?;
+        offset += metric_len;
+        if metric != self.metric {
+            return Err(format!(
+                "Metric mismatch: index has {metric}, expected {}",
+                self.metric
+            ));
+        }
+
+        let dimension = read_u32!("dimension");
+        if dimension != self.dimension {
+            return Err(format!(
+                "Dimension mismatch: index has {dimension}, expected {}",
+                self.dimension
+            ));
+        }
+        let node_count = read_u32!("node_count");
+
+        check_bounds!(dimension * 4, "thresholds");
+        let thresholds = data[offset..offset + dimension * 4]
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        offset += dimension * 4;
+
+        let word_count = node_count * self.words_per_vector();
+        check_bounds!(word_count * 8, "codes");
+        let codes = data[offset..offset + word_count * 8]
+            .chunks_exact(8)
+            .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+
+        self.thresholds = thresholds;
+        self.codes = codes;
+        self.node_count = node_count;
+
+        Ok(())
+    }
+
+    // ─── Internal: encoding ─────────────────────────────────────────────────
+
+    /// Pack one bit per dimension (LSB-first within each word).
+    fn encode(&self, v: &[f32]) -> Vec<u64> {
+        let mut code = vec![0u64; self.words_per_vector()];
+        for (d, (&x, &t)) in v.iter().zip(&self.thresholds).enumerate() {
+            if x > t {
+                code[d / WORD_BITS] |= 1 << (d % WORD_BITS);
+            }
+        }
+        code
+    }
+}
+
+/// Number of differing bits between two packed codes.
+#[inline]
+fn hamming_distance(a: &[u64], b: &[u64]) -> u32 {
+    a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_vectors(n: usize) -> Vec<Vec<f32>> {
+        (0..n)
+            .map(|i| {
+                let f = i as f32;
+                (0..96).map(|d| (f * 0.37 + d as f32 * 1.3).sin()).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_build_and_search_finds_exact_match() {
+        let vectors = sample_vectors(100);
+        let mut index = BinaryIndex::new("cosine", 96);
+        index.build(&vectors);
+        assert_eq!(index.words_per_vector(), 2);
+        assert_eq!(index.memory_size_bytes(), 96 * 4 + 100 * 2 * 8);
+
+        // Nearly periodic sample data can share a code, so only require the
+        // exact match among the zero-distance hits.
+        let results = index.search(&vectors[42], 5);
+        assert_eq!(results.len(), 5);
+        assert_eq!(results[0].1, 1.0);
+        assert!(results.contains(&(42, 1.0)));
+        assert!(results.windows(2).all(|w| w[0].1 >= w[1].1));
+    }
+
+    #[test]
+    fn test_search_filtered() {
+        let vectors = sample_vectors(50);
+        let mut index = BinaryIndex::new("l2", 96);
+        index.build(&vectors);
+
+        let results = index.search_filtered(&vectors[10], 10, |id| id % 5 == 0);
+        assert_eq!(results.len(), 10);
+        assert!(results.iter().all(|(id, _)| id % 5 == 0));
+        assert_eq!(results[0].0, 10);
+    }
+
+    #[test]
+    fn test_serialize_deserialize_roundtrip() {
+        let vectors = sample_vectors(30);
+        let mut index = BinaryIndex::new("dot", 96);
+        index.build(&vectors);
+        let before = index.search(&vectors[3], 5);
+
+        let mut restored = BinaryIndex::new("dot", 96);
+        restored.deserialize(&index.serialize()).unwrap();
+        assert_eq!(restored.search(&vectors[3], 5), before);
+
+        let mut wrong_metric = BinaryIndex::new("l2", 96);
+        assert!(wrong_metric.deserialize(&index.serialize()).is_err());
+        let mut truncated = BinaryIndex::new("dot", 96);
+        let data = index.serialize();
+        assert!(truncated.deserialize(&data[..data.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_hamming_distance() {
+        assert_eq!(hamming_distance(&[0b1011, 0], &[0b0001, 1 << 63]), 3);
+        assert_eq!(hamming_distance(&[u64::MAX], &[u64::MAX]), 0);
+    }
+
+    #[test]
+    fn test_empty_index() {
+        let mut index = BinaryIndex::new("l2", 8);
+        index.build(&[]);
+        assert!(index.search(&[0.0; 8], 3).is_empty());
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::error::{AkiDbError, Result};
 use crate::metadata::{Collection, MetadataStore};
+use crate::text::STEMMERS;
 
 pub struct CreateCollectionOptions {
     pub collection_id: String,
@@ -13,8 +14,19 @@ pub struct CreateCollectionOptions {
     pub hnsw_m: i64,
     pub hnsw_ef_construction: i64,
     pub hnsw_ef_search: i64,
+    pub index_type: String,
+    pub ivf_num_clusters: Option<i64>,
+    pub ivf_num_probes: Option<i64>,
+    pub pq_num_subquantizers: Option<i64>,
+    pub tokenizer: String,
+    pub stemmer: Option<String>,
+    pub stop_words: Option<String>,
+    pub remove_diacritics: bool,
 }
 
+/// Default IVF lists probed per query when the collection does not set one.
+pub const DEFAULT_IVF_NUM_PROBES: i64 = 8;
+
 pub struct CollectionManager;
 
 impl CollectionManager {
@@ -31,6 +43,19 @@ impl CollectionManager {
                 )));
             }
 
+        // Resolve IVF-PQ defaults up front so every segment is built the same way.
+        let (ivf_num_probes, pq_num_subquantizers) = if opts.index_type == "ivf_pq" {
+            (
+                Some(opts.ivf_num_probes.unwrap_or(DEFAULT_IVF_NUM_PROBES)),
+                Some(
+                    opts.pq_num_subquantizers
+                        .unwrap_or_else(|| default_num_subquantizers(opts.dimension)),
+                ),
+            )
+        } else {
+            (opts.ivf_num_probes, opts.pq_num_subquantizers)
+        };
+
         let collection = Collection {
             collection_id: opts.collection_id.clone(),
             dimension: opts.dimension,
@@ -43,6 +68,14 @@ impl CollectionManager {
             hnsw_m: opts.hnsw_m,
             hnsw_ef_construction: opts.hnsw_ef_construction,
             hnsw_ef_search: opts.hnsw_ef_search,
+            index_type: opts.index_type.clone(),
+            ivf_num_clusters: opts.ivf_num_clusters,
+            ivf_num_probes,
+            pq_num_subquantizers,
+            tokenizer: opts.tokenizer.clone(),
+            stemmer: opts.stemmer.clone(),
+            stop_words: opts.stop_words.clone(),
+            remove_diacritics: opts.remove_diacritics,
         };
 
         metadata.create_collection(&collection)?;
@@ -93,9 +126,9 @@ impl CollectionManager {
                 "schemaVersion must be a non-empty string".into(),
             ));
         }
-        if !["fp16", "sq8"].contains(&opts.quantization.as_str()) {
+        if !["fp16", "sq8", "fp32"].contains(&opts.quantization.as_str()) {
             return Err(AkiDbError::InvalidArgument(format!(
-                "quantization must be one of fp16, sq8 — got \"{}\"",
+                "quantization must be one of fp16, sq8, fp32 — got \"{}\"",
                 opts.quantization
             )));
         }
@@ -117,10 +150,68 @@ impl CollectionManager {
                 opts.hnsw_ef_search
             )));
         }
+        if !["hnsw", "ivf_pq", "binary"].contains(&opts.index_type.as_str()) {
+            return Err(AkiDbError::InvalidArgument(format!(
+                "index_type must be one of hnsw, ivf_pq, binary — got \"{}\"",
+                opts.index_type
+            )));
+        }
+        if let Some(n) = opts.ivf_num_clusters
+            && !(1..=65536).contains(&n)
+        {
+            return Err(AkiDbError::InvalidArgument(format!(
+                "ivf_num_clusters must be between 1 and 65536, got {n}"
+            )));
+        }
+        if let Some(n) = opts.ivf_num_probes
+            && n <= 0
+        {
+            return Err(AkiDbError::InvalidArgument(format!(
+                "ivf_num_probes must be a positive integer, got {n}"
+            )));
+        }
+        if let Some(n) = opts.pq_num_subquantizers
+            && (n <= 0 || opts.dimension % n != 0)
+        {
+            return Err(AkiDbError::InvalidArgument(format!(
+                "pq_num_subquantizers must be a positive divisor of dimension {}, got {n}",
+                opts.dimension
+            )));
+        }
+        if !["unicode61", "cjk_bigram"].contains(&opts.tokenizer.as_str()) {
+            return Err(AkiDbError::InvalidArgument(format!(
+                "tokenizer must be one of unicode61, cjk_bigram — got \"{}\"",
+                opts.tokenizer
+            )));
+        }
+        if let Some(ref stemmer) = opts.stemmer
+            && !STEMMERS.contains(&stemmer.as_str())
+        {
+            return Err(AkiDbError::InvalidArgument(format!(
+                "stemmer must be one of {} — got \"{stemmer}\"",
+                STEMMERS.join(", ")
+            )));
+        }
+        if let Some(ref stop_words) = opts.stop_words
+            && stop_words != "english"
+        {
+            return Err(AkiDbError::InvalidArgument(format!(
+                "stop_words must be english — got \"{stop_words}\""
+            )));
+        }
         Ok(())
     }
 }
 
+/// Pick a subquantizer count that splits `dimension` into ~8-wide subvectors.
+fn default_num_subquantizers(dimension: i64) -> i64 {
+    [8, 4, 2]
+        .into_iter()
+        .find(|width| dimension % width == 0)
+        .map(|width| dimension / width)
+        .unwrap_or(dimension)
+}
+
 fn iso_now() -> String {
     crate::write::epoch_to_iso8601(
         std::time::SystemTime::now()
@@ -6,8 +6,7 @@
 use std::collections::HashSet;
 
 use crate::error::{AkiDbError, Result};
-use crate::hnsw::HnswGraph;
-use crate::write::HnswParams;
+use crate::write::IndexParams;
 use crate::manifest::{ManifestManager, PublishManifestOptions};
 use crate::metadata::{Manifest, MetadataStore};
 use crate::segment::builder::SegmentBuilder;
@@ -17,6 +16,8 @@ use crate::storage::LocalFsBackend;
 
 struct ExtractedRecord {
     chunk_id: String,
+    doc_id: String,
+    ordinal: Option<u32>,
     vector: Vec<f32>,
     metadata: serde_json::Value,
     chunk_text: Option<String>,
@@ -51,7 +52,7 @@ pub fn compact(
         .tombstone_ids
         .iter()
         .cloned()
-        .chain(metadata.list_tombstone_chunk_ids(collection_id)?.into_iter())
+        .chain(metadata.list_tombstone_chunk_ids(collection_id)?)
         .collect::<HashSet<_>>()
         .into_iter()
         .collect();
@@ -87,11 +88,7 @@ pub fn compact(
             &all_records,
             collection.dimension as usize,
             &collection.metric,
-            HnswParams {
-                m: collection.hnsw_m as usize,
-                ef_construction: collection.hnsw_ef_construction as usize,
-                ef_search: collection.hnsw_ef_search as usize,
-            },
+            IndexParams::from_collection(&collection),
         )?;
         (vec![seg_id], seg_bytes)
     } else {
@@ -154,6 +151,8 @@ fn extract_from_segment(
     let reader = SegmentReader::from_buffer(buffer)?;
 
     let chunk_ids = reader.get_chunk_ids()?;
+    let doc_ids = reader.get_doc_ids()?;
+    let ordinals = reader.get_ordinals()?;
     let vectors = reader.get_vectors()?;
     let metadata_list = reader.get_metadata()?;
     let chunk_texts = reader.get_chunk_texts();
@@ -168,6 +167,8 @@ fn extract_from_segment(
         let text = chunk_texts.as_ref().and_then(|texts| texts.get(i).cloned());
         records.push(ExtractedRecord {
             chunk_id: chunk_ids[i].clone(),
+            doc_id: doc_ids[i].clone(),
+            ordinal: ordinals[i],
             vector: vectors[i].clone(),
             metadata: metadata_list[i].clone(),
             chunk_text: text,
@@ -184,25 +185,23 @@ fn build_compacted_segment(
     records: &[ExtractedRecord],
     dimension: usize,
     metric: &str,
-    hnsw: HnswParams,
+    index: IndexParams,
 ) -> Result<(String, i64)> {
     // Collect vectors first (one clone each) so they can be moved into the
-    // builder after HNSW construction — avoiding the double-clone per record
-    // that occurs when both the builder and the HNSW graph need ownership.
+    // builder after index construction — avoiding the double-clone per record
+    // that occurs when both the builder and the index need ownership.
     let mut vectors: Vec<Vec<f32>> = Vec::with_capacity(records.len());
     for rec in records {
         vectors.push(rec.vector.clone());
     }
 
-    // Build HNSW index with collection-configured parameters.
-    let mut graph = HnswGraph::new(metric, dimension, hnsw.m, hnsw.ef_construction, hnsw.ef_search);
-    graph.build(&vectors);
-    let index_data = graph.serialize();
+    // Build the vector index with collection-configured parameters.
+    let index_data = index.build_index_data(metric, dimension, &vectors);
 
     // Feed builder by moving vectors — no additional clone per record.
-    let mut builder = SegmentBuilder::new();
-    for (rec, vector) in records.iter().zip(vectors.into_iter()) {
-        builder.add_record_with_text(rec.chunk_id.clone(), vector, rec.metadata.clone(), rec.chunk_text.clone())?;
+    let mut builder = SegmentBuilder::with_encoding(index.encoding).with_analyzer(index.analyzer);
+    for (rec, vector) in records.iter().zip(vectors) {
+        builder.add_record_with_text(rec.chunk_id.clone(), rec.doc_id.clone(), rec.ordinal, vector, rec.metadata.clone(), rec.chunk_text.clone())?;
     }
 
     let result = builder.build(Some(&index_data))?;
@@ -0,0 +1,380 @@
+//! Distance functions for vector similarity search.
+//!
+//! All distance functions return values where **lower = closer**.
+//! Score conversion to "higher = more relevant" happens at the NAPI boundary.
+//!
+//! Kernels are selected once at runtime by CPU feature detection — AVX-512F,
+//! AVX2+FMA+F16C, or SSE2 on x86_64 — with the scalar loops as the fallback
+//! on other targets. `get_distance_fn` hands out the selected kernel itself,
+//! so `HnswGraph` and the buffer scan call it without re-dispatching.
+
+use std::sync::OnceLock;
+
+/// Build a `KernelSet` from its primitive kernels, deriving the metric
+/// functions (cosine, L2, dot) from them.
+macro_rules! kernel_set {
+    ($name:literal, $dot:path, $l2_squared:path, $dot_f16:path, $l2_squared_f16:path, $dot_norm_f16:path) => {{
+        fn cosine(a: &[f32], b: &[f32]) -> f32 {
+            1.0 - $dot(a, b)
+        }
+        fn l2(a: &[f32], b: &[f32]) -> f32 {
+            $l2_squared(a, b).sqrt()
+        }
+        fn dot(a: &[f32], b: &[f32]) -> f32 {
+            -$dot(a, b)
+        }
+        fn cosine_f16(a: &[f32], b: &[u8]) -> f32 {
+            let (dot, norm_squared) = $dot_norm_f16(a, b);
+            // Matches `normalize`, which leaves zero vectors unchanged.
+            if norm_squared == 0.0 { 1.0 } else { 1.0 - dot / norm_squared.sqrt() }
+        }
+        fn l2_f16(a: &[f32], b: &[u8]) -> f32 {
+            $l2_squared_f16(a, b).sqrt()
+        }
+        fn dot_f16(a: &[f32], b: &[u8]) -> f32 {
+            -$dot_f16(a, b)
+        }
+        $crate::distance::KernelSet {
+            name: $name,
+            dot_product: $dot,
+            l2_squared: $l2_squared,
+            cosine_distance: cosine,
+            l2_distance: l2,
+            dot_distance: dot,
+            cosine_distance_f16: cosine_f16,
+            l2_distance_f16: l2_f16,
+            dot_distance_f16: dot_f16,
+        }
+    }};
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86;
+
+/// One implementation of every distance kernel.
+///
+/// The `_f16` kernels take the stored vector as little-endian IEEE 754 half
+/// precision bytes (a segment's FP16 VectorBlock record), so it is scored
+/// without a decode pass.
+#[derive(Clone, Copy)]
+pub struct KernelSet {
+    /// "scalar", "sse", "avx2" or "avx512".
+    pub name: &'static str,
+    pub dot_product: fn(&[f32], &[f32]) -> f32,
+    /// Squared Euclidean distance.
+    pub l2_squared: fn(&[f32], &[f32]) -> f32,
+    pub cosine_distance: fn(&[f32], &[f32]) -> f32,
+    pub l2_distance: fn(&[f32], &[f32]) -> f32,
+    pub dot_distance: fn(&[f32], &[f32]) -> f32,
+    /// Cosine distance to an FP16 vector that need not be normalized; the
+    /// f32 side must be.
+    pub cosine_distance_f16: fn(&[f32], &[u8]) -> f32,
+    pub l2_distance_f16: fn(&[f32], &[u8]) -> f32,
+    pub dot_distance_f16: fn(&[f32], &[u8]) -> f32,
+}
+
+/// Kernel sets supported by this CPU, from the scalar fallback to the widest.
+pub fn kernel_sets() -> Vec<KernelSet> {
+    #[allow(unused_mut)]
+    let mut sets = vec![scalar::kernels()];
+    #[cfg(target_arch = "x86_64")]
+    {
+        sets.push(x86::sse());
+        sets.extend(x86::avx2());
+        sets.extend(x86::avx512());
+    }
+    sets
+}
+
+/// The kernel set used by this process (the widest supported).
+pub fn active_kernels() -> &'static KernelSet {
+    static ACTIVE: OnceLock<KernelSet> = OnceLock::new();
+    ACTIVE.get_or_init(|| *kernel_sets().last().expect("scalar kernels are always supported"))
+}
+
+/// Cosine distance = 1 - cosine_similarity.
+/// Expects **pre-normalized** vectors (norm = 1).
+#[inline]
+pub fn cosine_distance(a: &[f32], b: &[f32]) -> f32 {
+    debug_assert_eq!(a.len(), b.len());
+    (active_kernels().cosine_distance)(a, b)
+}
+
+/// Euclidean (L2) distance (not squared — takes sqrt).
+#[inline]
+pub fn l2_distance(a: &[f32], b: &[f32]) -> f32 {
+    debug_assert_eq!(a.len(), b.len());
+    (active_kernels().l2_distance)(a, b)
+}
+
+/// Dot product distance = -dot(a, b).
+/// For dot metric, higher dot = more similar, so distance = -dot.
+#[inline]
+pub fn dot_distance(a: &[f32], b: &[f32]) -> f32 {
+    debug_assert_eq!(a.len(), b.len());
+    (active_kernels().dot_distance)(a, b)
+}
+
+/// Raw dot product.
+#[inline]
+pub fn dot_product(a: &[f32], b: &[f32]) -> f32 {
+    debug_assert_eq!(a.len(), b.len());
+    (active_kernels().dot_product)(a, b)
+}
+
+/// Normalize a vector to unit length.
+pub fn normalize(v: &[f32]) -> Vec<f32> {
+    let n = dot_product(v, v).sqrt();
+    if n == 0.0 {
+        return v.to_vec();
+    }
+    v.iter().map(|x| x / n).collect()
+}
+
+/// Convert a raw distance to a similarity score (higher = more relevant).
+#[inline]
+pub fn distance_to_score(metric: &str, distance: f32) -> f32 {
+    match metric {
+        "cosine" => 1.0 - distance,
+        "l2" => 1.0 / (1.0 + distance),
+        "dot" => -distance,
+        _ => 0.0,
+    }
+}
+
+/// Get the distance function for a metric name.
+pub fn get_distance_fn(metric: &str) -> fn(&[f32], &[f32]) -> f32 {
+    let kernels = active_kernels();
+    match metric {
+        "cosine" => kernels.cosine_distance,
+        "l2" => kernels.l2_distance,
+        "dot" => kernels.dot_distance,
+        _ => kernels.l2_distance,
+    }
+}
+
+/// Get the distance function for a metric name against FP16-encoded stored
+/// vectors (little-endian bytes). For cosine only the query must be
+/// normalized.
+pub fn get_fp16_distance_fn(metric: &str) -> fn(&[f32], &[u8]) -> f32 {
+    let kernels = active_kernels();
+    match metric {
+        "cosine" => kernels.cosine_distance_f16,
+        "l2" => kernels.l2_distance_f16,
+        "dot" => kernels.dot_distance_f16,
+        _ => kernels.l2_distance_f16,
+    }
+}
+
+// ─── Scalar fallback ────────────────────────────────────────────────────────
+
+/// Portable kernels, unrolled 4-wide for auto-vectorization. Also used for
+/// the tails of the SIMD kernels.
+mod scalar {
+    use super::KernelSet;
+    use crate::fp16::decode_float16;
+
+    pub(super) fn kernels() -> KernelSet {
+        kernel_set!("scalar", dot_product, l2_squared, dot_product_f16, l2_squared_f16, dot_norm_f16)
+    }
+
+    pub(super) fn dot_product(a: &[f32], b: &[f32]) -> f32 {
+        let n = a.len().min(b.len());
+        let (a, b) = (&a[..n], &b[..n]);
+        let mut sum = 0.0_f32;
+        let chunks = n / 4;
+        let remainder = n % 4;
+
+        for i in 0..chunks {
+            let base = i * 4;
+            sum += a[base] * b[base]
+                + a[base + 1] * b[base + 1]
+                + a[base + 2] * b[base + 2]
+                + a[base + 3] * b[base + 3];
+        }
+
+        let base = chunks * 4;
+        for i in 0..remainder {
+            sum += a[base + i] * b[base + i];
+        }
+
+        sum
+    }
+
+    pub(super) fn l2_squared(a: &[f32], b: &[f32]) -> f32 {
+        let n = a.len().min(b.len());
+        let (a, b) = (&a[..n], &b[..n]);
+        let mut sum = 0.0_f32;
+        let chunks = n / 4;
+        let remainder = n % 4;
+
+        for i in 0..chunks {
+            let base = i * 4;
+            let d0 = a[base] - b[base];
+            let d1 = a[base + 1] - b[base + 1];
+            let d2 = a[base + 2] - b[base + 2];
+            let d3 = a[base + 3] - b[base + 3];
+            sum += d0 * d0 + d1 * d1 + d2 * d2 + d3 * d3;
+        }
+
+        let base = chunks * 4;
+        for i in 0..remainder {
+            let d = a[base + i] - b[base + i];
+            sum += d * d;
+        }
+
+        sum
+    }
+
+    #[inline]
+    fn halves(b: &[u8]) -> impl Iterator<Item = f32> + '_ {
+        b.chunks_exact(2).map(|h| decode_float16(u16::from_le_bytes([h[0], h[1]])))
+    }
+
+    pub(super) fn dot_product_f16(a: &[f32], b: &[u8]) -> f32 {
+        a.iter().zip(halves(b)).map(|(x, y)| x * y).sum()
+    }
+
+    pub(super) fn l2_squared_f16(a: &[f32], b: &[u8]) -> f32 {
+        a.iter()
+            .zip(halves(b))
+            .map(|(x, y)| {
+                let d = x - y;
+                d * d
+            })
+            .sum()
+    }
+
+    pub(super) fn dot_norm_f16(a: &[f32], b: &[u8]) -> (f32, f32) {
+        a.iter()
+            .zip(halves(b))
+            .fold((0.0, 0.0), |(dot, norm), (x, y)| (dot + x * y, norm + y * y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosine_identical() {
+        let a = normalize(&[1.0, 0.0, 0.0, 0.0]);
+        let d = cosine_distance(&a, &a);
+        assert!((d - 0.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_cosine_orthogonal() {
+        let a = normalize(&[1.0, 0.0, 0.0, 0.0]);
+        let b = normalize(&[0.0, 1.0, 0.0, 0.0]);
+        let d = cosine_distance(&a, &b);
+        assert!((d - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_l2_identical() {
+        let a = [1.0, 2.0, 3.0, 4.0];
+        let d = l2_distance(&a, &a);
+        assert!((d - 0.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_l2_known() {
+        let a = [0.0, 0.0, 0.0, 0.0];
+        let b = [3.0, 4.0, 0.0, 0.0];
+        let d = l2_distance(&a, &b);
+        assert!((d - 5.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_dot_product_basic() {
+        let a = [1.0, 2.0, 3.0, 4.0];
+        let b = [4.0, 3.0, 2.0, 1.0];
+        let dp = dot_product(&a, &b);
+        assert!((dp - 20.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let v = normalize(&[3.0, 4.0, 0.0, 0.0]);
+        let n = dot_product(&v, &v).sqrt();
+        assert!((n - 1.0).abs() < 1e-6);
+    }
+
+    fn sample(len: usize, seed: usize) -> Vec<f32> {
+        (0..len).map(|i| ((seed * 131 + i) as f32 * 0.37).sin() * 2.0).collect()
+    }
+
+    fn assert_close(actual: f32, expected: f32, context: &str) {
+        let tolerance = 1e-4 * expected.abs().max(1.0);
+        assert!((actual - expected).abs() <= tolerance, "{context}: {actual} vs {expected}");
+    }
+
+    #[test]
+    fn test_kernel_sets_match_scalar() {
+        let sets = kernel_sets();
+        assert_eq!(sets[0].name, "scalar");
+        assert_eq!(active_kernels().name, sets.last().unwrap().name);
+
+        let reference = &sets[0];
+        // Lengths cover empty input, pure tails and every SIMD body width.
+        for len in [0, 1, 3, 7, 8, 15, 16, 17, 31, 32, 33, 64, 100, 384] {
+            let a = sample(len, 1);
+            let b = sample(len, 2);
+            let a_unit = normalize(&a);
+            let b_unit = normalize(&b);
+            let b_f16 = crate::fp16::encode_fp16_vector(&b);
+            let b_decoded = crate::fp16::decode_fp16_vector(&b_f16);
+            let b_decoded_unit = normalize(&b_decoded);
+            for set in &sets {
+                let ctx = format!("{} len={len}", set.name);
+                assert_close((set.dot_product)(&a, &b), (reference.dot_product)(&a, &b), &ctx);
+                assert_close((set.l2_squared)(&a, &b), (reference.l2_squared)(&a, &b), &ctx);
+                assert_close(
+                    (set.cosine_distance)(&a_unit, &b_unit),
+                    (reference.cosine_distance)(&a_unit, &b_unit),
+                    &ctx,
+                );
+                // FP16 kernels agree with decoding first and using the f32 kernels.
+                assert_close(
+                    (set.dot_distance_f16)(&a, &b_f16),
+                    (reference.dot_distance)(&a, &b_decoded),
+                    &ctx,
+                );
+                assert_close(
+                    (set.l2_distance_f16)(&a, &b_f16),
+                    (reference.l2_distance)(&a, &b_decoded),
+                    &ctx,
+                );
+                let expected_cosine = if len == 0 {
+                    1.0
+                } else {
+                    (reference.cosine_distance)(&a_unit, &b_decoded_unit)
+                };
+                assert_close((set.cosine_distance_f16)(&a_unit, &b_f16), expected_cosine, &ctx);
+            }
+        }
+    }
+
+    #[test]
+    fn test_kernels_tolerate_mismatched_lengths() {
+        let a = sample(40, 3);
+        let b = sample(37, 4);
+        let b_f16 = crate::fp16::encode_fp16_vector(&b);
+        for set in kernel_sets() {
+            let expected = (kernel_sets()[0].dot_product)(&a[..37], &b);
+            assert_close((set.dot_product)(&a, &b), expected, set.name);
+            assert!((set.l2_distance_f16)(&a, &b_f16[..b_f16.len() - 1]).is_finite());
+        }
+    }
+
+    #[test]
+    fn test_get_fp16_distance_fn() {
+        let q = normalize(&[1.0, 2.0, 3.0, 4.0]);
+        let v = [2.0, 4.0, 6.0, 8.0];
+        let v_f16 = crate::fp16::encode_fp16_vector(&v);
+        assert_close(get_fp16_distance_fn("cosine")(&q, &v_f16), 0.0, "cosine");
+        assert_close(get_fp16_distance_fn("l2")(&[2.0, 4.0, 6.0, 5.0], &v_f16), 3.0, "l2");
+        assert_close(get_fp16_distance_fn("dot")(&[1.0, 0.0, 0.0, 1.0], &v_f16), -10.0, "dot");
+    }
+}
@@ -0,0 +1,352 @@
+//! x86_64 SSE2 / AVX2 / AVX-512 distance kernels.
+//!
+//! The `#[target_feature]` implementations are only reachable through the
+//! `KernelSet`s returned here, and the AVX2 and AVX-512 sets are only built
+//! after runtime feature detection — that is the safety argument for every
+//! `unsafe` call below. All kernels bound their loads by the shorter input, so
+//! mismatched lengths cannot read out of bounds.
+//!
+//! FP16 inputs are converted in registers with F16C (`vcvtph2ps`). Encoded
+//! segment vectors never contain denormals (the encoder flushes them), so the
+//! hardware conversion agrees with `fp16::decode_float16`.
+
+use std::arch::x86_64::*;
+
+use super::{scalar, KernelSet};
+
+// ─── SSE2 (x86_64 baseline) ─────────────────────────────────────────────────
+
+pub(super) fn sse() -> KernelSet {
+    kernel_set!(
+        "sse",
+        dot_sse,
+        l2_squared_sse,
+        scalar::dot_product_f16,
+        scalar::l2_squared_f16,
+        scalar::dot_norm_f16
+    )
+}
+
+#[inline]
+#[target_feature(enable = "sse")]
+fn hsum_sse(v: __m128) -> f32 {
+    let high = _mm_movehl_ps(v, v);
+    let sums = _mm_add_ps(v, high);
+    let odd = _mm_shuffle_ps(sums, sums, 0b01);
+    _mm_cvtss_f32(_mm_add_ss(sums, odd))
+}
+
+fn dot_sse(a: &[f32], b: &[f32]) -> f32 {
+    // SAFETY: SSE2 is part of the x86_64 baseline.
+    unsafe { dot_sse_impl(a, b) }
+}
+
+#[target_feature(enable = "sse2")]
+fn dot_sse_impl(a: &[f32], b: &[f32]) -> f32 {
+    let n = a.len().min(b.len());
+    let body = n - n % 8;
+    let (mut acc0, mut acc1) = (_mm_setzero_ps(), _mm_setzero_ps());
+    for i in (0..body).step_by(8) {
+        // SAFETY: i + 8 <= n.
+        unsafe {
+            let (pa, pb) = (a.as_ptr().add(i), b.as_ptr().add(i));
+            acc0 = _mm_add_ps(acc0, _mm_mul_ps(_mm_loadu_ps(pa), _mm_loadu_ps(pb)));
+            acc1 = _mm_add_ps(acc1, _mm_mul_ps(_mm_loadu_ps(pa.add(4)), _mm_loadu_ps(pb.add(4))));
+        }
+    }
+    hsum_sse(_mm_add_ps(acc0, acc1)) + scalar::dot_product(&a[body..n], &b[body..n])
+}
+
+fn l2_squared_sse(a: &[f32], b: &[f32]) -> f32 {
+    // SAFETY: SSE2 is part of the x86_64 baseline.
+    unsafe { l2_squared_sse_impl(a, b) }
+}
+
+#[target_feature(enable = "sse2")]
+fn l2_squared_sse_impl(a: &[f32], b: &[f32]) -> f32 {
+    let n = a.len().min(b.len());
+    let body = n - n % 8;
+    let (mut acc0, mut acc1) = (_mm_setzero_ps(), _mm_setzero_ps());
+    for i in (0..body).step_by(8) {
+        // SAFETY: i + 8 <= n.
+        unsafe {
+            let (pa, pb) = (a.as_ptr().add(i), b.as_ptr().add(i));
+            let d0 = _mm_sub_ps(_mm_loadu_ps(pa), _mm_loadu_ps(pb));
+            let d1 = _mm_sub_ps(_mm_loadu_ps(pa.add(4)), _mm_loadu_ps(pb.add(4)));
+            acc0 = _mm_add_ps(acc0, _mm_mul_ps(d0, d0));
+            acc1 = _mm_add_ps(acc1, _mm_mul_ps(d1, d1));
+        }
+    }
+    hsum_sse(_mm_add_ps(acc0, acc1)) + scalar::l2_squared(&a[body..n], &b[body..n])
+}
+
+// ─── AVX2 + FMA + F16C ──────────────────────────────────────────────────────
+
+pub(super) fn avx2() -> Option<KernelSet> {
+    let supported = is_x86_feature_detected!("avx2")
+        && is_x86_feature_detected!("fma")
+        && is_x86_feature_detected!("f16c");
+    supported.then(|| {
+        kernel_set!(
+            "avx2",
+            dot_avx2,
+            l2_squared_avx2,
+            dot_f16_avx2,
+            l2_squared_f16_avx2,
+            dot_norm_f16_avx2
+        )
+    })
+}
+
+#[inline]
+#[target_feature(enable = "avx2")]
+fn hsum_avx2(v: __m256) -> f32 {
+    hsum_sse(_mm_add_ps(_mm256_castps256_ps128(v), _mm256_extractf128_ps(v, 1)))
+}
+
+/// Load 8 FP16 values starting at element `i` of `b` and widen them to f32.
+///
+/// # Safety
+/// `b` must hold at least `2 * (i + 8)` bytes.
+#[inline]
+#[target_feature(enable = "avx2,f16c")]
+unsafe fn load_f16x8(b: &[u8], i: usize) -> __m256 {
+    // SAFETY: guaranteed by the caller.
+    _mm256_cvtph_ps(unsafe { _mm_loadu_si128(b.as_ptr().add(i * 2) as *const __m128i) })
+}
+
+fn dot_avx2(a: &[f32], b: &[f32]) -> f32 {
+    // SAFETY: only reachable through `avx2()`, which checked the features.
+    unsafe { dot_avx2_impl(a, b) }
+}
+
+#[target_feature(enable = "avx2,fma")]
+fn dot_avx2_impl(a: &[f32], b: &[f32]) -> f32 {
+    let n = a.len().min(b.len());
+    let body = n - n % 16;
+    let (mut acc0, mut acc1) = (_mm256_setzero_ps(), _mm256_setzero_ps());
+    for i in (0..body).step_by(16) {
+        // SAFETY: i + 16 <= n.
+        unsafe {
+            let (pa, pb) = (a.as_ptr().add(i), b.as_ptr().add(i));
+            acc0 = _mm256_fmadd_ps(_mm256_loadu_ps(pa), _mm256_loadu_ps(pb), acc0);
+            acc1 = _mm256_fmadd_ps(_mm256_loadu_ps(pa.add(8)), _mm256_loadu_ps(pb.add(8)), acc1);
+        }
+    }
+    hsum_avx2(_mm256_add_ps(acc0, acc1)) + scalar::dot_product(&a[body..n], &b[body..n])
+}
+
+fn l2_squared_avx2(a: &[f32], b: &[f32]) -> f32 {
+    // SAFETY: only reachable through `avx2()`, which checked the features.
+    unsafe { l2_squared_avx2_impl(a, b) }
+}
+
+#[target_feature(enable = "avx2,fma")]
+fn l2_squared_avx2_impl(a: &[f32], b: &[f32]) -> f32 {
+    let n = a.len().min(b.len());
+    let body = n - n % 16;
+    let (mut acc0, mut acc1) = (_mm256_setzero_ps(), _mm256_setzero_ps());
+    for i in (0..body).step_by(16) {
+        // SAFETY: i + 16 <= n.
+        unsafe {
+            let (pa, pb) = (a.as_ptr().add(i), b.as_ptr().add(i));
+            let d0 = _mm256_sub_ps(_mm256_loadu_ps(pa), _mm256_loadu_ps(pb));
+            let d1 = _mm256_sub_ps(_mm256_loadu_ps(pa.add(8)), _mm256_loadu_ps(pb.add(8)));
+            acc0 = _mm256_fmadd_ps(d0, d0, acc0);
+            acc1 = _mm256_fmadd_ps(d1, d1, acc1);
+        }
+    }
+    hsum_avx2(_mm256_add_ps(acc0, acc1)) + scalar::l2_squared(&a[body..n], &b[body..n])
+}
+
+fn dot_f16_avx2(a: &[f32], b: &[u8]) -> f32 {
+    // SAFETY: only reachable through `avx2()`, which checked the features.
+    unsafe { dot_f16_avx2_impl(a, b) }
+}
+
+#[target_feature(enable = "avx2,fma,f16c")]
+fn dot_f16_avx2_impl(a: &[f32], b: &[u8]) -> f32 {
+    let n = a.len().min(b.len() / 2);
+    let body = n - n % 8;
+    let mut acc = _mm256_setzero_ps();
+    for i in (0..body).step_by(8) {
+        // SAFETY: i + 8 <= n, so both loads are in bounds.
+        unsafe {
+            acc = _mm256_fmadd_ps(_mm256_loadu_ps(a.as_ptr().add(i)), load_f16x8(b, i), acc);
+        }
+    }
+    hsum_avx2(acc) + scalar::dot_product_f16(&a[body..n], &b[body * 2..n * 2])
+}
+
+fn l2_squared_f16_avx2(a: &[f32], b: &[u8]) -> f32 {
+    // SAFETY: only reachable through `avx2()`, which checked the features.
+    unsafe { l2_squared_f16_avx2_impl(a, b) }
+}
+
+#[target_feature(enable = "avx2,fma,f16c")]
+fn l2_squared_f16_avx2_impl(a: &[f32], b: &[u8]) -> f32 {
+    let n = a.len().min(b.len() / 2);
+    let body = n - n % 8;
+    let mut acc = _mm256_setzero_ps();
+    for i in (0..body).step_by(8) {
+        // SAFETY: i + 8 <= n, so both loads are in bounds.
+        unsafe {
+            let d = _mm256_sub_ps(_mm256_loadu_ps(a.as_ptr().add(i)), load_f16x8(b, i));
+            acc = _mm256_fmadd_ps(d, d, acc);
+        }
+    }
+    hsum_avx2(acc) + scalar::l2_squared_f16(&a[body..n], &b[body * 2..n * 2])
+}
+
+fn dot_norm_f16_avx2(a: &[f32], b: &[u8]) -> (f32, f32) {
+    // SAFETY: only reachable through `avx2()`, which checked the features.
+    unsafe { dot_norm_f16_avx2_impl(a, b) }
+}
+
+#[target_feature(enable = "avx2,fma,f16c")]
+fn dot_norm_f16_avx2_impl(a: &[f32], b: &[u8]) -> (f32, f32) {
+    let n = a.len().min(b.len() / 2);
+    let body = n - n % 8;
+    let (mut dot, mut norm) = (_mm256_setzero_ps(), _mm256_setzero_ps());
+    for i in (0..body).step_by(8) {
+        // SAFETY: i + 8 <= n, so both loads are in bounds.
+        unsafe {
+            let bv = load_f16x8(b, i);
+            dot = _mm256_fmadd_ps(_mm256_loadu_ps(a.as_ptr().add(i)), bv, dot);
+            norm = _mm256_fmadd_ps(bv, bv, norm);
+        }
+    }
+    let (tail_dot, tail_norm) = scalar::dot_norm_f16(&a[body..n], &b[body * 2..n * 2]);
+    (hsum_avx2(dot) + tail_dot, hsum_avx2(norm) + tail_norm)
+}
+
+// ─── AVX-512F ───────────────────────────────────────────────────────────────
+
+pub(super) fn avx512() -> Option<KernelSet> {
+    is_x86_feature_detected!("avx512f").then(|| {
+        kernel_set!(
+            "avx512",
+            dot_avx512,
+            l2_squared_avx512,
+            dot_f16_avx512,
+            l2_squared_f16_avx512,
+            dot_norm_f16_avx512
+        )
+    })
+}
+
+/// Load 16 FP16 values starting at element `i` of `b` and widen them to f32.
+///
+/// # Safety
+/// `b` must hold at least `2 * (i + 16)` bytes.
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn load_f16x16(b: &[u8], i: usize) -> __m512 {
+    // SAFETY: guaranteed by the caller.
+    _mm512_cvtph_ps(unsafe { _mm256_loadu_si256(b.as_ptr().add(i * 2) as *const __m256i) })
+}
+
+fn dot_avx512(a: &[f32], b: &[f32]) -> f32 {
+    // SAFETY: only reachable through `avx512()`, which checked the features.
+    unsafe { dot_avx512_impl(a, b) }
+}
+
+#[target_feature(enable = "avx512f")]
+fn dot_avx512_impl(a: &[f32], b: &[f32]) -> f32 {
+    let n = a.len().min(b.len());
+    let body = n - n % 32;
+    let (mut acc0, mut acc1) = (_mm512_setzero_ps(), _mm512_setzero_ps());
+    for i in (0..body).step_by(32) {
+        // SAFETY: i + 32 <= n.
+        unsafe {
+            let (pa, pb) = (a.as_ptr().add(i), b.as_ptr().add(i));
+            acc0 = _mm512_fmadd_ps(_mm512_loadu_ps(pa), _mm512_loadu_ps(pb), acc0);
+            acc1 = _mm512_fmadd_ps(_mm512_loadu_ps(pa.add(16)), _mm512_loadu_ps(pb.add(16)), acc1);
+        }
+    }
+    _mm512_reduce_add_ps(_mm512_add_ps(acc0, acc1)) + scalar::dot_product(&a[body..n], &b[body..n])
+}
+
+fn l2_squared_avx512(a: &[f32], b: &[f32]) -> f32 {
+    // SAFETY: only reachable through `avx512()`, which checked the features.
+    unsafe { l2_squared_avx512_impl(a, b) }
+}
+
+#[target_feature(enable = "avx512f")]
+fn l2_squared_avx512_impl(a: &[f32], b: &[f32]) -> f32 {
+    let n = a.len().min(b.len());
+    let body = n - n % 32;
+    let (mut acc0, mut acc1) = (_mm512_setzero_ps(), _mm512_setzero_ps());
+    for i in (0..body).step_by(32) {
+        // SAFETY: i + 32 <= n.
+        unsafe {
+            let (pa, pb) = (a.as_ptr().add(i), b.as_ptr().add(i));
+            let d0 = _mm512_sub_ps(_mm512_loadu_ps(pa), _mm512_loadu_ps(pb));
+            let d1 = _mm512_sub_ps(_mm512_loadu_ps(pa.add(16)), _mm512_loadu_ps(pb.add(16)));
+            acc0 = _mm512_fmadd_ps(d0, d0, acc0);
+            acc1 = _mm512_fmadd_ps(d1, d1, acc1);
+        }
+    }
+    _mm512_reduce_add_ps(_mm512_add_ps(acc0, acc1)) + scalar::l2_squared(&a[body..n], &b[body..n])
+}
+
+fn dot_f16_avx512(a: &[f32], b: &[u8]) -> f32 {
+    // SAFETY: only reachable through `avx512()`, which checked the features.
+    unsafe { dot_f16_avx512_impl(a, b) }
+}
+
+#[target_feature(enable = "avx512f")]
+fn dot_f16_avx512_impl(a: &[f32], b: &[u8]) -> f32 {
+    let n = a.len().min(b.len() / 2);
+    let body = n - n % 16;
+    let mut acc = _mm512_setzero_ps();
+    for i in (0..body).step_by(16) {
+        // SAFETY: i + 16 <= n, so both loads are in bounds.
+        unsafe {
+            acc = _mm512_fmadd_ps(_mm512_loadu_ps(a.as_ptr().add(i)), load_f16x16(b, i), acc);
+        }
+    }
+    _mm512_reduce_add_ps(acc) + scalar::dot_product_f16(&a[body..n], &b[body * 2..n * 2])
+}
+
+fn l2_squared_f16_avx512(a: &[f32], b: &[u8]) -> f32 {
+    // SAFETY: only reachable through `avx512()`, which checked the features.
+    unsafe { l2_squared_f16_avx512_impl(a, b) }
+}
+
+#[target_feature(enable = "avx512f")]
+fn l2_squared_f16_avx512_impl(a: &[f32], b: &[u8]) -> f32 {
+    let n = a.len().min(b.len() / 2);
+    let body = n - n % 16;
+    let mut acc = _mm512_setzero_ps();
+    for i in (0..body).step_by(16) {
+        // SAFETY: i + 16 <= n, so both loads are in bounds.
+        unsafe {
+            let d = _mm512_sub_ps(_mm512_loadu_ps(a.as_ptr().add(i)), load_f16x16(b, i));
+            acc = _mm512_fmadd_ps(d, d, acc);
+        }
+    }
+    _mm512_reduce_add_ps(acc) + scalar::l2_squared_f16(&a[body..n], &b[body * 2..n * 2])
+}
+
+fn dot_norm_f16_avx512(a: &[f32], b: &[u8]) -> (f32, f32) {
+    // SAFETY: only reachable through `avx512()`, which checked the features.
+    unsafe { dot_norm_f16_avx512_impl(a, b) }
+}
+
+#[target_feature(enable = "avx512f")]
+fn dot_norm_f16_avx512_impl(a: &[f32], b: &[u8]) -> (f32, f32) {
+    let n = a.len().min(b.len() / 2);
+    let body = n - n % 16;
+    let (mut dot, mut norm) = (_mm512_setzero_ps(), _mm512_setzero_ps());
+    for i in (0..body).step_by(16) {
+        // SAFETY: i + 16 <= n, so both loads are in bounds.
+        unsafe {
+            let bv = load_f16x16(b, i);
+            dot = _mm512_fmadd_ps(_mm512_loadu_ps(a.as_ptr().add(i)), bv, dot);
+            norm = _mm512_fmadd_ps(bv, bv, norm);
+        }
+    }
+    let (tail_dot, tail_norm) = scalar::dot_norm_f16(&a[body..n], &b[body * 2..n * 2]);
+    (_mm512_reduce_add_ps(dot) + tail_dot, _mm512_reduce_add_ps(norm) + tail_norm)
+}
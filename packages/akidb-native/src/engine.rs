@@ -3,26 +3,34 @@
 //! This is the internal engine that holds all state. It is wrapped by
 //! `AkiDbEngine` in lib.rs behind an `RwLock` for thread safety.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex, MutexGuard};
 
 use crate::collection::{CollectionManager, CreateCollectionOptions};
 use crate::compaction::{compact, CompactResult};
 use crate::error::{AkiDbError, Result};
-use crate::index::{ExplainInfo, SearchResult};
+use crate::hnsw::HnswGraph;
+use crate::index::{ExplainInfo, FusionInfo, SearchResult};
 use crate::manifest::{ManifestManager, PublishManifestOptions};
 use crate::metadata::{Collection, Manifest, MetadataStore, Tombstone};
+use crate::query::highlight::{self, Highlights};
 use crate::query::{
-    hybrid, keyword, tombstone_fingerprint, QueryEngine, SearchMode, SearchOptions, SearchResponse,
-    VectorSearchSnapshot,
+    context, cursor, group, hybrid, keyword, rerank, syntax, tombstone_fingerprint, AssembledContext, BatchQuery,
+    ContextBudget, KeywordQuery, QueryEngine, RerankCandidate, Reranker, SearchCursor, SearchMode, SearchOptions,
+    ManifestSnapshot, SearchResponse, StoredRecord,
 };
+use crate::segment::keyword::KeywordIndex;
 use crate::storage::LocalFsBackend;
-use crate::write::{HnswParams, NativeRecord, UpsertResult, WritePath, WritePathOptions};
+use crate::text::Analyzer;
+use crate::write::{IndexParams, NativeRecord, UpsertResult, WritePath, WritePathOptions};
 
 pub struct EngineOptions {
     pub storage_path: PathBuf,
     pub disable_wal: bool,
+    /// Worker threads for per-segment vector search. None uses the available
+    /// parallelism; 1 searches segments serially on the calling thread.
+    pub search_threads: Option<usize>,
 }
 
 pub struct EngineInner {
@@ -34,13 +42,34 @@ pub struct EngineInner {
     storage_path: PathBuf,
 }
 
-struct ManifestSearchSnapshot {
-    manifest: Manifest,
-    live_tombstone_set: std::collections::HashSet<String>,
+/// State shared by every query of a `search` or `search_batch` call.
+struct SearchSnapshot {
+    buffer_records: Vec<NativeRecord>,
+    /// `WriteBuffer::generation` of `buffer_records`.
+    buffer_generation: u64,
+    manifest: ManifestSnapshot,
+}
+
+/// What a page's cursor records beyond its last result.
+struct RankedPage {
+    /// Score each result is ranked by: raw BM25 in keyword search, else the
+    /// returned score.
+    rank_scores: Vec<f64>,
+    /// Hybrid leg depths of the last result's fusion and of the fusion
+    /// before it (see `SearchCursor`); 0 in other modes.
+    leg_depth: usize,
+    seen_depth: usize,
+    /// Whether any result ranks after this page.
+    has_more: bool,
 }
 
 impl EngineInner {
     pub fn open(opts: EngineOptions) -> Result<Self> {
+        if opts.search_threads == Some(0) {
+            return Err(AkiDbError::InvalidArgument(
+                "search_threads must be at least 1".to_string(),
+            ));
+        }
         std::fs::create_dir_all(&opts.storage_path)?;
 
         let db_path = opts.storage_path.join("metadata.db");
@@ -49,7 +78,7 @@ impl EngineInner {
         })?;
         let metadata = Arc::new(Mutex::new(MetadataStore::open(db_path_str)?));
         let storage = Arc::new(LocalFsBackend::open(&opts.storage_path)?);
-        let query_engine = QueryEngine::new(None);
+        let query_engine = QueryEngine::new(None, opts.search_threads);
 
         Ok(Self {
             metadata,
@@ -74,22 +103,35 @@ impl EngineInner {
         hnsw_m: i64,
         hnsw_ef_construction: i64,
         hnsw_ef_search: i64,
+    ) -> Result<Collection> {
+        self.create_collection_with_options(&CreateCollectionOptions {
+            collection_id: collection_id.to_string(),
+            dimension,
+            metric: metric.to_string(),
+            embedding_model_id: embedding_model_id.to_string(),
+            schema_version: "1".to_string(),
+            quantization: quantization.to_string(),
+            hnsw_m,
+            hnsw_ef_construction,
+            hnsw_ef_search,
+            index_type: "hnsw".to_string(),
+            ivf_num_clusters: None,
+            ivf_num_probes: None,
+            pq_num_subquantizers: None,
+            tokenizer: "unicode61".to_string(),
+            stemmer: None,
+            stop_words: None,
+            remove_diacritics: true,
+        })
+    }
+
+    /// Create a collection with the full option set (index type, IVF-PQ parameters).
+    pub fn create_collection_with_options(
+        &self,
+        opts: &CreateCollectionOptions,
     ) -> Result<Collection> {
         let metadata = self.lock_metadata()?;
-        CollectionManager::create(
-            &metadata,
-            &CreateCollectionOptions {
-                collection_id: collection_id.to_string(),
-                dimension,
-                metric: metric.to_string(),
-                embedding_model_id: embedding_model_id.to_string(),
-                schema_version: "1".to_string(),
-                quantization: quantization.to_string(),
-                hnsw_m,
-                hnsw_ef_construction,
-                hnsw_ef_search,
-            },
-        )
+        CollectionManager::create(&metadata, opts)
     }
 
     pub fn get_collection(&self, collection_id: &str) -> Result<Option<Collection>> {
@@ -114,6 +156,25 @@ impl EngineInner {
         collection_id: &str,
         records: &[NativeRecord],
     ) -> Result<UpsertResult> {
+        let collection = self.writable_collection(collection_id, records)?;
+
+        // Get or create write path for this collection.
+        let write_path = self.get_or_create_write_path(collection_id)?;
+        let mut write_path = write_path
+            .lock()
+            .map_err(|_| AkiDbError::InvalidArgument("Write path lock poisoned".to_string()))?;
+        write_path.upsert_batch(
+            collection_id,
+            records,
+            collection.dimension as usize,
+            &collection.metric,
+            IndexParams::from_collection(&collection),
+        )
+    }
+
+    /// Look up a collection that `records` are about to be written to and
+    /// validate them against it.
+    fn writable_collection(&self, collection_id: &str, records: &[NativeRecord]) -> Result<Collection> {
         let collection = self
             .lock_metadata()?
             .get_collection(collection_id)?
@@ -150,23 +211,7 @@ impl EngineInner {
                 });
             }
         }
-
-        // Get or create write path for this collection.
-        let write_path = self.get_or_create_write_path(collection_id)?;
-        let mut write_path = write_path
-            .lock()
-            .map_err(|_| AkiDbError::InvalidArgument("Write path lock poisoned".to_string()))?;
-        write_path.upsert_batch(
-            collection_id,
-            records,
-            collection.dimension as usize,
-            &collection.metric,
-            HnswParams {
-                m: collection.hnsw_m as usize,
-                ef_construction: collection.hnsw_ef_construction as usize,
-                ef_search: collection.hnsw_ef_search as usize,
-            },
-        )
+        Ok(collection)
     }
 
     pub fn flush_writes(&self, collection_id: &str) -> Result<Vec<String>> {
@@ -187,11 +232,7 @@ impl EngineInner {
                 collection_id,
                 collection.dimension as usize,
                 &collection.metric,
-                HnswParams {
-                    m: collection.hnsw_m as usize,
-                    ef_construction: collection.hnsw_ef_construction as usize,
-                    ef_search: collection.hnsw_ef_search as usize,
-                },
+                IndexParams::from_collection(&collection),
             )
         } else {
             Ok(Vec::new())
@@ -232,7 +273,7 @@ impl EngineInner {
         // Flush pending writes.
         self.flush_writes(collection_id)?;
 
-        let (manifest, segment_paths, tombstone_ids, collection) = {
+        let (manifest, segment_paths, collection) = {
             let metadata = self.lock_metadata()?;
             let segments = metadata.list_segments(collection_id, Some("ready"))?;
             let segment_ids: Vec<String> = segments.iter().map(|s| s.segment_id.clone()).collect();
@@ -256,7 +297,7 @@ impl EngineInner {
                 collection_id,
                 &PublishManifestOptions {
                     segment_ids,
-                    tombstone_ids: tombstone_ids.clone(),
+                    tombstone_ids,
                     embedding_model_id: embedding_model_id.to_string(),
                     pipeline_signature: pipeline_signature.to_string(),
                 },
@@ -265,19 +306,13 @@ impl EngineInner {
                 metadata.delete_tombstones(&pending_tombstone_ids)?;
             }
             let collection = metadata.get_collection(collection_id)?;
-            (manifest, segment_paths, tombstone_ids, collection)
+            (manifest, segment_paths, collection)
         };
 
-        // Prewarm index cache: load HNSW graphs for all new segments so the
+        // Prewarm index cache: load segment indexes for all new segments so the
         // first search query doesn't pay deserialization cost (best-effort).
         if let Some(collection) = collection {
-            let tombstone_set: std::collections::HashSet<String> =
-                tombstone_ids.into_iter().collect();
-            let hnsw = HnswParams {
-                m: collection.hnsw_m as usize,
-                ef_construction: collection.hnsw_ef_construction as usize,
-                ef_search: collection.hnsw_ef_search as usize,
-            };
+            let index = IndexParams::from_collection(&collection);
             let metric = collection.metric.clone();
             let storage = Arc::clone(&self.storage);
             // Prewarm is best-effort; a panic on a corrupt segment must not
@@ -286,10 +321,7 @@ impl EngineInner {
                 self.query_engine.prewarm_paths(
                     &storage,
                     &segment_paths,
-                    manifest.version,
-                    &tombstone_set,
-                    tombstone_fingerprint(&tombstone_set),
-                    hnsw,
+                    index,
                     &metric,
                 );
             }));
@@ -301,190 +333,635 @@ impl EngineInner {
     // ─── Search ─────────────────────────────────────────────────────────────
 
     pub fn search(&self, opts: SearchOptions) -> Result<SearchResponse> {
+        self.query_engine.validate_search_opts(&opts)?;
+        let snapshot = self.take_search_snapshot(&opts)?;
+        let mut response = self.search_page(&opts, &snapshot)?;
+        self.attach_payloads(&opts, &snapshot, &mut response.results)?;
+        if opts.group_by.is_some() {
+            response.groups = Some(group::collect(&response.results));
+        }
+        Ok(response)
+    }
+
+    /// Run several queries against one collection with shared options.
+    ///
+    /// `opts` supplies everything but the query itself; each `BatchQuery`
+    /// replaces `query_vector` and `query_text`. The write buffer, manifest and
+    /// tombstone snapshot are taken once, so every response is bound to the
+    /// same `manifest_version_used`. Each response's cursor continues its
+    /// query through `search`; `search_after` itself is not accepted here.
+    pub fn search_batch(
+        &self,
+        opts: SearchOptions,
+        queries: Vec<BatchQuery>,
+    ) -> Result<Vec<SearchResponse>> {
+        if opts.search_after.is_some() {
+            return Err(AkiDbError::InvalidArgument(
+                "searchAfter is not supported in batch search".into(),
+            ));
+        }
+        let batch: Vec<SearchOptions> = queries
+            .into_iter()
+            .map(|query| SearchOptions {
+                query_vector: query.query_vector,
+                query_text: query.query_text,
+                ..opts.clone()
+            })
+            .collect();
+        for query_opts in &batch {
+            self.query_engine.validate_search_opts(query_opts)?;
+        }
+        let Some(first) = batch.first() else {
+            return Ok(Vec::new());
+        };
+
+        let snapshot = self.take_search_snapshot(first)?;
+        batch
+            .iter()
+            .map(|query_opts| {
+                let mut response = self.search_page(query_opts, &snapshot)?;
+                self.attach_payloads(query_opts, &snapshot, &mut response.results)?;
+                if query_opts.group_by.is_some() {
+                    response.groups = Some(group::collect(&response.results));
+                }
+                Ok(response)
+            })
+            .collect()
+    }
+
+    /// Search, then re-score the best `rerank_depth` candidates (default
+    /// `top_k`) with `reranker` and keep its top `top_k`. The reranker sees
+    /// each candidate's text and metadata and runs after the search has
+    /// released every engine lock. With `explain`, results also record their
+    /// ranks before and after re-ranking.
+    pub fn search_reranked(
+        &self,
+        opts: SearchOptions,
+        reranker: &dyn Reranker,
+        rerank_depth: Option<usize>,
+    ) -> Result<SearchResponse> {
+        self.query_engine.validate_search_opts(&opts)?;
+        if opts.group_by.is_some() {
+            return Err(AkiDbError::InvalidArgument(
+                "groupBy is not supported with re-ranking".into(),
+            ));
+        }
+        if opts.search_after.is_some() {
+            return Err(AkiDbError::InvalidArgument(
+                "searchAfter is not supported with re-ranking".into(),
+            ));
+        }
+        let depth = rerank_depth.unwrap_or(opts.top_k);
+        if depth < opts.top_k {
+            return Err(AkiDbError::InvalidArgument(format!(
+                "rerankDepth must be at least topK ({}), got {depth}",
+                opts.top_k
+            )));
+        }
+        let snapshot = self.take_search_snapshot(&opts)?;
+        let candidate_opts = SearchOptions { top_k: depth, ..opts.clone() };
+        let mut response = self.search_with_snapshot(&candidate_opts, &snapshot)?;
+        if response.results.is_empty() {
+            return Ok(response);
+        }
+
+        let texts = self.load_previews(&snapshot.manifest, &snapshot.buffer_records, &response.results)?;
+        let mut metadata = self.load_metadata(&snapshot.manifest, &snapshot.buffer_records, &response.results)?;
+        let candidates: Vec<RerankCandidate> = response
+            .results
+            .iter()
+            .map(|r| RerankCandidate {
+                chunk_id: r.chunk_id.clone(),
+                score: r.score,
+                text: texts.get(&r.chunk_id).cloned(),
+                metadata: metadata.remove(&r.chunk_id).unwrap_or_else(|| serde_json::json!({})),
+            })
+            .collect();
+        let scores = reranker.rerank(opts.query_text.as_deref(), &candidates)?;
+        response.results = rerank::apply(response.results, &scores, opts.top_k)?;
+        self.attach_payloads(&opts, &snapshot, &mut response.results)?;
+        Ok(response)
+    }
+
+    /// Search, then assemble the hits' chunk text into passages that fit
+    /// `budget`: duplicate or contained texts are dropped and adjacent chunks
+    /// of a document (by ordinal) merged. Hits without text are skipped.
+    pub fn assemble_context(&self, opts: SearchOptions, budget: ContextBudget) -> Result<AssembledContext> {
+        self.query_engine.validate_search_opts(&opts)?;
+        if budget.max_chars() == 0 {
+            return Err(AkiDbError::InvalidArgument("budget must be a positive integer".into()));
+        }
+        let snapshot = self.take_search_snapshot(&opts)?;
+        let response = self.search_page(&opts, &snapshot)?;
+        let chunk_ids: Vec<String> = response.results.iter().map(|r| r.chunk_id.clone()).collect();
+        let mut records: HashMap<String, StoredRecord> = self
+            .snapshot_records(&snapshot, opts.include_uncommitted, &chunk_ids)?
+            .into_iter()
+            .map(|r| (r.chunk_id.clone(), r))
+            .collect();
+        let chunks = response
+            .results
+            .iter()
+            .filter_map(|hit| {
+                let record = records.remove(&hit.chunk_id)?;
+                Some(context::ContextChunk {
+                    chunk_id: record.chunk_id,
+                    doc_id: record.doc_id,
+                    ordinal: record.ordinal,
+                    text: record.chunk_text?,
+                    score: hit.score,
+                })
+            })
+            .collect();
+        let (passages, total_chars) = context::assemble(chunks, budget);
+        Ok(AssembledContext { passages, total_chars, manifest_version_used: response.manifest_version_used })
+    }
+
+    // ─── Point Lookup ───────────────────────────────────────────────────────
+
+    /// Fetch records by chunk ID, in request order with duplicates and
+    /// missing or deleted chunks dropped. Without `manifest_version` this reads
+    /// the live state: the latest manifest, pending deletes and the write
+    /// buffer. With one, only that manifest's records and tombstones count.
+    pub fn get_records(
+        &self,
+        collection_id: &str,
+        chunk_ids: &[String],
+        manifest_version: Option<i64>,
+    ) -> Result<Vec<StoredRecord>> {
+        let live = manifest_version.is_none();
+        let snapshot = self.take_snapshot(collection_id, manifest_version, live)?;
+        self.snapshot_records(&snapshot, live, chunk_ids)
+    }
+
+    /// Fetch a chunk and up to `before` / `after` chunks either side of it in
+    /// its document, ordered by ordinal. Reads the same state as
+    /// `get_records`. Empty when the chunk is missing or deleted; an error
+    /// when it has no ordinal. Chunks of the document without one are skipped.
+    pub fn get_neighbors(
+        &self,
+        collection_id: &str,
+        chunk_id: &str,
+        before: usize,
+        after: usize,
+        manifest_version: Option<i64>,
+    ) -> Result<Vec<StoredRecord>> {
+        let live = manifest_version.is_none();
+        let snapshot = self.take_snapshot(collection_id, manifest_version, live)?;
+        let Some(hit) = self.snapshot_records(&snapshot, live, &[chunk_id.to_string()])?.pop() else {
+            return Ok(Vec::new());
+        };
+        if hit.ordinal.is_none() {
+            return Err(AkiDbError::InvalidArgument(format!("Chunk \"{chunk_id}\" has no ordinal")));
+        }
+
+        let mut chunk_ids: HashSet<String> = self
+            .query_engine
+            .document_chunk_ids(&self.storage, &snapshot.manifest.segment_paths, &hit.doc_id)?
+            .into_iter()
+            .collect();
+        if live {
+            overlay_buffered_chunks(&mut chunk_ids, &snapshot.buffer_records, &hit.doc_id);
+        }
+        let chunk_ids: Vec<String> = chunk_ids.into_iter().collect();
+        let mut records: Vec<StoredRecord> = self
+            .snapshot_records(&snapshot, live, &chunk_ids)?
+            .into_iter()
+            .filter(|r| r.ordinal.is_some())
+            .collect();
+        records.sort_by(|a, b| a.ordinal.cmp(&b.ordinal).then_with(|| a.chunk_id.cmp(&b.chunk_id)));
+
+        let position = records.iter().position(|r| r.chunk_id == chunk_id).unwrap_or(0);
+        let start = position.saturating_sub(before);
+        let end = (position + after + 1).min(records.len());
+        Ok(records.drain(start..end).collect())
+    }
+
+    /// `chunk_ids`' records in `snapshot`, in request order with duplicates
+    /// and missing or deleted chunks dropped. `live` lets the write buffer
+    /// override the segments.
+    fn snapshot_records(
+        &self,
+        snapshot: &SearchSnapshot,
+        live: bool,
+        chunk_ids: &[String],
+    ) -> Result<Vec<StoredRecord>> {
+        let SearchSnapshot { buffer_records, manifest, .. } = snapshot;
+        let mut committed = self.query_engine.get_records(&self.storage, manifest, chunk_ids)?;
+
+        let mut seen = HashSet::new();
+        let mut records = Vec::new();
+        for chunk_id in chunk_ids {
+            if !seen.insert(chunk_id.as_str()) {
+                continue;
+            }
+            let buffered = live
+                .then(|| buffer_records.iter().rev().find(|rec| &rec.chunk_id == chunk_id))
+                .flatten();
+            let record = match buffered {
+                Some(_) if manifest.live_tombstone_set.contains(chunk_id) => None,
+                Some(rec) => Some(StoredRecord {
+                    chunk_id: rec.chunk_id.clone(),
+                    doc_id: rec.doc_id.clone(),
+                    ordinal: rec.ordinal,
+                    vector: rec.vector.clone(),
+                    metadata: rec.metadata.clone(),
+                    chunk_text: rec.chunk_text.clone(),
+                    committed: false,
+                }),
+                None => committed.remove(chunk_id),
+            };
+            records.extend(record);
+        }
+        Ok(records)
+    }
+
+    /// Capture the write buffer and the manifest/tombstone state a search reads.
+    /// A `search_after` page reads its cursor's manifest and the buffered
+    /// records the first page saw, but applies pending deletes like the first
+    /// page did.
+    fn take_search_snapshot(&self, opts: &SearchOptions) -> Result<SearchSnapshot> {
+        let pending_deletes = opts.include_uncommitted && opts.manifest_version.is_none();
+        let mut snapshot = self.take_snapshot(&opts.collection_id, opts.pinned_manifest_version(), pending_deletes)?;
+        if let Some(cursor) = &opts.search_after
+            && opts.include_uncommitted
+        {
+            // Records are only appended between drains, so the first page's
+            // buffer is a prefix of this one unless it has been flushed since.
+            if cursor.buffer_generation != snapshot.buffer_generation
+                || cursor.buffer_len > snapshot.buffer_records.len()
+            {
+                return Err(AkiDbError::InvalidArgument(
+                    "searchAfter cursor's uncommitted records have been flushed since it was issued; restart the search"
+                        .into(),
+                ));
+            }
+            snapshot.buffer_records.truncate(cursor.buffer_len);
+        }
+        Ok(snapshot)
+    }
+
+    /// Capture the write buffer and the manifest (latest, or `manifest_version`)
+    /// with its tombstones, plus pending ones when `pending_deletes` is set.
+    fn take_snapshot(
+        &self,
+        collection_id: &str,
+        manifest_version: Option<i64>,
+        pending_deletes: bool,
+    ) -> Result<SearchSnapshot> {
         let write_path = {
             let write_paths = self.lock_write_paths()?;
-            write_paths.get(&opts.collection_id).cloned()
+            write_paths.get(collection_id).cloned()
         };
 
         // Snapshot the write buffer before touching metadata so search does not
         // invert the lock order used by flush/build paths (write path -> metadata).
-        // The clone is limited to uncommitted records for the queried collection.
-        let buffer_records = if let Some(write_path) = write_path {
+        // The clone is limited to uncommitted records for the queried collection;
+        // the buffer's HNSW graph and keyword index are shared, not copied.
+        let (buffer_records, buffer_generation, buffer_index, buffer_keyword_index) = if let Some(write_path) = write_path {
             let write_path = write_path
                 .lock()
                 .map_err(|_| AkiDbError::InvalidArgument("Write path lock poisoned".to_string()))?;
-            write_path.peek_buffer().to_vec()
+            (
+                write_path.peek_buffer().to_vec(),
+                write_path.buffer_generation(),
+                write_path.peek_buffer_index(),
+                write_path.peek_buffer_keyword_index(),
+            )
         } else {
-            Vec::new()
+            (Vec::new(), 0, None, None)
         };
 
-        if opts.mode == SearchMode::Vector {
-            let snapshot = self.build_vector_snapshot(&opts, &buffer_records)?;
-            let mut response = self.query_engine.search_vector_with_snapshot(
-                &self.storage,
-                &opts,
-                &buffer_records,
-                &snapshot,
-            )?;
+        let manifest = self.build_manifest_snapshot(
+            collection_id,
+            manifest_version,
+            pending_deletes,
+            buffer_index,
+            buffer_keyword_index,
+        )?;
+        Ok(SearchSnapshot { buffer_records, buffer_generation, manifest })
+    }
 
-            if opts.explain {
-                let metadata = self.lock_metadata()?;
-                let preview_map = Self::load_previews(&metadata, &response.results);
-                let matched_terms = Self::matched_terms(opts.query_text.as_deref());
-                for (rank, r) in response.results.iter_mut().enumerate() {
-                    r.explain = Some(Self::build_explain(
-                        &preview_map,
-                        &r.chunk_id,
-                        &matched_terms,
-                        Some(r.score),
-                        None,
-                        None,
-                        Some(rank + 1),
-                        None,
-                    ));
-                }
-            }
+    /// Search one page, continuing after `opts.search_after` when set, with
+    /// a cursor for the next page when more results follow.
+    fn search_page(&self, opts: &SearchOptions, snapshot: &SearchSnapshot) -> Result<SearchResponse> {
+        let (mut response, page) = self.ranked_search(opts, snapshot)?;
+        if opts.group_by.is_none()
+            && page.has_more
+            && let (Some(last), Some(&score)) = (response.results.last(), page.rank_scores.last())
+        {
+            let cursor = SearchCursor {
+                manifest_version: response.manifest_version_used,
+                query: opts.query_fingerprint(),
+                leg_depth: page.leg_depth,
+                seen_depth: page.seen_depth,
+                buffer_generation: snapshot.buffer_generation,
+                buffer_len: snapshot.buffer_records.len(),
+                score,
+                chunk_id: last.chunk_id.clone(),
+                offset: opts.search_after.as_ref().map_or(0, |c| c.offset) + response.results.len(),
+            };
+            response.cursor = Some(cursor.encode());
+        }
+        Ok(response)
+    }
+
+    fn search_with_snapshot(
+        &self,
+        opts: &SearchOptions,
+        snapshot: &SearchSnapshot,
+    ) -> Result<SearchResponse> {
+        self.ranked_search(opts, snapshot).map(|(response, _)| response)
+    }
 
-            Ok(response)
-        } else if opts.mode == SearchMode::Keyword {
-            let snapshot = self.build_manifest_search_snapshot(&opts)?;
-            let mut response = {
-                let metadata = self.lock_metadata()?;
-                let query_text = opts.query_text.as_deref().unwrap_or("");
-                let results = keyword::keyword_search(
-                    &metadata,
-                    &opts.collection_id,
-                    query_text,
-                    opts.top_k,
-                    &snapshot.live_tombstone_set,
+    /// Run one search, or with `opts.search_after` the page after its cursor.
+    fn ranked_search(
+        &self,
+        opts: &SearchOptions,
+        snapshot: &SearchSnapshot,
+    ) -> Result<(SearchResponse, RankedPage)> {
+        let SearchSnapshot { buffer_records, manifest: snapshot, .. } = snapshot;
+        // A continuation ranks the earlier pages too, then skips them by cursor key.
+        let cursor = opts.search_after.as_ref();
+        // Ungrouped searches rank one result past the page, only to tell
+        // whether another page follows.
+        let lookahead = usize::from(opts.group_by.is_none());
+        let first_rank = cursor.map_or(1, |c| c.offset + 1);
+        let analyzer = Analyzer::from_collection(&snapshot.collection);
+        // Buffered records the keyword leg scores alongside the segments.
+        let uncommitted: &[NativeRecord] = if opts.include_uncommitted { buffer_records } else { &[] };
+        match opts.mode {
+            SearchMode::Vector => {
+                let depth = cursor.map_or(opts.top_k, |c| c.offset + opts.top_k) + lookahead;
+                let deep_opts = SearchOptions { top_k: depth, ..opts.clone() };
+                let mut response = self.query_engine.search_vector_with_snapshot(
+                    &self.storage,
+                    &deep_opts,
+                    buffer_records,
+                    snapshot,
                 )?;
-                SearchResponse {
-                    results,
-                    manifest_version_used: snapshot.manifest.version,
+                if let Some(cursor) = cursor {
+                    response.results.sort_by(|a, b| cursor::rank_order(a.score, &a.chunk_id, b.score, &b.chunk_id));
+                    response.results.retain(|r| cursor.precedes(r.score, &r.chunk_id));
                 }
-            };
-
-            if opts.explain {
-                let preview_map = {
-                    let metadata = self.lock_metadata()?;
-                    Self::load_previews(&metadata, &response.results)
-                };
-                let matched_terms = Self::matched_terms(opts.query_text.as_deref());
-                for (rank, r) in response.results.iter_mut().enumerate() {
-                    r.explain = Some(Self::build_explain(
-                        &preview_map,
-                        &r.chunk_id,
-                        &matched_terms,
-                        None,
-                        Some(r.score),
-                        None,
-                        None,
-                        Some(rank + 1),
-                    ));
+                let has_more = lookahead > 0 && response.results.len() > opts.top_k;
+                if lookahead > 0 {
+                    response.results.truncate(opts.top_k);
                 }
-            }
 
-            Ok(response)
-        } else if opts.mode == SearchMode::Hybrid {
-            let snapshot = self.build_vector_snapshot(&opts, &buffer_records)?;
-            let vector_opts = SearchOptions {
-                collection_id: opts.collection_id.clone(),
-                query_vector: opts.query_vector.clone(),
-                top_k: opts.top_k * 2,
-                filters: opts.filters.clone(),
-                manifest_version: opts.manifest_version,
-                include_uncommitted: opts.include_uncommitted,
-                mode: SearchMode::Vector,
-                query_text: None,
-                vector_weight: opts.vector_weight,
-                keyword_weight: opts.keyword_weight,
-                explain: false,
-                ef_search: opts.ef_search,
-            };
-            let vector_response = self.query_engine.search_vector_with_snapshot(
-                &self.storage,
-                &vector_opts,
-                &buffer_records,
-                &snapshot,
-            )?;
+                if opts.explain {
+                    let preview_map = self.load_previews(snapshot, buffer_records, &response.results)?;
+                    let highlight_query = Self::highlight_query(opts, &analyzer);
+                    for (rank, r) in response.results.iter_mut().enumerate() {
+                        r.explain = Some(Self::build_explain(
+                            &preview_map,
+                            &r.chunk_id,
+                            highlight_query.as_ref(),
+                            &analyzer,
+                            Some(r.score),
+                            None,
+                            None,
+                            Some(first_rank + rank),
+                            None,
+                        ));
+                    }
+                }
 
-            let (keyword_results, preview_map) = {
-                let metadata = self.lock_metadata()?;
-                let query_text = opts.query_text.as_deref().unwrap_or("");
-                let keyword_results = keyword::keyword_search(
-                    &metadata,
-                    &opts.collection_id,
-                    query_text,
-                    opts.top_k * 2,
-                    &snapshot.live_tombstone_set,
-                )?;
-                let preview_map = if opts.explain {
-                    let fused_preview_ids = hybrid::rrf_fuse(
-                        &vector_response.results,
+                let rank_scores = response.results.iter().map(|r| r.score).collect();
+                Ok((response, RankedPage { rank_scores, leg_depth: 0, seen_depth: 0, has_more }))
+            }
+            // Hybrid: vector leg over the snapshot, fused with the keyword leg.
+            SearchMode::Hybrid => {
+                let keyword_query = syntax::parse(opts.query_text.as_deref().unwrap_or(""), opts.keyword_syntax, &analyzer)?;
+                // Fusion scores depend on how deep each leg is, so pages split
+                // the fusion of legs of one depth. Once it runs out, the legs
+                // double in depth and paging continues with the chunks the
+                // deeper fusion adds; the first `seen_depth` entries of each
+                // deeper leg stand in for the shallower legs.
+                let (mut leg_depth, mut seen_depth) =
+                    cursor.map_or((opts.candidate_depth() * 2, 0), |c| (c.leg_depth, c.seen_depth));
+                let mut after = cursor;
+                let mut fused: Vec<SearchResult> = Vec::new();
+                // `(leg_depth, seen_depth)` of the fusion each result came from.
+                let mut fusions: Vec<(usize, usize)> = Vec::new();
+                loop {
+                    let (vector_results, keyword_results) =
+                        self.hybrid_legs(opts, snapshot, buffer_records, uncommitted, &keyword_query, leg_depth)?;
+                    let candidates = vector_results.iter().chain(&keyword_results);
+                    let grouping = self.query_engine.grouping(&self.storage, opts, snapshot, uncommitted, candidates)?;
+                    let mut results = hybrid::fuse(
+                        &vector_results,
                         &keyword_results,
-                        opts.top_k,
+                        if grouping.is_some() { opts.top_k } else { usize::MAX },
                         opts.vector_weight,
                         opts.keyword_weight,
-                    )
-                    .into_iter()
-                    .map(|r| r.chunk_id)
-                    .collect::<Vec<_>>();
-                    metadata
-                        .fts_get_texts(&fused_preview_ids)
-                        .unwrap_or_default()
-                } else {
-                    HashMap::new()
-                };
-                (keyword_results, preview_map)
-            };
-
-            let mut fused = hybrid::rrf_fuse(
-                &vector_response.results,
-                &keyword_results,
-                opts.top_k,
-                opts.vector_weight,
-                opts.keyword_weight,
-            );
+                        opts.fusion,
+                        grouping.as_ref(),
+                    );
+                    let seen: HashSet<&str> = vector_results
+                        .iter()
+                        .take(seen_depth)
+                        .chain(keyword_results.iter().take(seen_depth))
+                        .map(|r| r.chunk_id.as_str())
+                        .collect();
+                    results.retain(|r| {
+                        !seen.contains(r.chunk_id.as_str()) && after.is_none_or(|c| c.precedes(r.score, &r.chunk_id))
+                    });
+                    if lookahead > 0 {
+                        results.truncate(opts.top_k + lookahead - fused.len());
+                    }
+                    if opts.explain {
+                        self.explain_fused(opts, snapshot, buffer_records, &analyzer, &vector_results, &keyword_results, &mut results)?;
+                    }
+                    fusions.extend(std::iter::repeat_n((leg_depth, seen_depth), results.len()));
+                    fused.extend(results);
+
+                    // Only a leg that came back full can add chunks when deeper.
+                    let deeper = vector_results.len() >= leg_depth || keyword_results.len() >= leg_depth;
+                    if lookahead == 0 || fused.len() > opts.top_k || !deeper {
+                        break;
+                    }
+                    seen_depth = leg_depth;
+                    leg_depth = leg_depth.saturating_mul(2);
+                    after = None;
+                }
+                let has_more = lookahead > 0 && fused.len() > opts.top_k;
+                if lookahead > 0 {
+                    fused.truncate(opts.top_k);
+                }
+                let (leg_depth, seen_depth) = fused.len().checked_sub(1).map_or((0, 0), |last| fusions[last]);
 
-            if opts.explain {
-                let matched_terms = Self::matched_terms(opts.query_text.as_deref());
-                let vector_map: HashMap<String, (usize, f64)> = vector_response
-                    .results
-                    .iter()
-                    .enumerate()
-                    .map(|(i, r)| (r.chunk_id.clone(), (i + 1, r.score)))
-                    .collect();
-                let keyword_map: HashMap<String, (usize, f64)> = keyword_results
-                    .iter()
-                    .enumerate()
-                    .map(|(i, r)| (r.chunk_id.clone(), (i + 1, r.score)))
-                    .collect();
+                let rank_scores = fused.iter().map(|r| r.score).collect();
+                let response = SearchResponse {
+                    results: fused,
+                    manifest_version_used: snapshot.manifest.version,
+                    groups: None,
+                    cursor: None,
+                };
+                Ok((response, RankedPage { rank_scores, leg_depth, seen_depth, has_more }))
+            }
+            SearchMode::Keyword => {
+                let keyword_query = syntax::parse(opts.query_text.as_deref().unwrap_or(""), opts.keyword_syntax, &analyzer)?;
+                let depth = cursor.map_or(opts.candidate_depth(), |c| c.offset + opts.top_k);
+                let mut results = self.query_engine.search_keyword_with_snapshot(
+                    &self.storage,
+                    &keyword_query,
+                    depth + lookahead,
+                    opts.filters.as_ref(),
+                    uncommitted,
+                    snapshot,
+                )?;
+                if let Some(grouping) =
+                    self.query_engine.grouping(&self.storage, opts, snapshot, uncommitted, &results)?
+                {
+                    results = grouping.limit(results);
+                }
+                // The lookahead result is not normalized along with the rest.
+                let has_more = lookahead > 0 && results.len() > depth;
+                if lookahead > 0 {
+                    results.truncate(depth);
+                }
+                // Pages are cut by raw BM25 score, which unlike the normalized
+                // score does not depend on how deep the search went.
+                let mut rank_scores: Vec<f64> = results.iter().map(|r| r.score).collect();
+                keyword::normalize_scores(&mut results);
+                if let Some(cursor) = cursor {
+                    (results, rank_scores) = results
+                        .into_iter()
+                        .zip(rank_scores)
+                        .filter(|(r, raw)| cursor.precedes(*raw, &r.chunk_id))
+                        .take(opts.top_k)
+                        .unzip();
+                }
+                let mut response = SearchResponse {
+                    results,
+                    manifest_version_used: snapshot.manifest.version,
+                    groups: None,
+                    cursor: None,
+                };
 
-                for r in &mut fused {
-                    let v_info = vector_map.get(&r.chunk_id);
-                    let k_info = keyword_map.get(&r.chunk_id);
-                    r.explain = Some(Self::build_explain(
-                        &preview_map,
-                        &r.chunk_id,
-                        &matched_terms,
-                        v_info.map(|(_, s)| *s),
-                        k_info.map(|(_, s)| *s),
-                        Some(r.score),
-                        v_info.map(|(rank, _)| *rank),
-                        k_info.map(|(rank, _)| *rank),
-                    ));
+                if opts.explain {
+                    let preview_map = self.load_previews(snapshot, buffer_records, &response.results)?;
+                    let highlight_query = Self::highlight_query(opts, &analyzer);
+                    for (rank, r) in response.results.iter_mut().enumerate() {
+                        r.explain = Some(Self::build_explain(
+                            &preview_map,
+                            &r.chunk_id,
+                            highlight_query.as_ref(),
+                            &analyzer,
+                            None,
+                            Some(r.score),
+                            None,
+                            None,
+                            Some(first_rank + rank),
+                        ));
+                    }
                 }
+
+                Ok((response, RankedPage { rank_scores, leg_depth: 0, seen_depth: 0, has_more }))
             }
+        }
+    }
 
-            Ok(SearchResponse {
-                results: std::mem::take(&mut fused),
-                manifest_version_used: snapshot.manifest.version,
-            })
-        } else {
-            let metadata = self.lock_metadata()?;
-            self.query_engine
-                .search(&metadata, &self.storage, &opts, &buffer_records)
+
+    /// The vector and keyword legs of a hybrid search, `leg_depth` deep; the
+    /// keyword leg's scores are normalized.
+    fn hybrid_legs(
+        &self,
+        opts: &SearchOptions,
+        snapshot: &ManifestSnapshot,
+        buffer_records: &[NativeRecord],
+        uncommitted: &[NativeRecord],
+        keyword_query: &KeywordQuery,
+        leg_depth: usize,
+    ) -> Result<(Vec<SearchResult>, Vec<SearchResult>)> {
+        let vector_opts = SearchOptions {
+            top_k: leg_depth,
+            mode: SearchMode::Vector,
+            query_text: None,
+            explain: false,
+            include_metadata: false,
+            include_text: false,
+            include_vector: false,
+            group_by: None,
+            max_per_group: None,
+            search_after: None,
+            ..opts.clone()
+        };
+        let vector_response = self.query_engine.search_vector_with_snapshot(
+            &self.storage,
+            &vector_opts,
+            buffer_records,
+            snapshot,
+        )?;
+        let mut keyword_results = self.query_engine.search_keyword_with_snapshot(
+            &self.storage,
+            keyword_query,
+            leg_depth,
+            opts.filters.as_ref(),
+            uncommitted,
+            snapshot,
+        )?;
+        keyword::normalize_scores(&mut keyword_results);
+        Ok((vector_response.results, keyword_results))
+    }
+
+    /// Explain fused hybrid results by their ranks and scores in each leg.
+    #[allow(clippy::too_many_arguments)]
+    fn explain_fused(
+        &self,
+        opts: &SearchOptions,
+        snapshot: &ManifestSnapshot,
+        buffer_records: &[NativeRecord],
+        analyzer: &Analyzer,
+        vector_results: &[SearchResult],
+        keyword_results: &[SearchResult],
+        fused: &mut [SearchResult],
+    ) -> Result<()> {
+        let preview_map = self.load_previews(snapshot, buffer_records, fused)?;
+        let highlight_query = Self::highlight_query(opts, analyzer);
+        // Per chunk: 1-based rank, raw score and normalized score in each leg.
+        let vector_map: HashMap<String, (usize, f64, f64)> = vector_results
+            .iter()
+            .zip(opts.fusion.normalize(vector_results))
+            .enumerate()
+            .map(|(i, (r, norm))| (r.chunk_id.clone(), (i + 1, r.score, norm)))
+            .collect();
+        let keyword_map: HashMap<String, (usize, f64, f64)> = keyword_results
+            .iter()
+            .zip(opts.fusion.normalize(keyword_results))
+            .enumerate()
+            .map(|(i, (r, norm))| (r.chunk_id.clone(), (i + 1, r.score, norm)))
+            .collect();
+        let (vector_weight, keyword_weight) =
+            opts.fusion.effective_weights(opts.vector_weight, opts.keyword_weight);
+
+        for r in fused {
+            let v_info = vector_map.get(&r.chunk_id);
+            let k_info = keyword_map.get(&r.chunk_id);
+            let mut explain = Self::build_explain(
+                &preview_map,
+                &r.chunk_id,
+                highlight_query.as_ref(),
+                analyzer,
+                v_info.map(|(_, s, _)| *s),
+                k_info.map(|(_, s, _)| *s),
+                opts.fusion.rrf_k().map(|_| r.score),
+                v_info.map(|(rank, _, _)| *rank),
+                k_info.map(|(rank, _, _)| *rank),
+            );
+            explain.fusion = Some(FusionInfo {
+                method: opts.fusion.name().to_string(),
+                rrf_k: opts.fusion.rrf_k(),
+                vector_weight,
+                keyword_weight,
+                vector_normalized: v_info.map(|(_, _, norm)| *norm),
+                keyword_normalized: k_info.map(|(_, _, norm)| *norm),
+                score: r.score,
+            });
+            r.explain = Some(explain);
         }
+        Ok(())
     }
 
     // ─── Delete ─────────────────────────────────────────────────────────────
@@ -505,13 +982,101 @@ impl EngineInner {
                 deleted_at: now.clone(),
                 reason_code: reason_code.to_string(),
             })?;
-            // Remove from FTS5 index.
-            let _ = metadata.fts_delete(chunk_id, collection_id);
             count += 1;
         }
         Ok(count)
     }
 
+    /// Delete every chunk of a document. Returns the number of chunks deleted.
+    pub fn delete_document(&self, collection_id: &str, doc_id: &str) -> Result<usize> {
+        self.writable_collection(collection_id, &[])?;
+        let write_path = self.get_or_create_write_path(collection_id)?;
+        let write_path = write_path
+            .lock()
+            .map_err(|_| AkiDbError::InvalidArgument("Write path lock poisoned".to_string()))?;
+        let chunk_ids = self.document_chunk_ids(collection_id, doc_id, write_path.peek_buffer())?;
+        self.delete_chunks(collection_id, &chunk_ids, "file_deleted")
+    }
+
+    /// Replace a document's chunks with `records`, which must all carry
+    /// `doc_id`: the new chunks are upserted and then old chunks missing
+    /// from `records` deleted, under one write-path lock. Chunk IDs the new
+    /// version reuses are superseded by the upsert rather than deleted.
+    pub fn replace_document(
+        &self,
+        collection_id: &str,
+        doc_id: &str,
+        records: &[NativeRecord],
+    ) -> Result<UpsertResult> {
+        let collection = self.writable_collection(collection_id, records)?;
+        if let Some(rec) = records.iter().find(|rec| rec.doc_id != doc_id) {
+            return Err(AkiDbError::InvalidArgument(format!(
+                "Record \"{}\" has doc_id \"{}\", expected \"{}\"",
+                rec.chunk_id, rec.doc_id, doc_id
+            )));
+        }
+
+        let write_path = self.get_or_create_write_path(collection_id)?;
+        let mut write_path = write_path
+            .lock()
+            .map_err(|_| AkiDbError::InvalidArgument("Write path lock poisoned".to_string()))?;
+        let kept: HashSet<&str> = records.iter().map(|rec| rec.chunk_id.as_str()).collect();
+        let stale: Vec<String> = self
+            .document_chunk_ids(collection_id, doc_id, write_path.peek_buffer())?
+            .into_iter()
+            .filter(|chunk_id| !kept.contains(chunk_id.as_str()))
+            .collect();
+        // Upsert before tombstoning, so a failed write leaves the old version
+        // searchable instead of deleting it without a replacement.
+        let result = write_path.upsert_batch(
+            collection_id,
+            records,
+            collection.dimension as usize,
+            &collection.metric,
+            IndexParams::from_collection(&collection),
+        )?;
+        self.delete_chunks(collection_id, &stale, "file_updated")?;
+        Ok(result)
+    }
+
+    /// Undeleted chunks of `doc_id` across every flushed segment, published
+    /// or not (in the order `auto_publish` publishes them), and
+    /// `buffer_records`, with the latest write of each chunk deciding its
+    /// document.
+    fn document_chunk_ids(
+        &self,
+        collection_id: &str,
+        doc_id: &str,
+        buffer_records: &[NativeRecord],
+    ) -> Result<Vec<String>> {
+        let (segment_paths, deleted) = {
+            let metadata = self.lock_metadata()?;
+            let segment_paths: Vec<(String, String)> = metadata
+                .list_segments(collection_id, Some("ready"))?
+                .into_iter()
+                .map(|segment| (segment.segment_id, segment.storage_path))
+                .collect();
+            let deleted: HashSet<String> = metadata
+                .get_latest_manifest(collection_id)?
+                .map(|manifest| manifest.tombstone_ids)
+                .unwrap_or_default()
+                .into_iter()
+                .chain(metadata.list_tombstone_chunk_ids(collection_id)?)
+                .collect();
+            (segment_paths, deleted)
+        };
+
+        let mut chunk_ids: HashSet<String> = self
+            .query_engine
+            .document_chunk_ids(&self.storage, &segment_paths, doc_id)?
+            .into_iter()
+            .collect();
+        overlay_buffered_chunks(&mut chunk_ids, buffer_records, doc_id);
+        let mut chunk_ids: Vec<String> = chunk_ids.into_iter().filter(|id| !deleted.contains(id)).collect();
+        chunk_ids.sort();
+        Ok(chunk_ids)
+    }
+
     // ─── Compaction ─────────────────────────────────────────────────────────
 
     pub fn compact(&self, collection_id: &str) -> Result<CompactResult> {
@@ -528,7 +1093,6 @@ impl EngineInner {
         if !pending_tombstone_ids.is_empty() {
             metadata.delete_tombstones(&pending_tombstone_ids)?;
         }
-        self.rebuild_fts_for_manifest(&metadata, &manifest)?;
         Ok(manifest)
     }
 
@@ -598,19 +1162,22 @@ impl EngineInner {
         Ok(write_paths.get(collection_id).cloned().unwrap())
     }
 
-    fn build_vector_snapshot(
+    fn build_manifest_snapshot(
         &self,
-        opts: &SearchOptions,
-        _buffer_records: &[NativeRecord],
-    ) -> Result<VectorSearchSnapshot> {
+        collection_id: &str,
+        manifest_version: Option<i64>,
+        pending_deletes: bool,
+        buffer_index: Option<Arc<HnswGraph>>,
+        buffer_keyword_index: Option<Arc<KeywordIndex>>,
+    ) -> Result<ManifestSnapshot> {
         let metadata = self.lock_metadata()?;
         let collection = metadata
-            .get_collection(&opts.collection_id)?
-            .ok_or_else(|| AkiDbError::CollectionNotFound(opts.collection_id.clone()))?;
-        let snapshot = build_manifest_search_snapshot_from_metadata(&metadata, opts)?;
-        let live_tombstone_fingerprint = tombstone_fingerprint(&snapshot.live_tombstone_set);
-        let segment_paths: Vec<(String, String)> = snapshot
-            .manifest
+            .get_collection(collection_id)?
+            .ok_or_else(|| AkiDbError::CollectionNotFound(collection_id.to_string()))?;
+        let (manifest, live_tombstone_set) =
+            resolve_search_manifest(&metadata, collection_id, manifest_version, pending_deletes)?;
+        let live_tombstone_fingerprint = tombstone_fingerprint(&live_tombstone_set);
+        let segment_paths: Vec<(String, String)> = manifest
             .segment_ids
             .iter()
             .map(|segment_id| {
@@ -620,23 +1187,17 @@ impl EngineInner {
                     .ok_or_else(|| AkiDbError::SegmentNotFound(segment_id.clone()))
             })
             .collect::<Result<Vec<_>>>()?;
-        Ok(VectorSearchSnapshot {
+        Ok(ManifestSnapshot {
             collection,
-            manifest: snapshot.manifest,
+            manifest,
             segment_paths,
-            live_tombstone_set: snapshot.live_tombstone_set,
+            live_tombstone_set,
             live_tombstone_fingerprint,
+            buffer_index,
+            buffer_keyword_index,
         })
     }
 
-    fn build_manifest_search_snapshot(
-        &self,
-        opts: &SearchOptions,
-    ) -> Result<ManifestSearchSnapshot> {
-        let metadata = self.lock_metadata()?;
-        build_manifest_search_snapshot_from_metadata(&metadata, opts)
-    }
-
     fn lock_metadata(&self) -> Result<MutexGuard<'_, MetadataStore>> {
         self.metadata
             .lock()
@@ -649,99 +1210,147 @@ impl EngineInner {
             .map_err(|_| AkiDbError::InvalidArgument("Write paths lock poisoned".to_string()))
     }
 
+    /// Chunk texts for explain previews: committed results are read from the
+    /// snapshot's segments, uncommitted ones from the write buffer.
     fn load_previews(
-        metadata: &MetadataStore,
+        &self,
+        snapshot: &ManifestSnapshot,
+        buffer_records: &[NativeRecord],
         results: &[SearchResult],
-    ) -> HashMap<String, String> {
-        metadata
-            .fts_get_texts(
-                &results
-                    .iter()
-                    .map(|result| result.chunk_id.clone())
-                    .collect::<Vec<_>>(),
-            )
-            .unwrap_or_default()
+    ) -> Result<HashMap<String, String>> {
+        Self::load_fields(
+            results,
+            buffer_records,
+            |ids| self.query_engine.chunk_texts(&self.storage, snapshot, ids),
+            |rec| rec.chunk_text.clone(),
+        )
     }
 
-    fn matched_terms(query_text: Option<&str>) -> Vec<String> {
-        query_text
-            .map(|text| {
-                text.split_whitespace()
-                    .map(|term| term.to_string())
-                    .collect()
-            })
-            .unwrap_or_default()
+    /// Record metadata for `results`, read like `load_previews`.
+    fn load_metadata(
+        &self,
+        snapshot: &ManifestSnapshot,
+        buffer_records: &[NativeRecord],
+        results: &[SearchResult],
+    ) -> Result<HashMap<String, serde_json::Value>> {
+        Self::load_fields(
+            results,
+            buffer_records,
+            |ids| self.query_engine.chunk_metadata(&self.storage, snapshot, ids),
+            |rec| Some(rec.metadata.clone()),
+        )
     }
 
-    #[allow(clippy::too_many_arguments)]
-    fn build_explain(
-        preview_map: &HashMap<String, String>,
-        chunk_id: &str,
-        matched_terms: &[String],
+    /// Record vectors for `results`, read like `load_previews`. Buffered
+    /// vectors are returned as written, segment vectors decoded.
+    fn load_vectors(
+        &self,
+        snapshot: &ManifestSnapshot,
+        buffer_records: &[NativeRecord],
+        results: &[SearchResult],
+    ) -> Result<HashMap<String, Vec<f32>>> {
+        Self::load_fields(
+            results,
+            buffer_records,
+            |ids| self.query_engine.chunk_vectors(&self.storage, snapshot, ids),
+            |rec| Some(rec.vector.clone()),
+        )
+    }
+
+    /// Per-chunk values for `results`: committed results through `committed`,
+    /// uncommitted ones from their latest write-buffer record through `buffered`.
+    fn load_fields<T>(
+        results: &[SearchResult],
+        buffer_records: &[NativeRecord],
+        committed: impl FnOnce(&[String]) -> Result<HashMap<String, T>>,
+        buffered: impl Fn(&NativeRecord) -> Option<T>,
+    ) -> Result<HashMap<String, T>> {
+        let (committed_results, uncommitted): (Vec<&SearchResult>, Vec<&SearchResult>) =
+            results.iter().partition(|r| r.committed != Some(false));
+        let committed_ids: Vec<String> = committed_results.iter().map(|r| r.chunk_id.clone()).collect();
+        let mut fields = committed(&committed_ids)?;
+        for result in uncommitted {
+            if let Some(value) = buffer_records
+                .iter()
+                .rev()
+                .find(|rec| rec.chunk_id == result.chunk_id)
+                .and_then(&buffered)
+            {
+                fields.insert(result.chunk_id.clone(), value);
+            }
+        }
+        Ok(fields)
+    }
+
+    /// Fill in the stored payloads `opts` asks for.
+    fn attach_payloads(
+        &self,
+        opts: &SearchOptions,
+        snapshot: &SearchSnapshot,
+        results: &mut [SearchResult],
+    ) -> Result<()> {
+        let SearchSnapshot { buffer_records, manifest, .. } = snapshot;
+        if opts.include_metadata {
+            let mut metadata = self.load_metadata(manifest, buffer_records, results)?;
+            for r in results.iter_mut() {
+                r.metadata = metadata.remove(&r.chunk_id);
+            }
+        }
+        if opts.include_text {
+            let mut texts = self.load_previews(manifest, buffer_records, results)?;
+            for r in results.iter_mut() {
+                r.text = texts.remove(&r.chunk_id);
+            }
+        }
+        if opts.include_vector {
+            let mut vectors = self.load_vectors(manifest, buffer_records, results)?;
+            for r in results.iter_mut() {
+                r.vector = vectors.remove(&r.chunk_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// The query explain output highlights: the keyword query, or for vector
+    /// search any query text given alongside the vector. Text that does not
+    /// parse is not highlighted (only keyword modes validate it).
+    fn highlight_query(opts: &SearchOptions, analyzer: &Analyzer) -> Option<KeywordQuery> {
+        let text = opts.query_text.as_deref()?;
+        syntax::parse(text, opts.keyword_syntax, analyzer).ok()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn build_explain(
+        preview_map: &HashMap<String, String>,
+        chunk_id: &str,
+        highlight_query: Option<&KeywordQuery>,
+        analyzer: &Analyzer,
         vector_score: Option<f64>,
         bm25_score: Option<f64>,
         rrf_score: Option<f64>,
         vector_rank: Option<usize>,
         bm25_rank: Option<usize>,
     ) -> ExplainInfo {
+        let text = preview_map.get(chunk_id);
+        let highlights = match (text, highlight_query) {
+            (Some(text), Some(query)) => highlight::highlight(query, text, analyzer),
+            _ => Highlights::default(),
+        };
+        let (snippet_start, snippet) = highlights.snippet.unzip();
         ExplainInfo {
             vector_score,
             bm25_score,
             rrf_score,
             vector_rank,
             bm25_rank,
-            chunk_preview: preview_map
-                .get(chunk_id)
-                .map(|text| text.chars().take(200).collect()),
-            matched_terms: matched_terms.to_vec(),
-        }
-    }
-
-    fn rebuild_fts_for_manifest(
-        &self,
-        metadata: &MetadataStore,
-        manifest: &Manifest,
-    ) -> Result<()> {
-        metadata.fts_delete_collection(&manifest.collection_id)?;
-
-        let tombstone_set: std::collections::HashSet<&str> =
-            manifest.tombstone_ids.iter().map(String::as_str).collect();
-        let mut fts_owned: Vec<(String, String, String)> = Vec::new();
-
-        for segment_id in &manifest.segment_ids {
-            let segment = metadata
-                .get_segment(segment_id)?
-                .ok_or_else(|| AkiDbError::SegmentNotFound(segment_id.clone()))?;
-            let buffer = self.storage.get_object(&segment.storage_path)?;
-            let reader = crate::segment::reader::SegmentReader::from_buffer(buffer)?;
-            let chunk_ids = reader.get_chunk_ids()?;
-            let Some(chunk_texts) = reader.get_chunk_texts() else {
-                continue;
-            };
-
-            for (chunk_id, chunk_text) in chunk_ids.into_iter().zip(chunk_texts.into_iter()) {
-                if tombstone_set.contains(chunk_id.as_str()) || chunk_text.is_empty() {
-                    continue;
-                }
-                fts_owned.push((chunk_id, manifest.collection_id.clone(), chunk_text));
-            }
+            chunk_preview: text.map(|text| text.chars().take(200).collect()),
+            matched_terms: highlights.matched_terms,
+            term_matches: highlights.term_matches,
+            snippet,
+            snippet_start,
+            fusion: None,
+            rerank: None,
         }
-
-        if !fts_owned.is_empty() {
-            let fts_refs: Vec<(&str, &str, &str)> = fts_owned
-                .iter()
-                .map(|(chunk_id, collection_id, chunk_text)| {
-                    (
-                        chunk_id.as_str(),
-                        collection_id.as_str(),
-                        chunk_text.as_str(),
-                    )
-                })
-                .collect();
-            metadata.fts_insert_batch(&fts_refs)?;
-        }
-
-        Ok(())
     }
 }
 
@@ -761,49 +1370,61 @@ fn dir_size(path: &std::path::Path) -> Result<u64> {
     Ok(total)
 }
 
-fn build_manifest_search_snapshot_from_metadata(
+/// The manifest a search reads, and the tombstones that apply to it: the
+/// manifest's own, plus pending deletes when `pending_deletes` is set.
+fn resolve_search_manifest(
     metadata: &MetadataStore,
-    opts: &SearchOptions,
-) -> Result<ManifestSearchSnapshot> {
-    let manifest = if let Some(version) = opts.manifest_version {
+    collection_id: &str,
+    manifest_version: Option<i64>,
+    pending_deletes: bool,
+) -> Result<(Manifest, HashSet<String>)> {
+    let manifest = if let Some(version) = manifest_version {
         metadata
-            .get_manifest_by_version(&opts.collection_id, version)?
+            .get_manifest_by_version(collection_id, version)?
             .ok_or_else(|| {
                 AkiDbError::ManifestNotFound(format!(
                     "version {} for collection \"{}\"",
-                    version, opts.collection_id
+                    version, collection_id
                 ))
             })?
     } else {
         metadata
-            .get_latest_manifest(&opts.collection_id)?
+            .get_latest_manifest(collection_id)?
             .ok_or_else(|| {
                 AkiDbError::ManifestNotFound(format!(
                     "no manifest published for collection \"{}\"",
-                    opts.collection_id
+                    collection_id
                 ))
             })?
     };
 
-    let live_tombstone_set = if opts.include_uncommitted && opts.manifest_version.is_none() {
+    let live_tombstone_set = if pending_deletes {
         manifest
             .tombstone_ids
             .iter()
             .cloned()
-            .chain(
-                metadata
-                    .list_tombstone_chunk_ids(&opts.collection_id)?
-                    .into_iter(),
-            )
+            .chain(metadata.list_tombstone_chunk_ids(collection_id)?)
             .collect()
     } else {
         manifest.tombstone_ids.iter().cloned().collect()
     };
 
-    Ok(ManifestSearchSnapshot {
-        manifest,
-        live_tombstone_set,
-    })
+    Ok((manifest, live_tombstone_set))
+}
+
+/// Apply the write buffer, which is newer than every segment, to the chunks
+/// the segments place in `doc_id`: buffered chunks of the document join it
+/// and buffered chunks moved to another document leave it.
+fn overlay_buffered_chunks(chunk_ids: &mut HashSet<String>, buffer_records: &[NativeRecord], doc_id: &str) {
+    let buffered: HashMap<&str, &str> =
+        buffer_records.iter().map(|rec| (rec.chunk_id.as_str(), rec.doc_id.as_str())).collect();
+    for (chunk_id, buffered_doc_id) in buffered {
+        if buffered_doc_id == doc_id {
+            chunk_ids.insert(chunk_id.to_string());
+        } else {
+            chunk_ids.remove(chunk_id);
+        }
+    }
 }
 
 fn iso_now() -> String {
@@ -818,6 +1439,57 @@ fn iso_now() -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::query::{ContextBudget, Fusion, GroupBy, KeywordSyntax, SearchCursor};
+    use crate::{distance, fp16};
+
+    #[test]
+    fn corrupted_segment_fails_checksum_on_first_map() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = || EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        };
+        let storage_path = {
+            let engine = EngineInner::open(options()).unwrap();
+            engine
+                .create_collection("docs", 2, "l2", "model", "fp32", 16, 200, 100)
+                .unwrap();
+            let record = NativeRecord {
+                chunk_id: "c0".to_string(),
+                doc_id: "doc".to_string(),
+                ordinal: None,
+                vector: vec![1.0, 2.0],
+                metadata: serde_json::json!({}),
+                chunk_text: None,
+            };
+            engine.upsert_batch("docs", &[record]).unwrap();
+            let manifest = engine.auto_publish("docs", "model", "sig").unwrap();
+            let metadata = engine.lock_metadata().unwrap();
+            metadata.get_segment(&manifest.segment_ids[0]).unwrap().unwrap().storage_path
+        };
+
+        // Flip a VectorBlock byte behind the sidecar checksum's back.
+        let path = dir.path().join(&storage_path);
+        let mut bytes = std::fs::read(&path).unwrap();
+        let at = bytes.windows(4).position(|w| w == 2.0f32.to_le_bytes()).unwrap();
+        bytes[at + 3] ^= 0x01;
+        std::fs::write(&path, bytes).unwrap();
+
+        let engine = EngineInner::open(options()).unwrap();
+        let err = engine.get_records("docs", &["c0".to_string()], None).unwrap_err();
+        assert!(matches!(err, AkiDbError::ChecksumMismatch { .. }), "{err}");
+        let err = engine
+            .search(SearchOptions {
+                collection_id: "docs".to_string(),
+                query_vector: vec![1.0, 2.0],
+                top_k: 1,
+                ..SearchOptions::default()
+            })
+            .err()
+            .unwrap();
+        assert!(matches!(err, AkiDbError::ChecksumMismatch { .. }), "{err}");
+    }
 
     #[test]
     fn vector_search_errors_when_manifest_references_missing_segment() {
@@ -825,6 +1497,7 @@ mod tests {
         let engine = EngineInner::open(EngineOptions {
             storage_path: dir.path().to_path_buf(),
             disable_wal: true,
+            search_threads: None,
         })
         .unwrap();
 
@@ -853,15 +1526,7 @@ mod tests {
             collection_id: "docs".to_string(),
             query_vector: vec![0.1, 0.2, 0.3, 0.4],
             top_k: 5,
-            filters: None,
-            manifest_version: None,
-            include_uncommitted: false,
-            mode: SearchMode::Vector,
-            query_text: None,
-            vector_weight: 1.0,
-            keyword_weight: 1.0,
-            explain: false,
-            ef_search: None,
+            ..SearchOptions::default()
         });
 
         assert!(
@@ -875,6 +1540,7 @@ mod tests {
         let engine = EngineInner::open(EngineOptions {
             storage_path: dir.path().to_path_buf(),
             disable_wal: true,
+            search_threads: None,
         })
         .unwrap();
 
@@ -886,6 +1552,7 @@ mod tests {
             NativeRecord {
                 chunk_id: "chunk-a".to_string(),
                 doc_id: "doc-a".to_string(),
+                ordinal: None,
                 vector: vec![1.0, 0.0],
                 metadata: serde_json::json!({"topic": "alpha"}),
                 chunk_text: Some("alpha document".to_string()),
@@ -893,6 +1560,7 @@ mod tests {
             NativeRecord {
                 chunk_id: "chunk-b".to_string(),
                 doc_id: "doc-b".to_string(),
+                ordinal: None,
                 vector: vec![0.0, 1.0],
                 metadata: serde_json::json!({"topic": "beta"}),
                 chunk_text: Some("beta document".to_string()),
@@ -911,25 +1579,7 @@ mod tests {
             assert_eq!(tombstones, vec![String::from("chunk-a")]);
         }
 
-        let snapshot = engine
-            .build_vector_snapshot(
-                &SearchOptions {
-                    collection_id: "docs".to_string(),
-                    query_vector: vec![1.0, 0.0],
-                    top_k: 5,
-                    filters: None,
-                    manifest_version: None,
-                    include_uncommitted: true,
-                    mode: SearchMode::Vector,
-                    query_text: None,
-                    vector_weight: 1.0,
-                    keyword_weight: 1.0,
-                    explain: false,
-                    ef_search: None,
-                },
-                &[],
-            )
-            .unwrap();
+        let snapshot = engine.build_manifest_snapshot("docs", None, true, None, None).unwrap();
         assert!(snapshot.live_tombstone_set.contains("chunk-a"));
 
         let vector_result = engine
@@ -937,15 +1587,8 @@ mod tests {
                 collection_id: "docs".to_string(),
                 query_vector: vec![1.0, 0.0],
                 top_k: 5,
-                filters: None,
-                manifest_version: None,
                 include_uncommitted: true,
-                mode: SearchMode::Vector,
-                query_text: None,
-                vector_weight: 1.0,
-                keyword_weight: 1.0,
-                explain: false,
-                ef_search: None,
+                ..SearchOptions::default()
             })
             .unwrap();
         assert!(
@@ -961,15 +1604,10 @@ mod tests {
                 collection_id: "docs".to_string(),
                 query_vector: vec![1.0, 0.0],
                 top_k: 5,
-                filters: None,
-                manifest_version: None,
                 include_uncommitted: true,
                 mode: SearchMode::Hybrid,
                 query_text: Some("alpha".to_string()),
-                vector_weight: 1.0,
-                keyword_weight: 1.0,
-                explain: false,
-                ef_search: None,
+                ..SearchOptions::default()
             })
             .unwrap();
         assert!(
@@ -991,15 +1629,8 @@ mod tests {
                 collection_id: "docs".to_string(),
                 query_vector: vec![1.0, 0.0],
                 top_k: 5,
-                filters: None,
-                manifest_version: None,
                 include_uncommitted: true,
-                mode: SearchMode::Vector,
-                query_text: None,
-                vector_weight: 1.0,
-                keyword_weight: 1.0,
-                explain: false,
-                ef_search: None,
+                ..SearchOptions::default()
             })
             .unwrap();
         assert!(
@@ -1020,15 +1651,8 @@ mod tests {
                 collection_id: "docs".to_string(),
                 query_vector: vec![1.0, 0.0],
                 top_k: 5,
-                filters: None,
-                manifest_version: None,
                 include_uncommitted: true,
-                mode: SearchMode::Vector,
-                query_text: None,
-                vector_weight: 1.0,
-                keyword_weight: 1.0,
-                explain: false,
-                ef_search: None,
+                ..SearchOptions::default()
             })
             .unwrap();
         assert!(
@@ -1044,15 +1668,10 @@ mod tests {
                 collection_id: "docs".to_string(),
                 query_vector: vec![1.0, 0.0],
                 top_k: 5,
-                filters: None,
-                manifest_version: None,
                 include_uncommitted: true,
                 mode: SearchMode::Keyword,
                 query_text: Some("alpha".to_string()),
-                vector_weight: 1.0,
-                keyword_weight: 1.0,
-                explain: false,
-                ef_search: None,
+                ..SearchOptions::default()
             })
             .unwrap();
         assert!(
@@ -1068,15 +1687,10 @@ mod tests {
                 collection_id: "docs".to_string(),
                 query_vector: vec![1.0, 0.0],
                 top_k: 5,
-                filters: None,
-                manifest_version: None,
                 include_uncommitted: true,
                 mode: SearchMode::Hybrid,
                 query_text: Some("alpha".to_string()),
-                vector_weight: 1.0,
-                keyword_weight: 1.0,
-                explain: false,
-                ef_search: None,
+                ..SearchOptions::default()
             })
             .unwrap();
         assert!(
@@ -1094,6 +1708,7 @@ mod tests {
         let engine = EngineInner::open(EngineOptions {
             storage_path: dir.path().to_path_buf(),
             disable_wal: true,
+            search_threads: None,
         })
         .unwrap();
 
@@ -1107,6 +1722,7 @@ mod tests {
                 &[NativeRecord {
                     chunk_id: "chunk-v0".to_string(),
                     doc_id: "doc-v0".to_string(),
+                    ordinal: None,
                     vector: vec![1.0, 0.0],
                     metadata: serde_json::json!({"topic": "baseline"}),
                     chunk_text: Some("baseline release notes".to_string()),
@@ -1121,6 +1737,7 @@ mod tests {
                 &[NativeRecord {
                     chunk_id: "chunk-v1".to_string(),
                     doc_id: "doc-v1".to_string(),
+                    ordinal: None,
                     vector: vec![0.0, 1.0],
                     metadata: serde_json::json!({"topic": "new"}),
                     chunk_text: Some("new feature launch".to_string()),
@@ -1134,15 +1751,10 @@ mod tests {
                 collection_id: "docs".to_string(),
                 query_vector: vec![0.0, 1.0],
                 top_k: 5,
-                filters: None,
-                manifest_version: None,
                 include_uncommitted: true,
                 mode: SearchMode::Keyword,
                 query_text: Some("new feature".to_string()),
-                vector_weight: 1.0,
-                keyword_weight: 1.0,
-                explain: false,
-                ef_search: None,
+                ..SearchOptions::default()
             })
             .unwrap();
         assert!(before_rollback
@@ -1157,15 +1769,10 @@ mod tests {
                 collection_id: "docs".to_string(),
                 query_vector: vec![1.0, 0.0],
                 top_k: 5,
-                filters: None,
-                manifest_version: None,
                 include_uncommitted: true,
                 mode: SearchMode::Keyword,
                 query_text: Some("baseline release".to_string()),
-                vector_weight: 1.0,
-                keyword_weight: 1.0,
-                explain: false,
-                ef_search: None,
+                ..SearchOptions::default()
             })
             .unwrap();
         assert!(after_rollback_old
@@ -1178,15 +1785,10 @@ mod tests {
                 collection_id: "docs".to_string(),
                 query_vector: vec![0.0, 1.0],
                 top_k: 5,
-                filters: None,
-                manifest_version: None,
                 include_uncommitted: true,
                 mode: SearchMode::Keyword,
                 query_text: Some("new feature".to_string()),
-                vector_weight: 1.0,
-                keyword_weight: 1.0,
-                explain: false,
-                ef_search: None,
+                ..SearchOptions::default()
             })
             .unwrap();
         assert!(
@@ -1204,6 +1806,7 @@ mod tests {
         let engine = EngineInner::open(EngineOptions {
             storage_path: dir.path().to_path_buf(),
             disable_wal: true,
+            search_threads: None,
         })
         .unwrap();
 
@@ -1215,6 +1818,7 @@ mod tests {
             .map(|i| NativeRecord {
                 chunk_id: format!("chunk-{i}"),
                 doc_id: format!("doc-{i}"),
+                ordinal: None,
                 vector: vec![1.0 - (i as f32 * 0.1), i as f32 * 0.1],
                 metadata: serde_json::json!({ "batch": 1, "offset": i }),
                 chunk_text: Some(format!("batch one chunk {i}")),
@@ -1224,6 +1828,7 @@ mod tests {
             .map(|i| NativeRecord {
                 chunk_id: format!("chunk-{i}"),
                 doc_id: format!("doc-{i}"),
+                ordinal: None,
                 vector: vec![1.0 - (i as f32 * 0.05), i as f32 * 0.05],
                 metadata: serde_json::json!({ "batch": 2, "offset": i }),
                 chunk_text: Some(format!("batch two chunk {i}")),
@@ -1253,15 +1858,8 @@ mod tests {
                 collection_id: "docs".to_string(),
                 query_vector: vec![1.0, 0.0],
                 top_k: 20,
-                filters: None,
-                manifest_version: None,
                 include_uncommitted: true,
-                mode: SearchMode::Vector,
-                query_text: None,
-                vector_weight: 1.0,
-                keyword_weight: 1.0,
-                explain: false,
-                ef_search: None,
+                ..SearchOptions::default()
             })
             .unwrap();
         assert!(latest.results.iter().all(|r| r.chunk_id != "chunk-3"));
@@ -1271,17 +1869,1858 @@ mod tests {
                 collection_id: "docs".to_string(),
                 query_vector: vec![1.0, 0.0],
                 top_k: 20,
-                filters: None,
                 manifest_version: Some(manifest0.version),
                 include_uncommitted: true,
-                mode: SearchMode::Vector,
-                query_text: None,
-                vector_weight: 1.0,
-                keyword_weight: 1.0,
-                explain: false,
-                ef_search: None,
+                ..SearchOptions::default()
             })
             .unwrap();
         assert!(historical.results.iter().any(|r| r.chunk_id == "chunk-3"));
     }
+
+    #[test]
+    fn ivf_pq_collection_searches_segments_and_applies_tombstones() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+
+        let collection = engine
+            .create_collection_with_options(&CreateCollectionOptions {
+                collection_id: "docs".to_string(),
+                dimension: 8,
+                metric: "l2".to_string(),
+                embedding_model_id: "model".to_string(),
+                schema_version: "1".to_string(),
+                quantization: "fp16".to_string(),
+                hnsw_m: 16,
+                hnsw_ef_construction: 200,
+                hnsw_ef_search: 100,
+                index_type: "ivf_pq".to_string(),
+                ivf_num_clusters: Some(4),
+                ivf_num_probes: None,
+                pq_num_subquantizers: None,
+                tokenizer: "unicode61".to_string(),
+                stemmer: None,
+                stop_words: None,
+                remove_diacritics: true,
+            })
+            .unwrap();
+        assert_eq!(collection.ivf_num_probes, Some(8));
+        assert_eq!(collection.pq_num_subquantizers, Some(1));
+
+        let records: Vec<NativeRecord> = (0..64)
+            .map(|i| {
+                let f = i as f32;
+                NativeRecord {
+                    chunk_id: format!("chunk-{i}"),
+                    doc_id: "doc".to_string(),
+                    ordinal: None,
+                    vector: (0..8).map(|d| (f * 0.37 + d as f32).sin()).collect(),
+                    metadata: serde_json::json!({"i": i}),
+                    chunk_text: None,
+                }
+            })
+            .collect();
+        engine.upsert_batch("docs", &records).unwrap();
+        engine.auto_publish("docs", "model", "sig").unwrap();
+
+        let search = |ivf_num_probes: Option<usize>| {
+            engine
+                .search(SearchOptions {
+                    collection_id: "docs".to_string(),
+                    query_vector: records[10].vector.clone(),
+                    top_k: 3,
+                    ivf_num_probes,
+                    ..SearchOptions::default()
+                })
+                .unwrap()
+        };
+
+        let exhaustive = search(Some(4));
+        assert_eq!(exhaustive.results.len(), 3);
+        assert_eq!(exhaustive.results[0].chunk_id, "chunk-10");
+        assert_eq!(search(None).results[0].chunk_id, "chunk-10");
+
+        engine
+            .delete_chunks("docs", &["chunk-10".to_string()], "manual_revoke")
+            .unwrap();
+        engine.auto_publish("docs", "model", "sig").unwrap();
+
+        let after_delete = search(Some(4));
+        assert_eq!(after_delete.results.len(), 3);
+        assert!(after_delete.results.iter().all(|r| r.chunk_id != "chunk-10"));
+    }
+
+    #[test]
+    fn sq8_collection_searches_vector_block_codes() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 8, "l2", "model", "sq8", 16, 200, 100)
+            .unwrap();
+
+        let records: Vec<NativeRecord> = (0..64)
+            .map(|i| {
+                let f = i as f32;
+                NativeRecord {
+                    chunk_id: format!("chunk-{i}"),
+                    doc_id: "doc".to_string(),
+                    ordinal: None,
+                    vector: (0..8).map(|d| (f * 0.37 + d as f32).sin()).collect(),
+                    metadata: serde_json::json!({"i": i}),
+                    chunk_text: None,
+                }
+            })
+            .collect();
+        engine.upsert_batch("docs", &records).unwrap();
+        engine.auto_publish("docs", "model", "sig").unwrap();
+
+        let search = |collection_id: &str, query_vector: &[f32], top_k: usize, rescore: bool| {
+            engine
+                .search(SearchOptions {
+                    collection_id: collection_id.to_string(),
+                    query_vector: query_vector.to_vec(),
+                    top_k,
+                    rescore,
+                    ..SearchOptions::default()
+                })
+                .unwrap()
+        };
+
+        let approx = search("docs", &records[10].vector, 3, false);
+        assert_eq!(approx.results.len(), 3);
+        assert_eq!(approx.results[0].chunk_id, "chunk-10");
+
+        // The IndexBlock holds the graph alone; search runs on the VectorBlock codes.
+        let manifest = engine.metadata.lock().unwrap().get_latest_manifest("docs").unwrap().unwrap();
+        let segment = engine.metadata.lock().unwrap().get_segment(&manifest.segment_ids[0]).unwrap().unwrap();
+        let reader = crate::segment::reader::SegmentReader::from_buffer(
+            engine.storage.get_object(&segment.storage_path).unwrap(),
+        )
+        .unwrap();
+        assert!(HnswGraph::read_serialized_vector(reader.get_index_data(), 0).is_none());
+
+        // Cosine codes encode the stored, unnormalized vectors.
+        engine
+            .create_collection("cos", 8, "cosine", "model", "sq8", 16, 200, 100)
+            .unwrap();
+        let scaled: Vec<NativeRecord> = records
+            .iter()
+            .enumerate()
+            .map(|(i, r)| NativeRecord {
+                vector: r.vector.iter().map(|x| x * (1 + i % 3) as f32).collect(),
+                ..r.clone()
+            })
+            .collect();
+        engine.upsert_batch("cos", &scaled).unwrap();
+        engine.auto_publish("cos", "model", "sig").unwrap();
+        let cosine = search("cos", &records[10].vector, 3, false);
+        assert_eq!(cosine.results[0].chunk_id, "chunk-10");
+        assert!((cosine.results[0].score - 1.0).abs() < 0.01);
+
+        // 100.0 and 100.3 share a code on a 0..255 range; re-scoring against
+        // the FP16 copies breaks the tie the codes leave to chunk order.
+        engine
+            .create_collection("ties", 8, "l2", "model", "sq8", 16, 200, 100)
+            .unwrap();
+        let tied: Vec<NativeRecord> = [("lo", 0.0), ("hi", 255.0), ("tie-a", 100.0), ("tie-b", 100.3)]
+            .into_iter()
+            .map(|(id, x)| {
+                let mut vector = vec![if id == "hi" { 255.0 } else { 0.0 }; 8];
+                vector[0] = x;
+                NativeRecord { chunk_id: id.to_string(), vector, ..records[0].clone() }
+            })
+            .collect();
+        engine.upsert_batch("ties", &tied).unwrap();
+        engine.auto_publish("ties", "model", "sig").unwrap();
+        let mut query = vec![0.0; 8];
+        query[0] = 100.45;
+        let ranked = |r: &SearchResponse| r.results.iter().map(|r| (r.chunk_id.clone(), r.score)).collect::<Vec<_>>();
+        let approx = ranked(&search("ties", &query, 2, false));
+        assert_eq!(approx.iter().map(|(id, _)| id.as_str()).collect::<Vec<_>>(), ["tie-a", "tie-b"]);
+        assert_eq!(approx[0].1, approx[1].1);
+        let rescored = ranked(&search("ties", &query, 2, true));
+        assert_eq!(rescored.iter().map(|(id, _)| id.as_str()).collect::<Vec<_>>(), ["tie-b", "tie-a"]);
+        assert!(rescored[0].1 > rescored[1].1);
+    }
+
+    #[test]
+    fn binary_collection_rescores_hamming_candidates_against_fp16_vectors() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection_with_options(&CreateCollectionOptions {
+                collection_id: "docs".to_string(),
+                dimension: 64,
+                metric: "l2".to_string(),
+                embedding_model_id: "model".to_string(),
+                schema_version: "1".to_string(),
+                quantization: "fp16".to_string(),
+                hnsw_m: 16,
+                hnsw_ef_construction: 200,
+                hnsw_ef_search: 100,
+                index_type: "binary".to_string(),
+                ivf_num_clusters: None,
+                ivf_num_probes: None,
+                pq_num_subquantizers: None,
+                tokenizer: "unicode61".to_string(),
+                stemmer: None,
+                stop_words: None,
+                remove_diacritics: true,
+            })
+            .unwrap();
+
+        let records: Vec<NativeRecord> = (0..128)
+            .map(|i| {
+                let f = i as f32;
+                NativeRecord {
+                    chunk_id: format!("chunk-{i}"),
+                    doc_id: "doc".to_string(),
+                    ordinal: None,
+                    vector: (0..64).map(|d| (f * 0.37 + d as f32 * 1.3).sin()).collect(),
+                    metadata: serde_json::json!({"i": i}),
+                    chunk_text: None,
+                }
+            })
+            .collect();
+        engine.upsert_batch("docs", &records).unwrap();
+        engine.auto_publish("docs", "model", "sig").unwrap();
+
+        let query = records[10].vector.clone();
+        let search = |oversampling: Option<f64>| {
+            engine.search(SearchOptions {
+                collection_id: "docs".to_string(),
+                query_vector: query.clone(),
+                top_k: 5,
+                oversampling,
+                ..SearchOptions::default()
+            })
+        };
+
+        // Exact top-5 over the FP16 VectorBlock contents.
+        let mut expected: Vec<(String, f64)> = records
+            .iter()
+            .map(|r| {
+                let stored = fp16::decode_fp16_vector(&fp16::encode_fp16_vector(&r.vector));
+                let dist = distance::l2_distance(&query, &stored);
+                (r.chunk_id.clone(), distance::distance_to_score("l2", dist) as f64)
+            })
+            .collect();
+        expected.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        expected.truncate(5);
+
+        // Oversampling past the segment size re-scores every record.
+        let full = search(Some(40.0)).unwrap();
+        let got: Vec<(String, f64)> = full
+            .results
+            .iter()
+            .map(|r| (r.chunk_id.clone(), r.score))
+            .collect();
+        assert_eq!(got, expected);
+
+        // The default oversampling still re-scores (exact self-match score).
+        let default = search(None).unwrap();
+        assert_eq!(default.results[0].chunk_id, "chunk-10");
+        assert_eq!(default.results[0].score, expected[0].1);
+
+        assert!(search(Some(0.5)).is_err());
+        assert!(search(Some(f64::NAN)).is_err());
+
+        engine
+            .delete_chunks("docs", &["chunk-10".to_string()], "manual_revoke")
+            .unwrap();
+        engine.auto_publish("docs", "model", "sig").unwrap();
+        let after_delete = search(Some(40.0)).unwrap();
+        assert_eq!(after_delete.results.len(), 5);
+        assert!(after_delete.results.iter().all(|r| r.chunk_id != "chunk-10"));
+        assert_eq!(after_delete.results[0].chunk_id, expected[1].0);
+    }
+
+    #[test]
+    fn fp32_collection_scores_match_buffer_exactly_across_compaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 4, "l2", "model", "fp32", 16, 200, 100)
+            .unwrap();
+
+        // Components beyond the FP16 range (±65504) and below its precision.
+        let records: Vec<NativeRecord> = (0..20)
+            .map(|i| NativeRecord {
+                chunk_id: format!("chunk-{i}"),
+                doc_id: "doc".to_string(),
+                ordinal: None,
+                vector: vec![70_000.0 + i as f32 * 0.125, -1.0e-3 * i as f32, 3.0, i as f32],
+                metadata: serde_json::json!({}),
+                chunk_text: None,
+            })
+            .collect();
+        let query = vec![70_000.3_f32, -0.004, 3.0, 2.5];
+        let expected: Vec<f64> = records
+            .iter()
+            .map(|r| {
+                distance::distance_to_score("l2", distance::l2_distance(&query, &r.vector)) as f64
+            })
+            .collect();
+
+        let search = || {
+            engine
+                .search(SearchOptions {
+                    collection_id: "docs".to_string(),
+                    query_vector: query.clone(),
+                    top_k: 3,
+                    include_uncommitted: true,
+                    ..SearchOptions::default()
+                })
+                .unwrap()
+                .results
+        };
+        let record_index =
+            |r: &NativeRecord| -> usize { r.chunk_id.trim_start_matches("chunk-").parse().unwrap() };
+        let assert_exact = |results: &[SearchResult]| {
+            assert_eq!(results.len(), 3);
+            for r in results {
+                let i: usize = r.chunk_id.trim_start_matches("chunk-").parse().unwrap();
+                assert_eq!(r.score, expected[i], "{}", r.chunk_id);
+            }
+        };
+
+        // Commit the even records and leave the odd ones in the write buffer.
+        let (even, odd): (Vec<NativeRecord>, Vec<NativeRecord>) =
+            records.iter().cloned().partition(|r| record_index(r) % 2 == 0);
+        engine.upsert_batch("docs", &even).unwrap();
+        engine.auto_publish("docs", "model", "sig").unwrap();
+        engine.upsert_batch("docs", &odd).unwrap();
+        let mixed = search();
+        assert!(mixed.iter().any(|r| r.committed == Some(true)));
+        assert!(mixed.iter().any(|r| r.committed == Some(false)));
+        assert_exact(&mixed);
+
+        engine.auto_publish("docs", "model", "sig").unwrap();
+        let committed = search();
+        assert!(committed.iter().all(|r| r.committed == Some(true)));
+        assert_eq!(
+            committed.iter().map(|r| &r.chunk_id).collect::<Vec<_>>(),
+            mixed.iter().map(|r| &r.chunk_id).collect::<Vec<_>>()
+        );
+        assert_exact(&committed);
+
+        engine
+            .delete_chunks("docs", &["chunk-0".to_string()], "manual_revoke")
+            .unwrap();
+        engine.auto_publish("docs", "model", "sig").unwrap();
+        engine.compact("docs").unwrap();
+        assert_exact(&search());
+    }
+
+    #[test]
+    fn buffered_records_use_incremental_hnsw_reused_at_flush() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 8, "cosine", "model", "fp16", 16, 200, 100)
+            .unwrap();
+
+        let records: Vec<NativeRecord> = (0..200)
+            .map(|i| {
+                let f = i as f32;
+                NativeRecord {
+                    chunk_id: format!("chunk-{i}"),
+                    doc_id: "doc".to_string(),
+                    ordinal: None,
+                    vector: (0..8).map(|d| (f * 0.37 + d as f32).sin()).collect(),
+                    metadata: serde_json::json!({"even": i % 2 == 0}),
+                    chunk_text: None,
+                }
+            })
+            .collect();
+        // Publish one record, then buffer the rest in two batches: the second
+        // extends the graph built by the first.
+        engine.upsert_batch("docs", &records[..1]).unwrap();
+        engine.auto_publish("docs", "model", "sig").unwrap();
+        engine.upsert_batch("docs", &records[1..120]).unwrap();
+        engine.upsert_batch("docs", &records[120..]).unwrap();
+
+        let search = |filters: Option<serde_json::Value>| {
+            engine
+                .search(SearchOptions {
+                    collection_id: "docs".to_string(),
+                    query_vector: records[150].vector.clone(),
+                    top_k: 5,
+                    filters,
+                    include_uncommitted: true,
+                    ..SearchOptions::default()
+                })
+                .unwrap()
+                .results
+        };
+
+        let unfiltered = search(None);
+        assert_eq!(unfiltered.len(), 5);
+        assert_eq!(unfiltered[0].chunk_id, "chunk-150");
+        assert_eq!(unfiltered[0].committed, Some(false));
+
+        let odd = search(Some(serde_json::json!({"even": false})));
+        assert_eq!(odd.len(), 5);
+        assert!(odd.iter().all(|r| {
+            let i: usize = r.chunk_id.trim_start_matches("chunk-").parse().unwrap();
+            i % 2 == 1
+        }));
+
+        // The flushed IndexBlock is the buffer's graph, identical to a full build.
+        let segment_ids = engine.flush_writes("docs").unwrap();
+        assert_eq!(segment_ids.len(), 1);
+        let segment = engine
+            .metadata
+            .lock()
+            .unwrap()
+            .get_segment(&segment_ids[0])
+            .unwrap()
+            .unwrap();
+        let reader = crate::segment::reader::SegmentReader::from_buffer(
+            engine.storage.get_object(&segment.storage_path).unwrap(),
+        )
+        .unwrap();
+        let mut built = HnswGraph::new("cosine", 8, 16, 200, 100);
+        built.build(&records[1..].iter().map(|r| r.vector.clone()).collect::<Vec<_>>());
+        assert_eq!(reader.get_index_data(), built.serialize().as_slice());
+    }
+
+    #[test]
+    fn parallel_segment_search_matches_serial_search() {
+        let records: Vec<NativeRecord> = (0..48)
+            .map(|i| NativeRecord {
+                chunk_id: format!("chunk-{i:02}"),
+                doc_id: "doc".to_string(),
+                ordinal: None,
+                // Every vector appears in three segments under different chunk
+                // IDs, so the top results tie and rely on chunk_id ordering.
+                vector: vec![1.0, (i % 16) as f32 * 0.1, 0.5, 0.25],
+                metadata: serde_json::json!({ "segment": i / 8 }),
+                chunk_text: None,
+            })
+            .collect();
+
+        let run = |search_threads: usize| {
+            let dir = tempfile::tempdir().unwrap();
+            let engine = EngineInner::open(EngineOptions {
+                storage_path: dir.path().to_path_buf(),
+                disable_wal: true,
+                search_threads: Some(search_threads),
+            })
+            .unwrap();
+            assert_eq!(engine.query_engine.search_threads(), search_threads);
+            engine
+                .create_collection("docs", 4, "cosine", "model", "fp16", 16, 200, 100)
+                .unwrap();
+            for batch in records.chunks(8) {
+                engine.upsert_batch("docs", batch).unwrap();
+                engine.flush_writes("docs").unwrap();
+            }
+            let manifest = engine.auto_publish("docs", "model", "sig").unwrap();
+            assert_eq!(manifest.segment_ids.len(), 6);
+
+            engine
+                .search(SearchOptions {
+                    collection_id: "docs".to_string(),
+                    query_vector: records[3].vector.clone(),
+                    top_k: 10,
+                    ..SearchOptions::default()
+                })
+                .unwrap()
+                .results
+                .into_iter()
+                .map(|r| (r.chunk_id, r.score))
+                .collect::<Vec<_>>()
+        };
+
+        let serial = run(1);
+        assert_eq!(serial.len(), 10);
+        assert_eq!(
+            serial[..3].iter().map(|(id, _)| id.as_str()).collect::<Vec<_>>(),
+            ["chunk-03", "chunk-19", "chunk-35"]
+        );
+        for _ in 0..3 {
+            assert_eq!(run(4), serial);
+        }
+    }
+
+    #[test]
+    fn search_batch_matches_individual_searches_under_one_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 4, "cosine", "model", "fp16", 16, 200, 100)
+            .unwrap();
+        let records: Vec<NativeRecord> = (0..20)
+            .map(|i| NativeRecord {
+                chunk_id: format!("chunk-{i:02}"),
+                doc_id: "doc".to_string(),
+                ordinal: None,
+                vector: vec![1.0, i as f32 * 0.1, (i % 3) as f32, 0.5],
+                metadata: serde_json::json!({ "even": i % 2 == 0 }),
+                chunk_text: Some(format!("chunk number {i} about {}", ["rust", "python"][i % 2])),
+            })
+            .collect();
+        engine.upsert_batch("docs", &records).unwrap();
+        let manifest = engine.auto_publish("docs", "model", "sig").unwrap();
+
+        let opts = |mode: SearchMode| SearchOptions {
+            collection_id: "docs".to_string(),
+            top_k: 3,
+            filters: Some(serde_json::json!({ "even": true })),
+            include_uncommitted: true,
+            mode,
+            ..SearchOptions::default()
+        };
+        let ids = |response: &SearchResponse| {
+            response.results.iter().map(|r| r.chunk_id.clone()).collect::<Vec<_>>()
+        };
+
+        let queries = BatchQuery::zip(
+            Some(vec![records[4].vector.clone(), records[10].vector.clone(), records[7].vector.clone()]),
+            Some(vec!["rust".to_string(), "python".to_string(), "number".to_string()]),
+        )
+        .unwrap();
+        for mode in [SearchMode::Vector, SearchMode::Hybrid] {
+            let batch = engine.search_batch(opts(mode.clone()), queries.clone()).unwrap();
+            assert_eq!(batch.len(), 3);
+            for (query, response) in queries.iter().zip(&batch) {
+                assert_eq!(response.manifest_version_used, manifest.version);
+                let single = engine
+                    .search(SearchOptions {
+                        query_vector: query.query_vector.clone(),
+                        query_text: query.query_text.clone(),
+                        ..opts(mode.clone())
+                    })
+                    .unwrap();
+                assert_eq!(ids(response), ids(&single));
+            }
+        }
+        let vector_batch = engine.search_batch(opts(SearchMode::Vector), queries.clone()).unwrap();
+        assert_eq!(vector_batch[1].results[0].chunk_id, "chunk-10");
+
+        let keyword_queries =
+            BatchQuery::zip(None, Some(vec!["rust".to_string(), "python".to_string()])).unwrap();
+        let keyword = engine.search_batch(opts(SearchMode::Keyword), keyword_queries).unwrap();
+        assert_eq!(keyword.len(), 2);
+        assert!(!keyword[0].results.is_empty());
+
+        assert!(engine.search_batch(opts(SearchMode::Vector), Vec::new()).unwrap().is_empty());
+        // Every query is validated before any is run.
+        let invalid = vec![queries[0].clone(), BatchQuery::default()];
+        assert!(matches!(
+            engine.search_batch(opts(SearchMode::Vector), invalid),
+            Err(AkiDbError::InvalidArgument(_))
+        ));
+        assert!(BatchQuery::zip(Some(vec![vec![1.0]]), Some(Vec::new())).is_err());
+    }
+
+    #[test]
+    fn zero_search_threads_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: Some(0),
+        });
+        assert!(matches!(err, Err(AkiDbError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn keyword_search_is_bound_to_the_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 2, "cosine", "model", "fp16", 16, 200, 100)
+            .unwrap();
+        let record = |chunk_id: &str, text: &str| NativeRecord {
+            chunk_id: chunk_id.to_string(),
+            doc_id: "doc".to_string(),
+            ordinal: None,
+            vector: vec![1.0, 0.0],
+            metadata: serde_json::json!({}),
+            chunk_text: Some(text.to_string()),
+        };
+        let keyword = |query: &str, manifest_version: Option<i64>| {
+            let response = engine
+                .search(SearchOptions {
+                    collection_id: "docs".to_string(),
+                    top_k: 5,
+                    manifest_version,
+                    mode: SearchMode::Keyword,
+                    query_text: Some(query.to_string()),
+                    explain: true,
+                    ..SearchOptions::default()
+                })
+                .unwrap();
+            response
+                .results
+                .into_iter()
+                .map(|r| (r.chunk_id, r.explain.unwrap().chunk_preview))
+                .collect::<Vec<_>>()
+        };
+
+        engine.upsert_batch("docs", &[record("chunk-a", "alpha release notes")]).unwrap();
+        let v1 = engine.auto_publish("docs", "model", "sig-1").unwrap();
+
+        // Flushed but unpublished segments are invisible to keyword search.
+        engine.upsert_batch("docs", &[record("chunk-b", "alpha beta launch")]).unwrap();
+        engine.flush_writes("docs").unwrap();
+        assert_eq!(
+            keyword("alpha", None),
+            vec![("chunk-a".to_string(), Some("alpha release notes".to_string()))]
+        );
+
+        let v2 = engine.auto_publish("docs", "model", "sig-2").unwrap();
+        assert_eq!(keyword("alpha", None).len(), 2);
+        assert_eq!(keyword("beta", Some(v2.version)).len(), 1);
+        // Time travel reads the older manifest's segments only.
+        assert!(keyword("beta", Some(v1.version)).is_empty());
+        assert_eq!(keyword("alpha", Some(v1.version)).len(), 1);
+    }
+
+    #[test]
+    fn collection_analyzer_applies_to_segments_buffer_and_queries() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        let create = |collection_id: &str, tokenizer: &str, stemmer: Option<&str>, stop_words: Option<&str>| {
+            engine.create_collection_with_options(&CreateCollectionOptions {
+                collection_id: collection_id.to_string(),
+                dimension: 2,
+                metric: "cosine".to_string(),
+                embedding_model_id: "model".to_string(),
+                schema_version: "1".to_string(),
+                quantization: "fp16".to_string(),
+                hnsw_m: 16,
+                hnsw_ef_construction: 200,
+                hnsw_ef_search: 100,
+                index_type: "hnsw".to_string(),
+                ivf_num_clusters: None,
+                ivf_num_probes: None,
+                pq_num_subquantizers: None,
+                tokenizer: tokenizer.to_string(),
+                stemmer: stemmer.map(str::to_string),
+                stop_words: stop_words.map(str::to_string),
+                remove_diacritics: true,
+            })
+        };
+        let record = |chunk_id: &str, text: &str| NativeRecord {
+            chunk_id: chunk_id.to_string(),
+            doc_id: "doc".to_string(),
+            ordinal: None,
+            vector: vec![1.0, 0.0],
+            metadata: serde_json::json!({}),
+            chunk_text: Some(text.to_string()),
+        };
+        let keyword = |collection_id: &str, query: &str, keyword_syntax: KeywordSyntax| {
+            let response = engine
+                .search(SearchOptions {
+                    collection_id: collection_id.to_string(),
+                    top_k: 10,
+                    include_uncommitted: true,
+                    mode: SearchMode::Keyword,
+                    query_text: Some(query.to_string()),
+                    explain: true,
+                    keyword_syntax,
+                    ..SearchOptions::default()
+                })
+                .unwrap();
+            let mut results: Vec<(String, Vec<(usize, usize)>)> = response
+                .results
+                .into_iter()
+                .map(|r| (r.chunk_id, r.explain.unwrap().term_matches.iter().map(|m| (m.start, m.end)).collect()))
+                .collect();
+            results.sort();
+            results
+        };
+
+        create("english", "unicode61", Some("porter"), Some("english")).unwrap();
+        engine
+            .upsert_batch("english", &[
+                record("a", "Configuration guide"),
+                record("b", "State of the art retrieval"),
+            ])
+            .unwrap();
+        engine.auto_publish("english", "model", "sig-1").unwrap();
+        // Buffered records are analyzed the same way as segments.
+        engine.upsert_batch("english", &[record("c", "Configuring logs")]).unwrap();
+        assert_eq!(
+            keyword("english", "configure", KeywordSyntax::Simple),
+            vec![("a".to_string(), vec![(0, 13)]), ("c".to_string(), vec![(0, 11)])]
+        );
+        // Stop words are neither indexed nor required by phrases.
+        assert_eq!(
+            keyword("english", "\"state of art\"", KeywordSyntax::Advanced),
+            vec![("b".to_string(), vec![(0, 16)])]
+        );
+        assert!(keyword("english", "the", KeywordSyntax::Simple).is_empty());
+
+        create("cjk", "cjk_bigram", None, None).unwrap();
+        engine.upsert_batch("cjk", &[record("t", "東京タワーの夜景"), record("k", "京都の夜")]).unwrap();
+        engine.auto_publish("cjk", "model", "sig-1").unwrap();
+        assert_eq!(keyword("cjk", "タワー", KeywordSyntax::Simple), vec![("t".to_string(), vec![(2, 5)])]);
+        assert_eq!(
+            keyword("cjk", "夜景 OR 京都", KeywordSyntax::Advanced),
+            vec![("k".to_string(), vec![(0, 2)]), ("t".to_string(), vec![(6, 8)])]
+        );
+
+        assert!(matches!(create("bad", "unicode61", Some("klingon"), None), Err(AkiDbError::InvalidArgument(_))));
+        assert!(matches!(create("bad", "whitespace", None, None), Err(AkiDbError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn explain_reports_matched_terms_and_snippets() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 2, "cosine", "model", "fp16", 16, 200, 100)
+            .unwrap();
+        let text = "Token refresh keeps sessions alive; a stale token is rejected.";
+        engine
+            .upsert_batch("docs", &[NativeRecord {
+                chunk_id: "chunk-a".to_string(),
+                doc_id: "doc".to_string(),
+                ordinal: None,
+                vector: vec![1.0, 0.0],
+                metadata: serde_json::json!({}),
+                chunk_text: Some(text.to_string()),
+            }])
+            .unwrap();
+        engine.auto_publish("docs", "model", "sig-1").unwrap();
+
+        for mode in [SearchMode::Keyword, SearchMode::Hybrid, SearchMode::Vector] {
+            let response = engine
+                .search(SearchOptions {
+                    collection_id: "docs".to_string(),
+                    query_vector: vec![1.0, 0.0],
+                    top_k: 5,
+                    mode: mode.clone(),
+                    query_text: Some("token OR missing".to_string()),
+                    explain: true,
+                    keyword_syntax: KeywordSyntax::Advanced,
+                    ..SearchOptions::default()
+                })
+                .unwrap();
+            let explain = response.results[0].explain.clone().unwrap();
+            assert_eq!(explain.matched_terms, vec!["token"], "{mode:?}");
+            let spans: Vec<(usize, usize)> = explain.term_matches.iter().map(|m| (m.start, m.end)).collect();
+            assert_eq!(spans, vec![(0, 5), (44, 49)], "{mode:?}");
+            assert_eq!(explain.snippet.as_deref(), Some(text));
+            assert_eq!(explain.snippet_start, Some(0));
+        }
+    }
+
+    #[test]
+    fn hybrid_fusion_method_is_configurable_and_explained() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 2, "cosine", "model", "fp16", 16, 200, 100)
+            .unwrap();
+        let records: Vec<NativeRecord> = [("chunk-a", [1.0, 0.0], "token refresh"), ("chunk-b", [0.0, 1.0], "other words")]
+            .into_iter()
+            .map(|(chunk_id, vector, text)| NativeRecord {
+                chunk_id: chunk_id.to_string(),
+                doc_id: "doc".to_string(),
+                ordinal: None,
+                vector: vector.to_vec(),
+                metadata: serde_json::json!({}),
+                chunk_text: Some(text.to_string()),
+            })
+            .collect();
+        engine.upsert_batch("docs", &records).unwrap();
+        engine.auto_publish("docs", "model", "sig-1").unwrap();
+
+        let search = |fusion: Fusion| {
+            engine.search(SearchOptions {
+                collection_id: "docs".to_string(),
+                query_vector: vec![1.0, 0.0],
+                top_k: 5,
+                mode: SearchMode::Hybrid,
+                query_text: Some("token".to_string()),
+                vector_weight: 3.0,
+                explain: true,
+                fusion,
+                ..SearchOptions::default()
+            })
+        };
+
+        // Min-max: weights scaled to 0.75 / 0.25; chunk-b is missing from the
+        // keyword list and takes that list's minimum.
+        let response = search(Fusion::MinMax).unwrap();
+        let ids: Vec<&str> = response.results.iter().map(|r| r.chunk_id.as_str()).collect();
+        assert_eq!(ids, vec!["chunk-a", "chunk-b"]);
+        let explain = response.results[0].explain.clone().unwrap();
+        assert_eq!(explain.rrf_score, None);
+        let fusion = explain.fusion.unwrap();
+        assert_eq!(fusion.method, "min_max");
+        assert_eq!(fusion.rrf_k, None);
+        assert_eq!((fusion.vector_weight, fusion.keyword_weight), (0.75, 0.25));
+        assert_eq!((fusion.vector_normalized, fusion.keyword_normalized), (Some(1.0), Some(1.0)));
+        assert!((fusion.score - 1.0).abs() < 1e-9);
+        let fusion = response.results[1].explain.clone().unwrap().fusion.unwrap();
+        assert_eq!((fusion.vector_normalized, fusion.keyword_normalized), (Some(0.0), None));
+        assert!((fusion.score - 0.25).abs() < 1e-9);
+
+        // RRF keeps the raw weights and reports its k.
+        let response = search(Fusion::Rrf { k: 10.0 }).unwrap();
+        let explain = response.results[0].explain.clone().unwrap();
+        let fusion = explain.fusion.unwrap();
+        assert_eq!((fusion.method.as_str(), fusion.rrf_k), ("rrf", Some(10.0)));
+        assert_eq!((fusion.vector_weight, fusion.keyword_weight), (3.0, 1.0));
+        assert!((fusion.score - 4.0 / 11.0).abs() < 1e-9);
+        assert_eq!(explain.rrf_score, Some(fusion.score));
+
+        let err = search(Fusion::Rrf { k: -1.0 }).err().expect("negative k is rejected");
+        assert!(err.to_string().contains("rrfK must be a non-negative number"), "{err}");
+    }
+
+    #[test]
+    fn search_returns_requested_payloads_for_committed_and_buffered_hits() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 2, "cosine", "model", "fp32", 16, 200, 100)
+            .unwrap();
+        let record = |chunk_id: &str, vector: Vec<f32>, text: Option<&str>| NativeRecord {
+            chunk_id: chunk_id.to_string(),
+            doc_id: "doc".to_string(),
+            ordinal: None,
+            vector,
+            metadata: serde_json::json!({ "source": chunk_id }),
+            chunk_text: text.map(str::to_string),
+        };
+        engine
+            .upsert_batch("docs", &[record("a", vec![1.0, 0.0], Some("alpha")), record("b", vec![0.6, 0.8], None)])
+            .unwrap();
+        engine.auto_publish("docs", "model", "sig-1").unwrap();
+        engine.upsert_batch("docs", &[record("c", vec![0.8, 0.6], Some("gamma"))]).unwrap();
+
+        let search = |include: bool| {
+            engine
+                .search(SearchOptions {
+                    collection_id: "docs".to_string(),
+                    query_vector: vec![1.0, 0.0],
+                    top_k: 5,
+                    include_uncommitted: true,
+                    include_metadata: include,
+                    include_text: include,
+                    include_vector: include,
+                    ..SearchOptions::default()
+                })
+                .unwrap()
+        };
+
+        let response = search(true);
+        let payloads: Vec<_> = response
+            .results
+            .iter()
+            .map(|r| {
+                (
+                    r.chunk_id.as_str(),
+                    r.committed,
+                    r.metadata.clone().unwrap(),
+                    r.text.as_deref(),
+                    r.vector.clone().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(payloads, vec![
+            ("a", Some(true), serde_json::json!({ "source": "a" }), Some("alpha"), vec![1.0, 0.0]),
+            ("c", Some(false), serde_json::json!({ "source": "c" }), Some("gamma"), vec![0.8, 0.6]),
+            ("b", Some(true), serde_json::json!({ "source": "b" }), None, vec![0.6, 0.8]),
+        ]);
+
+        let response = search(false);
+        assert!(response.results.iter().all(|r| r.metadata.is_none() && r.text.is_none() && r.vector.is_none()));
+    }
+
+    #[test]
+    fn get_records_reads_latest_versions_and_honors_deletes_and_pinned_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 2, "cosine", "model", "fp32", 16, 200, 100)
+            .unwrap();
+        let record = |chunk_id: &str, vector: Vec<f32>, text: &str| NativeRecord {
+            chunk_id: chunk_id.to_string(),
+            doc_id: "doc".to_string(),
+            ordinal: None,
+            vector,
+            metadata: serde_json::json!({ "text": text }),
+            chunk_text: Some(text.to_string()),
+        };
+        engine
+            .upsert_batch("docs", &[record("a", vec![1.0, 0.0], "a1"), record("b", vec![0.6, 0.8], "b1")])
+            .unwrap();
+        let first = engine.auto_publish("docs", "model", "sig-1").unwrap();
+        engine
+            .upsert_batch("docs", &[record("a", vec![0.0, 1.0], "a2"), record("c", vec![0.8, 0.6], "c1")])
+            .unwrap();
+        engine.auto_publish("docs", "model", "sig-2").unwrap();
+        engine.delete_chunks("docs", &["b".to_string()], "manual_revoke").unwrap();
+        engine.upsert_batch("docs", &[record("d", vec![1.0, 0.0], "d1")]).unwrap();
+
+        let ids: Vec<String> = ["a", "b", "c", "d", "missing", "a"].iter().map(|s| s.to_string()).collect();
+        let summary = |records: Vec<StoredRecord>| -> Vec<_> {
+            records
+                .into_iter()
+                .map(|r| (r.chunk_id, r.chunk_text.unwrap(), r.vector, r.metadata, r.committed))
+                .collect()
+        };
+
+        let live = summary(engine.get_records("docs", &ids, None).unwrap());
+        assert_eq!(live, vec![
+            ("a".to_string(), "a2".to_string(), vec![0.0, 1.0], serde_json::json!({ "text": "a2" }), true),
+            ("c".to_string(), "c1".to_string(), vec![0.8, 0.6], serde_json::json!({ "text": "c1" }), true),
+            ("d".to_string(), "d1".to_string(), vec![1.0, 0.0], serde_json::json!({ "text": "d1" }), false),
+        ]);
+
+        let pinned = summary(engine.get_records("docs", &ids, Some(first.version)).unwrap());
+        assert_eq!(pinned, vec![
+            ("a".to_string(), "a1".to_string(), vec![1.0, 0.0], serde_json::json!({ "text": "a1" }), true),
+            ("b".to_string(), "b1".to_string(), vec![0.6, 0.8], serde_json::json!({ "text": "b1" }), true),
+        ]);
+
+        engine.delete_chunks("docs", &["d".to_string()], "manual_revoke").unwrap();
+        assert!(engine.get_records("docs", &["d".to_string()], None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn document_operations_find_chunks_in_segments_and_the_write_buffer() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 2, "cosine", "model", "fp32", 16, 200, 100)
+            .unwrap();
+        let record = |chunk_id: &str, doc_id: &str, x: f32| NativeRecord {
+            chunk_id: chunk_id.to_string(),
+            doc_id: doc_id.to_string(),
+            ordinal: None,
+            vector: vec![x, 1.0],
+            metadata: serde_json::json!({}),
+            chunk_text: None,
+        };
+        engine
+            .upsert_batch("docs", &[record("a", "doc-1", 0.1), record("b", "doc-1", 0.2), record("c", "doc-2", 0.3)])
+            .unwrap();
+        engine.auto_publish("docs", "model", "sig-1").unwrap();
+        // Flushed but unpublished, then buffered; "b" moves to doc-2.
+        engine.upsert_batch("docs", &[record("d", "doc-1", 0.4)]).unwrap();
+        engine.flush_writes("docs").unwrap();
+        engine.upsert_batch("docs", &[record("e", "doc-1", 0.5), record("b", "doc-2", 0.6)]).unwrap();
+
+        assert_eq!(engine.delete_document("docs", "doc-1").unwrap(), 3);
+        assert_eq!(engine.delete_document("docs", "doc-1").unwrap(), 0);
+        engine.auto_publish("docs", "model", "sig-2").unwrap();
+
+        let ids: Vec<String> = ["a", "b", "c", "d", "e", "f"].iter().map(|s| s.to_string()).collect();
+        let live = |engine: &EngineInner| -> Vec<_> {
+            engine
+                .get_records("docs", &ids, None)
+                .unwrap()
+                .into_iter()
+                .map(|r| (r.chunk_id, r.doc_id, r.vector[0]))
+                .collect()
+        };
+        assert_eq!(live(&engine), vec![("b".to_string(), "doc-2".to_string(), 0.6), ("c".to_string(), "doc-2".to_string(), 0.3)]);
+
+        let result = engine
+            .replace_document("docs", "doc-2", &[record("c", "doc-2", 0.7), record("f", "doc-2", 0.8)])
+            .unwrap();
+        assert_eq!(result.buffered_count, 2);
+        assert_eq!(live(&engine), vec![("c".to_string(), "doc-2".to_string(), 0.7), ("f".to_string(), "doc-2".to_string(), 0.8)]);
+
+        let err = engine.replace_document("docs", "doc-2", &[record("g", "doc-3", 0.9)]).err().expect("mismatched doc_id");
+        assert!(err.to_string().contains("expected \"doc-2\""), "{err}");
+        assert!(engine.delete_document("missing", "doc-1").is_err());
+    }
+
+    #[test]
+    fn get_neighbors_returns_adjacent_chunks_of_the_document_by_ordinal() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 2, "cosine", "model", "fp32", 16, 200, 100)
+            .unwrap();
+        let record = |chunk_id: &str, doc_id: &str, ordinal: Option<u32>, text: &str| NativeRecord {
+            chunk_id: chunk_id.to_string(),
+            doc_id: doc_id.to_string(),
+            ordinal,
+            vector: vec![1.0, 0.0],
+            metadata: serde_json::json!({}),
+            chunk_text: Some(text.to_string()),
+        };
+        let mut records: Vec<NativeRecord> =
+            (0..5).map(|i| record(&format!("p{i}"), "doc-1", Some(i), &format!("part {i}"))).collect();
+        records.push(record("q0", "doc-2", Some(0), "other"));
+        records.push(record("n", "doc-1", None, "unordered"));
+        engine.upsert_batch("docs", &records).unwrap();
+        let first = engine.auto_publish("docs", "model", "sig-1").unwrap();
+        // Buffered edit and append, plus a pending delete.
+        engine
+            .upsert_batch("docs", &[record("p2", "doc-1", Some(2), "part 2 edited"), record("p5", "doc-1", Some(5), "part 5")])
+            .unwrap();
+        engine.delete_chunks("docs", &["p3".to_string()], "manual_revoke").unwrap();
+
+        let window = |chunk_id: &str, before: usize, after: usize, version: Option<i64>| -> Vec<(String, String)> {
+            engine
+                .get_neighbors("docs", chunk_id, before, after, version)
+                .unwrap()
+                .into_iter()
+                .map(|r| (r.chunk_id, r.chunk_text.unwrap()))
+                .collect()
+        };
+        let pairs = |items: &[(&str, &str)]| -> Vec<(String, String)> {
+            items.iter().map(|(id, text)| (id.to_string(), text.to_string())).collect()
+        };
+        assert_eq!(
+            window("p2", 1, 2, None),
+            pairs(&[("p1", "part 1"), ("p2", "part 2 edited"), ("p4", "part 4"), ("p5", "part 5")])
+        );
+        assert_eq!(window("p0", 2, 0, None), pairs(&[("p0", "part 0")]));
+        assert_eq!(
+            window("p2", 1, 1, Some(first.version)),
+            pairs(&[("p1", "part 1"), ("p2", "part 2"), ("p3", "part 3")])
+        );
+        assert!(window("p3", 1, 1, None).is_empty());
+        assert!(window("missing", 1, 1, None).is_empty());
+
+        let err = engine.get_neighbors("docs", "n", 1, 1, None).expect_err("chunk without an ordinal");
+        assert!(err.to_string().contains("has no ordinal"), "{err}");
+
+        // Ordinals survive flushing and compaction.
+        engine.auto_publish("docs", "model", "sig-2").unwrap();
+        engine.compact("docs").unwrap();
+        assert_eq!(
+            window("p4", 2, 1, None),
+            pairs(&[("p1", "part 1"), ("p2", "part 2 edited"), ("p4", "part 4"), ("p5", "part 5")])
+        );
+    }
+
+    #[test]
+    fn assemble_context_merges_adjacent_chunks_within_the_budget() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 2, "cosine", "model", "fp32", 16, 200, 100)
+            .unwrap();
+        let record = |chunk_id: &str, doc_id: &str, ordinal: Option<u32>, y: f32, text: Option<&str>| NativeRecord {
+            chunk_id: chunk_id.to_string(),
+            doc_id: doc_id.to_string(),
+            ordinal,
+            vector: vec![1.0, y],
+            metadata: serde_json::json!({}),
+            chunk_text: text.map(str::to_string),
+        };
+        engine
+            .upsert_batch(
+                "docs",
+                &[
+                    record("c0", "doc-1", Some(0), 0.0, Some("one two three four")),
+                    record("c1", "doc-1", Some(1), 0.1, Some("three four five six")),
+                    record("c2", "doc-1", Some(2), 0.2, Some("five six seven eight")),
+                    record("q0", "doc-2", Some(0), 0.3, Some("unrelated")),
+                    record("dup", "doc-3", None, 0.4, Some("unrelated")),
+                ],
+            )
+            .unwrap();
+        engine.auto_publish("docs", "model", "sig-1").unwrap();
+        engine.upsert_batch("docs", &[record("bare", "doc-4", None, 0.05, None)]).unwrap();
+
+        let opts = SearchOptions {
+            collection_id: "docs".to_string(),
+            query_vector: vec![1.0, 0.0],
+            top_k: 6,
+            include_uncommitted: true,
+            ..SearchOptions::default()
+        };
+        let passages = |budget: ContextBudget| -> Vec<(String, Vec<String>, String)> {
+            engine
+                .assemble_context(opts.clone(), budget)
+                .unwrap()
+                .passages
+                .into_iter()
+                .map(|p| (p.doc_id, p.chunk_ids, p.text))
+                .collect()
+        };
+        let passage = |doc_id: &str, chunk_ids: &[&str], text: &str| {
+            (doc_id.to_string(), chunk_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>(), text.to_string())
+        };
+
+        let context = engine.assemble_context(opts.clone(), ContextBudget::Tokens(100)).unwrap();
+        assert_eq!(context.total_chars, "one two three four five six seven eight".len() + "unrelated".len());
+        assert_eq!(
+            passages(ContextBudget::Tokens(100)),
+            vec![
+                passage("doc-1", &["c0", "c1", "c2"], "one two three four five six seven eight"),
+                passage("doc-2", &["q0"], "unrelated"),
+            ]
+        );
+        assert_eq!(passages(ContextBudget::Chars(20)), vec![passage("doc-1", &["c0"], "one two three four")]);
+
+        let err = engine.assemble_context(opts, ContextBudget::Chars(0)).expect_err("empty budget is rejected");
+        assert!(err.to_string().contains("budget must be a positive integer"), "{err}");
+    }
+
+    #[test]
+    fn search_after_pages_through_a_pinned_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 2, "cosine", "model", "fp32", 16, 200, 100)
+            .unwrap();
+        // Pairs of identical vectors and texts, so pages split ties; across
+        // pairs the vector and BM25 scores vary.
+        let record = |i: usize| NativeRecord {
+            chunk_id: format!("c{i:02}"),
+            doc_id: "doc".to_string(),
+            ordinal: None,
+            vector: vec![1.0, (i / 2) as f32 * 0.1],
+            metadata: serde_json::json!({}),
+            chunk_text: Some(format!("{}section{}", "handbook ".repeat(1 + (i / 2) % 3), " filler".repeat(i / 2))),
+        };
+        engine.upsert_batch("docs", &(0..12).map(record).collect::<Vec<_>>()).unwrap();
+        engine.auto_publish("docs", "model", "sig-1").unwrap();
+
+        let opts = |mode: SearchMode, top_k: usize| SearchOptions {
+            collection_id: "docs".to_string(),
+            query_vector: vec![1.0, 0.0],
+            top_k,
+            mode,
+            query_text: Some("handbook".to_string()),
+            ..SearchOptions::default()
+        };
+        let ids = |response: &SearchResponse| -> Vec<String> {
+            response.results.iter().map(|r| r.chunk_id.clone()).collect()
+        };
+
+        let keyword_scores: HashSet<u64> =
+            engine.search(opts(SearchMode::Keyword, 12)).unwrap().results.iter().map(|r| r.score.to_bits()).collect();
+        assert_eq!(keyword_scores.len(), 6);
+
+        let mut cases = vec![(SearchMode::Vector, Fusion::default()), (SearchMode::Keyword, Fusion::default())];
+        for fusion in [Fusion::default(), Fusion::MinMax, Fusion::ZScore, Fusion::Dbsf] {
+            cases.push((SearchMode::Hybrid, fusion));
+        }
+        for (mode, fusion) in cases {
+            let opts = |mode: SearchMode, top_k: usize| SearchOptions { fusion, ..opts(mode, top_k) };
+            let page1 = engine.search(opts(mode.clone(), 5)).unwrap();
+            let version = page1.manifest_version_used;
+            // A write published between pages does not shift them.
+            engine.upsert_batch("docs", &[NativeRecord { chunk_id: "new".to_string(), ..record(0) }]).unwrap();
+            engine.auto_publish("docs", "model", "sig-2").unwrap();
+
+            let mut paged = ids(&page1);
+            let mut next = page1.cursor.clone();
+            while let Some(encoded) = next {
+                assert!(paged.len() < 12, "{mode:?} {fusion:?} pages past the end");
+                let cursor = SearchCursor::decode(&encoded).unwrap();
+                let page = engine.search(SearchOptions { search_after: Some(cursor.clone()), ..opts(mode.clone(), 5) }).unwrap();
+                assert_eq!(page.manifest_version_used, version);
+                let again = engine.search(SearchOptions { search_after: Some(cursor), ..opts(mode.clone(), 5) }).unwrap();
+                assert_eq!(ids(&again), ids(&page));
+                if mode == SearchMode::Keyword {
+                    assert!(page.results.iter().all(|r| (0.0..=1.0).contains(&r.score)));
+                }
+                paged.extend(ids(&page));
+                next = page.cursor;
+            }
+
+            let pinned = |mode: SearchMode, top_k: usize| SearchOptions { manifest_version: Some(version), ..opts(mode, top_k) };
+            let full: Vec<String> = if mode == SearchMode::Hybrid {
+                // Every page fuses legs of the first page's depth, 2 x topK.
+                let leg = |mode: SearchMode| engine.search(pinned(mode, 10)).unwrap().results;
+                hybrid::fuse(&leg(SearchMode::Vector), &leg(SearchMode::Keyword), usize::MAX, 1.0, 1.0, fusion, None)
+                    .into_iter()
+                    .map(|r| r.chunk_id)
+                    .collect()
+            } else {
+                ids(&engine.search(pinned(mode.clone(), 12)).unwrap())
+            };
+            assert_eq!(paged, full, "{mode:?} {fusion:?}");
+            assert!(paged.len() > 10, "{mode:?} {fusion:?}");
+            engine.delete_chunks("docs", &["new".to_string()], "manual_revoke").unwrap();
+            engine.auto_publish("docs", "model", "sig-3").unwrap();
+        }
+
+        // Pending deletes still apply past the first page of a live search.
+        let live = |search_after| SearchOptions { include_uncommitted: true, search_after, ..opts(SearchMode::Vector, 5) };
+        let page1 = engine.search(live(None)).unwrap();
+        engine.delete_chunks("docs", &["c07".to_string()], "manual_revoke").unwrap();
+        let cursor = SearchCursor::decode(page1.cursor.as_deref().unwrap()).unwrap();
+        let page2 = engine.search(live(Some(cursor.clone()))).unwrap();
+        assert!(!ids(&page2).contains(&"c07".to_string()));
+        assert_eq!(page2.results.len(), 5);
+        // The page size may change between pages.
+        assert_eq!(engine.search(SearchOptions { top_k: 3, ..live(Some(cursor.clone())) }).unwrap().results.len(), 3);
+
+        let rejected = [
+            SearchOptions { group_by: Some(GroupBy::DocId), ..live(Some(cursor.clone())) },
+            SearchOptions { manifest_version: Some(cursor.manifest_version + 1), ..live(Some(cursor.clone())) },
+            // A cursor only continues the query and mode it was issued for.
+            SearchOptions { mode: SearchMode::Keyword, ..live(Some(cursor.clone())) },
+            SearchOptions { query_vector: vec![0.0, 1.0], ..live(Some(cursor.clone())) },
+            SearchOptions { filters: Some(serde_json::json!({"lang": "en"})), ..live(Some(cursor.clone())) },
+        ];
+        for bad in rejected {
+            assert!(engine.search(bad).is_err());
+        }
+        let Err(err) = engine.search(SearchOptions { query_text: Some("section".into()), ..live(Some(cursor.clone())) }) else {
+            panic!("a cursor replayed on another query is rejected")
+        };
+        assert!(err.to_string().contains("different query"), "{err}");
+        assert!(engine.search_batch(live(Some(cursor.clone())), Vec::new()).is_err());
+        let reranker = BoostReranker { seen: Default::default() };
+        let Err(err) = engine.search_reranked(live(Some(cursor)), &reranker, None) else { panic!("searchAfter with re-ranking is rejected") };
+        assert!(err.to_string().contains("searchAfter is not supported with re-ranking"), "{err}");
+        assert!(SearchCursor::decode("not a cursor").is_err());
+    }
+
+    #[test]
+    fn search_after_pages_hybrid_results_past_the_leg_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 2, "cosine", "model", "fp32", 16, 200, 100)
+            .unwrap();
+        // Vector scores fall with i, BM25 scores rise with it, so the legs'
+        // top entries barely overlap.
+        let record = |i: usize| NativeRecord {
+            chunk_id: format!("c{i:02}"),
+            doc_id: "doc".to_string(),
+            ordinal: None,
+            vector: vec![1.0, i as f32 * 0.05],
+            metadata: serde_json::json!({}),
+            chunk_text: Some(format!("{}{}", "handbook ".repeat(1 + i / 8), " filler".repeat(40 - i))),
+        };
+        engine.upsert_batch("docs", &(0..40).map(record).collect::<Vec<_>>()).unwrap();
+        engine.auto_publish("docs", "model", "sig").unwrap();
+
+        let opts = |mode: SearchMode, fusion: Fusion, search_after: Option<SearchCursor>| SearchOptions {
+            collection_id: "docs".to_string(),
+            query_vector: vec![1.0, 0.0],
+            top_k: 4,
+            mode,
+            query_text: Some("handbook".to_string()),
+            fusion,
+            search_after,
+            ..SearchOptions::default()
+        };
+        let page_all = |mode: SearchMode, fusion: Fusion| {
+            let mut pages = Vec::new();
+            let mut cursor = None;
+            loop {
+                let page = engine.search(opts(mode.clone(), fusion, cursor)).unwrap();
+                pages.push(page.results.iter().map(|r| r.chunk_id.clone()).collect::<Vec<_>>());
+                let Some(next) = page.cursor else { break };
+                assert!(pages.len() < 20, "{mode:?} {fusion:?} pages past the end");
+                cursor = Some(SearchCursor::decode(&next).unwrap());
+            }
+            pages
+        };
+
+        for fusion in [Fusion::default(), Fusion::MinMax, Fusion::ZScore, Fusion::Dbsf] {
+            let pages = page_all(SearchMode::Hybrid, fusion);
+            // 40 results in full pages of 4, well past the 2 x 8 chunks the
+            // first page's legs hold, and no cursor after the last page.
+            assert_eq!(pages.len(), 10, "{fusion:?}");
+            assert!(pages.iter().all(|p| p.len() == 4), "{fusion:?}");
+            let paged: Vec<String> = pages.concat();
+            let unique: HashSet<&String> = paged.iter().collect();
+            assert_eq!(unique.len(), 40, "{fusion:?}");
+
+            // Pages start with the fusion of legs 8 deep, in its order.
+            let leg = |mode: SearchMode, top_k: usize| {
+                engine.search(SearchOptions { top_k, ..opts(mode, fusion, None) }).unwrap().results
+            };
+            let first: Vec<String> =
+                hybrid::fuse(&leg(SearchMode::Vector, 8), &leg(SearchMode::Keyword, 8), usize::MAX, 1.0, 1.0, fusion, None)
+                    .into_iter()
+                    .map(|r| r.chunk_id)
+                    .collect();
+            assert!(first.len() <= 16, "{fusion:?}");
+            assert_eq!(paged[..first.len()], first[..], "{fusion:?}");
+        }
+
+        assert_eq!(page_all(SearchMode::Vector, Fusion::default()).len(), 10);
+        assert_eq!(page_all(SearchMode::Keyword, Fusion::default()).concat().len(), 40);
+    }
+
+    #[test]
+    fn search_after_pins_the_buffered_records_it_paged() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 2, "cosine", "model", "fp32", 16, 200, 100)
+            .unwrap();
+        let record = |i: usize| NativeRecord {
+            chunk_id: format!("c{i:02}"),
+            doc_id: "doc".to_string(),
+            ordinal: None,
+            vector: vec![1.0, i as f32 * 0.1],
+            metadata: serde_json::json!({}),
+            chunk_text: None,
+        };
+        engine.upsert_batch("docs", &(0..3).map(record).collect::<Vec<_>>()).unwrap();
+        engine.auto_publish("docs", "model", "sig").unwrap();
+        engine.upsert_batch("docs", &(3..6).map(record).collect::<Vec<_>>()).unwrap();
+        let live = |search_after| SearchOptions {
+            collection_id: "docs".to_string(),
+            query_vector: vec![1.0, 0.0],
+            top_k: 3,
+            include_uncommitted: true,
+            search_after,
+            ..SearchOptions::default()
+        };
+        let ids = |response: &SearchResponse| -> Vec<String> {
+            response.results.iter().map(|r| r.chunk_id.clone()).collect()
+        };
+
+        let page1 = engine.search(live(None)).unwrap();
+        assert_eq!(ids(&page1), ["c00", "c01", "c02"]);
+        let cursor = SearchCursor::decode(page1.cursor.as_deref().unwrap()).unwrap();
+        // A record buffered after the first page, ranked on the second, is
+        // not seen by its continuation.
+        engine.upsert_batch("docs", &[NativeRecord { chunk_id: "late".to_string(), ..record(3) }]).unwrap();
+        let page2 = engine.search(live(Some(cursor.clone()))).unwrap();
+        assert_eq!(ids(&page2), ["c03", "c04", "c05"]);
+        assert!(page2.cursor.is_none());
+
+        // Once the buffer is flushed its records cannot be pinned.
+        engine.flush_writes("docs").unwrap();
+        let Err(err) = engine.search(live(Some(cursor))) else { panic!("a flushed buffer invalidates the cursor") };
+        assert!(err.to_string().contains("flushed since it was issued"), "{err}");
+    }
+
+    /// Scores candidates by their `boost` metadata and remembers what it saw.
+    struct BoostReranker {
+        seen: std::cell::RefCell<Vec<RerankCandidate>>,
+    }
+
+    impl Reranker for BoostReranker {
+        fn rerank(&self, query_text: Option<&str>, candidates: &[RerankCandidate]) -> Result<Vec<f64>> {
+            assert_eq!(query_text, Some("boost"));
+            self.seen.borrow_mut().extend_from_slice(candidates);
+            Ok(candidates.iter().map(|c| c.metadata["boost"].as_f64().unwrap_or(0.0)).collect())
+        }
+    }
+
+    #[test]
+    fn search_reranked_rescores_committed_and_buffered_candidates() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 2, "cosine", "model", "fp16", 16, 200, 100)
+            .unwrap();
+        let record = |chunk_id: &str, y: f32, boost: f64| NativeRecord {
+            chunk_id: chunk_id.to_string(),
+            doc_id: "doc".to_string(),
+            ordinal: None,
+            vector: vec![1.0, y],
+            metadata: serde_json::json!({ "boost": boost }),
+            chunk_text: Some(format!("text of {chunk_id}")),
+        };
+        engine
+            .upsert_batch("docs", &[record("a", 0.0, 1.0), record("b", 0.1, 3.0), record("d", 0.3, 4.0)])
+            .unwrap();
+        engine.auto_publish("docs", "model", "sig-1").unwrap();
+        engine.upsert_batch("docs", &[record("c", 0.2, 2.0)]).unwrap();
+
+        let opts = SearchOptions {
+            collection_id: "docs".to_string(),
+            query_vector: vec![1.0, 0.0],
+            top_k: 2,
+            include_uncommitted: true,
+            query_text: Some("boost".to_string()),
+            explain: true,
+            ..SearchOptions::default()
+        };
+        let reranker = BoostReranker { seen: Default::default() };
+        let response = engine.search_reranked(opts.clone(), &reranker, Some(3)).unwrap();
+
+        // The reranker saw the top 3 by similarity, with text and metadata
+        // from both the segment and the write buffer.
+        let seen = reranker.seen.borrow();
+        let seen_ids: Vec<&str> = seen.iter().map(|c| c.chunk_id.as_str()).collect();
+        assert_eq!(seen_ids, vec!["a", "b", "c"]);
+        assert_eq!(seen[1].text.as_deref(), Some("text of b"));
+        assert_eq!(seen[2].text.as_deref(), Some("text of c"));
+        assert_eq!(seen[2].metadata, serde_json::json!({ "boost": 2.0 }));
+
+        let ranked: Vec<(&str, f64)> = response.results.iter().map(|r| (r.chunk_id.as_str(), r.score)).collect();
+        assert_eq!(ranked, vec![("b", 3.0), ("c", 2.0)]);
+        let rerank = response.results[0].explain.as_ref().unwrap().rerank.clone().unwrap();
+        assert_eq!((rerank.pre_rerank_rank, rerank.post_rerank_rank), (2, 1));
+        assert_eq!(rerank.pre_rerank_score, seen[1].score);
+        let rerank = response.results[1].explain.as_ref().unwrap().rerank.clone().unwrap();
+        assert_eq!((rerank.pre_rerank_rank, rerank.post_rerank_rank), (3, 2));
+
+        let err = engine.search_reranked(opts, &reranker, Some(1)).err().expect("depth below topK is rejected");
+        assert!(err.to_string().contains("rerankDepth must be at least topK"), "{err}");
+    }
+
+    #[test]
+    fn grouped_search_limits_results_per_document_and_metadata_value() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 2, "cosine", "model", "fp32", 16, 200, 100)
+            .unwrap();
+        let record = |chunk_id: &str, doc_id: &str, y: f32, metadata: serde_json::Value| NativeRecord {
+            chunk_id: chunk_id.to_string(),
+            doc_id: doc_id.to_string(),
+            ordinal: None,
+            vector: vec![1.0, y],
+            metadata,
+            chunk_text: Some(format!("handbook chunk {chunk_id}")),
+        };
+        let sales = || serde_json::json!({ "dept": "sales" });
+        let legal = || serde_json::json!({ "dept": "legal" });
+        engine
+            .upsert_batch(
+                "docs",
+                &[
+                    record("a", "doc-1", 0.0, sales()),
+                    record("b", "doc-1", 0.1, sales()),
+                    record("c", "doc-1", 0.2, legal()),
+                    record("d", "doc-2", 0.3, sales()),
+                    record("e", "doc-2", 0.4, legal()),
+                ],
+            )
+            .unwrap();
+        engine.auto_publish("docs", "model", "sig-1").unwrap();
+        engine.upsert_batch("docs", &[record("f", "doc-3", 0.5, serde_json::json!({}))]).unwrap();
+
+        let opts = |top_k: usize, group_by: &str, max_per_group: Option<usize>| SearchOptions {
+            collection_id: "docs".to_string(),
+            query_vector: vec![1.0, 0.0],
+            top_k,
+            include_uncommitted: true,
+            query_text: Some("handbook".to_string()),
+            group_by: Some(GroupBy::from_str(group_by)),
+            max_per_group,
+            ..SearchOptions::default()
+        };
+        let ids = |response: &SearchResponse| -> Vec<String> {
+            response.results.iter().map(|r| r.chunk_id.clone()).collect()
+        };
+        let group_keys = |response: &SearchResponse| -> Vec<serde_json::Value> {
+            response.groups.as_ref().unwrap().iter().map(|g| g.key.clone()).collect()
+        };
+
+        // Best chunk per document, including the buffered one.
+        let response = engine.search(opts(3, "doc_id", None)).unwrap();
+        assert_eq!(ids(&response), vec!["a", "d", "f"]);
+        assert_eq!(group_keys(&response), vec![serde_json::json!("doc-1"), serde_json::json!("doc-2"), serde_json::json!("doc-3")]);
+
+        let response = engine.search(opts(2, "doc_id", Some(2))).unwrap();
+        assert_eq!(ids(&response), vec!["a", "b", "d", "e"]);
+        let groups = response.groups.as_ref().unwrap();
+        assert_eq!(groups[1].score, response.results[2].score);
+        assert_eq!(groups[1].results.iter().map(|r| r.chunk_id.as_str()).collect::<Vec<_>>(), vec!["d", "e"]);
+
+        // Records without the field share the null group.
+        let response = engine.search(opts(3, "dept", None)).unwrap();
+        assert_eq!(ids(&response), vec!["a", "c", "f"]);
+        assert_eq!(group_keys(&response), vec![serde_json::json!("sales"), serde_json::json!("legal"), serde_json::Value::Null]);
+
+        for mode in [SearchMode::Keyword, SearchMode::Hybrid] {
+            let response = engine.search(SearchOptions { mode, ..opts(3, "doc_id", None) }).unwrap();
+            let mut keys = group_keys(&response);
+            keys.sort_by_key(|k| k.to_string());
+            assert_eq!(keys, vec![serde_json::json!("doc-1"), serde_json::json!("doc-2"), serde_json::json!("doc-3")]);
+            assert_eq!(response.results.len(), 3);
+        }
+
+        let err = engine.search(opts(3, "doc_id", Some(0))).err().expect("maxPerGroup 0 is rejected");
+        assert!(err.to_string().contains("maxPerGroup must be a positive integer"), "{err}");
+        let reranker = BoostReranker { seen: Default::default() };
+        let err = engine.search_reranked(opts(3, "doc_id", None), &reranker, None).err().expect("grouping with re-ranking is rejected");
+        assert!(err.to_string().contains("groupBy is not supported with re-ranking"), "{err}");
+    }
+
+    #[test]
+    fn keyword_and_hybrid_legs_apply_metadata_filters() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 2, "cosine", "model", "fp16", 16, 200, 100)
+            .unwrap();
+        // Two segments; the keyword "handbook" appears in both departments.
+        for batch in [0..10, 10..20] {
+            let records: Vec<NativeRecord> = batch
+                .map(|i| {
+                    let dept = ["sales", "legal"][i % 2];
+                    NativeRecord {
+                        chunk_id: format!("chunk-{i:02}"),
+                        doc_id: "doc".to_string(),
+                        ordinal: None,
+                        vector: vec![1.0, i as f32 * 0.05],
+                        metadata: serde_json::json!({ "dept": dept, "rank": i }),
+                        chunk_text: Some(format!("{dept} handbook section {i}")),
+                    }
+                })
+                .collect();
+            engine.upsert_batch("docs", &records).unwrap();
+            engine.flush_writes("docs").unwrap();
+        }
+        engine.auto_publish("docs", "model", "sig").unwrap();
+
+        let search = |mode: SearchMode, filters: serde_json::Value| {
+            engine
+                .search(SearchOptions {
+                    collection_id: "docs".to_string(),
+                    query_vector: vec![1.0, 0.0],
+                    top_k: 4,
+                    filters: Some(filters),
+                    mode,
+                    query_text: Some("handbook".to_string()),
+                    ..SearchOptions::default()
+                })
+                .unwrap()
+                .results
+                .into_iter()
+                .map(|r| r.chunk_id)
+                .collect::<Vec<_>>()
+        };
+        let odd = |ids: &[String]| {
+            ids.iter()
+                .all(|id| id.trim_start_matches("chunk-").parse::<usize>().unwrap() % 2 == 1)
+        };
+
+        for mode in [SearchMode::Keyword, SearchMode::Hybrid] {
+            let legal = search(mode.clone(), serde_json::json!({ "dept": "legal" }));
+            assert_eq!(
+                legal.len(),
+                4,
+                "{mode:?} should fill top_k from matching records"
+            );
+            assert!(
+                odd(&legal),
+                "{mode:?} returned another department: {legal:?}"
+            );
+
+            let ranged = search(
+                mode.clone(),
+                serde_json::json!({ "dept": "legal", "rank": { "$gt": 14 } }),
+            );
+            assert_eq!(ranged.len(), 3, "{mode:?}: {ranged:?}");
+            assert!(odd(&ranged));
+        }
+        assert!(search(SearchMode::Keyword, serde_json::json!({ "dept": "hr" })).is_empty());
+    }
+
+    #[test]
+    fn keyword_search_covers_uncommitted_buffer() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 2, "cosine", "model", "fp16", 16, 200, 100)
+            .unwrap();
+        let record = |chunk_id: &str, text: &str| NativeRecord {
+            chunk_id: chunk_id.to_string(),
+            doc_id: "doc".to_string(),
+            ordinal: None,
+            vector: vec![1.0, 0.0],
+            metadata: serde_json::json!({}),
+            chunk_text: Some(text.to_string()),
+        };
+        engine
+            .upsert_batch("docs", &[record("chunk-a", "alpha handbook"), record("chunk-c", "gamma handbook")])
+            .unwrap();
+        engine.flush_writes("docs").unwrap();
+        engine.auto_publish("docs", "model", "sig").unwrap();
+        // Buffered: a new chunk, and a rewrite of chunk-a that drops the keyword.
+        engine
+            .upsert_batch("docs", &[record("chunk-b", "beta handbook"), record("chunk-a", "alpha notes")])
+            .unwrap();
+
+        let search = |mode: SearchMode, include_uncommitted: bool| {
+            let mut hits: Vec<(String, Option<bool>)> = engine
+                .search(SearchOptions {
+                    collection_id: "docs".to_string(),
+                    query_vector: vec![1.0, 0.0],
+                    top_k: 5,
+                    include_uncommitted,
+                    mode,
+                    query_text: Some("handbook".to_string()),
+                    vector_weight: 0.0,
+                    ..SearchOptions::default()
+                })
+                .unwrap()
+                .results
+                .into_iter()
+                .filter(|r| r.score > 0.0)
+                .map(|r| (r.chunk_id, r.committed))
+                .collect();
+            hits.sort();
+            hits
+        };
+        let hit = |id: &str, committed: bool| (id.to_string(), Some(committed));
+
+        assert_eq!(
+            search(SearchMode::Keyword, true),
+            vec![hit("chunk-b", false), hit("chunk-c", true)]
+        );
+        assert_eq!(
+            search(SearchMode::Keyword, false),
+            vec![hit("chunk-a", true), hit("chunk-c", true)]
+        );
+        assert_eq!(
+            search(SearchMode::Hybrid, true),
+            vec![hit("chunk-b", false), hit("chunk-c", true)]
+        );
+
+        // Searches share the buffer's keyword index; writes extend it and a
+        // flush resets it.
+        let keyword_index = || engine.take_snapshot("docs", None, true).unwrap().manifest.buffer_keyword_index.unwrap();
+        let first = keyword_index();
+        assert_eq!(first.record_count(), 2);
+        assert!(Arc::ptr_eq(&first, &keyword_index()));
+        engine.upsert_batch("docs", &[record("chunk-d", "delta handbook")]).unwrap();
+        assert_eq!(keyword_index().record_count(), 3);
+        assert_eq!(first.record_count(), 2);
+        assert_eq!(search(SearchMode::Keyword, true).len(), 3);
+        engine.flush_writes("docs").unwrap();
+        assert_eq!(keyword_index().record_count(), 0);
+    }
+
+    #[test]
+    fn keyword_search_scores_only_the_latest_published_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 2, "cosine", "model", "fp16", 16, 200, 100)
+            .unwrap();
+        let record = |chunk_id: &str, text: &str| NativeRecord {
+            chunk_id: chunk_id.to_string(),
+            doc_id: "doc".to_string(),
+            ordinal: None,
+            vector: vec![1.0, 0.0],
+            metadata: serde_json::json!({}),
+            chunk_text: Some(text.to_string()),
+        };
+        engine
+            .upsert_batch("docs", &[record("chunk-a", "apple banana"), record("chunk-b", "cherry tart")])
+            .unwrap();
+        engine.flush_writes("docs").unwrap();
+        engine.auto_publish("docs", "model", "sig").unwrap();
+        engine.upsert_batch("docs", &[record("chunk-a", "apple pie recipe")]).unwrap();
+        engine.flush_writes("docs").unwrap();
+        engine.auto_publish("docs", "model", "sig").unwrap();
+        let manifest = engine.metadata.lock().unwrap().get_latest_manifest("docs").unwrap().unwrap();
+        assert_eq!(manifest.segment_ids.len(), 2);
+
+        let search = |mode: SearchMode, text: &str| {
+            engine
+                .search(SearchOptions {
+                    collection_id: "docs".to_string(),
+                    query_vector: vec![1.0, 0.0],
+                    top_k: 5,
+                    mode,
+                    query_text: Some(text.to_string()),
+                    vector_weight: 0.0,
+                    ..SearchOptions::default()
+                })
+                .unwrap()
+                .results
+                .into_iter()
+                .filter(|r| r.score > 0.0)
+                .map(|r| (r.chunk_id, r.score))
+                .collect::<Vec<_>>()
+        };
+
+        // The first segment's chunk-a is superseded: it neither matches nor
+        // shows up next to the second segment's version.
+        for mode in [SearchMode::Keyword, SearchMode::Hybrid] {
+            let apple = search(mode.clone(), "apple");
+            assert_eq!(apple.iter().map(|(id, _)| id.as_str()).collect::<Vec<_>>(), ["chunk-a"]);
+            assert!(search(mode.clone(), "banana").is_empty());
+            assert_eq!(search(mode, "recipe").len(), 1);
+        }
+        assert_eq!(search(SearchMode::Keyword, "apple"), [("chunk-a".to_string(), 1.0)]);
+    }
+
+    #[test]
+    fn keyword_search_parses_advanced_syntax() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 2, "cosine", "model", "fp16", 16, 200, 100)
+            .unwrap();
+        let texts = [
+            "Release notes for the authentication service",
+            "Draft release notes, do not publish",
+            "Authorization tokens expire after an hour",
+            "Changelog: notes on the release process",
+        ];
+        let records: Vec<NativeRecord> = texts
+            .iter()
+            .enumerate()
+            .map(|(i, text)| NativeRecord {
+                chunk_id: format!("chunk-{i}"),
+                doc_id: "doc".to_string(),
+                ordinal: None,
+                vector: vec![1.0, i as f32],
+                metadata: serde_json::json!({}),
+                chunk_text: Some(text.to_string()),
+            })
+            .collect();
+        engine.upsert_batch("docs", &records).unwrap();
+        engine.flush_writes("docs").unwrap();
+        engine.auto_publish("docs", "model", "sig").unwrap();
+
+        let search = |query: &str, keyword_syntax: KeywordSyntax| {
+            engine.search(SearchOptions {
+                collection_id: "docs".to_string(),
+                top_k: 10,
+                mode: SearchMode::Keyword,
+                query_text: Some(query.to_string()),
+                keyword_syntax,
+                ..SearchOptions::default()
+            })
+        };
+        let ids = |query: &str| {
+            let mut ids: Vec<String> = search(query, KeywordSyntax::Advanced)
+                .unwrap()
+                .results
+                .into_iter()
+                .map(|r| r.chunk_id)
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        assert_eq!(ids("\"release notes\" -draft"), vec!["chunk-0"]);
+        assert_eq!(ids("auth*"), vec!["chunk-0", "chunk-2"]);
+        assert_eq!(ids("changelog OR authorization"), vec!["chunk-2", "chunk-3"]);
+        assert_eq!(ids("NEAR(release process, 1)"), vec!["chunk-3"]);
+        // The simple syntax treats operators and quotes as plain text.
+        let simple = search("\"release notes\" -draft", KeywordSyntax::Simple).unwrap();
+        assert_eq!(simple.results.len(), 1);
+        assert_eq!(simple.results[0].chunk_id, "chunk-1");
+
+        let err = search("(release OR", KeywordSyntax::Advanced).err().expect("malformed query is rejected");
+        assert!(matches!(err, AkiDbError::InvalidArgument(ref m) if m.contains("Invalid keyword query")));
+    }
 }
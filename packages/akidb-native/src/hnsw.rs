@@ -9,6 +9,7 @@ use std::collections::BinaryHeap;
 use std::cmp::Ordering;
 
 use crate::distance;
+use crate::sq8::Sq8Vectors;
 
 // ─── Internal types ─────────────────────────────────────────────────────────
 
@@ -80,11 +81,13 @@ impl Ord for MaxCandidate {
 // ─── HNSW Graph ─────────────────────────────────────────────────────────────
 
 /// HNSW node: adjacency lists per layer.
+#[derive(Clone)]
 struct HnswNode {
     connections: Vec<Vec<u32>>,
 }
 
 /// The core HNSW index.
+#[derive(Clone)]
 pub struct HnswGraph {
     metric: String,
     dimension: usize,
@@ -94,6 +97,8 @@ pub struct HnswGraph {
     level_multiplier: f64,
 
     vectors: Vec<Vec<f32>>,
+    /// SQ8 codes that replace `vectors` for search after `quantize_sq8`.
+    quantized: Option<Sq8Vectors>,
     nodes: Vec<HnswNode>,
     max_level: i32,
     entry_point: i32,
@@ -116,6 +121,7 @@ impl HnswGraph {
             ef_search,
             level_multiplier: 1.0 / (m as f64).ln(),
             vectors: Vec::new(),
+            quantized: None,
             nodes: Vec::new(),
             max_level: -1,
             entry_point: -1,
@@ -127,6 +133,7 @@ impl HnswGraph {
     /// For cosine metric, vectors are normalized before insertion.
     pub fn build(&mut self, vectors: &[Vec<f32>]) {
         self.vectors.clear();
+        self.quantized = None;
         self.nodes.clear();
         self.max_level = -1;
         self.entry_point = -1;
@@ -145,6 +152,65 @@ impl HnswGraph {
         self.vectors = processed;
     }
 
+    /// Append one vector as the next node id and link it into the graph.
+    ///
+    /// Inserting vectors one at a time yields the same graph as `build` over
+    /// the same sequence. Must not be called on a quantized graph.
+    pub fn insert(&mut self, vector: &[f32]) -> u32 {
+        debug_assert!(self.quantized.is_none(), "insert into a quantized HNSW graph");
+        let node_id = self.nodes.len() as u32;
+        if self.metric == "cosine" {
+            self.insert_node(&distance::normalize(vector), node_id);
+        } else {
+            self.insert_node(vector, node_id);
+        }
+        node_id
+    }
+
+    /// Number of nodes in the graph.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Replace the full-precision vectors with SQ8 codes.
+    ///
+    /// Search distances are then computed on the codes (~4× less memory);
+    /// the graph itself is unchanged. Call after `build` or `deserialize`.
+    pub fn quantize_sq8(&mut self) {
+        if self.quantized.is_some() {
+            return;
+        }
+        self.quantized = Some(Sq8Vectors::encode(&self.metric, self.dimension, &self.vectors));
+        self.vectors = Vec::new();
+    }
+
+    /// Search on `vectors` (one row per node) instead of full-precision
+    /// vectors, e.g. the codes of an SQ8 segment's VectorBlock for a graph
+    /// deserialized from `serialize_graph` output.
+    pub fn use_sq8_vectors(&mut self, vectors: Sq8Vectors) -> Result<(), String> {
+        if vectors.len() != self.nodes.len() {
+            return Err(format!(
+                "SQ8 vector count {} does not match {} graph nodes",
+                vectors.len(),
+                self.nodes.len()
+            ));
+        }
+        self.quantized = Some(vectors);
+        self.vectors = Vec::new();
+        Ok(())
+    }
+
+    /// Whether search runs on SQ8 codes rather than full-precision vectors.
+    pub fn is_quantized(&self) -> bool {
+        self.quantized.is_some()
+    }
+
+    /// Whether every node has a vector to search on; false for a graph read
+    /// from `serialize_graph` output until `use_sq8_vectors`.
+    pub fn has_vectors(&self) -> bool {
+        self.quantized.is_some() || self.vectors.len() == self.nodes.len()
+    }
+
     /// Search for the topK most similar vectors to the query.
     /// Returns (node_id, score) pairs sorted by descending score.
     pub fn search(&self, query: &[f32], top_k: usize) -> Vec<(u32, f32)> {
@@ -246,9 +312,25 @@ impl HnswGraph {
 
     /// Serialize to the binary format (v2), compatible with HnswIndex.
     pub fn serialize(&self) -> Vec<u8> {
+        self.write_v2(true)
+    }
+
+    /// Serialize the graph alone (v2 with vectorCount 0), for segments whose
+    /// VectorBlock supplies the vectors searched on (SQ8).
+    pub fn serialize_graph(&self) -> Vec<u8> {
+        self.write_v2(false)
+    }
+
+    fn write_v2(&self, include_vectors: bool) -> Vec<u8> {
         let metric_bytes = self.metric.as_bytes();
         let node_count = self.nodes.len();
-        let vector_count = self.vectors.len();
+        // A quantized graph writes its dequantized vectors.
+        let dequantized: Vec<Vec<f32>> = match &self.quantized {
+            Some(q) if include_vectors => (0..q.len()).map(|i| q.decode(i)).collect(),
+            _ => Vec::new(),
+        };
+        let vectors = if self.quantized.is_some() || !include_vectors { &dequantized } else { &self.vectors };
+        let vector_count = vectors.len();
 
         // Calculate graph block size.
         let mut graph_size = 0usize;
@@ -313,7 +395,7 @@ impl HnswGraph {
         offset += 4;
 
         // Vectors block.
-        for vec in &self.vectors {
+        for vec in vectors {
             for &val in vec {
                 write_f32_le(&mut buf, offset, val);
                 offset += 4;
@@ -417,8 +499,11 @@ impl HnswGraph {
         offset += 4;
         let vector_count = read_u32_le(data, offset) as usize;
         offset += 4;
+        if vector_count != 0 && vector_count != node_count {
+            return Err(format!("Vector count {vector_count} does not match {node_count} nodes"));
+        }
 
-        // Read vectors.
+        // Read vectors (none for `serialize_graph` output).
         let mut vectors = Vec::with_capacity(vector_count);
         for i in 0..vector_count {
             let needed = dimension * 4;
@@ -460,11 +545,36 @@ impl HnswGraph {
         self.max_level = max_level;
         self.entry_point = entry_point;
         self.vectors = vectors;
+        self.quantized = None;
         self.nodes = nodes;
 
         Ok(())
     }
 
+    /// Read a single full-precision vector from serialized (v2) bytes without
+    /// deserializing the graph. Returns None for non-HNSW data or an
+    /// out-of-range `node_id`.
+    pub fn read_serialized_vector(data: &[u8], node_id: usize) -> Option<Vec<f32>> {
+        if data.len() < 24 || &data[0..4] != b"HNSW" || read_u32_le(data, 4) != 2 {
+            return None;
+        }
+        let metric_len = read_u32_le(data, 20) as usize;
+        let dims_offset = 24 + metric_len;
+        if data.len() < dims_offset + 20 {
+            return None;
+        }
+        let dimension = read_u32_le(data, dims_offset) as usize;
+        let vector_count = read_u32_le(data, dims_offset + 16) as usize;
+        if node_id >= vector_count {
+            return None;
+        }
+        let start = dims_offset + 20 + node_id * dimension * 4;
+        if data.len() < start + dimension * 4 {
+            return None;
+        }
+        Some((0..dimension).map(|d| read_f32_le(data, start + d * 4)).collect())
+    }
+
     // ─── Internal: level assignment ─────────────────────────────────────────
 
     /// Deterministic level assignment matching the TypeScript implementation.
@@ -593,9 +703,18 @@ impl HnswGraph {
 
     // ─── Internal: search ───────────────────────────────────────────────────
 
+    /// Distance from a (pre-processed) query to a stored node vector.
+    #[inline]
+    fn distance_to(&self, query: &[f32], id: u32) -> f32 {
+        match &self.quantized {
+            Some(q) => q.distance(query, id as usize),
+            None => (self.distance_fn)(query, &self.vectors[id as usize]),
+        }
+    }
+
     fn greedy_search(&self, query: &[f32], entry_id: u32, layer: usize) -> u32 {
         let mut current_id = entry_id;
-        let mut current_dist = (self.distance_fn)(query, &self.vectors[current_id as usize]);
+        let mut current_dist = self.distance_to(query, current_id);
 
         loop {
             let node = &self.nodes[current_id as usize];
@@ -607,7 +726,7 @@ impl HnswGraph {
 
             let mut improved = false;
             for &neighbor_id in neighbors {
-                let dist = (self.distance_fn)(query, &self.vectors[neighbor_id as usize]);
+                let dist = self.distance_to(query, neighbor_id);
                 if dist < current_dist || (dist == current_dist && neighbor_id < current_id) {
                     current_id = neighbor_id;
                     current_dist = dist;
@@ -637,7 +756,7 @@ impl HnswGraph {
     where
         F: Fn(u32) -> bool,
     {
-        let entry_dist = (self.distance_fn)(query, &self.vectors[entry_id as usize]);
+        let entry_dist = self.distance_to(query, entry_id);
         let entry_candidate = Candidate {
             id: entry_id,
             distance: entry_dist,
@@ -682,7 +801,7 @@ impl HnswGraph {
                 }
                 visited.insert(neighbor_id);
 
-                let dist = (self.distance_fn)(query, &self.vectors[neighbor_id as usize]);
+                let dist = self.distance_to(query, neighbor_id);
                 let c = Candidate {
                     id: neighbor_id,
                     distance: dist,
@@ -727,12 +846,16 @@ impl HnswGraph {
         F: Fn(u32) -> bool,
     {
         let mut results: Vec<Candidate> = Vec::new();
-        for (i, vec) in self.vectors.iter().enumerate() {
+        let vector_count = match &self.quantized {
+            Some(q) => q.len(),
+            None => self.vectors.len(),
+        };
+        for i in 0..vector_count {
             let id = i as u32;
-            if !filter(id) || vec.is_empty() {
+            if !filter(id) || (self.quantized.is_none() && self.vectors[i].is_empty()) {
                 continue;
             }
-            let dist = (self.distance_fn)(query, vec);
+            let dist = self.distance_to(query, id);
             results.push(Candidate { id, distance: dist });
         }
 
@@ -747,7 +870,7 @@ impl HnswGraph {
     }
 
     fn search_layer(&self, query: &[f32], entry_id: u32, ef: usize, layer: usize) -> Vec<Candidate> {
-        let entry_dist = (self.distance_fn)(query, &self.vectors[entry_id as usize]);
+        let entry_dist = self.distance_to(query, entry_id);
         let entry_candidate = Candidate {
             id: entry_id,
             distance: entry_dist,
@@ -783,7 +906,7 @@ impl HnswGraph {
                 }
                 visited.insert(neighbor_id);
 
-                let dist = (self.distance_fn)(query, &self.vectors[neighbor_id as usize]);
+                let dist = self.distance_to(query, neighbor_id);
                 let current_worst = results.peek().unwrap();
 
                 if results.len() < ef || dist < current_worst.0.distance {
@@ -952,6 +1075,80 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_read_serialized_vector() {
+        let mut graph = HnswGraph::new("l2", 4, 16, 200, 100);
+        graph.build(&[vec4(1.0, 2.0, 3.0, 4.0), vec4(5.0, 6.0, 7.0, 8.0)]);
+        let serialized = graph.serialize();
+
+        assert_eq!(
+            HnswGraph::read_serialized_vector(&serialized, 1),
+            Some(vec4(5.0, 6.0, 7.0, 8.0))
+        );
+        assert!(HnswGraph::read_serialized_vector(&serialized, 2).is_none());
+        assert!(HnswGraph::read_serialized_vector(b"IVPQ....", 0).is_none());
+    }
+
+    #[test]
+    fn test_quantized_search_matches_full_precision() {
+        let vectors: Vec<Vec<f32>> = (0..60)
+            .map(|i| {
+                let i = i as f32;
+                vec![(i * 0.7).sin(), (i * 0.3).cos(), (i * 1.1).sin(), (i * 0.5).cos()]
+            })
+            .collect();
+        let mut graph = HnswGraph::new("cosine", 4, 16, 200, 100);
+        graph.build(&vectors);
+        let query = [0.3, -0.2, 0.8, 0.1];
+        let exact = graph.search(&query, 5);
+
+        graph.quantize_sq8();
+        assert!(graph.is_quantized());
+        let approx = graph.search(&query, 5);
+        assert_eq!(approx.len(), 5);
+        assert_eq!(approx[0].0, exact[0].0);
+        for (a, e) in approx.iter().zip(&exact) {
+            assert!((a.1 - e.1).abs() < 0.05, "approx={a:?}, exact={e:?}");
+        }
+
+        // Filtered brute-force fallback also runs on codes.
+        let filtered = graph.search_filtered(&query, 2, |id| id == 7 || id == 11);
+        let ids: Vec<u32> = filtered.iter().map(|r| r.0).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&7) && ids.contains(&11));
+    }
+
+    #[test]
+    fn test_graph_only_serialization_searches_stored_codes() {
+        let vectors: Vec<Vec<f32>> = (0..60)
+            .map(|i| {
+                let i = i as f32;
+                vec![(i * 0.7).sin() * 3.0, (i * 0.3).cos(), (i * 1.1).sin(), (i * 0.5).cos() + 2.0]
+            })
+            .collect();
+        let mut graph = HnswGraph::new("cosine", 4, 16, 200, 100);
+        graph.build(&vectors);
+        let query = [0.3, -0.2, 0.8, 0.1];
+        let exact = graph.search(&query, 5);
+
+        let serialized = graph.serialize_graph();
+        assert!(serialized.len() < graph.serialize().len());
+        let mut restored = HnswGraph::new("cosine", 4, 16, 200, 100);
+        restored.deserialize(&serialized).unwrap();
+        assert!(!restored.has_vectors());
+
+        // Codes of the stored (unnormalized) vectors, as in an SQ8 VectorBlock.
+        let codec = crate::sq8::Sq8Codec::train(&vectors, 4);
+        let codes: Vec<u8> = vectors.iter().flat_map(|v| codec.encode(v)).collect();
+        assert!(restored.use_sq8_vectors(Sq8Vectors::from_codes("cosine", 4, codec.clone(), codes[4..].to_vec())).is_err());
+        restored.use_sq8_vectors(Sq8Vectors::from_codes("cosine", 4, codec, codes)).unwrap();
+        let approx = restored.search(&query, 5);
+        assert_eq!(approx[0].0, exact[0].0);
+        for (a, e) in approx.iter().zip(&exact) {
+            assert!((a.1 - e.1).abs() < 0.05, "approx={a:?}, exact={e:?}");
+        }
+    }
+
     #[test]
     fn test_deterministic() {
         let mut graph = HnswGraph::new("cosine", 4, 16, 200, 100);
@@ -977,6 +1174,26 @@ mod tests {
         assert_eq!(r2, r3);
     }
 
+    #[test]
+    fn test_incremental_insert_matches_build() {
+        let vectors: Vec<Vec<f32>> = (0..60)
+            .map(|i| {
+                let f = i as f32;
+                vec![f.sin(), (f * 0.3).cos(), (f * 1.1).sin(), (f * 0.5).cos()]
+            })
+            .collect();
+
+        let mut built = HnswGraph::new("cosine", 4, 16, 200, 100);
+        built.build(&vectors);
+        let mut inserted = HnswGraph::new("cosine", 4, 16, 200, 100);
+        for (i, v) in vectors.iter().enumerate() {
+            assert_eq!(inserted.insert(v), i as u32);
+        }
+
+        assert_eq!(inserted.node_count(), 60);
+        assert_eq!(inserted.serialize(), built.serialize());
+    }
+
     // ── search_filtered tests ────────────────────────────────────────────
 
     #[test]
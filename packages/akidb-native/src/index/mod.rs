@@ -5,18 +5,71 @@
 pub struct ExplainInfo {
     /// Raw vector similarity score (if vector search was used).
     pub vector_score: Option<f64>,
-    /// Raw BM25 score from FTS5 (if keyword search was used).
+    /// Normalized BM25 score (if keyword search was used).
     pub bm25_score: Option<f64>,
-    /// Final RRF score (if hybrid search was used).
+    /// Final RRF score (if hybrid search fused with RRF).
     pub rrf_score: Option<f64>,
     /// 1-based rank in vector results (if vector search was used).
     pub vector_rank: Option<usize>,
     /// 1-based rank in keyword results (if keyword search was used).
     pub bm25_rank: Option<usize>,
-    /// First 200 chars of chunk text (if stored in FTS5).
+    /// First 200 chars of chunk text (if the segment stores text).
     pub chunk_preview: Option<String>,
-    /// FTS5 matched terms in the query.
+    /// Query terms and phrases found in the chunk text, in query order.
+    /// Prefix terms keep their trailing `*`.
     pub matched_terms: Vec<String>,
+    /// Every occurrence of a matched term in the chunk text, by position.
+    pub term_matches: Vec<TermMatch>,
+    /// Passage of the chunk text with the most matched terms; None when
+    /// nothing matched.
+    pub snippet: Option<String>,
+    /// Character offset of `snippet` in the chunk text.
+    pub snippet_start: Option<usize>,
+    /// How hybrid search fused this result (hybrid search only).
+    pub fusion: Option<FusionInfo>,
+    /// Ranks before and after re-ranking (re-ranked searches only).
+    pub rerank: Option<RerankInfo>,
+}
+
+/// Where a re-ranked result stood before and after re-ranking.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RerankInfo {
+    /// 1-based rank among the candidates passed to the reranker.
+    pub pre_rerank_rank: usize,
+    /// Score the search ranked the candidate by; the result's score is the
+    /// reranker's.
+    pub pre_rerank_score: f64,
+    /// 1-based rank after re-ranking.
+    pub post_rerank_rank: usize,
+}
+
+/// The fusion strategy, its parameters and this result's inputs to it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FusionInfo {
+    /// "rrf", "min_max", "z_score" or "dbsf".
+    pub method: String,
+    /// RRF constant `k` (RRF only).
+    pub rrf_k: Option<f64>,
+    /// Weights as applied; the score-based strategies scale them to sum to 1.
+    pub vector_weight: f64,
+    pub keyword_weight: f64,
+    /// Unweighted contribution of the vector result: `1 / (k + rank)` for
+    /// RRF, the normalized score otherwise. None if not in the vector results.
+    pub vector_normalized: Option<f64>,
+    /// Unweighted contribution of the keyword result, as for `vector_normalized`.
+    pub keyword_normalized: Option<f64>,
+    /// Fused score the result was ranked by.
+    pub score: f64,
+}
+
+/// One occurrence of a query term in a chunk's text. Offsets are character
+/// (code point) positions, end-exclusive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TermMatch {
+    /// The query term or phrase, as listed in `matched_terms`.
+    pub term: String,
+    pub start: usize,
+    pub end: usize,
 }
 
 /// A search result from an index search.
@@ -26,4 +79,14 @@ pub struct SearchResult {
     pub score: f64,
     pub committed: Option<bool>,
     pub explain: Option<ExplainInfo>,
+    /// Stored record metadata (with `include_metadata`).
+    pub metadata: Option<serde_json::Value>,
+    /// Stored chunk text (with `include_text`; None if the record has none).
+    pub text: Option<String>,
+    /// Stored vector (with `include_vector`), decoded from the segment's
+    /// encoding, so lossy for fp16 and sq8 collections.
+    pub vector: Option<Vec<f32>>,
+    /// The result's `group_by` value (with `group_by`; null if the record
+    /// lacks the field).
+    pub group_key: Option<serde_json::Value>,
 }
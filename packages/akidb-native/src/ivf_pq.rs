@@ -0,0 +1,626 @@
+//! IVF-PQ (Inverted File with Product Quantization) index implementation.
+//!
+//! A k-means coarse quantizer partitions vectors into inverted lists; each
+//! vector's residual (vector − assigned centroid) is product-quantized into
+//! one byte per subquantizer. Search probes the `num_probes` closest lists and
+//! scores their codes with asymmetric distance lookup tables, so only the
+//! centroids, codebooks and codes have to stay resident in memory.
+//!
+//! Deterministic guarantees:
+//!   - k-means trains on a hash-selected sample of at most
+//!     `KMEANS_SAMPLES_PER_CENTROID` vectors per centroid, seeded evenly
+//!     strided over it (no randomness); every vector is then assigned.
+//!   - Tie-breaking: ascending node id when scores are equal.
+
+use std::cmp::Ordering;
+
+use crate::distance;
+
+/// Maximum centroids per subquantizer codebook (codes are stored as u8).
+const PQ_CODEBOOK_SIZE: usize = 256;
+
+/// Lloyd iterations for both the coarse quantizer and the PQ codebooks.
+const KMEANS_ITERATIONS: usize = 10;
+
+/// Training vectors per centroid; larger inputs are sampled down, so a
+/// k-means iteration costs O(k² · dim) rather than O(n · k · dim).
+const KMEANS_SAMPLES_PER_CENTROID: usize = 256;
+
+/// IVF-PQ index over a single segment's vectors.
+pub struct IvfPqIndex {
+    metric: String,
+    dimension: usize,
+    /// Requested coarse cluster count; 0 derives √n at build time.
+    num_clusters: usize,
+    num_subquantizers: usize,
+
+    /// Coarse centroids, `nlist × dimension`.
+    centroids: Vec<Vec<f32>>,
+    /// Codebook entries per subquantizer, flattened `m × ksub × dsub`.
+    codebooks: Vec<f32>,
+    codebook_size: usize,
+    /// Node ids per coarse cluster, ascending.
+    lists: Vec<Vec<u32>>,
+    /// PQ codes in node order, `node_count × m`.
+    codes: Vec<u8>,
+    node_count: usize,
+}
+
+impl IvfPqIndex {
+    pub fn new(metric: &str, dimension: usize, num_clusters: usize, num_subquantizers: usize) -> Self {
+        IvfPqIndex {
+            metric: metric.to_string(),
+            dimension,
+            num_clusters,
+            num_subquantizers: num_subquantizers.max(1),
+            centroids: Vec::new(),
+            codebooks: Vec::new(),
+            codebook_size: 0,
+            lists: Vec::new(),
+            codes: Vec::new(),
+            node_count: 0,
+        }
+    }
+
+    /// Approximate resident size in bytes (centroids, codebooks, lists, codes).
+    pub fn memory_size_bytes(&self) -> usize {
+        self.centroids.len() * self.dimension * 4
+            + self.codebooks.len() * 4
+            + self.node_count * 4
+            + self.codes.len()
+    }
+
+    /// Build the index from a batch of vectors.
+    /// For cosine metric, vectors are normalized before training.
+    pub fn build(&mut self, vectors: &[Vec<f32>]) {
+        self.centroids.clear();
+        self.codebooks.clear();
+        self.lists.clear();
+        self.codes.clear();
+        self.codebook_size = 0;
+        self.node_count = vectors.len();
+
+        if vectors.is_empty() {
+            return;
+        }
+
+        let processed: Vec<Vec<f32>> = if self.metric == "cosine" {
+            vectors.iter().map(|v| distance::normalize(v)).collect()
+        } else {
+            vectors.to_vec()
+        };
+
+        let n = processed.len();
+        let nlist = if self.num_clusters == 0 {
+            ((n as f64).sqrt().round() as usize).max(1)
+        } else {
+            self.num_clusters.min(n)
+        };
+
+        // Coarse quantizer.
+        let (centroids, assignments) = kmeans(&processed, nlist);
+        self.lists = vec![Vec::new(); centroids.len()];
+        for (id, &list) in assignments.iter().enumerate() {
+            self.lists[list].push(id as u32);
+        }
+
+        // Residuals feed the product quantizer.
+        let residuals: Vec<Vec<f32>> = processed
+            .iter()
+            .zip(&assignments)
+            .map(|(v, &list)| v.iter().zip(&centroids[list]).map(|(a, b)| a - b).collect())
+            .collect();
+        self.centroids = centroids;
+
+        let m = self.num_subquantizers;
+        let dsub = self.dimension / m;
+        let ksub = n.min(PQ_CODEBOOK_SIZE);
+        self.codebook_size = ksub;
+        self.codebooks = vec![0.0; m * ksub * dsub];
+        self.codes = vec![0u8; n * m];
+
+        for s in 0..m {
+            let sub_vectors: Vec<Vec<f32>> = residuals
+                .iter()
+                .map(|r| r[s * dsub..(s + 1) * dsub].to_vec())
+                .collect();
+            let (codebook, codes) = kmeans(&sub_vectors, ksub);
+            for (k, entry) in codebook.iter().enumerate() {
+                let base = (s * ksub + k) * dsub;
+                self.codebooks[base..base + dsub].copy_from_slice(entry);
+            }
+            for (id, &code) in codes.iter().enumerate() {
+                self.codes[id * m + s] = code as u8;
+            }
+        }
+    }
+
+    /// Search for the topK most similar vectors, probing `num_probes` lists.
+    /// Returns (node_id, score) pairs sorted by descending score.
+    pub fn search(&self, query: &[f32], top_k: usize, num_probes: usize) -> Vec<(u32, f32)> {
+        self.search_filtered(query, top_k, num_probes, |_| true)
+    }
+
+    /// Probe-based search with an inline filter predicate.
+    ///
+    /// If the probed lists yield fewer than topK passing candidates, every
+    /// list is scanned so selective filters still return all matches.
+    ///
+    /// `filter` returns true for node IDs that PASS the filter.
+    pub fn search_filtered<F>(
+        &self,
+        query: &[f32],
+        top_k: usize,
+        num_probes: usize,
+        filter: F,
+    ) -> Vec<(u32, f32)>
+    where
+        F: Fn(u32) -> bool,
+    {
+        if self.node_count == 0 || top_k == 0 || self.centroids.is_empty() {
+            return Vec::new();
+        }
+
+        let processed_query = if self.metric == "cosine" {
+            distance::normalize(query)
+        } else {
+            query.to_vec()
+        };
+
+        // Rank coarse lists by centroid distance.
+        let dist_fn = distance::get_distance_fn(&self.metric);
+        let mut ranked_lists: Vec<(usize, f32)> = self
+            .centroids
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (i, dist_fn(&processed_query, c)))
+            .collect();
+        ranked_lists.sort_by(|a, b| {
+            a.1.partial_cmp(&b.1)
+                .unwrap_or(Ordering::Equal)
+                .then(a.0.cmp(&b.0))
+        });
+
+        let probes = num_probes.clamp(1, ranked_lists.len());
+        let mut candidates = self.scan_lists(&processed_query, &ranked_lists[..probes], &filter);
+        if candidates.len() < top_k && probes < ranked_lists.len() {
+            candidates.extend(self.scan_lists(&processed_query, &ranked_lists[probes..], &filter));
+        }
+
+        // Deterministic sort: ascending distance, ascending id for ties.
+        candidates.sort_by(|a, b| {
+            a.1.partial_cmp(&b.1)
+                .unwrap_or(Ordering::Equal)
+                .then(a.0.cmp(&b.0))
+        });
+        candidates.truncate(top_k);
+
+        candidates
+            .into_iter()
+            .map(|(id, d)| (id, distance::distance_to_score(&self.metric, d)))
+            .collect()
+    }
+
+    /// Serialize to the binary format (v1).
+    pub fn serialize(&self) -> Vec<u8> {
+        let metric_bytes = self.metric.as_bytes();
+        let mut buf = Vec::with_capacity(
+            32 + metric_bytes.len() + self.memory_size_bytes() + self.lists.len() * 4,
+        );
+
+        buf.extend_from_slice(b"IVPQ");
+        buf.extend_from_slice(&1u32.to_le_bytes());
+        buf.extend_from_slice(&(metric_bytes.len() as u32).to_le_bytes());
+        buf.extend_from_slice(metric_bytes);
+        buf.extend_from_slice(&(self.dimension as u32).to_le_bytes());
+        buf.extend_from_slice(&(self.centroids.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&(self.num_subquantizers as u32).to_le_bytes());
+        buf.extend_from_slice(&(self.codebook_size as u32).to_le_bytes());
+        buf.extend_from_slice(&(self.node_count as u32).to_le_bytes());
+
+        for centroid in &self.centroids {
+            for &val in centroid {
+                buf.extend_from_slice(&val.to_le_bytes());
+            }
+        }
+        for &val in &self.codebooks {
+            buf.extend_from_slice(&val.to_le_bytes());
+        }
+        for list in &self.lists {
+            buf.extend_from_slice(&(list.len() as u32).to_le_bytes());
+            for &id in list {
+                buf.extend_from_slice(&id.to_le_bytes());
+            }
+        }
+        buf.extend_from_slice(&self.codes);
+
+        buf
+    }
+
+    /// Deserialize from the binary format (v1).
+    pub fn deserialize(&mut self, data: &[u8]) -> Result<(), String> {
+        let len = data.len();
+        if len < 12 {
+            return Err("Buffer too small for IVF-PQ header".into());
+        }
+        if &data[0..4] != b"IVPQ" {
+            return Err("Invalid magic bytes".into());
+        }
+
+        let mut offset = 4;
+
+        macro_rules! check_bounds {
+            ($n:expr, $label:expr) => {
+                if $n > len - offset {
+                    return Err(format!(
+                        "Truncated IVF-PQ data: need {} bytes for {} at offset {}, but only {} remain",
+                        $n, $label, offset, len - offset
+                    ));
+                }
+            };
+        }
+
+        // Byte size of a section from untrusted header counts.
+        macro_rules! section_len {
+            ($label:expr, $($factor:expr),+) => {{
+                let mut n: usize = 1;
+                $(
+                    n = n.checked_mul($factor).ok_or_else(|| format!("IVF-PQ {} size overflows", $label))?;
+                )+
+                n
+            }};
+        }
+
+        macro_rules! read_u32 {
+            ($label:expr) => {{
+                check_bounds!(4, $label);
+                let v = u32::from_le_bytes([
+                    data[offset],
+                    data[offset + 1],
+                    data[offset + 2],
+                    data[offset + 3],
+                ]);
+                offset += 4;
+                v as usize
+            }};
+        }
+
+        let version = read_u32!("version");
+        if version != 1 {
+            return Err(format!("Unsupported version: {version}"));
+        }
+
+        let metric_len = read_u32!("metric_len");
+        check_bounds!(metric_len, "metric string");
+        let metric = std::str::from_utf8(&data[offset..offset + metric_len])
+            .map_err(|e| format!("Invalid metric string: {e}"))?;
+        offset += metric_len;
+        if metric != self.metric {
+            return Err(format!(
+                "Metric mismatch: index has {metric}, expected {}",
+                self.metric
+            ));
+        }
+
+        let dimension = read_u32!("dimension");
+        if dimension != self.dimension {
+            return Err(format!(
+                "Dimension mismatch: index has {dimension}, expected {}",
+                self.dimension
+            ));
+        }
+
+        let nlist = read_u32!("nlist");
+        let m = read_u32!("num_subquantizers");
+        let ksub = read_u32!("codebook_size");
+        let node_count = read_u32!("node_count");
+        if m == 0 || dimension % m != 0 {
+            return Err(format!(
+                "Invalid subquantizer count {m} for dimension {dimension}"
+            ));
+        }
+        let dsub = dimension / m;
+
+        let read_f32s = |offset: &mut usize, count: usize| -> Vec<f32> {
+            let out = data[*offset..*offset + count * 4]
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect();
+            *offset += count * 4;
+            out
+        };
+
+        check_bounds!(section_len!("centroids", nlist, dimension, 4), "centroids");
+        let centroids: Vec<Vec<f32>> = (0..nlist)
+            .map(|_| read_f32s(&mut offset, dimension))
+            .collect();
+
+        check_bounds!(section_len!("codebooks", m, ksub, dsub, 4), "codebooks");
+        let codebooks = read_f32s(&mut offset, m * ksub * dsub);
+
+        let mut lists = Vec::with_capacity(nlist);
+        for i in 0..nlist {
+            let count = read_u32!(&format!("list {i} length"));
+            check_bounds!(section_len!(format!("list {i}"), count, 4), &format!("list {i} ids"));
+            let mut list = Vec::with_capacity(count);
+            for _ in 0..count {
+                let id = read_u32!("list id") as u32;
+                if id as usize >= node_count {
+                    return Err(format!("List {i} references node {id} beyond {node_count}"));
+                }
+                list.push(id);
+            }
+            lists.push(list);
+        }
+
+        check_bounds!(section_len!("codes", node_count, m), "codes");
+        let codes = data[offset..offset + node_count * m].to_vec();
+        if codes.iter().any(|&c| c as usize >= ksub) {
+            return Err("PQ code out of codebook range".into());
+        }
+
+        self.num_subquantizers = m;
+        self.centroids = centroids;
+        self.codebooks = codebooks;
+        self.codebook_size = ksub;
+        self.lists = lists;
+        self.codes = codes;
+        self.node_count = node_count;
+
+        Ok(())
+    }
+
+    // ─── Internal: asymmetric distance computation ──────────────────────────
+
+    /// Score every passing node in the given lists. Returns (node_id, distance).
+    fn scan_lists<F>(&self, query: &[f32], lists: &[(usize, f32)], filter: &F) -> Vec<(u32, f32)>
+    where
+        F: Fn(u32) -> bool,
+    {
+        let m = self.num_subquantizers;
+        let dsub = self.dimension / m;
+        let ksub = self.codebook_size;
+        let mut out = Vec::new();
+        let mut table = vec![0.0_f32; m * ksub];
+
+        for &(list_idx, _) in lists {
+            let list = &self.lists[list_idx];
+            if list.is_empty() {
+                continue;
+            }
+            let centroid = &self.centroids[list_idx];
+
+            // L2 decomposes over the residual; inner products decompose over
+            // the centroid plus per-subspace residual contributions.
+            let (base, residual_query): (f32, Vec<f32>) = if self.metric == "l2" {
+                (0.0, query.iter().zip(centroid).map(|(q, c)| q - c).collect())
+            } else {
+                (distance::dot_product(query, centroid), query.to_vec())
+            };
+
+            for s in 0..m {
+                let q_sub = &residual_query[s * dsub..(s + 1) * dsub];
+                for k in 0..ksub {
+                    let entry_base = (s * ksub + k) * dsub;
+                    let entry = &self.codebooks[entry_base..entry_base + dsub];
+                    table[s * ksub + k] = if self.metric == "l2" {
+                        q_sub.iter().zip(entry).map(|(a, b)| (a - b) * (a - b)).sum()
+                    } else {
+                        distance::dot_product(q_sub, entry)
+                    };
+                }
+            }
+
+            for &id in list {
+                if !filter(id) {
+                    continue;
+                }
+                let code = &self.codes[id as usize * m..(id as usize + 1) * m];
+                let acc: f32 = base
+                    + code
+                        .iter()
+                        .enumerate()
+                        .map(|(s, &c)| table[s * ksub + c as usize])
+                        .sum::<f32>();
+                let dist = match self.metric.as_str() {
+                    "l2" => acc.max(0.0).sqrt(),
+                    "cosine" => 1.0 - acc,
+                    _ => -acc,
+                };
+                out.push((id, dist));
+            }
+        }
+
+        out
+    }
+}
+
+// ─── k-means ────────────────────────────────────────────────────────────────
+
+/// Deterministic Lloyd's k-means over `training_sample`, with evenly strided
+/// seeding. Returns (centroids, assignment per input vector).
+fn kmeans(vectors: &[Vec<f32>], k: usize) -> (Vec<Vec<f32>>, Vec<usize>) {
+    let n = vectors.len();
+    let k = k.clamp(1, n.max(1));
+    let dim = vectors.first().map(|v| v.len()).unwrap_or(0);
+
+    let sample: Vec<&Vec<f32>> = training_sample(n, k.saturating_mul(KMEANS_SAMPLES_PER_CENTROID))
+        .into_iter()
+        .map(|i| &vectors[i])
+        .collect();
+    let s = sample.len();
+    let mut centroids: Vec<Vec<f32>> = (0..k).map(|i| sample[i * s / k].clone()).collect();
+    let mut assignments = vec![0usize; s];
+
+    for iteration in 0..KMEANS_ITERATIONS {
+        let mut changed = false;
+        for (i, v) in sample.iter().enumerate() {
+            let nearest = nearest_centroid(v, &centroids);
+            if nearest != assignments[i] {
+                assignments[i] = nearest;
+                changed = true;
+            }
+        }
+        if iteration > 0 && !changed {
+            break;
+        }
+
+        let mut sums = vec![vec![0.0_f32; dim]; k];
+        let mut counts = vec![0usize; k];
+        for (&v, &c) in sample.iter().zip(&assignments) {
+            counts[c] += 1;
+            for (s, x) in sums[c].iter_mut().zip(v) {
+                *s += x;
+            }
+        }
+        // Empty clusters keep their previous centroid.
+        for c in 0..k {
+            if counts[c] > 0 {
+                let inv = 1.0 / counts[c] as f32;
+                centroids[c] = sums[c].iter().map(|s| s * inv).collect();
+            }
+        }
+    }
+
+    let assignments = vectors.iter().map(|v| nearest_centroid(v, &centroids)).collect();
+    (centroids, assignments)
+}
+
+/// Ascending indices of up to `max` of `n` vectors, chosen by a fixed hash of
+/// the index so the same input always trains the same way.
+fn training_sample(n: usize, max: usize) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..n).collect();
+    if n > max {
+        indices.select_nth_unstable_by_key(max, |&i| sample_hash(i as u64));
+        indices.truncate(max);
+        indices.sort_unstable();
+    }
+    indices
+}
+
+/// SplitMix64 finalizer.
+fn sample_hash(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+/// Index of the nearest centroid by squared L2; lowest index wins ties.
+fn nearest_centroid(v: &[f32], centroids: &[Vec<f32>]) -> usize {
+    let mut best = 0;
+    let mut best_dist = f32::INFINITY;
+    for (i, c) in centroids.iter().enumerate() {
+        let d: f32 = v.iter().zip(c).map(|(a, b)| (a - b) * (a - b)).sum();
+        if d < best_dist {
+            best = i;
+            best_dist = d;
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_vectors(n: usize) -> Vec<Vec<f32>> {
+        (0..n)
+            .map(|i| {
+                let f = i as f32;
+                vec![
+                    (f * 0.7).sin(),
+                    (f * 0.3).cos(),
+                    (f * 1.1).sin(),
+                    (f * 0.5).cos(),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_build_and_search_finds_exact_match() {
+        let vectors = sample_vectors(200);
+        for metric in ["cosine", "l2", "dot"] {
+            let mut index = IvfPqIndex::new(metric, 4, 8, 2);
+            index.build(&vectors);
+            assert_eq!(index.node_count, 200);
+
+            // Probing every list makes the search exhaustive over the codes.
+            let results = index.search(&vectors[42], 5, 8);
+            assert_eq!(results.len(), 5, "metric {metric}");
+            for i in 1..results.len() {
+                assert!(results[i - 1].1 >= results[i].1);
+            }
+            if metric != "dot" {
+                assert!(results.iter().any(|(id, _)| *id == 42), "metric {metric}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_search_filtered_widens_probes() {
+        let vectors = sample_vectors(100);
+        let mut index = IvfPqIndex::new("l2", 4, 10, 2);
+        index.build(&vectors);
+
+        // A single probe cannot hold all even ids; the fallback must widen.
+        let results = index.search_filtered(&[0.5, 0.5, 0.5, 0.5], 10, 1, |id| id % 2 == 0);
+        assert_eq!(results.len(), 10);
+        for (id, _) in &results {
+            assert_eq!(id % 2, 0);
+        }
+    }
+
+    #[test]
+    fn test_serialize_deserialize_roundtrip() {
+        let vectors = sample_vectors(64);
+        let mut index = IvfPqIndex::new("cosine", 4, 0, 4);
+        index.build(&vectors);
+        let query = vec![0.8, 0.2, 0.1, 0.0];
+        let before = index.search(&query, 5, 3);
+
+        let mut restored = IvfPqIndex::new("cosine", 4, 0, 4);
+        restored.deserialize(&index.serialize()).unwrap();
+        assert_eq!(before, restored.search(&query, 5, 3));
+
+        let mut wrong_metric = IvfPqIndex::new("l2", 4, 0, 4);
+        assert!(wrong_metric.deserialize(&index.serialize()).is_err());
+
+        // Huge nlist, codebook size or node count in the header is rejected.
+        let nlist_offset = 4 + 4 + 4 + "cosine".len() + 4;
+        for field in [0, 2, 3] {
+            let mut data = index.serialize();
+            let at = nlist_offset + field * 4;
+            data[at..at + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+            assert!(restored.deserialize(&data).is_err(), "field {field}");
+        }
+    }
+
+    #[test]
+    fn test_training_sample_is_capped_and_deterministic() {
+        assert_eq!(training_sample(5, 10), vec![0, 1, 2, 3, 4]);
+        let sample = training_sample(10_000, 300);
+        assert_eq!(sample.len(), 300);
+        assert!(sample.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(sample, training_sample(10_000, 300));
+        // Not just a prefix of the input.
+        assert!(*sample.last().unwrap() > 5_000);
+
+        // Vectors outside the sample are still assigned to a list.
+        let vectors = sample_vectors(600);
+        let (centroids, assignments) = kmeans(&vectors, 2);
+        assert_eq!(assignments.len(), 600);
+        assert!(assignments.iter().all(|&c| c < centroids.len()));
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let vectors = sample_vectors(80);
+        let mut a = IvfPqIndex::new("cosine", 4, 6, 2);
+        let mut b = IvfPqIndex::new("cosine", 4, 6, 2);
+        a.build(&vectors);
+        b.build(&vectors);
+        assert_eq!(a.serialize(), b.serialize());
+    }
+}
@@ -1,4 +1,5 @@
 // ─── Module declarations ─────────────────────────────────────────────────────
+mod binary;
 mod collection;
 mod compaction;
 mod distance;
@@ -6,36 +7,54 @@ mod engine;
 mod error;
 mod fp16;
 mod hnsw;
+mod ivf_pq;
 mod index;
 mod manifest;
 mod metadata;
 mod query;
 mod segment;
+mod sq8;
 mod storage;
+mod text;
 mod wal;
 mod write;
 
+pub use crate::collection::CreateCollectionOptions;
 pub use crate::engine::{EngineInner, EngineOptions};
 pub use crate::error::AkiDbError;
+pub use crate::index::SearchResult;
 pub use crate::metadata::{Collection, Manifest};
-pub use crate::query::{SearchMode, SearchOptions};
+pub use crate::query::{
+    AssembledContext, BatchQuery, ContextBudget, ContextPassage, Fusion, GroupBy, KeywordSyntax, RerankCandidate,
+    Reranker, SearchCursor, SearchGroup, SearchMode, SearchOptions, SearchResponse, StoredRecord,
+};
 pub use crate::write::NativeRecord;
 #[doc(hidden)]
 pub mod bench_support {
-    pub use crate::distance::{cosine_distance, dot_product, l2_distance, normalize};
+    pub use crate::distance::{
+        active_kernels, cosine_distance, dot_distance, dot_product, kernel_sets, l2_distance,
+        normalize, KernelSet,
+    };
+    pub use crate::fp16::encode_fp16_vector;
     pub use crate::hnsw::HnswGraph;
     pub use crate::write::NativeRecord;
 }
 
 #[cfg(feature = "node-api")]
 mod node_api {
+use std::sync::Arc;
+
 use napi::bindgen_prelude::*;
+use napi::threadsafe_function::ThreadsafeFunction;
 use napi_derive::napi;
 
 use crate::engine::{EngineInner, EngineOptions};
 use crate::write::NativeRecord;
 
 mod job_registry;
+mod reranker;
+
+use reranker::{JsReranker, RerankRequestJs, SearchRerankedTask};
 
 // ═════════════════════════════════════════════════════════════════════════════
 // AkiDbEngine — the new v2.5 NAPI surface (full engine)
@@ -46,6 +65,8 @@ mod job_registry;
 pub struct EngineOptionsJs {
     pub storage_path: String,
     pub disable_wal: Option<bool>,
+    /// Worker threads for per-segment vector search. Default: available parallelism.
+    pub search_threads: Option<i64>,
 }
 
 #[napi(object)]
@@ -61,6 +82,14 @@ pub struct CollectionJs {
     pub hnsw_m: i64,
     pub hnsw_ef_construction: i64,
     pub hnsw_ef_search: i64,
+    pub index_type: String,
+    pub ivf_num_clusters: Option<i64>,
+    pub ivf_num_probes: Option<i64>,
+    pub pq_num_subquantizers: Option<i64>,
+    pub tokenizer: String,
+    pub stemmer: Option<String>,
+    pub stop_words: Option<String>,
+    pub remove_diacritics: bool,
 }
 
 #[napi(object)]
@@ -69,7 +98,7 @@ pub struct CreateCollectionOptsJs {
     pub dimension: i64,
     pub metric: String,
     pub embedding_model_id: String,
-    /// Vector quantization: "fp16" (default) or "sq8".
+    /// Vector quantization: "fp16" (default), "sq8", or "fp32" (lossless).
     pub quantization: Option<String>,
     /// HNSW M parameter (max connections per node). Default: 16, range: 4-64.
     pub hnsw_m: Option<i64>,
@@ -77,12 +106,30 @@ pub struct CreateCollectionOptsJs {
     pub hnsw_ef_construction: Option<i64>,
     /// HNSW efSearch parameter. Default: 100, range: 10-500.
     pub hnsw_ef_search: Option<i64>,
+    /// Per-segment vector index: "hnsw" (default), "ivf_pq", or "binary" (1 bit per dimension, always re-scored).
+    pub index_type: Option<String>,
+    /// IVF coarse clusters per segment. Default: √(segment record count).
+    pub ivf_num_clusters: Option<i64>,
+    /// IVF lists probed per query. Default: 8.
+    pub ivf_num_probes: Option<i64>,
+    /// PQ subquantizers; must divide dimension. Default: dimension / 8.
+    pub pq_num_subquantizers: Option<i64>,
+    /// Keyword tokenizer: "unicode61" (default) or "cjk_bigram" (overlapping character pairs for CJK text).
+    pub tokenizer: Option<String>,
+    /// Snowball stemmer for keyword search: "porter" (English) or a language such as "french". Default: none.
+    pub stemmer: Option<String>,
+    /// Stop words dropped from keyword search: "english". Default: none.
+    pub stop_words: Option<String>,
+    /// Fold diacritics in keyword search ("Café" matches "cafe"). Default: true.
+    pub remove_diacritics: Option<bool>,
 }
 
 #[napi(object)]
 pub struct RecordJs {
     pub chunk_id: String,
     pub doc_id: String,
+    /// Position of the chunk in its document, for getNeighbors.
+    pub ordinal: Option<u32>,
     pub vector: Vec<f64>,
     pub metadata_json: String,
     pub chunk_text: Option<String>,
@@ -106,14 +153,96 @@ pub struct SearchOptsJs {
     pub mode: Option<String>,
     /// Query text for keyword/hybrid search.
     pub query_text: Option<String>,
-    /// Weight for vector results in hybrid RRF fusion (default 1.0).
+    /// Weight for vector results in hybrid fusion (default 1.0).
+    pub vector_weight: Option<f64>,
+    /// Weight for keyword results in hybrid fusion (default 1.0).
+    pub keyword_weight: Option<f64>,
+    /// When true, include per-result scoring breakdown.
+    pub explain: Option<bool>,
+    /// Per-query ef_search override (range: 10-500). Overrides collection default.
+    pub ef_search: Option<i64>,
+    /// Per-query IVF probe count override (IVF-PQ collections only).
+    pub ivf_num_probes: Option<i64>,
+    /// Re-score approximate IVF-PQ and sq8 candidates with full-precision vectors
+    /// (default false). sq8 segments re-score against FP16 copies of their vectors.
+    pub rescore: Option<bool>,
+    /// Candidates fetched per result when re-scoring, >= 1 (default 4).
+    pub oversampling: Option<f64>,
+    /// Keyword query syntax: "simple" (default) or "advanced" (phrases, prefix*,
+    /// -exclusion, OR, NEAR).
+    pub keyword_syntax: Option<String>,
+    /// Hybrid fusion: "rrf" (default), "min_max", "z_score", or "dbsf" (distribution-based).
+    pub fusion: Option<String>,
+    /// RRF constant k, >= 0 (default 60). Only used by "rrf" fusion.
+    pub rrf_k: Option<f64>,
+    /// Return each hit's stored metadata as `metadataJson` (default false).
+    pub include_metadata: Option<bool>,
+    /// Return each hit's stored chunk text (default false).
+    pub include_text: Option<bool>,
+    /// Return each hit's stored vector, decoded from the collection's
+    /// quantization (default false).
+    pub include_vector: Option<bool>,
+    /// Group results by "doc_id" or a top-level metadata field: topK becomes
+    /// the number of groups, returned in `groups`.
+    pub group_by: Option<String>,
+    /// Results kept per group, >= 1 (default 1).
+    pub max_per_group: Option<i64>,
+    /// Continue after a previous page: its response's `cursor`. Searches the
+    /// manifest version and buffered records the first page read and only
+    /// continues the same query and mode; topK may change. Hybrid pages split
+    /// the candidates fused for the first page, then those deeper fusions add.
+    /// Fails once the buffer has been flushed. Not supported with groupBy.
+    pub search_after: Option<String>,
+}
+
+/// Batch search: `SearchOptsJs` with one query vector and/or text per query.
+#[napi(object)]
+pub struct SearchBatchOptsJs {
+    pub collection_id: String,
+    /// One query vector per query (vector/hybrid modes).
+    pub query_vectors: Option<Vec<Vec<f64>>>,
+    /// One query text per query (keyword/hybrid modes); same length as queryVectors.
+    pub query_texts: Option<Vec<String>>,
+    pub top_k: i64,
+    pub filters_json: Option<String>,
+    pub manifest_version: Option<i64>,
+    pub include_uncommitted: Option<bool>,
+    /// Search mode: "vector" (default), "keyword", or "hybrid".
+    pub mode: Option<String>,
+    /// Weight for vector results in hybrid fusion (default 1.0).
     pub vector_weight: Option<f64>,
-    /// Weight for keyword results in hybrid RRF fusion (default 1.0).
+    /// Weight for keyword results in hybrid fusion (default 1.0).
     pub keyword_weight: Option<f64>,
     /// When true, include per-result scoring breakdown.
     pub explain: Option<bool>,
     /// Per-query ef_search override (range: 10-500). Overrides collection default.
     pub ef_search: Option<i64>,
+    /// Per-query IVF probe count override (IVF-PQ collections only).
+    pub ivf_num_probes: Option<i64>,
+    /// Re-score approximate IVF-PQ and sq8 candidates with full-precision vectors
+    /// (default false). sq8 segments re-score against FP16 copies of their vectors.
+    pub rescore: Option<bool>,
+    /// Candidates fetched per result when re-scoring, >= 1 (default 4).
+    pub oversampling: Option<f64>,
+    /// Keyword query syntax: "simple" (default) or "advanced" (phrases, prefix*,
+    /// -exclusion, OR, NEAR).
+    pub keyword_syntax: Option<String>,
+    /// Hybrid fusion: "rrf" (default), "min_max", "z_score", or "dbsf" (distribution-based).
+    pub fusion: Option<String>,
+    /// RRF constant k, >= 0 (default 60). Only used by "rrf" fusion.
+    pub rrf_k: Option<f64>,
+    /// Return each hit's stored metadata as `metadataJson` (default false).
+    pub include_metadata: Option<bool>,
+    /// Return each hit's stored chunk text (default false).
+    pub include_text: Option<bool>,
+    /// Return each hit's stored vector, decoded from the collection's
+    /// quantization (default false).
+    pub include_vector: Option<bool>,
+    /// Group results by "doc_id" or a top-level metadata field: topK becomes
+    /// the number of groups, returned in `groups`.
+    pub group_by: Option<String>,
+    /// Results kept per group, >= 1 (default 1).
+    pub max_per_group: Option<i64>,
 }
 
 #[napi(object)]
@@ -124,7 +253,49 @@ pub struct ExplainInfoJs {
     pub vector_rank: Option<i64>,
     pub bm25_rank: Option<i64>,
     pub chunk_preview: Option<String>,
+    /// Query terms and phrases found in the chunk text (prefixes keep `*`).
     pub matched_terms: Vec<String>,
+    /// Occurrences of matched terms, as character offsets into the chunk text.
+    pub term_matches: Vec<TermMatchJs>,
+    /// Passage with the most matched terms (absent when nothing matched).
+    pub snippet: Option<String>,
+    /// Character offset of `snippet` in the chunk text.
+    pub snippet_start: Option<i64>,
+    /// Fusion strategy, parameters and per-leg inputs (hybrid search only).
+    pub fusion: Option<FusionInfoJs>,
+    /// Ranks before and after re-ranking (searchReranked only).
+    pub rerank: Option<RerankInfoJs>,
+}
+
+#[napi(object)]
+pub struct RerankInfoJs {
+    /// 1-based rank among the candidates passed to the reranker.
+    pub pre_rerank_rank: i64,
+    /// Search score before re-ranking; the result's score is the reranker's.
+    pub pre_rerank_score: f64,
+    /// 1-based rank after re-ranking.
+    pub post_rerank_rank: i64,
+}
+
+#[napi(object)]
+pub struct FusionInfoJs {
+    /// "rrf", "min_max", "z_score" or "dbsf".
+    pub method: String,
+    pub rrf_k: Option<f64>,
+    pub vector_weight: f64,
+    pub keyword_weight: f64,
+    /// Unweighted vector contribution: 1 / (k + rank) for RRF, else the normalized score.
+    pub vector_normalized: Option<f64>,
+    /// Unweighted keyword contribution, as for vectorNormalized.
+    pub keyword_normalized: Option<f64>,
+    pub score: f64,
+}
+
+#[napi(object)]
+pub struct TermMatchJs {
+    pub term: String,
+    pub start: i64,
+    pub end: i64,
 }
 
 #[napi(object)]
@@ -133,12 +304,64 @@ pub struct SearchResultEngineJs {
     pub score: f64,
     pub committed: Option<bool>,
     pub explain: Option<ExplainInfoJs>,
+    /// Stored metadata as JSON (with includeMetadata).
+    pub metadata_json: Option<String>,
+    /// Stored chunk text (with includeText).
+    pub text: Option<String>,
+    /// Stored vector (with includeVector).
+    pub vector: Option<Vec<f64>>,
+    /// The result's groupBy value as JSON (with groupBy).
+    pub group_key_json: Option<String>,
+}
+
+#[napi(object)]
+pub struct SearchGroupJs {
+    /// The shared groupBy value as JSON; "null" when the field is missing.
+    pub key_json: String,
+    /// Score of the group's best result.
+    pub score: f64,
+    pub results: Vec<SearchResultEngineJs>,
 }
 
 #[napi(object)]
 pub struct SearchResponseJs {
     pub results: Vec<SearchResultEngineJs>,
     pub manifest_version_used: i64,
+    /// Results grouped by groupBy value, in rank order (with groupBy).
+    pub groups: Option<Vec<SearchGroupJs>>,
+    /// Pass as `searchAfter` for the next page; absent after the last page.
+    pub cursor: Option<String>,
+}
+
+#[napi(object)]
+pub struct StoredRecordJs {
+    pub chunk_id: String,
+    /// Empty for records flushed before doc IDs were stored in segments.
+    pub doc_id: String,
+    pub ordinal: Option<u32>,
+    pub vector: Vec<f64>,
+    pub metadata_json: String,
+    pub chunk_text: Option<String>,
+    /// False for a record still in the write buffer.
+    pub committed: bool,
+}
+
+#[napi(object)]
+pub struct ContextPassageJs {
+    pub doc_id: String,
+    /// Source chunks, in document order.
+    pub chunk_ids: Vec<String>,
+    pub text: String,
+    /// Score of the passage's best hit.
+    pub score: f64,
+}
+
+#[napi(object)]
+pub struct AssembledContextJs {
+    pub passages: Vec<ContextPassageJs>,
+    /// Characters across all passage texts; at most the budget.
+    pub total_chars: i64,
+    pub manifest_version_used: i64,
 }
 
 #[napi(object)]
@@ -174,7 +397,7 @@ pub struct CompactResultJs {
 /// Thread-safe via internal subsystem locks.
 #[napi]
 pub struct AkiDbEngine {
-    inner: EngineInner,
+    inner: Arc<EngineInner>,
 }
 
 #[napi]
@@ -184,11 +407,12 @@ impl AkiDbEngine {
         let engine = EngineInner::open(EngineOptions {
             storage_path: opts.storage_path.into(),
             disable_wal: opts.disable_wal.unwrap_or(false),
+            search_threads: opts.search_threads.map(|v| v.max(0) as usize),
         })
         .map_err(napi::Error::from)?;
 
         Ok(Self {
-            inner: engine,
+            inner: Arc::new(engine),
         })
     }
 
@@ -197,16 +421,25 @@ impl AkiDbEngine {
     #[napi]
     pub fn create_collection(&self, opts: CreateCollectionOptsJs) -> Result<CollectionJs> {
         let c = self.inner
-            .create_collection(
-                &opts.collection_id,
-                opts.dimension,
-                &opts.metric,
-                &opts.embedding_model_id,
-                opts.quantization.as_deref().unwrap_or("fp16"),
-                opts.hnsw_m.unwrap_or(16),
-                opts.hnsw_ef_construction.unwrap_or(200),
-                opts.hnsw_ef_search.unwrap_or(100),
-            )
+            .create_collection_with_options(&crate::collection::CreateCollectionOptions {
+                collection_id: opts.collection_id,
+                dimension: opts.dimension,
+                metric: opts.metric,
+                embedding_model_id: opts.embedding_model_id,
+                schema_version: "1".to_string(),
+                quantization: opts.quantization.unwrap_or_else(|| "fp16".to_string()),
+                hnsw_m: opts.hnsw_m.unwrap_or(16),
+                hnsw_ef_construction: opts.hnsw_ef_construction.unwrap_or(200),
+                hnsw_ef_search: opts.hnsw_ef_search.unwrap_or(100),
+                index_type: opts.index_type.unwrap_or_else(|| "hnsw".to_string()),
+                ivf_num_clusters: opts.ivf_num_clusters,
+                ivf_num_probes: opts.ivf_num_probes,
+                pq_num_subquantizers: opts.pq_num_subquantizers,
+                tokenizer: opts.tokenizer.unwrap_or_else(|| "unicode61".to_string()),
+                stemmer: opts.stemmer,
+                stop_words: opts.stop_words,
+                remove_diacritics: opts.remove_diacritics.unwrap_or(true),
+            })
             .map_err(napi::Error::from)?;
         Ok(collection_to_js(c))
     }
@@ -285,57 +518,141 @@ impl AkiDbEngine {
 
     #[napi]
     pub fn search(&self, opts: SearchOptsJs) -> Result<SearchResponseJs> {
-        let filters = match opts.filters_json {
-            Some(s) => Some(
-                serde_json::from_str(&s)
-                    .map_err(|e| Error::from_reason(format!("Invalid filters JSON: {e}")))?,
-            ),
-            None => None,
-        };
+        let response = self.inner
+            .search(search_opts_from_js(opts)?)
+            .map_err(napi::Error::from)?;
 
-        let query_vector: Vec<f32> = opts.query_vector.iter().map(|&f| f as f32).collect();
-        let mode = crate::query::SearchMode::from_str(opts.mode.as_deref().unwrap_or("vector"));
+        Ok(search_response_to_js(response))
+    }
 
-        let response = self.inner
-            .search(crate::query::SearchOptions {
-                collection_id: opts.collection_id,
-                query_vector,
-                top_k: opts.top_k as usize,
-                filters,
-                manifest_version: opts.manifest_version,
-                include_uncommitted: opts.include_uncommitted.unwrap_or(true),
-                mode,
-                query_text: opts.query_text,
-                vector_weight: opts.vector_weight.unwrap_or(1.0),
-                keyword_weight: opts.keyword_weight.unwrap_or(1.0),
-                explain: opts.explain.unwrap_or(false),
-                ef_search: opts.ef_search.map(|v| v as usize),
-            })
+    /// Search, then re-score the best `rerankDepth` candidates (default topK)
+    /// with `reranker` and keep its top topK. The search runs on the libuv
+    /// thread pool; `reranker` is called on the JS thread with
+    /// `(err, request)` and returns one score per candidate, or a Promise
+    /// of them.
+    #[napi]
+    pub fn search_reranked(
+        &self,
+        opts: SearchOptsJs,
+        reranker: ThreadsafeFunction<RerankRequestJs>,
+        rerank_depth: Option<i64>,
+    ) -> Result<AsyncTask<SearchRerankedTask>> {
+        Ok(AsyncTask::new(SearchRerankedTask {
+            engine: Arc::clone(&self.inner),
+            opts: search_opts_from_js(opts)?,
+            reranker: JsReranker::new(reranker),
+            rerank_depth: rerank_depth.map(|v| v.max(0) as usize),
+        }))
+    }
+
+    /// Run many queries against one collection under a single manifest and
+    /// tombstone snapshot. Responses are in query order.
+    #[napi]
+    pub fn search_batch(&self, opts: SearchBatchOptsJs) -> Result<Vec<SearchResponseJs>> {
+        let query_vectors = opts
+            .query_vectors
+            .map(|vectors| vectors.iter().map(|v| v.iter().map(|&f| f as f32).collect()).collect());
+        let queries = crate::query::BatchQuery::zip(query_vectors, opts.query_texts)
+            .map_err(napi::Error::from)?;
+        // The per-query vector and text come from `queries`; everything else
+        // is mapped exactly as for a single search.
+        let search_opts = search_opts_from_js(SearchOptsJs {
+            collection_id: opts.collection_id,
+            query_vector: Vec::new(),
+            top_k: opts.top_k,
+            filters_json: opts.filters_json,
+            manifest_version: opts.manifest_version,
+            include_uncommitted: opts.include_uncommitted,
+            mode: opts.mode,
+            query_text: None,
+            vector_weight: opts.vector_weight,
+            keyword_weight: opts.keyword_weight,
+            explain: opts.explain,
+            ef_search: opts.ef_search,
+            ivf_num_probes: opts.ivf_num_probes,
+            rescore: opts.rescore,
+            oversampling: opts.oversampling,
+            keyword_syntax: opts.keyword_syntax,
+            fusion: opts.fusion,
+            rrf_k: opts.rrf_k,
+            include_metadata: opts.include_metadata,
+            include_text: opts.include_text,
+            include_vector: opts.include_vector,
+            group_by: opts.group_by,
+            max_per_group: opts.max_per_group,
+            search_after: None,
+        })?;
+
+        let responses = self.inner
+            .search_batch(search_opts, queries)
             .map_err(napi::Error::from)?;
 
-        Ok(SearchResponseJs {
-            results: response
-                .results
+        Ok(responses.into_iter().map(search_response_to_js).collect())
+    }
+
+    /// Search, then assemble the hits' chunk text into passages within
+    /// `budget` characters, or approximate tokens with `budgetUnit: "tokens"`.
+    /// Duplicate texts are dropped and adjacent chunks of a document (by
+    /// ordinal) merged; passages come back in rank order.
+    #[napi]
+    pub fn assemble_context(
+        &self,
+        opts: SearchOptsJs,
+        budget: u32,
+        budget_unit: Option<String>,
+    ) -> Result<AssembledContextJs> {
+        let budget =
+            crate::query::ContextBudget::from_options(budget_unit.as_deref().unwrap_or("chars"), budget as usize);
+        let context = self.inner
+            .assemble_context(search_opts_from_js(opts)?, budget)
+            .map_err(napi::Error::from)?;
+        Ok(AssembledContextJs {
+            passages: context
+                .passages
                 .into_iter()
-                .map(|r| SearchResultEngineJs {
-                    chunk_id: r.chunk_id,
-                    score: r.score,
-                    committed: r.committed,
-                    explain: r.explain.map(|e| ExplainInfoJs {
-                        vector_score: e.vector_score,
-                        bm25_score: e.bm25_score,
-                        rrf_score: e.rrf_score,
-                        vector_rank: e.vector_rank.map(|v| v as i64),
-                        bm25_rank: e.bm25_rank.map(|v| v as i64),
-                        chunk_preview: e.chunk_preview,
-                        matched_terms: e.matched_terms,
-                    }),
-                })
+                .map(|p| ContextPassageJs { doc_id: p.doc_id, chunk_ids: p.chunk_ids, text: p.text, score: p.score })
                 .collect(),
-            manifest_version_used: response.manifest_version_used,
+            total_chars: context.total_chars as i64,
+            manifest_version_used: context.manifest_version_used,
         })
     }
 
+    // ─── Point Lookup (sync, read lock) ─────────────────────────────────────
+
+    /// Fetch records by chunk ID, in request order; missing and deleted
+    /// chunks are omitted. Reads the live state (including the write buffer)
+    /// unless `manifestVersion` pins a published manifest.
+    #[napi]
+    pub fn get_records(
+        &self,
+        collection_id: String,
+        chunk_ids: Vec<String>,
+        manifest_version: Option<i64>,
+    ) -> Result<Vec<StoredRecordJs>> {
+        let records = self.inner
+            .get_records(&collection_id, &chunk_ids, manifest_version)
+            .map_err(napi::Error::from)?;
+        Ok(records.into_iter().map(stored_record_to_js).collect())
+    }
+
+    /// Fetch a chunk and up to `before` / `after` neighbors in its document,
+    /// ordered by ordinal. Empty if the chunk is missing or deleted; throws if
+    /// it was written without an ordinal. Reads the same state as getRecords.
+    #[napi]
+    pub fn get_neighbors(
+        &self,
+        collection_id: String,
+        chunk_id: String,
+        before: u32,
+        after: u32,
+        manifest_version: Option<i64>,
+    ) -> Result<Vec<StoredRecordJs>> {
+        let records = self.inner
+            .get_neighbors(&collection_id, &chunk_id, before as usize, after as usize, manifest_version)
+            .map_err(napi::Error::from)?;
+        Ok(records.into_iter().map(stored_record_to_js).collect())
+    }
+
     // ─── Delete (sync, write lock) ──────────────────────────────────────────
 
     #[napi]
@@ -351,6 +668,39 @@ impl AkiDbEngine {
         Ok(count as i64)
     }
 
+    /// Delete every chunk of a document. Returns the number of chunks deleted.
+    #[napi]
+    pub fn delete_document(&self, collection_id: String, doc_id: String) -> Result<i64> {
+        let count = self.inner
+            .delete_document(&collection_id, &doc_id)
+            .map_err(napi::Error::from)?;
+        Ok(count as i64)
+    }
+
+    /// Replace a document's chunks with `records` (all with `docId`): old
+    /// chunks missing from `records` are deleted, the rest upserted.
+    #[napi]
+    pub fn replace_document(
+        &self,
+        collection_id: String,
+        doc_id: String,
+        records: Vec<RecordJs>,
+    ) -> Result<UpsertResultJs> {
+        let records: Vec<NativeRecord> = records
+            .into_iter()
+            .map(record_js_to_native)
+            .collect::<Result<Vec<_>>>()?;
+
+        let result = self.inner
+            .replace_document(&collection_id, &doc_id, &records)
+            .map_err(napi::Error::from)?;
+
+        Ok(UpsertResultJs {
+            segment_ids: result.segment_ids,
+            buffered_count: result.buffered_count as i64,
+        })
+    }
+
     // ─── Compact (sync, write lock) ─────────────────────────────────────────
 
     #[napi]
@@ -410,6 +760,7 @@ fn record_js_to_native(record: RecordJs) -> Result<NativeRecord> {
     Ok(NativeRecord {
         chunk_id: record.chunk_id,
         doc_id: record.doc_id,
+        ordinal: record.ordinal,
         vector: record.vector.into_iter().map(|value| value as f32).collect(),
         metadata,
         chunk_text: record.chunk_text,
@@ -429,6 +780,136 @@ fn collection_to_js(c: crate::metadata::Collection) -> CollectionJs {
         hnsw_m: c.hnsw_m,
         hnsw_ef_construction: c.hnsw_ef_construction,
         hnsw_ef_search: c.hnsw_ef_search,
+        index_type: c.index_type,
+        ivf_num_clusters: c.ivf_num_clusters,
+        ivf_num_probes: c.ivf_num_probes,
+        pq_num_subquantizers: c.pq_num_subquantizers,
+        tokenizer: c.tokenizer,
+        stemmer: c.stemmer,
+        stop_words: c.stop_words,
+        remove_diacritics: c.remove_diacritics,
+    }
+}
+
+fn search_opts_from_js(opts: SearchOptsJs) -> Result<crate::query::SearchOptions> {
+    let filters = parse_filters_json(opts.filters_json)?;
+
+    let query_vector: Vec<f32> = opts.query_vector.iter().map(|&f| f as f32).collect();
+    let mode = crate::query::SearchMode::from_str(opts.mode.as_deref().unwrap_or("vector"));
+    let keyword_syntax =
+        crate::query::KeywordSyntax::from_str(opts.keyword_syntax.as_deref().unwrap_or("simple"));
+    let fusion = crate::query::Fusion::from_options(opts.fusion.as_deref().unwrap_or("rrf"), opts.rrf_k);
+
+    Ok(crate::query::SearchOptions {
+        collection_id: opts.collection_id,
+        query_vector,
+        top_k: opts.top_k as usize,
+        filters,
+        manifest_version: opts.manifest_version,
+        include_uncommitted: opts.include_uncommitted.unwrap_or(true),
+        mode,
+        query_text: opts.query_text,
+        vector_weight: opts.vector_weight.unwrap_or(1.0),
+        keyword_weight: opts.keyword_weight.unwrap_or(1.0),
+        explain: opts.explain.unwrap_or(false),
+        ef_search: opts.ef_search.map(|v| v as usize),
+        ivf_num_probes: opts.ivf_num_probes.map(|v| v as usize),
+        rescore: opts.rescore.unwrap_or(false),
+        oversampling: opts.oversampling,
+        keyword_syntax,
+        fusion,
+        include_metadata: opts.include_metadata.unwrap_or(false),
+        include_text: opts.include_text.unwrap_or(false),
+        include_vector: opts.include_vector.unwrap_or(false),
+        group_by: opts.group_by.as_deref().map(crate::query::GroupBy::from_str),
+        max_per_group: opts.max_per_group.map(|v| v as usize),
+        search_after: opts
+            .search_after
+            .as_deref()
+            .map(crate::query::SearchCursor::decode)
+            .transpose()
+            .map_err(napi::Error::from)?,
+    })
+}
+
+fn parse_filters_json(filters_json: Option<String>) -> Result<Option<serde_json::Value>> {
+    filters_json
+        .map(|s| {
+            serde_json::from_str(&s)
+                .map_err(|e| Error::from_reason(format!("Invalid filters JSON: {e}")))
+        })
+        .transpose()
+}
+
+fn search_response_to_js(response: crate::query::SearchResponse) -> SearchResponseJs {
+    SearchResponseJs {
+        results: response.results.into_iter().map(search_result_to_js).collect(),
+        manifest_version_used: response.manifest_version_used,
+        groups: response.groups.map(|groups| {
+            groups
+                .into_iter()
+                .map(|g| SearchGroupJs {
+                    key_json: g.key.to_string(),
+                    score: g.score,
+                    results: g.results.into_iter().map(search_result_to_js).collect(),
+                })
+                .collect()
+        }),
+        cursor: response.cursor,
+    }
+}
+
+fn search_result_to_js(r: crate::index::SearchResult) -> SearchResultEngineJs {
+    SearchResultEngineJs {
+        chunk_id: r.chunk_id,
+        score: r.score,
+        committed: r.committed,
+        explain: r.explain.map(|e| ExplainInfoJs {
+            vector_score: e.vector_score,
+            bm25_score: e.bm25_score,
+            rrf_score: e.rrf_score,
+            vector_rank: e.vector_rank.map(|v| v as i64),
+            bm25_rank: e.bm25_rank.map(|v| v as i64),
+            chunk_preview: e.chunk_preview,
+            matched_terms: e.matched_terms,
+            term_matches: e
+                .term_matches
+                .into_iter()
+                .map(|m| TermMatchJs { term: m.term, start: m.start as i64, end: m.end as i64 })
+                .collect(),
+            snippet: e.snippet,
+            snippet_start: e.snippet_start.map(|v| v as i64),
+            fusion: e.fusion.map(|f| FusionInfoJs {
+                method: f.method,
+                rrf_k: f.rrf_k,
+                vector_weight: f.vector_weight,
+                keyword_weight: f.keyword_weight,
+                vector_normalized: f.vector_normalized,
+                keyword_normalized: f.keyword_normalized,
+                score: f.score,
+            }),
+            rerank: e.rerank.map(|r| RerankInfoJs {
+                pre_rerank_rank: r.pre_rerank_rank as i64,
+                pre_rerank_score: r.pre_rerank_score,
+                post_rerank_rank: r.post_rerank_rank as i64,
+            }),
+        }),
+        metadata_json: r.metadata.map(|m| m.to_string()),
+        text: r.text,
+        vector: r.vector.map(|v| v.into_iter().map(f64::from).collect()),
+        group_key_json: r.group_key.map(|k| k.to_string()),
+    }
+}
+
+fn stored_record_to_js(r: crate::query::StoredRecord) -> StoredRecordJs {
+    StoredRecordJs {
+        chunk_id: r.chunk_id,
+        doc_id: r.doc_id,
+        ordinal: r.ordinal,
+        vector: r.vector.into_iter().map(f64::from).collect(),
+        metadata_json: r.metadata.to_string(),
+        chunk_text: r.chunk_text,
+        committed: r.committed,
     }
 }
 
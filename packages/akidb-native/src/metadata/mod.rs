@@ -11,7 +11,6 @@
 
 use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 
 use crate::error::{AkiDbError, Result};
 
@@ -20,7 +19,10 @@ const MIGRATION_002: &str = include_str!("sql/002-fts5.sql");
 const MIGRATION_003: &str = include_str!("sql/003-fts5-trigram.sql");
 const MIGRATION_004: &str = include_str!("sql/004-collection-params.sql");
 const MIGRATION_005: &str = include_str!("sql/005-index-types.sql");
-const FTS_TABLES: [&str; 2] = ["chunk_text_fts", "chunk_text_trigram"];
+const MIGRATION_006: &str = include_str!("sql/006-fp32-quantization.sql");
+const MIGRATION_007: &str = include_str!("sql/007-binary-index-type.sql");
+const MIGRATION_008: &str = include_str!("sql/008-drop-fts.sql");
+const MIGRATION_009: &str = include_str!("sql/009-text-analyzer.sql");
 
 // ─── Data types ──────────────────────────────────────────────────────────────
 
@@ -37,6 +39,22 @@ pub struct Collection {
     pub hnsw_m: i64,
     pub hnsw_ef_construction: i64,
     pub hnsw_ef_search: i64,
+    /// Per-segment vector index: "hnsw", "ivf_pq" or "binary".
+    pub index_type: String,
+    /// IVF coarse clusters per segment; `None` derives √n from the segment size.
+    pub ivf_num_clusters: Option<i64>,
+    /// Default IVF lists probed per query.
+    pub ivf_num_probes: Option<i64>,
+    /// PQ subquantizers (must divide `dimension`).
+    pub pq_num_subquantizers: Option<i64>,
+    /// Keyword tokenizer: "unicode61" or "cjk_bigram".
+    pub tokenizer: String,
+    /// Snowball stemmer ("porter", "french", ...); `None` disables stemming.
+    pub stemmer: Option<String>,
+    /// Stop-word list ("english"); `None` keeps every word.
+    pub stop_words: Option<String>,
+    /// Fold diacritics in keyword tokens ("Café" → "cafe").
+    pub remove_diacritics: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -121,6 +139,18 @@ impl MetadataStore {
         if current < 5 {
             self.conn.execute_batch(MIGRATION_005)?;
         }
+        if current < 6 {
+            self.conn.execute_batch(MIGRATION_006)?;
+        }
+        if current < 7 {
+            self.conn.execute_batch(MIGRATION_007)?;
+        }
+        if current < 8 {
+            self.conn.execute_batch(MIGRATION_008)?;
+        }
+        if current < 9 {
+            self.conn.execute_batch(MIGRATION_009)?;
+        }
         Ok(())
     }
 
@@ -141,8 +171,10 @@ impl MetadataStore {
         self.conn.execute(
             "INSERT INTO collections
                (collection_id, dimension, metric, embedding_model_id, schema_version, created_at, deleted_at,
-                quantization, hnsw_m, hnsw_ef_construction, hnsw_ef_search)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                quantization, hnsw_m, hnsw_ef_construction, hnsw_ef_search,
+                index_type, ivf_num_clusters, ivf_num_probes, pq_num_subquantizers,
+                tokenizer, stemmer, stop_words, remove_diacritics)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)",
             params![
                 c.collection_id,
                 c.dimension,
@@ -154,7 +186,15 @@ impl MetadataStore {
                 c.quantization,
                 c.hnsw_m,
                 c.hnsw_ef_construction,
-                c.hnsw_ef_search
+                c.hnsw_ef_search,
+                c.index_type,
+                c.ivf_num_clusters,
+                c.ivf_num_probes,
+                c.pq_num_subquantizers,
+                c.tokenizer,
+                c.stemmer,
+                c.stop_words,
+                c.remove_diacritics
             ],
         )?;
         Ok(())
@@ -165,7 +205,9 @@ impl MetadataStore {
             .conn
             .query_row(
                 "SELECT collection_id, dimension, metric, embedding_model_id, schema_version, created_at, deleted_at,
-                        quantization, hnsw_m, hnsw_ef_construction, hnsw_ef_search
+                        quantization, hnsw_m, hnsw_ef_construction, hnsw_ef_search,
+                        index_type, ivf_num_clusters, ivf_num_probes, pq_num_subquantizers,
+                        tokenizer, stemmer, stop_words, remove_diacritics
                  FROM collections WHERE collection_id = ?1",
                 params![collection_id],
                 |row| {
@@ -181,6 +223,14 @@ impl MetadataStore {
                         hnsw_m: row.get(8)?,
                         hnsw_ef_construction: row.get(9)?,
                         hnsw_ef_search: row.get(10)?,
+                        index_type: row.get(11)?,
+                        ivf_num_clusters: row.get(12)?,
+                        ivf_num_probes: row.get(13)?,
+                        pq_num_subquantizers: row.get(14)?,
+                        tokenizer: row.get(15)?,
+                        stemmer: row.get(16)?,
+                        stop_words: row.get(17)?,
+                        remove_diacritics: row.get(18)?,
                     })
                 },
             )
@@ -191,7 +241,9 @@ impl MetadataStore {
     pub fn list_collections(&self) -> Result<Vec<Collection>> {
         let mut stmt = self.conn.prepare(
             "SELECT collection_id, dimension, metric, embedding_model_id, schema_version, created_at, deleted_at,
-                    quantization, hnsw_m, hnsw_ef_construction, hnsw_ef_search
+                    quantization, hnsw_m, hnsw_ef_construction, hnsw_ef_search,
+                    index_type, ivf_num_clusters, ivf_num_probes, pq_num_subquantizers,
+                    tokenizer, stemmer, stop_words, remove_diacritics
              FROM collections WHERE deleted_at IS NULL",
         )?;
         let rows = stmt.query_map([], |row| {
@@ -207,6 +259,14 @@ impl MetadataStore {
                 hnsw_m: row.get(8)?,
                 hnsw_ef_construction: row.get(9)?,
                 hnsw_ef_search: row.get(10)?,
+                index_type: row.get(11)?,
+                ivf_num_clusters: row.get(12)?,
+                ivf_num_probes: row.get(13)?,
+                pq_num_subquantizers: row.get(14)?,
+                tokenizer: row.get(15)?,
+                stemmer: row.get(16)?,
+                stop_words: row.get(17)?,
+                remove_diacritics: row.get(18)?,
             })
         })?;
         let mut result = Vec::new();
@@ -522,181 +582,6 @@ impl MetadataStore {
         )?;
         Ok(count)
     }
-
-    // ═════════════════════════════════════════════════════════════════════════
-    //  5. FTS5 Full-Text Search Index
-    // ═════════════════════════════════════════════════════════════════════════
-
-    /// Insert a chunk's text into both FTS5 indexes (unicode61 + trigram).
-    pub fn fts_insert(&self, chunk_id: &str, collection_id: &str, chunk_text: &str) -> Result<()> {
-        // Delete-then-insert to make re-ingestion idempotent.
-        // FTS5 virtual tables don't support ON CONFLICT / UPSERT.
-        for table in FTS_TABLES {
-            self.conn.execute(
-                &format!("DELETE FROM {table} WHERE chunk_id = ?1 AND collection_id = ?2"),
-                params![chunk_id, collection_id],
-            )?;
-            self.conn.execute(
-                &format!(
-                    "INSERT INTO {table}(chunk_id, collection_id, chunk_text)
-                     VALUES (?1, ?2, ?3)"
-                ),
-                params![chunk_id, collection_id, chunk_text],
-            )?;
-        }
-        Ok(())
-    }
-
-    /// Insert multiple chunks' text into both FTS5 indexes in a single transaction.
-    /// Dramatically faster than calling `fts_insert` in a loop — avoids per-row autocommit overhead.
-    pub fn fts_insert_batch(&self, records: &[(&str, &str, &str)]) -> Result<()> {
-        if records.is_empty() {
-            return Ok(());
-        }
-        // unchecked_transaction() takes &self (no &mut required), safe here because
-        // MetadataStore is the sole owner of the connection.
-        let tx = self.conn.unchecked_transaction()?;
-        for (chunk_id, collection_id, chunk_text) in records {
-            for table in FTS_TABLES {
-                tx.execute(
-                    &format!("DELETE FROM {table} WHERE chunk_id = ?1 AND collection_id = ?2"),
-                    params![chunk_id, collection_id],
-                )?;
-                tx.execute(
-                    &format!(
-                        "INSERT INTO {table}(chunk_id, collection_id, chunk_text)
-                         VALUES (?1, ?2, ?3)"
-                    ),
-                    params![chunk_id, collection_id, chunk_text],
-                )?;
-            }
-        }
-        tx.commit()?;
-        Ok(())
-    }
-
-    /// Remove a chunk's text from both FTS5 indexes.
-    pub fn fts_delete(&self, chunk_id: &str, collection_id: &str) -> Result<()> {
-        for table in FTS_TABLES {
-            self.conn.execute(
-                &format!("DELETE FROM {table} WHERE chunk_id = ?1 AND collection_id = ?2"),
-                params![chunk_id, collection_id],
-            )?;
-        }
-        Ok(())
-    }
-
-    /// Remove all chunk text rows for a collection from both FTS5 indexes.
-    pub fn fts_delete_collection(&self, collection_id: &str) -> Result<()> {
-        for table in FTS_TABLES {
-            self.conn.execute(
-                &format!("DELETE FROM {table} WHERE collection_id = ?1"),
-                params![collection_id],
-            )?;
-        }
-        Ok(())
-    }
-
-    /// Retrieve the chunk text stored in the FTS5 index for a given chunk_id.
-    #[allow(dead_code)]
-    pub fn fts_get_text(&self, chunk_id: &str) -> Result<Option<String>> {
-        let map = self.fts_get_texts(&[chunk_id.to_string()])?;
-        Ok(map.get(chunk_id).cloned())
-    }
-
-    /// Retrieve chunk texts for many chunk IDs in batched `IN (...)` queries.
-    pub fn fts_get_texts(&self, chunk_ids: &[String]) -> Result<HashMap<String, String>> {
-        if chunk_ids.is_empty() {
-            return Ok(HashMap::new());
-        }
-
-        // SQLite default SQLITE_MAX_VARIABLE_NUMBER is 999.
-        const BATCH_SIZE: usize = 999;
-        let mut out = HashMap::with_capacity(chunk_ids.len());
-
-        for batch in chunk_ids.chunks(BATCH_SIZE) {
-            let placeholders: Vec<String> = (1..=batch.len()).map(|i| format!("?{i}")).collect();
-            let sql = format!(
-                "SELECT chunk_id, chunk_text FROM chunk_text_fts WHERE chunk_id IN ({})",
-                placeholders.join(", ")
-            );
-
-            let params: Vec<&dyn rusqlite::types::ToSql> = batch
-                .iter()
-                .map(|id| id as &dyn rusqlite::types::ToSql)
-                .collect();
-
-            let mut stmt = self.conn.prepare(&sql)?;
-            let rows = stmt.query_map(params.as_slice(), |row| {
-                let chunk_id: String = row.get(0)?;
-                let chunk_text: String = row.get(1)?;
-                Ok((chunk_id, chunk_text))
-            })?;
-
-            for row in rows {
-                let (chunk_id, chunk_text) = row?;
-                out.insert(chunk_id, chunk_text);
-            }
-        }
-
-        Ok(out)
-    }
-
-    /// BM25 keyword search using FTS5 MATCH.
-    /// Returns (chunk_id, bm25_score) pairs sorted by relevance (lower bm25 = more relevant).
-    pub fn fts_search(
-        &self,
-        collection_id: &str,
-        query_text: &str,
-        top_k: usize,
-    ) -> Result<Vec<(String, f64)>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT chunk_id, bm25(chunk_text_fts)
-             FROM chunk_text_fts
-             WHERE chunk_text MATCH ?1 AND collection_id = ?2
-             ORDER BY bm25(chunk_text_fts)
-             LIMIT ?3",
-        )?;
-        let rows = stmt.query_map(params![query_text, collection_id, top_k as i64], |row| {
-            let chunk_id: String = row.get(0)?;
-            let score: f64 = row.get(1)?;
-            Ok((chunk_id, score))
-        })?;
-        let mut results = Vec::new();
-        for row in rows {
-            results.push(row?);
-        }
-        Ok(results)
-    }
-
-    /// Trigram-based substring search using the chunk_text_trigram FTS5 index.
-    /// Works for CJK and other scripts without whitespace word boundaries.
-    /// Returns (chunk_id, score) pairs. Trigram doesn't support BM25, so
-    /// scores are based on match count ranking.
-    pub fn fts_trigram_search(
-        &self,
-        collection_id: &str,
-        query_text: &str,
-        top_k: usize,
-    ) -> Result<Vec<(String, f64)>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT chunk_id, rank
-             FROM chunk_text_trigram
-             WHERE chunk_text MATCH ?1 AND collection_id = ?2
-             ORDER BY rank
-             LIMIT ?3",
-        )?;
-        let rows = stmt.query_map(params![query_text, collection_id, top_k as i64], |row| {
-            let chunk_id: String = row.get(0)?;
-            let score: f64 = row.get(1)?;
-            Ok((chunk_id, score))
-        })?;
-        let mut results = Vec::new();
-        for row in rows {
-            results.push(row?);
-        }
-        Ok(results)
-    }
 }
 
 // ─── Manifest deserialization helper ─────────────────────────────────────────
@@ -739,9 +624,38 @@ mod tests {
             hnsw_m: 16,
             hnsw_ef_construction: 200,
             hnsw_ef_search: 100,
+            index_type: "hnsw".to_string(),
+            ivf_num_clusters: None,
+            ivf_num_probes: None,
+            pq_num_subquantizers: None,
+            tokenizer: "unicode61".to_string(),
+            stemmer: None,
+            stop_words: None,
+            remove_diacritics: true,
         }
     }
 
+    /// Insert `c` into a store migrated only up to schema 8, which predates
+    /// the text analyzer columns.
+    fn insert_legacy_collection(store: &MetadataStore, c: &Collection) -> rusqlite::Result<usize> {
+        store.conn.execute(
+            "INSERT INTO collections
+               (collection_id, dimension, metric, embedding_model_id, schema_version, created_at,
+                quantization, index_type)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                c.collection_id,
+                c.dimension,
+                c.metric,
+                c.embedding_model_id,
+                c.schema_version,
+                c.created_at,
+                c.quantization,
+                c.index_type
+            ],
+        )
+    }
+
     #[test]
     fn create_and_get_collection() {
         let store = test_store();
@@ -1150,153 +1064,135 @@ mod tests {
         // Open again — migrations should detect current schema version and skip.
         // Since we can't re-open :memory:, just verify the version check works.
         let version = store.get_schema_version();
-        assert_eq!(version, 5);
+        assert_eq!(version, 9);
     }
 
     #[test]
-    fn fts_insert_and_search() {
-        let store = test_store();
-        store
-            .create_collection(&sample_collection("coll-1"))
+    fn migration_006_keeps_rows_and_allows_fp32() {
+        // Bring a store up to schema 5, seed it, then apply the remaining migrations.
+        let conn = Connection::open_in_memory().unwrap();
+        let store = MetadataStore { conn };
+        store.apply_pragmas().unwrap();
+        for sql in [MIGRATION_001, MIGRATION_002, MIGRATION_003, MIGRATION_004, MIGRATION_005] {
+            store.conn.execute_batch(sql).unwrap();
+        }
+        let mut sq8 = sample_collection("old");
+        sq8.quantization = "sq8".to_string();
+        insert_legacy_collection(&store, &sq8).unwrap();
+        let mut fp32 = sample_collection("new");
+        fp32.quantization = "fp32".to_string();
+        assert!(insert_legacy_collection(&store, &fp32).is_err());
+
+        store.run_migrations().unwrap();
+        assert_eq!(store.get_schema_version(), 9);
+        assert_eq!(store.get_collection("old").unwrap().unwrap().quantization, "sq8");
+        store.create_collection(&fp32).unwrap();
+        assert_eq!(store.get_collection("new").unwrap().unwrap().quantization, "fp32");
+
+        let mut bogus = sample_collection("bogus");
+        bogus.quantization = "int4".to_string();
+        assert!(store.create_collection(&bogus).is_err());
+
+        // Foreign keys still resolve against the rebuilt table.
+        let fk_on: i64 = store
+            .conn
+            .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
             .unwrap();
-
-        // Verify the latest schema version is applied.
-        let version = store.get_schema_version();
-        assert_eq!(version, 5, "All migrations should have been applied");
-
-        // Verify the FTS5 table exists.
-        let table_exists: bool = store.conn.query_row(
-            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type='table' AND name='chunk_text_fts'",
+        assert_eq!(fk_on, 1);
+        let orphan = store.conn.execute(
+            "INSERT INTO tombstones (chunk_id, collection_id, deleted_at, reason_code)
+             VALUES ('c-1', 'ghost', '2026-01-01T00:00:00Z', 'manual_revoke')",
             [],
-            |row| row.get(0),
-        ).unwrap();
-        assert!(table_exists, "chunk_text_fts table should exist");
-
-        store
-            .fts_insert(
-                "c-1",
-                "coll-1",
-                "The quick brown fox jumps over the lazy dog",
-            )
-            .unwrap();
-        store
-            .fts_insert(
-                "c-2",
-                "coll-1",
-                "Machine learning and artificial intelligence",
-            )
-            .unwrap();
-        store
-            .fts_insert("c-3", "coll-1", "The brown fox was very quick today")
-            .unwrap();
-
-        // FTS5 MATCH queries use term-based matching.
-        let results = store.fts_search("coll-1", "quick", 10).unwrap();
-        assert!(
-            !results.is_empty(),
-            "FTS5 search for 'quick' should find results"
-        );
-        let ids: Vec<&str> = results.iter().map(|(id, _)| id.as_str()).collect();
-        assert!(ids.contains(&"c-1"));
-
-        // Multi-term search.
-        let results2 = store.fts_search("coll-1", "brown fox", 10).unwrap();
-        assert!(
-            !results2.is_empty(),
-            "FTS5 search for 'brown fox' should find results"
         );
+        assert!(orphan.is_err());
     }
 
     #[test]
-    fn fts_trigram_cjk_search() {
-        let store = test_store();
-        store
-            .create_collection(&sample_collection("coll-1"))
-            .unwrap();
-
-        // Verify the trigram table exists.
-        let trigram_exists: bool = store
-            .conn
-            .query_row(
-                "SELECT COUNT(*) > 0 FROM sqlite_master WHERE name='chunk_text_trigram'",
-                [],
-                |row| row.get(0),
-            )
-            .unwrap();
-        assert!(trigram_exists, "chunk_text_trigram table should exist");
-
-        // Insert CJK text (Japanese, Korean, Chinese).
-        store
-            .fts_insert("ja-1", "coll-1", "すべての人間は生れながらにして自由であり")
-            .unwrap();
-        store
-            .fts_insert(
-                "ko-1",
-                "coll-1",
-                "모든 인류 구성원의 천부의 존엄성과 동등하고",
-            )
-            .unwrap();
-        store
-            .fts_insert("zh-1", "coll-1", "人人生而自由在尊严和权利上一律平等")
-            .unwrap();
-        store
-            .fts_insert("fr-1", "coll-1", "Tous les êtres humains naissent libres")
-            .unwrap();
-        store
-            .fts_insert(
-                "de-1",
-                "coll-1",
-                "Alle Menschen sind frei und gleich an Würde",
-            )
-            .unwrap();
-
-        // Verify data is in the trigram table.
-        let count: i64 = store
-            .conn
-            .query_row("SELECT COUNT(*) FROM chunk_text_trigram", [], |row| {
-                row.get(0)
-            })
-            .unwrap();
-        assert_eq!(count, 5, "Should have 5 rows in trigram table");
-
-        // Trigram search needs at least 3 characters. Use a longer CJK substring.
-        // FTS5 trigram tokenizer works on character trigrams, so "自由であり" should work.
-        let trigram_results = store
-            .fts_trigram_search("coll-1", "人間は生れ", 10)
-            .unwrap();
-        eprintln!("trigram results for '人間は生れ': {:?}", trigram_results);
-        assert!(
-            !trigram_results.is_empty(),
-            "Trigram should find Japanese text"
-        );
-        let ids: Vec<&str> = trigram_results.iter().map(|(id, _)| id.as_str()).collect();
-        assert!(ids.contains(&"ja-1"), "Should find Japanese chunk");
-
-        // Trigram search for Korean (3+ chars).
-        let ko_results = store.fts_trigram_search("coll-1", "존엄성과", 10).unwrap();
-        eprintln!("trigram results for '존엄성과': {:?}", ko_results);
-        assert!(!ko_results.is_empty(), "Trigram should find Korean text");
-        let ko_ids: Vec<&str> = ko_results.iter().map(|(id, _)| id.as_str()).collect();
-        assert!(ko_ids.contains(&"ko-1"));
-
-        // Chinese substring.
-        let zh_results = store
-            .fts_trigram_search("coll-1", "自由在尊严", 10)
-            .unwrap();
-        eprintln!("trigram results for '自由在尊严': {:?}", zh_results);
-        assert!(!zh_results.is_empty(), "Trigram should find Chinese text");
+    fn migration_007_allows_binary_index_type() {
+        let conn = Connection::open_in_memory().unwrap();
+        let store = MetadataStore { conn };
+        store.apply_pragmas().unwrap();
+        for sql in [
+            MIGRATION_001, MIGRATION_002, MIGRATION_003, MIGRATION_004, MIGRATION_005,
+            MIGRATION_006,
+        ] {
+            store.conn.execute_batch(sql).unwrap();
+        }
+        let mut ivf = sample_collection("old");
+        ivf.index_type = "ivf_pq".to_string();
+        insert_legacy_collection(&store, &ivf).unwrap();
+        let mut binary = sample_collection("new");
+        binary.index_type = "binary".to_string();
+        assert!(insert_legacy_collection(&store, &binary).is_err());
+
+        store.run_migrations().unwrap();
+        assert_eq!(store.get_schema_version(), 9);
+        assert_eq!(store.get_collection("old").unwrap().unwrap().index_type, "ivf_pq");
+        store.create_collection(&binary).unwrap();
+        assert_eq!(store.get_collection("new").unwrap().unwrap().index_type, "binary");
+    }
 
-        // Trigram also works for Latin scripts (substring match).
-        let fr_results = store
-            .fts_trigram_search("coll-1", "êtres humains", 10)
-            .unwrap();
-        assert!(!fr_results.is_empty(), "Trigram should find French text");
+    #[test]
+    fn migration_008_drops_fts_tables() {
+        let conn = Connection::open_in_memory().unwrap();
+        let store = MetadataStore { conn };
+        store.apply_pragmas().unwrap();
+        for sql in [
+            MIGRATION_001, MIGRATION_002, MIGRATION_003, MIGRATION_004, MIGRATION_005,
+            MIGRATION_006, MIGRATION_007,
+        ] {
+            store.conn.execute_batch(sql).unwrap();
+        }
+        insert_legacy_collection(&store, &sample_collection("coll-1")).unwrap();
+        let fts_tables = |store: &MetadataStore| -> i64 {
+            store
+                .conn
+                .query_row(
+                    "SELECT COUNT(*) FROM sqlite_master
+                     WHERE name IN ('chunk_text_fts', 'chunk_text_trigram')",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        assert_eq!(fts_tables(&store), 2);
 
-        // German with umlaut.
-        let de_results = store.fts_trigram_search("coll-1", "Würde", 10).unwrap();
-        assert!(
-            !de_results.is_empty(),
-            "Trigram should find German text with umlaut"
-        );
+        store.run_migrations().unwrap();
+        assert_eq!(store.get_schema_version(), 9);
+        assert_eq!(fts_tables(&store), 0);
+        assert!(store.get_collection("coll-1").unwrap().is_some());
+    }
+    #[test]
+    fn migration_009_adds_text_analyzer_columns() {
+        let conn = Connection::open_in_memory().unwrap();
+        let store = MetadataStore { conn };
+        store.apply_pragmas().unwrap();
+        for sql in [
+            MIGRATION_001, MIGRATION_002, MIGRATION_003, MIGRATION_004, MIGRATION_005,
+            MIGRATION_006, MIGRATION_007, MIGRATION_008,
+        ] {
+            store.conn.execute_batch(sql).unwrap();
+        }
+        insert_legacy_collection(&store, &sample_collection("old")).unwrap();
+
+        store.run_migrations().unwrap();
+        assert_eq!(store.get_schema_version(), 9);
+        let old = store.get_collection("old").unwrap().unwrap();
+        assert_eq!(old.tokenizer, "unicode61");
+        assert_eq!((old.stemmer, old.stop_words, old.remove_diacritics), (None, None, true));
+
+        let mut english = sample_collection("english");
+        english.stemmer = Some("porter".to_string());
+        english.stop_words = Some("english".to_string());
+        english.remove_diacritics = false;
+        store.create_collection(&english).unwrap();
+        let got = store.get_collection("english").unwrap().unwrap();
+        assert_eq!(got.stemmer.as_deref(), Some("porter"));
+        assert_eq!(got.stop_words.as_deref(), Some("english"));
+        assert!(!got.remove_diacritics);
+
+        let mut bogus = sample_collection("bogus");
+        bogus.tokenizer = "whitespace".to_string();
+        assert!(store.create_collection(&bogus).is_err());
     }
 }
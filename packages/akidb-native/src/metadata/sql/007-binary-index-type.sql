@@ -0,0 +1,58 @@
+-- Migration 007: Allow the binary (1-bit) per-segment index type.
+-- Rebuilds the collections table, as in 006, with
+-- index_type IN ('hnsw', 'ivf_pq', 'binary').
+
+PRAGMA foreign_keys = OFF;
+
+BEGIN;
+
+CREATE TABLE collections_new (
+  collection_id        TEXT    PRIMARY KEY,
+  dimension            INTEGER NOT NULL CHECK (dimension > 0),
+  metric               TEXT    NOT NULL CHECK (metric IN ('cosine', 'l2', 'dot')),
+  embedding_model_id   TEXT    NOT NULL,
+  schema_version       TEXT    NOT NULL,
+  created_at           TEXT    NOT NULL,
+  deleted_at           TEXT,            -- NULL means active
+  store_chunk_text     INTEGER NOT NULL DEFAULT 1,
+  quantization         TEXT    NOT NULL DEFAULT 'fp16'
+    CHECK (quantization IN ('fp16', 'sq8', 'fp32')),
+  hnsw_m               INTEGER NOT NULL DEFAULT 16
+    CHECK (hnsw_m BETWEEN 4 AND 64),
+  hnsw_ef_construction INTEGER NOT NULL DEFAULT 200
+    CHECK (hnsw_ef_construction BETWEEN 50 AND 800),
+  hnsw_ef_search       INTEGER NOT NULL DEFAULT 100
+    CHECK (hnsw_ef_search BETWEEN 10 AND 500),
+  index_type           TEXT    NOT NULL DEFAULT 'hnsw'
+    CHECK (index_type IN ('hnsw', 'ivf_pq', 'binary')),
+  ivf_num_clusters     INTEGER
+    CHECK (ivf_num_clusters IS NULL OR ivf_num_clusters > 0),
+  ivf_num_probes       INTEGER
+    CHECK (ivf_num_probes IS NULL OR ivf_num_probes > 0),
+  pq_num_subquantizers INTEGER
+    CHECK (pq_num_subquantizers IS NULL OR pq_num_subquantizers > 0)
+);
+
+INSERT INTO collections_new (
+  collection_id, dimension, metric, embedding_model_id, schema_version, created_at, deleted_at,
+  store_chunk_text, quantization, hnsw_m, hnsw_ef_construction, hnsw_ef_search,
+  index_type, ivf_num_clusters, ivf_num_probes, pq_num_subquantizers
+)
+SELECT
+  collection_id, dimension, metric, embedding_model_id, schema_version, created_at, deleted_at,
+  store_chunk_text, quantization, hnsw_m, hnsw_ef_construction, hnsw_ef_search,
+  index_type, ivf_num_clusters, ivf_num_probes, pq_num_subquantizers
+FROM collections;
+
+DROP TABLE collections;
+
+ALTER TABLE collections_new RENAME TO collections;
+
+CREATE INDEX IF NOT EXISTS idx_collections_deleted
+  ON collections (deleted_at);
+
+INSERT INTO schema_version (version) VALUES (7);
+
+COMMIT;
+
+PRAGMA foreign_keys = ON;
@@ -0,0 +1,10 @@
+-- AkiDB Metadata Layer — Retire the FTS5 tables
+-- Migration 008: Keyword search now reads a BM25 index stored in each segment,
+-- resolved through the manifest like vector search. The collection-wide FTS5
+-- tables from 002/003 are no longer written or read.
+
+DROP TABLE IF EXISTS chunk_text_fts;
+DROP TABLE IF EXISTS chunk_text_trigram;
+
+-- Record migration version
+INSERT INTO schema_version (version) VALUES (8);
@@ -0,0 +1,16 @@
+-- Migration 009: Per-collection text analyzer for the segment keyword index.
+-- Defaults reproduce the unicode61 analysis used so far, so existing
+-- segments stay consistent with their collection's queries.
+
+ALTER TABLE collections ADD COLUMN tokenizer TEXT NOT NULL DEFAULT 'unicode61'
+  CHECK (tokenizer IN ('unicode61', 'cjk_bigram'));
+
+ALTER TABLE collections ADD COLUMN stemmer TEXT;
+
+ALTER TABLE collections ADD COLUMN stop_words TEXT
+  CHECK (stop_words IS NULL OR stop_words IN ('english'));
+
+ALTER TABLE collections ADD COLUMN remove_diacritics INTEGER NOT NULL DEFAULT 1
+  CHECK (remove_diacritics IN (0, 1));
+
+INSERT INTO schema_version (version) VALUES (9);
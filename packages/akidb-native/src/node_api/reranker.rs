@@ -0,0 +1,87 @@
+//! Re-ranking through a JavaScript callback.
+//!
+//! `searchReranked` runs the search as an `AsyncTask` on the libuv thread
+//! pool, so the engine can block on the callback while the JS thread runs it.
+
+use std::sync::Arc;
+
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::ThreadsafeFunction;
+use napi_derive::napi;
+
+use crate::engine::EngineInner;
+use crate::error::AkiDbError;
+use crate::query::{RerankCandidate, Reranker, SearchOptions, SearchResponse};
+
+#[napi(object)]
+pub struct RerankCandidateJs {
+    pub chunk_id: String,
+    /// Score the search ranked the candidate by.
+    pub score: f64,
+    pub text: Option<String>,
+    pub metadata_json: String,
+}
+
+#[napi(object)]
+pub struct RerankRequestJs {
+    pub query_text: Option<String>,
+    pub candidates: Vec<RerankCandidateJs>,
+}
+
+/// A JS function `(err, request) => number[] | Promise<number[]>`.
+pub struct JsReranker {
+    callback: ThreadsafeFunction<RerankRequestJs>,
+}
+
+impl JsReranker {
+    pub fn new(callback: ThreadsafeFunction<RerankRequestJs>) -> Self {
+        Self { callback }
+    }
+}
+
+impl Reranker for JsReranker {
+    fn rerank(&self, query_text: Option<&str>, candidates: &[RerankCandidate]) -> crate::error::Result<Vec<f64>> {
+        let request = RerankRequestJs {
+            query_text: query_text.map(str::to_string),
+            candidates: candidates
+                .iter()
+                .map(|c| RerankCandidateJs {
+                    chunk_id: c.chunk_id.clone(),
+                    score: c.score,
+                    text: c.text.clone(),
+                    metadata_json: c.metadata.to_string(),
+                })
+                .collect(),
+        };
+        // Must not run on the JS thread: the call is queued to it.
+        block_on(async {
+            match self.callback.call_async::<Either<Vec<f64>, Promise<Vec<f64>>>>(Ok(request)).await? {
+                Either::A(scores) => Ok(scores),
+                Either::B(promise) => promise.await,
+            }
+        })
+        .map_err(|e| AkiDbError::InvalidArgument(format!("reranker failed: {}", e.reason)))
+    }
+}
+
+pub struct SearchRerankedTask {
+    pub engine: Arc<EngineInner>,
+    pub opts: SearchOptions,
+    pub reranker: JsReranker,
+    pub rerank_depth: Option<usize>,
+}
+
+impl Task for SearchRerankedTask {
+    type Output = SearchResponse;
+    type JsValue = super::SearchResponseJs;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        self.engine
+            .search_reranked(self.opts.clone(), &self.reranker, self.rerank_depth)
+            .map_err(napi::Error::from)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(super::search_response_to_js(output))
+    }
+}
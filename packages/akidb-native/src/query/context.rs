@@ -0,0 +1,227 @@
+//! Context assembly: turn ranked search hits into passages that fit a
+//! character or approximate-token budget for an LLM prompt.
+//!
+//! Hits are taken in rank order. A hit whose text repeats, or is contained in,
+//! an already chosen chunk is dropped. Chosen chunks of one document with
+//! consecutive ordinals are merged into a single passage, dropping the text
+//! they overlap by; a hit that would take the merged passages past the budget
+//! is skipped so smaller, lower-ranked chunks can still use it. Passages are
+//! ordered by their best hit.
+
+/// Characters per token assumed by `ContextBudget::Tokens`.
+pub const CHARS_PER_TOKEN: usize = 4;
+
+/// Shortest suffix/prefix overlap removed when merging adjacent chunks;
+/// shorter matches are more likely coincidence than a chunking overlap.
+const MIN_MERGE_OVERLAP: usize = 8;
+
+/// Separator between merged chunks that do not overlap.
+const CHUNK_SEPARATOR: &str = "\n";
+
+/// Size limit of an assembled context.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContextBudget {
+    Chars(usize),
+    /// Approximate tokens, at `CHARS_PER_TOKEN` characters each.
+    Tokens(usize),
+}
+
+impl ContextBudget {
+    /// `"tokens"`, or any other unit as characters.
+    pub fn from_options(unit: &str, amount: usize) -> Self {
+        match unit {
+            "tokens" => Self::Tokens(amount),
+            _ => Self::Chars(amount),
+        }
+    }
+
+    pub fn max_chars(self) -> usize {
+        match self {
+            Self::Chars(chars) => chars,
+            Self::Tokens(tokens) => tokens.saturating_mul(CHARS_PER_TOKEN),
+        }
+    }
+}
+
+/// A search hit with the stored fields context assembly needs.
+#[derive(Debug, Clone)]
+pub struct ContextChunk {
+    pub chunk_id: String,
+    pub doc_id: String,
+    pub ordinal: Option<u32>,
+    pub text: String,
+    pub score: f64,
+}
+
+/// Text of one or more adjacent chunks of a document.
+#[derive(Debug, Clone)]
+pub struct ContextPassage {
+    pub doc_id: String,
+    /// Source chunks, in document order.
+    pub chunk_ids: Vec<String>,
+    pub text: String,
+    /// Score of the passage's best hit.
+    pub score: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct AssembledContext {
+    pub passages: Vec<ContextPassage>,
+    /// Characters across all passage texts; at most the budget.
+    pub total_chars: usize,
+    pub manifest_version_used: i64,
+}
+
+/// Choose and merge `chunks` (ranked best first) into passages within
+/// `budget`. Returns the passages and their total character count.
+pub fn assemble(chunks: Vec<ContextChunk>, budget: ContextBudget) -> (Vec<ContextPassage>, usize) {
+    let max_chars = budget.max_chars();
+    let mut chosen: Vec<(usize, ContextChunk)> = Vec::new();
+    let mut normalized: Vec<String> = Vec::new();
+    let mut passages = Vec::new();
+    let mut total_chars = 0;
+    for (rank, chunk) in chunks.into_iter().enumerate() {
+        let text = normalize(&chunk.text);
+        if text.is_empty() || normalized.iter().any(|seen| seen.contains(&text)) {
+            continue;
+        }
+        // Measure the merged result, so overlap with a chosen neighbor is
+        // not charged twice.
+        chosen.push((rank, chunk));
+        let merged = merge(&chosen);
+        let merged_chars = merged.iter().map(|p| p.text.chars().count()).sum();
+        if merged_chars > max_chars {
+            chosen.pop();
+            continue;
+        }
+        normalized.push(text);
+        passages = merged;
+        total_chars = merged_chars;
+    }
+    (passages, total_chars)
+}
+
+/// Merge runs of consecutive ordinals within a document into one passage;
+/// chunks without an ordinal stand alone. Passages are ordered by best rank.
+fn merge(chosen: &[(usize, ContextChunk)]) -> Vec<ContextPassage> {
+    let mut ordered: Vec<&(usize, ContextChunk)> = chosen.iter().collect();
+    ordered.sort_by(|(rank_a, a), (rank_b, b)| {
+        (&a.doc_id, a.ordinal.is_none(), a.ordinal, rank_a).cmp(&(&b.doc_id, b.ordinal.is_none(), b.ordinal, rank_b))
+    });
+    let mut passages: Vec<(usize, Option<u32>, ContextPassage)> = Vec::new();
+    for (rank, chunk) in ordered {
+        match passages.last_mut() {
+            Some((best_rank, last_ordinal, passage))
+                if passage.doc_id == chunk.doc_id
+                    && chunk.ordinal.is_some()
+                    && last_ordinal.and_then(|o| o.checked_add(1)) == chunk.ordinal =>
+            {
+                *best_rank = (*best_rank).min(*rank);
+                *last_ordinal = chunk.ordinal;
+                passage.text = join_overlapping(&passage.text, &chunk.text);
+                passage.score = passage.score.max(chunk.score);
+                passage.chunk_ids.push(chunk.chunk_id.clone());
+            }
+            _ => passages.push((
+                *rank,
+                chunk.ordinal,
+                ContextPassage {
+                    doc_id: chunk.doc_id.clone(),
+                    chunk_ids: vec![chunk.chunk_id.clone()],
+                    text: chunk.text.clone(),
+                    score: chunk.score,
+                },
+            )),
+        }
+    }
+    passages.sort_by_key(|(rank, _, _)| *rank);
+    passages.into_iter().map(|(_, _, p)| p).collect()
+}
+
+/// Text with runs of whitespace collapsed, for duplicate detection.
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `a` followed by `b`, without the longest suffix of `a` that `b` starts
+/// with; joined by `CHUNK_SEPARATOR` when they overlap by less than
+/// `MIN_MERGE_OVERLAP` bytes.
+fn join_overlapping(a: &str, b: &str) -> String {
+    let overlap = (MIN_MERGE_OVERLAP..=a.len().min(b.len()))
+        .rev()
+        .find(|&len| b.is_char_boundary(len) && a.ends_with(&b[..len]));
+    match overlap {
+        Some(len) => format!("{a}{}", &b[len..]),
+        None => format!("{a}{CHUNK_SEPARATOR}{b}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(chunk_id: &str, doc_id: &str, ordinal: Option<u32>, text: &str, score: f64) -> ContextChunk {
+        ContextChunk {
+            chunk_id: chunk_id.to_string(),
+            doc_id: doc_id.to_string(),
+            ordinal,
+            text: text.to_string(),
+            score,
+        }
+    }
+
+    #[test]
+    fn merges_adjacent_chunks_and_drops_duplicates() {
+        let chunks = vec![
+            chunk("a2", "a", Some(2), "the second part overlaps here", 0.9),
+            chunk("b0", "b", Some(0), "unrelated document", 0.8),
+            chunk("a1", "a", Some(1), "first part, overlaps here", 0.7),
+            chunk("copy", "c", None, "unrelated   document", 0.6),
+            chunk("a3", "a", Some(3), "a third part", 0.5),
+        ];
+        let (passages, total_chars) = assemble(chunks, ContextBudget::Chars(1000));
+        let summary: Vec<(&str, Vec<&str>, &str, f64)> = passages
+            .iter()
+            .map(|p| (p.doc_id.as_str(), p.chunk_ids.iter().map(String::as_str).collect(), p.text.as_str(), p.score))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("a", vec!["a1", "a2", "a3"], "first part, overlaps here\nthe second part overlaps here\na third part", 0.9),
+                ("b", vec!["b0"], "unrelated document", 0.8),
+            ]
+        );
+        assert_eq!(total_chars, passages.iter().map(|p| p.text.chars().count()).sum::<usize>());
+
+        let merged = join_overlapping("window one two three", "one two three four");
+        assert_eq!(merged, "window one two three four");
+    }
+
+    #[test]
+    fn skips_chunks_that_do_not_fit_the_budget() {
+        let chunks = vec![
+            chunk("long", "a", None, &"x".repeat(30), 0.9),
+            chunk("short", "b", None, "0123456789", 0.8),
+            chunk("also", "c", None, "abcdefghi", 0.7),
+        ];
+        let (passages, total_chars) = assemble(chunks.clone(), ContextBudget::Chars(20));
+        let ids: Vec<&str> = passages.iter().map(|p| p.chunk_ids[0].as_str()).collect();
+        assert_eq!(ids, vec!["short", "also"]);
+        assert_eq!(total_chars, 19);
+
+        let (passages, _) = assemble(chunks, ContextBudget::Tokens(8));
+        let ids: Vec<&str> = passages.iter().map(|p| p.chunk_ids[0].as_str()).collect();
+        assert_eq!(ids, vec!["long"]);
+        assert_eq!(ContextBudget::from_options("tokens", 8).max_chars(), 32);
+
+        // Overlap with an adjacent chosen chunk is only counted once.
+        let window = vec![
+            chunk("w0", "a", Some(0), "alpha beta gamma delta", 0.9),
+            chunk("w1", "a", Some(1), "gamma delta epsilon", 0.8),
+        ];
+        let (passages, total_chars) = assemble(window, ContextBudget::Chars(30));
+        assert_eq!(passages[0].text, "alpha beta gamma delta epsilon");
+        assert_eq!(total_chars, 30);
+        assert_eq!(ContextBudget::from_options("chars", 8), ContextBudget::Chars(8));
+    }
+}
@@ -0,0 +1,150 @@
+//! Search cursors: continue a ranked result list page by page.
+//!
+//! A cursor pins the manifest version the first page read, fingerprints the
+//! query it continues, and marks the last result returned by its ranking
+//! score and chunk ID. Results are ordered by descending score, then
+//! ascending chunk ID, so the next page is the results strictly after that
+//! key. Approximate indexes cannot start a search at a score, so a
+//! continuation searches `offset + top_k` deep and drops what the earlier
+//! pages returned.
+//!
+//! That only works for scores that do not depend on the search depth.
+//! Keyword search pages over raw BM25 scores, not the normalized ones it
+//! returns. Hybrid fusion normalizes and ranks within each leg, so pages
+//! split the fusion of legs of one depth, recorded in the cursor. Once that
+//! fusion runs out, the next page doubles the leg depth and continues with
+//! the chunks the deeper fusion adds, ranked by their scores in it.
+//!
+//! Buffered records are pinned too: a continuation reads only the records
+//! the write buffer held when the first page was searched, and is rejected
+//! once that buffer has been flushed.
+//!
+//! The encoded form is an opaque hex string of
+//! `{manifest_version}:{query}:{leg_depth}:{seen_depth}:{buffer_generation}:{buffer_len}:{score bits}:{offset}:{chunk_id}`;
+//! the score is stored as its exact bit pattern so ties compare equal.
+
+use std::cmp::Ordering;
+
+use crate::error::{AkiDbError, Result};
+
+/// Position after the last result of a page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchCursor {
+    pub manifest_version: i64,
+    /// `SearchOptions::query_fingerprint` of the search being paged.
+    pub query: u64,
+    /// Candidates per leg of the hybrid fusion the last result came from;
+    /// 0 in other modes.
+    pub leg_depth: usize,
+    /// Leg depth of the fusion earlier pages exhausted, whose chunks the
+    /// current fusion skips; 0 while paging the first one.
+    pub seen_depth: usize,
+    /// `WriteBuffer::generation` and record count when the first page ran.
+    pub buffer_generation: u64,
+    pub buffer_len: usize,
+    /// Ranking score of the last result: raw BM25 in keyword search, else
+    /// the returned score.
+    pub score: f64,
+    pub chunk_id: String,
+    /// Results returned so far, across all pages.
+    pub offset: usize,
+}
+
+impl SearchCursor {
+    pub fn encode(&self) -> String {
+        format!(
+            "{}:{:016x}:{}:{}:{}:{}:{:016x}:{}:{}",
+            self.manifest_version,
+            self.query,
+            self.leg_depth,
+            self.seen_depth,
+            self.buffer_generation,
+            self.buffer_len,
+            self.score.to_bits(),
+            self.offset,
+            self.chunk_id
+        )
+        .bytes()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let invalid = || AkiDbError::InvalidArgument(format!("Invalid search cursor \"{cursor}\""));
+        if !cursor.len().is_multiple_of(2) {
+            return Err(invalid());
+        }
+        let bytes = (0..cursor.len())
+            .step_by(2)
+            .map(|i| cursor.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(invalid)?;
+        let text = String::from_utf8(bytes).map_err(|_| invalid())?;
+        let mut parts = text.splitn(9, ':');
+        let mut next = || parts.next().ok_or_else(invalid);
+        let (version, query, leg_depth, seen_depth, generation, buffer_len, bits, offset, chunk_id) =
+            (next()?, next()?, next()?, next()?, next()?, next()?, next()?, next()?, next()?);
+        Ok(Self {
+            manifest_version: version.parse().map_err(|_| invalid())?,
+            query: u64::from_str_radix(query, 16).map_err(|_| invalid())?,
+            leg_depth: leg_depth.parse().map_err(|_| invalid())?,
+            seen_depth: seen_depth.parse().map_err(|_| invalid())?,
+            buffer_generation: generation.parse().map_err(|_| invalid())?,
+            buffer_len: buffer_len.parse().map_err(|_| invalid())?,
+            score: f64::from_bits(u64::from_str_radix(bits, 16).map_err(|_| invalid())?),
+            chunk_id: chunk_id.to_string(),
+            offset: offset.parse().map_err(|_| invalid())?,
+        })
+    }
+
+    /// Whether a result ranked by `score` ranks after this cursor.
+    pub fn precedes(&self, score: f64, chunk_id: &str) -> bool {
+        rank_order(self.score, &self.chunk_id, score, chunk_id) == Ordering::Less
+    }
+}
+
+/// Order of two results: descending score, then ascending chunk ID.
+pub fn rank_order(score_a: f64, chunk_a: &str, score_b: f64, chunk_b: &str) -> Ordering {
+    score_b.partial_cmp(&score_a).unwrap_or(Ordering::Equal).then_with(|| chunk_a.cmp(chunk_b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_cursor(score: f64, chunk_id: &str) -> SearchCursor {
+        SearchCursor {
+            manifest_version: 3,
+            query: 0xfeed,
+            leg_depth: 40,
+            seen_depth: 20,
+            buffer_generation: 2,
+            buffer_len: 7,
+            score,
+            chunk_id: chunk_id.into(),
+            offset: 12,
+        }
+    }
+
+    #[test]
+    fn encode_round_trips_and_rejects_garbage() {
+        let cursor = make_cursor(0.1 + 0.2, "doc:1#2");
+        let decoded = SearchCursor::decode(&cursor.encode()).unwrap();
+        assert_eq!(decoded, cursor);
+        assert_eq!(decoded.score.to_bits(), (0.1f64 + 0.2).to_bits());
+
+        for bad in ["", "abc", "zz", "6869"] {
+            assert!(SearchCursor::decode(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn precedes_orders_by_score_then_chunk_id() {
+        let cursor = make_cursor(0.5, "b");
+        assert!(cursor.precedes(0.4, "a"));
+        assert!(cursor.precedes(0.5, "c"));
+        assert!(!cursor.precedes(0.5, "b"));
+        assert!(!cursor.precedes(0.5, "a"));
+        assert!(!cursor.precedes(0.6, "z"));
+    }
+}
@@ -0,0 +1,150 @@
+//! Result grouping: collapse search results by document or a metadata field,
+//! so one source cannot fill the whole top-K.
+//!
+//! Each search leg over-fetches (`SearchOptions::candidate_depth`), and the
+//! step that cuts the ranked list to `top_k` (`merge_and_dedup`, `fuse`, the
+//! keyword leg) applies `Grouping::limit` instead: the first `top_k` groups by
+//! best score, each keeping its best `max_per_group` results.
+
+use std::collections::HashMap;
+
+use crate::index::SearchResult;
+
+/// Candidates fetched per requested result when grouping.
+pub const GROUP_OVERSAMPLING: usize = 8;
+
+/// Field search results are grouped by.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GroupBy {
+    DocId,
+    /// A top-level metadata field.
+    Metadata(String),
+}
+
+impl GroupBy {
+    /// `"doc_id"`, or any other string as a metadata field name.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Self {
+        match s {
+            "doc_id" => Self::DocId,
+            field => Self::Metadata(field.to_string()),
+        }
+    }
+}
+
+/// A group of search results sharing a `group_by` value.
+#[derive(Debug, Clone)]
+pub struct SearchGroup {
+    /// The shared value; null for results whose record lacks the field.
+    pub key: serde_json::Value,
+    /// Score of the group's best result.
+    pub score: f64,
+    /// The group's best results, by descending score.
+    pub results: Vec<SearchResult>,
+}
+
+/// Group keys of the candidates being ranked, and the limits to apply.
+pub struct Grouping {
+    pub keys: HashMap<String, serde_json::Value>,
+    pub max_groups: usize,
+    pub max_per_group: usize,
+}
+
+impl Grouping {
+    /// Keep the results of the first `max_groups` groups, at most
+    /// `max_per_group` each, tagging them with their group key. `results`
+    /// must be sorted by descending score.
+    pub fn limit(&self, results: impl IntoIterator<Item = SearchResult>) -> Vec<SearchResult> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        let mut full_groups = 0;
+        let mut kept = Vec::new();
+        for mut r in results {
+            if full_groups == self.max_groups {
+                break;
+            }
+            let key = self.keys.get(&r.chunk_id).cloned().unwrap_or(serde_json::Value::Null);
+            let id = key.to_string();
+            let count = match counts.get(&id) {
+                Some(&count) if count >= self.max_per_group => continue,
+                None if counts.len() == self.max_groups => continue,
+                _ => counts.entry(id).or_default(),
+            };
+            *count += 1;
+            if *count == self.max_per_group {
+                full_groups += 1;
+            }
+            r.group_key = Some(key);
+            kept.push(r);
+        }
+        kept
+    }
+}
+
+/// Group results tagged by `Grouping::limit`, in order of each group's best
+/// result.
+pub fn collect(results: &[SearchResult]) -> Vec<SearchGroup> {
+    let mut groups: Vec<SearchGroup> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for r in results {
+        let key = r.group_key.clone().unwrap_or(serde_json::Value::Null);
+        match positions.get(&key.to_string()) {
+            Some(&i) => groups[i].results.push(r.clone()),
+            None => {
+                positions.insert(key.to_string(), groups.len());
+                groups.push(SearchGroup { key, score: r.score, results: vec![r.clone()] });
+            }
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn make_result(id: &str, score: f64) -> SearchResult {
+        SearchResult {
+            chunk_id: id.to_string(),
+            score,
+            committed: Some(true),
+            explain: None,
+            metadata: None,
+            text: None,
+            vector: None,
+            group_key: None,
+        }
+    }
+
+    #[test]
+    fn limit_caps_groups_and_results_per_group() {
+        let grouping = Grouping {
+            keys: [("a1", json!("a")), ("a2", json!("a")), ("a3", json!("a")), ("b1", json!("b")), ("c1", json!("c"))]
+                .into_iter()
+                .map(|(id, key)| (id.to_string(), key))
+                .collect(),
+            max_groups: 2,
+            max_per_group: 2,
+        };
+        let results = ["a1", "a2", "b1", "a3", "c1", "x1", "x2"]
+            .iter()
+            .enumerate()
+            .map(|(i, id)| make_result(id, 1.0 - i as f64 * 0.1));
+        let kept = grouping.limit(results);
+        let ids: Vec<&str> = kept.iter().map(|r| r.chunk_id.as_str()).collect();
+        assert_eq!(ids, vec!["a1", "a2", "b1"]);
+
+        let groups = collect(&kept);
+        let summary: Vec<(serde_json::Value, f64, usize)> =
+            groups.iter().map(|g| (g.key.clone(), g.score, g.results.len())).collect();
+        assert_eq!(summary, vec![(json!("a"), 1.0, 2), (json!("b"), 0.8, 1)]);
+    }
+
+    #[test]
+    fn results_without_a_key_share_the_null_group() {
+        let grouping = Grouping { keys: HashMap::new(), max_groups: 3, max_per_group: 1 };
+        let kept = grouping.limit(vec![make_result("x", 0.9), make_result("y", 0.8)]);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].group_key, Some(serde_json::Value::Null));
+    }
+}
@@ -0,0 +1,217 @@
+//! Match highlighting for explain output.
+//!
+//! Locates the query's phrases in a chunk's text with the collection's
+//! analyzer, as the keyword index does, so highlights agree with what was
+//! scored. Text without
+//! word matches (CJK runs, for example) falls back to case-insensitive
+//! substring matches of three or more characters, like keyword search.
+//! Offsets are character (code point) positions.
+
+use std::ops::Range;
+
+use crate::index::TermMatch;
+use crate::query::syntax::{KeywordQuery, Phrase};
+use crate::text::Analyzer;
+
+/// Snippet length in characters, the same budget as `chunk_preview`.
+pub const SNIPPET_CHARS: usize = 200;
+
+/// Where a query's terms occur in one chunk text.
+#[derive(Debug, Default)]
+pub struct Highlights {
+    pub matched_terms: Vec<String>,
+    pub term_matches: Vec<TermMatch>,
+    /// Best passage and its character offset in the text.
+    pub snippet: Option<(usize, String)>,
+}
+
+/// Find the phrases of `query` in `text` and pick the passage of up to
+/// `SNIPPET_CHARS` characters covering the most distinct matched terms.
+pub fn highlight(query: &KeywordQuery, text: &str, analyzer: &Analyzer) -> Highlights {
+    let phrases = query.phrases();
+    let tokens = analyzer.tokenize_with_offsets(text);
+    let mut matches = word_matches(&phrases, &tokens);
+    if matches.is_empty() {
+        matches = substring_matches(&phrases, text);
+    }
+    if matches.is_empty() {
+        return Highlights::default();
+    }
+    matches.sort_by_key(|m| (m.start, m.end));
+
+    let mut matched_terms: Vec<String> = Vec::new();
+    for phrase in &phrases {
+        let term = label(phrase);
+        if !matched_terms.contains(&term) && matches.iter().any(|m| m.term == term) {
+            matched_terms.push(term);
+        }
+    }
+    let window = best_window(&matches, &tokens, text.chars().count());
+    let snippet = text.chars().skip(window.start).take(window.len()).collect();
+
+    Highlights {
+        matched_terms,
+        term_matches: matches,
+        snippet: Some((window.start, snippet)),
+    }
+}
+
+/// How a phrase is reported: its query text, with `*` for a prefix.
+fn label(phrase: &Phrase) -> String {
+    if phrase.prefix {
+        format!("{}*", phrase.text)
+    } else {
+        phrase.text.clone()
+    }
+}
+
+fn word_matches(phrases: &[&Phrase], tokens: &[(String, Range<usize>)]) -> Vec<TermMatch> {
+    let mut matches = Vec::new();
+    for phrase in phrases {
+        let last = phrase.tokens.len() - 1;
+        for window in tokens.windows(phrase.tokens.len()) {
+            let hit = window.iter().zip(&phrase.tokens).enumerate().all(|(i, ((token, _), want))| {
+                if phrase.prefix && i == last {
+                    token.starts_with(want.as_str())
+                } else {
+                    token == want
+                }
+            });
+            if hit {
+                matches.push(TermMatch {
+                    term: label(phrase),
+                    start: window[0].1.start,
+                    end: window[last].1.end,
+                });
+            }
+        }
+    }
+    matches
+}
+
+fn substring_matches(phrases: &[&Phrase], text: &str) -> Vec<TermMatch> {
+    // Lowercase per character so offsets stay aligned with `text`.
+    let haystack: Vec<char> = text.chars().map(lower).collect();
+    let mut matches = Vec::new();
+    for phrase in phrases {
+        let needle: Vec<char> = phrase.text.chars().map(lower).collect();
+        if needle.len() < 3 {
+            continue;
+        }
+        let mut start = 0;
+        while start + needle.len() <= haystack.len() {
+            if haystack[start..start + needle.len()] == needle[..] {
+                matches.push(TermMatch {
+                    term: label(phrase),
+                    start,
+                    end: start + needle.len(),
+                });
+                start += needle.len();
+            } else {
+                start += 1;
+            }
+        }
+    }
+    matches
+}
+
+fn lower(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// The `SNIPPET_CHARS` window (in characters) that fully contains the most
+/// distinct terms, then the most matches; earliest wins ties. The window is
+/// centred on its matches and trimmed to token boundaries where possible.
+fn best_window(matches: &[TermMatch], tokens: &[(String, Range<usize>)], len: usize) -> Range<usize> {
+    if len <= SNIPPET_CHARS {
+        return 0..len;
+    }
+    let mut best: Option<(usize, usize, usize, usize)> = None; // (terms, count, start, last end)
+    for (i, first) in matches.iter().enumerate() {
+        let inside: Vec<&TermMatch> = matches[i..]
+            .iter()
+            .take_while(|m| m.end <= first.start + SNIPPET_CHARS)
+            .collect();
+        let mut terms: Vec<&str> = inside.iter().map(|m| m.term.as_str()).collect();
+        terms.sort_unstable();
+        terms.dedup();
+        let last_end = inside.iter().map(|m| m.end).max().unwrap_or(first.end);
+        let candidate = (terms.len(), inside.len(), first.start, last_end);
+        if best.is_none_or(|b| (candidate.0, candidate.1) > (b.0, b.1)) {
+            best = Some(candidate);
+        }
+    }
+    let (_, _, first_start, last_end) = best.expect("at least one match");
+
+    // Spread the unused budget on both sides of the matches.
+    let slack = SNIPPET_CHARS.saturating_sub(last_end - first_start);
+    let start = first_start.saturating_sub(slack / 2).min(len - SNIPPET_CHARS);
+    let end = start + SNIPPET_CHARS;
+    // Start and end on whole words, without dropping a match.
+    let start = tokens
+        .iter()
+        .map(|(_, r)| r.start)
+        .find(|&s| s >= start)
+        .map_or(start, |s| s.min(first_start));
+    let end = tokens
+        .iter()
+        .rev()
+        .map(|(_, r)| r.end)
+        .find(|&e| e <= end)
+        .map_or(end, |e| e.max(last_end));
+    start..end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::syntax::{self, KeywordSyntax};
+
+    fn run(query: &str, syntax: KeywordSyntax, text: &str) -> Highlights {
+        let analyzer = Analyzer::default();
+        highlight(&syntax::parse(query, syntax, &analyzer).unwrap(), text, &analyzer)
+    }
+
+    #[test]
+    fn highlight_reports_only_terms_that_matched() {
+        let h = run("brown missing fox", KeywordSyntax::Simple, "The Brown fox, the brown dog");
+        assert_eq!(h.matched_terms, vec!["brown", "fox"]);
+        let spans: Vec<(&str, usize, usize)> =
+            h.term_matches.iter().map(|m| (m.term.as_str(), m.start, m.end)).collect();
+        assert_eq!(spans, vec![("brown", 4, 9), ("fox", 10, 13), ("brown", 19, 24)]);
+        assert_eq!(h.snippet, Some((0, "The Brown fox, the brown dog".to_string())));
+    }
+
+    #[test]
+    fn highlight_handles_phrases_prefixes_and_exclusions() {
+        let text = "Authentication and authorization: token refresh flow";
+        let h = run("\"token refresh\" auth* -flow", KeywordSyntax::Advanced, text);
+        assert_eq!(h.matched_terms, vec!["token refresh", "auth*"]);
+        let spans: Vec<(usize, usize)> = h.term_matches.iter().map(|m| (m.start, m.end)).collect();
+        assert_eq!(spans, vec![(0, 14), (19, 32), (34, 47)]);
+        let m = &h.term_matches[2];
+        assert_eq!(text.chars().skip(m.start).take(m.end - m.start).collect::<String>(), "token refresh");
+    }
+
+    #[test]
+    fn highlight_falls_back_to_substrings() {
+        let h = run("自由在尊严", KeywordSyntax::Simple, "人人生而自由在尊严和权利上一律平等");
+        assert_eq!(h.matched_terms, vec!["自由在尊严"]);
+        assert_eq!((h.term_matches[0].start, h.term_matches[0].end), (4, 9));
+        assert!(run("nothing", KeywordSyntax::Simple, "some text").snippet.is_none());
+    }
+
+    #[test]
+    fn snippet_picks_the_densest_passage() {
+        let filler = "lorem ipsum dolor sit amet ".repeat(20);
+        let text = format!("alpha {filler}alpha beta gamma {filler}");
+        let h = run("alpha beta gamma", KeywordSyntax::Simple, &text);
+        let (start, snippet) = h.snippet.unwrap();
+        assert!(snippet.chars().count() <= SNIPPET_CHARS);
+        assert!(snippet.contains("alpha beta gamma"), "{snippet}");
+        assert!(start > 0);
+        // Trimmed to whole words.
+        let words = ["lorem", "ipsum", "dolor", "sit", "amet", "alpha", "beta", "gamma"];
+        assert!(snippet.split(' ').all(|w| words.contains(&w)), "{snippet}");
+    }
+}
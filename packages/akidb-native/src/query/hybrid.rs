@@ -1,42 +1,164 @@
-//! Hybrid search module — Reciprocal Rank Fusion (RRF).
+//! Hybrid search module — fuses vector (ANN) and BM25 keyword result lists.
 //!
-//! Combines vector (ANN) search results with BM25 keyword search results
-//! using the RRF formula: `score(d) = Σ 1 / (k + rank_i(d))`
-//! where `k` is a constant (default 60) and `rank_i(d)` is the 1-based
-//! rank of document `d` in result list `i`.
+//! Fusion strategies (`Fusion`):
+//!   - RRF (default): `score(d) = Σ w_i / (k + rank_i(d))`, where `k` is a
+//!     constant (default 60) and `rank_i(d)` is the 1-based rank of document
+//!     `d` in result list `i`.
+//!   - Min-max and z-score: each list's scores are normalized, then combined
+//!     as a weighted convex combination `Σ w_i · norm_i(d) / Σ w_i`.
+//!   - DBSF (distribution-based score fusion): like min-max, but each list is
+//!     scaled over `[μ − 3σ, μ + 3σ]` and clipped, so one outlier does not
+//!     squash the rest of the list.
+//!
+//! With the score-based strategies a document missing from one list scores
+//! as that list's lowest result; with RRF it gets nothing from that list.
 
 use std::collections::HashMap;
 
 use crate::index::SearchResult;
+use crate::query::group::Grouping;
 
 /// Default RRF constant `k`. Higher values give less weight to top ranks.
-const RRF_K: f64 = 60.0;
+pub const DEFAULT_RRF_K: f64 = 60.0;
+
+/// How hybrid search combines its vector and keyword result lists.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fusion {
+    /// Reciprocal Rank Fusion with constant `k`.
+    Rrf { k: f64 },
+    MinMax,
+    ZScore,
+    Dbsf,
+}
+
+impl Default for Fusion {
+    fn default() -> Self {
+        Self::Rrf { k: DEFAULT_RRF_K }
+    }
+}
+
+impl Fusion {
+    /// Fusion from its option name ("rrf", "min_max", "z_score", "dbsf");
+    /// unknown names fall back to RRF. `rrf_k` only applies to RRF.
+    pub fn from_options(method: &str, rrf_k: Option<f64>) -> Self {
+        match method {
+            "min_max" => Self::MinMax,
+            "z_score" => Self::ZScore,
+            "dbsf" => Self::Dbsf,
+            _ => Self::Rrf { k: rrf_k.unwrap_or(DEFAULT_RRF_K) },
+        }
+    }
+
+    /// Option name of this strategy, as reported by explain.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Rrf { .. } => "rrf",
+            Self::MinMax => "min_max",
+            Self::ZScore => "z_score",
+            Self::Dbsf => "dbsf",
+        }
+    }
+
+    /// The RRF constant, if this is RRF.
+    pub fn rrf_k(&self) -> Option<f64> {
+        match self {
+            Self::Rrf { k } => Some(*k),
+            _ => None,
+        }
+    }
 
-/// Fuse two ranked result lists using Reciprocal Rank Fusion.
+    /// Each result's contribution before weighting, in list order: `1 / (k + rank)`
+    /// for RRF, the normalized score otherwise.
+    pub fn normalize(&self, results: &[SearchResult]) -> Vec<f64> {
+        let scores: Vec<f64> = results.iter().map(|r| r.score).collect();
+        match *self {
+            Self::Rrf { k } => (1..=scores.len()).map(|rank| 1.0 / (k + rank as f64)).collect(),
+            Self::MinMax => {
+                let min = scores.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                scores
+                    .iter()
+                    .map(|s| if max > min { (s - min) / (max - min) } else { 1.0 })
+                    .collect()
+            }
+            Self::ZScore => {
+                let (mean, std_dev) = mean_std_dev(&scores);
+                scores
+                    .iter()
+                    .map(|s| if std_dev > 0.0 { (s - mean) / std_dev } else { 0.0 })
+                    .collect()
+            }
+            Self::Dbsf => {
+                let (mean, std_dev) = mean_std_dev(&scores);
+                let low = mean - 3.0 * std_dev;
+                scores
+                    .iter()
+                    .map(|s| if std_dev > 0.0 { ((s - low) / (6.0 * std_dev)).clamp(0.0, 1.0) } else { 0.5 })
+                    .collect()
+            }
+        }
+    }
+
+    /// Weights applied to the normalized lists: as given for RRF, scaled to
+    /// sum to 1 for the convex combinations.
+    pub fn effective_weights(&self, vector_weight: f64, keyword_weight: f64) -> (f64, f64) {
+        let total = vector_weight + keyword_weight;
+        match self {
+            Self::Rrf { .. } => (vector_weight, keyword_weight),
+            _ if total > 0.0 => (vector_weight / total, keyword_weight / total),
+            _ => (0.0, 0.0),
+        }
+    }
+
+    /// What a document missing from a list with normalized scores `normalized` gets.
+    fn missing_score(&self, normalized: &[f64]) -> f64 {
+        match self {
+            Self::Rrf { .. } => 0.0,
+            _ => normalized.iter().copied().reduce(f64::min).unwrap_or(0.0),
+        }
+    }
+}
+
+/// Population mean and standard deviation of `scores`.
+fn mean_std_dev(scores: &[f64]) -> (f64, f64) {
+    if scores.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = scores.len() as f64;
+    let mean = scores.iter().sum::<f64>() / n;
+    let variance = scores.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n;
+    (mean, variance.sqrt())
+}
+
+/// Fuse two ranked result lists with `fusion`.
 ///
 /// Both `vector_results` and `keyword_results` should already be sorted
-/// by descending score. The output is sorted by descending RRF score
-/// with deterministic tie-breaking on chunk_id.
-pub fn rrf_fuse(
+/// by descending score. The output is sorted by descending fused score
+/// with deterministic tie-breaking on chunk_id, and cut to `top_k` or, with
+/// `grouping`, to the best results of its groups.
+pub fn fuse(
     vector_results: &[SearchResult],
     keyword_results: &[SearchResult],
     top_k: usize,
     vector_weight: f64,
     keyword_weight: f64,
+    fusion: Fusion,
+    grouping: Option<&Grouping>,
 ) -> Vec<SearchResult> {
-    let mut scores: HashMap<String, f64> = HashMap::new();
+    let vector_norm = fusion.normalize(vector_results);
+    let keyword_norm = fusion.normalize(keyword_results);
+    let (vector_weight, keyword_weight) = fusion.effective_weights(vector_weight, keyword_weight);
 
-    // Vector results contribution.
-    for (rank_0, r) in vector_results.iter().enumerate() {
-        let rrf_score = vector_weight / (RRF_K + (rank_0 + 1) as f64);
-        *scores.entry(r.chunk_id.clone()).or_insert(0.0) += rrf_score;
+    // Per chunk: normalized vector and keyword contributions, if listed.
+    let mut legs: HashMap<String, (Option<f64>, Option<f64>)> = HashMap::new();
+    for (r, norm) in vector_results.iter().zip(&vector_norm) {
+        legs.entry(r.chunk_id.clone()).or_default().0 = Some(*norm);
     }
-
-    // Keyword results contribution.
-    for (rank_0, r) in keyword_results.iter().enumerate() {
-        let rrf_score = keyword_weight / (RRF_K + (rank_0 + 1) as f64);
-        *scores.entry(r.chunk_id.clone()).or_insert(0.0) += rrf_score;
+    for (r, norm) in keyword_results.iter().zip(&keyword_norm) {
+        legs.entry(r.chunk_id.clone()).or_default().1 = Some(*norm);
     }
+    let vector_missing = fusion.missing_score(&vector_norm);
+    let keyword_missing = fusion.missing_score(&keyword_norm);
 
     // Build committed map from both sources (prefer vector's committed value).
     let mut committed_map: HashMap<String, Option<bool>> = HashMap::new();
@@ -47,13 +169,18 @@ pub fn rrf_fuse(
         committed_map.insert(r.chunk_id.clone(), r.committed);
     }
 
-    let mut fused: Vec<SearchResult> = scores
+    let mut fused: Vec<SearchResult> = legs
         .into_iter()
-        .map(|(chunk_id, score)| SearchResult {
+        .map(|(chunk_id, (vector, keyword))| SearchResult {
             committed: committed_map.get(&chunk_id).copied().flatten(),
             chunk_id,
-            score,
+            score: vector_weight * vector.unwrap_or(vector_missing)
+                + keyword_weight * keyword.unwrap_or(keyword_missing),
             explain: None,
+            metadata: None,
+            text: None,
+            vector: None,
+            group_key: None,
         })
         .collect();
 
@@ -65,8 +192,13 @@ pub fn rrf_fuse(
             .then(a.chunk_id.cmp(&b.chunk_id))
     });
 
-    fused.truncate(top_k);
-    fused
+    match grouping {
+        Some(grouping) => grouping.limit(fused),
+        None => {
+            fused.truncate(top_k);
+            fused
+        }
+    }
 }
 
 #[cfg(test)]
@@ -79,6 +211,10 @@ mod tests {
             score,
             committed: Some(true),
             explain: None,
+            metadata: None,
+            text: None,
+            vector: None,
+            group_key: None,
         }
     }
 
@@ -95,7 +231,7 @@ mod tests {
             make_result("a", 0.70),
         ];
 
-        let fused = rrf_fuse(&vector, &keyword, 10, 1.0, 1.0);
+        let fused = fuse(&vector, &keyword, 10, 1.0, 1.0, Fusion::default(), None);
 
         // "a" appears in both lists (rank 1 vector, rank 3 keyword).
         // "b" appears in both lists (rank 2 vector, rank 1 keyword).
@@ -122,7 +258,7 @@ mod tests {
             make_result("e", 0.8),
         ];
 
-        let fused = rrf_fuse(&vector, &keyword, 2, 1.0, 1.0);
+        let fused = fuse(&vector, &keyword, 2, 1.0, 1.0, Fusion::default(), None);
         assert_eq!(fused.len(), 2);
     }
 
@@ -132,12 +268,12 @@ mod tests {
         let vector = vec![make_result("a", 0.9)];
 
         // One empty, one populated.
-        let fused = rrf_fuse(&vector, &empty, 10, 1.0, 1.0);
+        let fused = fuse(&vector, &empty, 10, 1.0, 1.0, Fusion::default(), None);
         assert_eq!(fused.len(), 1);
         assert_eq!(fused[0].chunk_id, "a");
 
         // Both empty.
-        let fused2 = rrf_fuse(&empty, &empty, 10, 1.0, 1.0);
+        let fused2 = fuse(&empty, &empty, 10, 1.0, 1.0, Fusion::default(), None);
         assert!(fused2.is_empty());
     }
 
@@ -147,13 +283,13 @@ mod tests {
         let vector = vec![make_result("a", 0.9)];
         let keyword = vec![make_result("b", 0.9)];
 
-        let fused = rrf_fuse(&vector, &keyword, 10, 0.0, 1.0);
+        let fused = fuse(&vector, &keyword, 10, 0.0, 1.0, Fusion::default(), None);
         let a_score = fused.iter().find(|r| r.chunk_id == "a").map(|r| r.score).unwrap_or(0.0);
         let b_score = fused.iter().find(|r| r.chunk_id == "b").unwrap().score;
         assert!(b_score > a_score);
 
         // When keyword_weight is 0, only vector results matter.
-        let fused2 = rrf_fuse(&vector, &keyword, 10, 1.0, 0.0);
+        let fused2 = fuse(&vector, &keyword, 10, 1.0, 0.0, Fusion::default(), None);
         let a_score2 = fused2.iter().find(|r| r.chunk_id == "a").unwrap().score;
         let b_score2 = fused2.iter().find(|r| r.chunk_id == "b").map(|r| r.score).unwrap_or(0.0);
         assert!(a_score2 > b_score2);
@@ -165,10 +301,57 @@ mod tests {
         let vector = vec![make_result("b", 0.9), make_result("a", 0.8)];
         let keyword = vec![make_result("a", 0.9), make_result("b", 0.8)];
 
-        let fused = rrf_fuse(&vector, &keyword, 10, 1.0, 1.0);
+        let fused = fuse(&vector, &keyword, 10, 1.0, 1.0, Fusion::default(), None);
         // "a" and "b" each appear at rank 1 in one list and rank 2 in the other.
         // Their RRF scores should be identical, so "a" < "b" in chunk_id order.
         assert_eq!(fused[0].chunk_id, "a");
         assert_eq!(fused[1].chunk_id, "b");
     }
+
+    #[test]
+    fn rrf_k_is_configurable() {
+        let vector = vec![make_result("a", 0.9), make_result("b", 0.8)];
+        let fused = fuse(&vector, &[], 10, 1.0, 1.0, Fusion::Rrf { k: 0.0 }, None);
+        assert_eq!((fused[0].score, fused[1].score), (1.0, 0.5));
+        assert_eq!(Fusion::from_options("rrf", Some(10.0)), Fusion::Rrf { k: 10.0 });
+        assert_eq!(Fusion::from_options("unknown", None), Fusion::default());
+        assert_eq!(Fusion::from_options("z_score", Some(10.0)), Fusion::ZScore);
+    }
+
+    #[test]
+    fn min_max_is_a_convex_combination() {
+        let vector = vec![make_result("a", 0.9), make_result("b", 0.7), make_result("c", 0.5)];
+        let keyword = vec![make_result("c", 1.0), make_result("d", 0.2)];
+        let fused = fuse(&vector, &keyword, 10, 3.0, 1.0, Fusion::MinMax, None);
+        let score = |id: &str| fused.iter().find(|r| r.chunk_id == id).unwrap().score;
+        // Weights 3:1 become 0.75/0.25; a missing leg scores as its minimum (0).
+        assert!((score("a") - 0.75).abs() < 1e-12);
+        assert!((score("b") - 0.375).abs() < 1e-12);
+        assert!((score("c") - 0.25).abs() < 1e-12);
+        assert!(score("d").abs() < 1e-12);
+        assert_eq!(fused[0].chunk_id, "a");
+    }
+
+    #[test]
+    fn z_score_and_dbsf_normalize_by_distribution() {
+        let results = vec![make_result("a", 3.0), make_result("b", 2.0), make_result("c", 1.0)];
+        let std_dev = (2.0f64 / 3.0).sqrt();
+        let z = Fusion::ZScore.normalize(&results);
+        assert!((z[0] - 1.0 / std_dev).abs() < 1e-12);
+        assert!(z[1].abs() < 1e-12);
+        let dbsf = Fusion::Dbsf.normalize(&results);
+        assert!((dbsf[1] - 0.5).abs() < 1e-12);
+        assert!((dbsf[0] - (0.5 + 1.0 / (6.0 * std_dev))).abs() < 1e-12);
+        // Constant lists do not divide by zero.
+        let flat = vec![make_result("a", 1.0), make_result("b", 1.0)];
+        assert_eq!(Fusion::ZScore.normalize(&flat), vec![0.0, 0.0]);
+        assert_eq!(Fusion::Dbsf.normalize(&flat), vec![0.5, 0.5]);
+        assert_eq!(Fusion::MinMax.normalize(&flat), vec![1.0, 1.0]);
+
+        // A chunk only in the keyword list gets the vector list's lowest z-score.
+        let keyword = vec![make_result("d", 5.0)];
+        let fused = fuse(&results, &keyword, 10, 1.0, 1.0, Fusion::ZScore, None);
+        let d = fused.iter().find(|r| r.chunk_id == "d").unwrap();
+        assert!((d.score - 0.5 * z[2]).abs() < 1e-12);
+    }
 }
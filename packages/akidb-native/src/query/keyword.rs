@@ -1,207 +1,667 @@
-//! Keyword search module — BM25 search via FTS5.
+//! Keyword search module — BM25 over per-segment keyword indexes.
 //!
-//! Wraps MetadataStore::fts_search to provide a consistent SearchResult
-//! interface that can be used standalone or combined with vector search
-//! in the hybrid module.
+//! Every segment stores a `KeywordIndex` next to its TextBlock, and a search
+//! only reads the segments of one manifest snapshot, so keyword results follow
+//! the same publish, time-travel and rollback rules as vector results. Corpus
+//! statistics (record count, average length, document frequency) are summed
+//! over the snapshot's segments, so a record's score does not depend on which
+//! segment it landed in.
+//!
+//! The query is a parsed `KeywordQuery` (see `syntax`). Its phrases and NEAR
+//! groups are the leaves: each is matched against a segment's postings, the
+//! boolean structure combines the leaf matches into the matching records, and
+//! every matched leaf outside an exclusion adds its BM25 weight to the score.
+//! When no record matches, each phrase is retried as a case-insensitive
+//! substring via the trigram index, which covers scripts without word
+//! boundaries (CJK, Thai). Prefixes and boolean operators still apply in the
+//! fallback; NEAR does not match there.
+//!
+//! Uncommitted write-buffer records are indexed on the fly and passed in as
+//! one more segment, so they share corpus statistics and normalization with
+//! committed records. A buffered chunk supersedes its committed versions, and
+//! a chunk re-upserted into a later segment supersedes its earlier ones.
 
 use std::collections::HashSet;
 
+use roaring::RoaringBitmap;
+
 use crate::error::Result;
 use crate::index::SearchResult;
-use crate::metadata::MetadataStore;
+use crate::query::syntax::{KeywordQuery, Phrase};
+use crate::segment::keyword::KeywordIndex;
+use crate::text;
 
-/// Run a BM25 keyword search against the FTS5 index.
-///
-/// Returns `SearchResult` items with scores normalized to [0, 1] range.
-/// FTS5 bm25() returns negative values where more-negative = more relevant,
-/// so we negate and normalize.
-/// Sanitize user input for FTS5 MATCH syntax.
-///
-/// FTS5 has its own query language (`AND`, `OR`, `NOT`, `NEAR`, `*`, `"`, `-`).
-/// Raw user text containing these operators would cause parse errors or alter
-/// search semantics. We quote each whitespace-delimited token so FTS5 treats
-/// them as literal terms.
-fn sanitize_fts5_query(text: &str) -> String {
-    text.split_whitespace()
-        .filter(|t| !t.is_empty())
-        .map(|t| format!("\"{}\"", t.replace('"', "")))
-        .collect::<Vec<_>>()
-        .join(" ")
+/// BM25 term-frequency saturation and length normalization (FTS5 defaults).
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+
+/// One segment's keyword index and chunk IDs (in record order).
+pub struct KeywordSegment<'a> {
+    pub index: &'a KeywordIndex,
+    pub chunk_ids: &'a [String],
+    /// False for the write buffer; its results are marked `committed: false`.
+    pub committed: bool,
+    /// Records passing the query's metadata filter; None when unfiltered.
+    pub allowed: Option<&'a RoaringBitmap>,
+}
+
+/// A query leaf, matched directly against postings.
+enum Leaf<'q> {
+    Phrase(&'q Phrase),
+    Near(&'q [Phrase], u32),
 }
 
-pub fn keyword_search(
-    metadata: &MetadataStore,
-    collection_id: &str,
-    query_text: &str,
+/// Matches of each leaf in one segment: `(record, term frequency)`, by record.
+type SegmentMatches = Vec<Vec<(u32, u32)>>;
+
+/// A scored record: chunk ID, raw BM25 score, and whether it is committed.
+type Scored<'a> = (&'a String, f64, bool);
+
+/// Run a BM25 keyword search for `query` across `segments`.
+///
+/// Returns up to `top_k` results ranked by raw BM25 score, which does not
+/// depend on `top_k`; `normalize_scores` maps a ranked list to [0, 1].
+/// Tombstoned chunks, and committed records superseded by a buffered or
+/// later committed version, neither match nor count towards document
+/// frequency; `is_latest(i, chunk_id, record)` tells whether `record` of the
+/// committed `segments[i]` is the latest committed version of `chunk_id`.
+/// Records outside a segment's `allowed` set still count towards corpus
+/// statistics, so a filter narrows the results without changing scores.
+/// `load_texts(i)` returns the chunk texts of `segments[i]`; it is only called
+/// by the substring fallback, for segments with trigram candidates.
+pub fn keyword_search<G, F>(
+    segments: &[KeywordSegment<'_>],
+    query: &KeywordQuery,
     top_k: usize,
     tombstone_set: &HashSet<String>,
-) -> Result<Vec<SearchResult>> {
-    let safe_query = sanitize_fts5_query(query_text);
-    if safe_query.is_empty() {
+    is_latest: G,
+    load_texts: F,
+) -> Result<Vec<SearchResult>>
+where
+    G: Fn(usize, &str, u32) -> bool,
+    F: Fn(usize) -> Result<Vec<String>>,
+{
+    let mut leaves = Vec::new();
+    collect_leaves(query, true, &mut leaves);
+    if leaves.is_empty() {
         return Ok(Vec::new());
     }
 
-    // Over-fetch to account for tombstoned results we'll filter out.
-    let fetch_k = if tombstone_set.is_empty() {
-        top_k
-    } else {
-        top_k * 2
+    let buffered: HashSet<&str> = segments
+        .iter()
+        .filter(|s| !s.committed)
+        .flat_map(|s| s.chunk_ids.iter().map(String::as_str))
+        .collect();
+    let is_live = |i: usize, record: u32| {
+        let segment = &segments[i];
+        segment.chunk_ids.get(record as usize).is_some_and(|chunk_id| {
+            let superseded = segment.committed
+                && (buffered.contains(chunk_id.as_str()) || !is_latest(i, chunk_id, record));
+            !superseded && !tombstone_set.contains(chunk_id)
+        })
     };
 
-    // Try unicode61 BM25 search first (best for space-delimited languages).
-    let raw_results = metadata.fts_search(collection_id, &safe_query, fetch_k)?;
-
-    // Fall back to trigram search if unicode61 returned nothing.
-    // This handles CJK and other scripts without whitespace word boundaries.
-    let raw_results = if raw_results.is_empty() {
-        metadata
-            .fts_trigram_search(collection_id, query_text, fetch_k)
-            .unwrap_or_default()
-    } else {
-        raw_results
-    };
+    let word_matches: Vec<SegmentMatches> = segments
+        .iter()
+        .enumerate()
+        .map(|(i, segment)| {
+            leaves
+                .iter()
+                .map(|(leaf, _)| {
+                    let mut matches = match leaf {
+                        Leaf::Phrase(phrase) => segment.index.phrase_matches(&phrase.tokens, phrase.prefix),
+                        Leaf::Near(phrases, distance) => near_matches(segment.index, phrases, *distance),
+                    };
+                    matches.retain(|&(record, _)| is_live(i, record));
+                    matches
+                })
+                .collect()
+        })
+        .collect();
+    let mut scored = score_matches(segments, query, &leaves, &word_matches);
 
-    if raw_results.is_empty() {
-        return Ok(Vec::new());
+    // Fall back to substring matching if the word index found nothing.
+    if scored.is_empty() {
+        let mut substring_matches = Vec::with_capacity(segments.len());
+        for (i, segment) in segments.iter().enumerate() {
+            substring_matches.push(substring_matches_in(segment, &leaves, |record| is_live(i, record), || load_texts(i))?);
+        }
+        scored = score_matches(segments, query, &leaves, &substring_matches);
     }
 
-    // Filter tombstones first, then normalize — so the min/max range is computed
-    // only from surviving results and scores are not deflated when high-scoring
-    // entries are tombstoned.
-    let active: Vec<(String, f64)> = raw_results
+    scored.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.0.cmp(b.0))
+    });
+    // One result per chunk, even if some version slipped past `is_latest`.
+    let mut seen = HashSet::new();
+    scored.retain(|(chunk_id, _, _)| seen.insert(*chunk_id));
+    scored.truncate(top_k);
+    Ok(scored
         .into_iter()
-        .filter(|(id, _)| !tombstone_set.contains(id))
-        .map(|(id, score)| (id, -score))
-        .collect();
+        .map(|(chunk_id, score, committed)| SearchResult {
+            chunk_id: chunk_id.clone(),
+            score,
+            committed: Some(committed),
+            explain: None,
+            metadata: None,
+            text: None,
+            vector: None,
+            group_key: None,
+        })
+        .collect())
+}
 
-    if active.is_empty() {
-        return Ok(Vec::new());
+/// Leaves of `query` in evaluation order, each flagged with whether it is
+/// scored. Leaves under an exclusion only filter.
+fn collect_leaves<'q>(query: &'q KeywordQuery, scored: bool, out: &mut Vec<(Leaf<'q>, bool)>) {
+    match query {
+        KeywordQuery::Phrase(phrase) => out.push((Leaf::Phrase(phrase), scored)),
+        KeywordQuery::Near { phrases, distance } => out.push((Leaf::Near(phrases, *distance), scored)),
+        KeywordQuery::And { include, exclude } => {
+            include.iter().for_each(|q| collect_leaves(q, scored, out));
+            exclude.iter().for_each(|q| collect_leaves(q, false, out));
+        }
+        KeywordQuery::Or(alternatives) => alternatives.iter().for_each(|q| collect_leaves(q, scored, out)),
+    }
+}
+
+/// Records matching `query`, given the matches of its leaves in
+/// `collect_leaves` order; `next` is the index of the next leaf.
+fn evaluate(query: &KeywordQuery, leaf_matches: &[Vec<(u32, u32)>], next: &mut usize) -> RoaringBitmap {
+    match query {
+        KeywordQuery::Phrase(_) | KeywordQuery::Near { .. } => {
+            let matches = &leaf_matches[*next];
+            *next += 1;
+            matches.iter().map(|&(record, _)| record).collect()
+        }
+        KeywordQuery::And { include, exclude } => {
+            let mut matched: Option<RoaringBitmap> = None;
+            for q in include {
+                let records = evaluate(q, leaf_matches, next);
+                matched = Some(match matched {
+                    Some(current) => current & records,
+                    None => records,
+                });
+            }
+            let mut matched = matched.unwrap_or_default();
+            for q in exclude {
+                matched -= evaluate(q, leaf_matches, next);
+            }
+            matched
+        }
+        KeywordQuery::Or(alternatives) => alternatives
+            .iter()
+            .fold(RoaringBitmap::new(), |acc, q| acc | evaluate(q, leaf_matches, next)),
     }
+}
 
-    let max_score = active
+/// Records where every phrase occurs close together: for some occurrence of
+/// each, at most `distance` tokens separate the end of the earliest-starting
+/// one from the start of the latest. The frequency is the number of such
+/// groupings, counted by their earliest occurrence.
+fn near_matches(index: &KeywordIndex, phrases: &[Phrase], distance: u32) -> Vec<(u32, u32)> {
+    let positions: Vec<Vec<(u32, Vec<u32>)>> = phrases
         .iter()
-        .map(|(_, s)| *s)
-        .fold(f64::NEG_INFINITY, f64::max);
-    let min_score = active
+        .map(|phrase| index.phrase_positions(&phrase.tokens, phrase.prefix))
+        .collect();
+    let Some((first, rest)) = positions.split_first() else {
+        return Vec::new();
+    };
+
+    first
         .iter()
-        .map(|(_, s)| *s)
-        .fold(f64::INFINITY, f64::min);
+        .filter_map(|(record, first_starts)| {
+            let mut starts = vec![first_starts.as_slice()];
+            for other in rest {
+                let i = other.binary_search_by_key(record, |(r, _)| *r).ok()?;
+                starts.push(other[i].1.as_slice());
+            }
+            let mut count = 0;
+            for (anchor_phrase, anchor_starts) in starts.iter().enumerate() {
+                let end = |start: u32| start + phrases[anchor_phrase].tokens.len() as u32;
+                for &start in *anchor_starts {
+                    // The earliest occurrence of every other phrase at or after the anchor.
+                    let latest = starts.iter().try_fold(start, |latest, other| {
+                        let i = other.partition_point(|&s| s < start);
+                        other.get(i).map(|&s| latest.max(s))
+                    });
+                    if latest.is_some_and(|latest| latest <= end(start).saturating_add(distance)) {
+                        count += 1;
+                    }
+                }
+            }
+            (count > 0).then_some((*record, count))
+        })
+        .collect()
+}
 
-    let range = max_score - min_score;
+/// Matches of each leaf in `segment` as case-insensitive substrings of the
+/// phrase's query text. NEAR groups need token positions and never match
+/// here. Trigram bitmaps prune candidates so texts are only loaded for
+/// segments that can match.
+fn substring_matches_in<L, F>(
+    segment: &KeywordSegment<'_>,
+    leaves: &[(Leaf<'_>, bool)],
+    is_live: L,
+    load_texts: F,
+) -> Result<SegmentMatches>
+where
+    L: Fn(u32) -> bool,
+    F: FnOnce() -> Result<Vec<String>>,
+{
+    let needles: Vec<Option<&str>> = leaves
+        .iter()
+        .map(|(leaf, _)| match leaf {
+            Leaf::Phrase(phrase) => Some(phrase.text.as_str()),
+            Leaf::Near(..) => None,
+        })
+        .collect();
+    let candidates: Vec<RoaringBitmap> = needles
+        .iter()
+        .map(|needle| needle.map_or_else(RoaringBitmap::new, |n| trigram_candidates(segment.index, n)))
+        .collect();
+    if candidates.iter().all(RoaringBitmap::is_empty) {
+        return Ok(vec![Vec::new(); leaves.len()]);
+    }
 
-    let mut results: Vec<SearchResult> = active
-        .into_iter()
-        .map(|(chunk_id, score)| {
-            let normalized = if range > 0.0 {
-                (score - min_score) / range
-            } else {
-                1.0
+    let texts = load_texts()?;
+    Ok(needles
+        .iter()
+        .zip(candidates)
+        .map(|(needle, candidates)| {
+            let Some(needle) = needle else {
+                return Vec::new();
             };
-            SearchResult {
-                chunk_id,
-                score: normalized,
-                committed: Some(true),
-                explain: None,
-            }
+            candidates
+                .iter()
+                .filter(|&record| is_live(record))
+                .filter_map(|record| {
+                    let text = texts.get(record as usize)?.to_lowercase();
+                    let tf = text.matches(needle).count() as u32;
+                    (tf > 0).then_some((record, tf))
+                })
+                .collect()
+        })
+        .collect())
+}
+
+/// Records whose text may contain `needle`, by its trigrams.
+fn trigram_candidates(index: &KeywordIndex, needle: &str) -> RoaringBitmap {
+    let mut candidates: Option<RoaringBitmap> = None;
+    // Like FTS5's trigram tokenizer, needles under three characters never match.
+    for gram in text::trigrams(needle) {
+        let Some(records) = index.trigram_records(&gram) else {
+            return RoaringBitmap::new();
+        };
+        candidates = Some(match candidates {
+            Some(current) => current & records,
+            None => records.clone(),
+        });
+    }
+    candidates.unwrap_or_default()
+}
+
+/// BM25-score the records matching `query`. Statistics are computed over
+/// every segment, as if they were one index; each scored leaf's document
+/// frequency is the number of live records it matches.
+fn score_matches<'a>(
+    segments: &[KeywordSegment<'a>],
+    query: &KeywordQuery,
+    leaves: &[(Leaf<'_>, bool)],
+    matches: &[SegmentMatches],
+) -> Vec<Scored<'a>> {
+    let record_count: usize = segments.iter().map(|s| s.index.record_count()).sum();
+    let total_length: u64 = segments.iter().map(|s| s.index.total_length()).sum();
+    if record_count == 0 {
+        return Vec::new();
+    }
+    let avg_length = (total_length as f64 / record_count as f64).max(1.0);
+
+    let idf: Vec<f64> = (0..leaves.len())
+        .map(|leaf| {
+            let hits: usize = matches.iter().map(|m| m[leaf].len()).sum();
+            let n = record_count as f64;
+            let hits = hits as f64;
+            // FTS5 clamps non-positive IDF (terms in most records) to a tiny weight.
+            let idf = ((n - hits + 0.5) / (hits + 0.5)).ln();
+            if idf <= 0.0 { 1e-6 } else { idf }
         })
         .collect();
 
-    results.truncate(top_k);
-    Ok(results)
+    let mut scored = Vec::new();
+    for (segment, leaf_matches) in segments.iter().zip(matches) {
+        let matched = evaluate(query, leaf_matches, &mut 0);
+        for record in &matched {
+            if segment.allowed.is_some_and(|a| !a.contains(record)) {
+                continue;
+            }
+            let length = segment.index.record_length(record) as f64;
+            let norm = BM25_K1 * (1.0 - BM25_B + BM25_B * length / avg_length);
+            let score: f64 = leaves
+                .iter()
+                .zip(leaf_matches)
+                .zip(&idf)
+                .filter(|(((_, is_scored), _), _)| *is_scored)
+                .filter_map(|((_, m), idf)| {
+                    let i = m.binary_search_by_key(&record, |&(r, _)| r).ok()?;
+                    let tf = m[i].1 as f64;
+                    Some(idf * tf * (BM25_K1 + 1.0) / (tf + norm))
+                })
+                .sum();
+            if let Some(chunk_id) = segment.chunk_ids.get(record as usize) {
+                scored.push((chunk_id, score, segment.committed));
+            }
+        }
+    }
+    scored
+}
+
+/// Min-max normalize ranked scores to [0, 1]; a single score (or all equal) maps to 1.
+pub fn normalize_scores(results: &mut [SearchResult]) {
+    let max_score = results.iter().map(|r| r.score).fold(f64::NEG_INFINITY, f64::max);
+    let min_score = results.iter().map(|r| r.score).fold(f64::INFINITY, f64::min);
+    let range = max_score - min_score;
+    for r in results {
+        r.score = if range > 0.0 { (r.score - min_score) / range } else { 1.0 };
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::query::syntax::{self, KeywordSyntax};
+    use crate::text::Analyzer;
 
-    fn test_store() -> MetadataStore {
-        MetadataStore::open(":memory:").unwrap()
-    }
-
-    fn setup_fts(store: &MetadataStore) {
-        use crate::metadata::Collection;
-        store
-            .create_collection(&Collection {
-                collection_id: "coll-1".to_string(),
-                dimension: 4,
-                metric: "cosine".to_string(),
-                embedding_model_id: "test".to_string(),
-                schema_version: "1".to_string(),
-                created_at: "2026-01-01T00:00:00Z".to_string(),
-                deleted_at: None,
-                quantization: "fp16".to_string(),
-                hnsw_m: 16,
-                hnsw_ef_construction: 200,
-                hnsw_ef_search: 100,
-            })
-            .unwrap();
+    struct Fixture {
+        indexes: Vec<KeywordIndex>,
+        chunk_ids: Vec<Vec<String>>,
+        texts: Vec<Vec<String>>,
+    }
+
+    impl Fixture {
+        /// One segment per inner slice of `(chunk_id, text)` pairs.
+        fn new(segments: &[&[(&str, &str)]]) -> Self {
+            Self {
+                indexes: segments
+                    .iter()
+                    .map(|records| KeywordIndex::build(records.iter().map(|(_, t)| *t), &Analyzer::default()))
+                    .collect(),
+                chunk_ids: segments
+                    .iter()
+                    .map(|records| records.iter().map(|(id, _)| id.to_string()).collect())
+                    .collect(),
+                texts: segments
+                    .iter()
+                    .map(|records| records.iter().map(|(_, t)| t.to_string()).collect())
+                    .collect(),
+            }
+        }
+
+        /// Whether `record` of segment `segment` is the last version of `chunk_id`.
+        fn is_latest(&self, segment: usize, chunk_id: &str, record: u32) -> bool {
+            let last = self
+                .chunk_ids
+                .iter()
+                .enumerate()
+                .flat_map(|(s, ids)| ids.iter().enumerate().filter(|(_, id)| *id == chunk_id).map(move |(r, _)| (s, r)))
+                .last();
+            last == Some((segment, record as usize))
+        }
+
+        fn search(&self, query: &str, top_k: usize, tombstones: &HashSet<String>) -> Vec<SearchResult> {
+            self.search_with(query, KeywordSyntax::Simple, top_k, tombstones)
+        }
+
+        /// Chunk IDs matching an advanced-syntax query, sorted.
+        fn advanced(&self, query: &str) -> Vec<String> {
+            let results = self.search_with(query, KeywordSyntax::Advanced, 10, &HashSet::new());
+            let mut ids: Vec<String> = results.into_iter().map(|r| r.chunk_id).collect();
+            ids.sort();
+            ids
+        }
 
-        store.fts_insert("c-1", "coll-1", "The quick brown fox jumps over the lazy dog").unwrap();
-        store.fts_insert("c-2", "coll-1", "Machine learning and artificial intelligence").unwrap();
-        store.fts_insert("c-3", "coll-1", "The brown fox was very quick today").unwrap();
-        store.fts_insert("c-4", "coll-1", "Deep learning neural networks").unwrap();
+        fn search_with(
+            &self,
+            query: &str,
+            syntax: KeywordSyntax,
+            top_k: usize,
+            tombstones: &HashSet<String>,
+        ) -> Vec<SearchResult> {
+            let query = syntax::parse(query, syntax, &Analyzer::default()).unwrap();
+            let segments: Vec<KeywordSegment<'_>> = self
+                .indexes
+                .iter()
+                .zip(&self.chunk_ids)
+                .map(|(index, chunk_ids)| KeywordSegment { index, chunk_ids, committed: true, allowed: None })
+                .collect();
+            keyword_search(&segments, &query, top_k, tombstones, |i, c, r| self.is_latest(i, c, r), |i| Ok(self.texts[i].clone()))
+                .unwrap()
+        }
     }
 
-    #[test]
-    fn keyword_search_basic() {
-        let store = test_store();
-        setup_fts(&store);
+    fn sample() -> Fixture {
+        Fixture::new(&[
+            &[
+                ("c-1", "The quick brown fox jumps over the lazy dog"),
+                ("c-2", "Machine learning and artificial intelligence"),
+            ],
+            &[
+                ("c-3", "The brown fox was very quick today"),
+                ("c-4", "Deep learning neural networks"),
+            ],
+        ])
+    }
 
-        let results = keyword_search(&store, "coll-1", "quick fox", 10, &HashSet::new()).unwrap();
-        assert!(!results.is_empty());
+    fn ids(results: &[SearchResult]) -> Vec<&str> {
+        results.iter().map(|r| r.chunk_id.as_str()).collect()
+    }
 
-        let ids: Vec<&str> = results.iter().map(|r| r.chunk_id.as_str()).collect();
+    #[test]
+    fn keyword_search_basic() {
+        let results = sample().search("quick fox", 10, &HashSet::new());
+        let ids = ids(&results);
+        assert_eq!(ids.len(), 2);
         assert!(ids.contains(&"c-1"));
         assert!(ids.contains(&"c-3"));
     }
 
     #[test]
-    fn keyword_search_respects_top_k() {
-        let store = test_store();
-        setup_fts(&store);
+    fn keyword_search_requires_every_term() {
+        let results = sample().search("learning neural", 10, &HashSet::new());
+        assert_eq!(ids(&results), vec!["c-4"]);
+    }
 
-        let results = keyword_search(&store, "coll-1", "quick fox", 1, &HashSet::new()).unwrap();
+    #[test]
+    fn keyword_search_matches_multi_word_terms_as_phrases() {
+        let fixture = sample();
+        assert_eq!(ids(&fixture.search("brown-fox", 10, &HashSet::new())).len(), 2);
+        assert!(fixture.search("fox-brown", 10, &HashSet::new()).is_empty());
+    }
+
+    #[test]
+    fn keyword_search_respects_top_k() {
+        let results = sample().search("quick fox", 1, &HashSet::new());
         assert_eq!(results.len(), 1);
     }
 
     #[test]
     fn keyword_search_filters_tombstones() {
-        let store = test_store();
-        setup_fts(&store);
-
         let tombstones: HashSet<String> = ["c-1".to_string()].into_iter().collect();
-        let results = keyword_search(&store, "coll-1", "quick fox", 10, &tombstones).unwrap();
-        let ids: Vec<&str> = results.iter().map(|r| r.chunk_id.as_str()).collect();
-        assert!(!ids.contains(&"c-1"));
-        assert!(ids.contains(&"c-3"));
+        let results = sample().search("quick fox", 10, &tombstones);
+        assert_eq!(ids(&results), vec!["c-3"]);
     }
 
     #[test]
     fn keyword_search_empty_query() {
-        let store = test_store();
-        setup_fts(&store);
-
-        let results = keyword_search(&store, "coll-1", "", 10, &HashSet::new()).unwrap();
-        assert!(results.is_empty());
+        assert!(sample().search("", 10, &HashSet::new()).is_empty());
+        assert!(sample().search(" -- ", 10, &HashSet::new()).is_empty());
     }
 
     #[test]
     fn keyword_search_no_matches() {
-        let store = test_store();
-        setup_fts(&store);
-
-        let results = keyword_search(&store, "coll-1", "xyzzyplugh", 10, &HashSet::new()).unwrap();
-        assert!(results.is_empty());
+        assert!(sample().search("xyzzyplugh", 10, &HashSet::new()).is_empty());
     }
 
     #[test]
     fn keyword_search_scores_normalized() {
-        let store = test_store();
-        setup_fts(&store);
-
-        let results = keyword_search(&store, "coll-1", "quick fox", 10, &HashSet::new()).unwrap();
+        let mut results = sample().search("learning", 10, &HashSet::new());
+        assert_eq!(results.len(), 2);
+        assert!(results[0].score > results[1].score && results[1].score > 0.0);
+        // Raw scores do not depend on how many results are kept.
+        assert_eq!(sample().search("learning", 1, &HashSet::new())[0].score, results[0].score);
+        normalize_scores(&mut results);
         for r in &results {
             assert!(r.score >= 0.0 && r.score <= 1.0, "Score {} out of [0,1] range", r.score);
         }
+        // The shorter record ranks first.
+        assert_eq!(results[0].chunk_id, "c-4");
+        assert_eq!(results[0].score, 1.0);
+    }
+
+    #[test]
+    fn keyword_search_statistics_span_segments() {
+        // The same records split differently across segments score the same.
+        let records = [
+            ("a", "rust search engine"),
+            ("b", "search engine internals"),
+            ("c", "rust compiler"),
+        ];
+        let one = Fixture::new(&[&records]);
+        let two = Fixture::new(&[&records[..1], &records[1..]]);
+        let scores = |f: &Fixture| {
+            f.search("rust search", 10, &HashSet::new())
+                .into_iter()
+                .map(|r| (r.chunk_id, r.score))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(scores(&one), scores(&two));
+    }
+
+    #[test]
+    fn keyword_search_falls_back_to_substrings_for_cjk() {
+        let fixture = Fixture::new(&[&[
+            ("ja-1", "すべての人間は生れながらにして自由であり"),
+            ("zh-1", "人人生而自由在尊严和权利上一律平等"),
+            ("fr-1", "Tous les êtres humains naissent libres"),
+        ]]);
+        assert_eq!(ids(&fixture.search("人間は生れ", 10, &HashSet::new())), vec!["ja-1"]);
+        assert_eq!(ids(&fixture.search("自由在尊严", 10, &HashSet::new())), vec!["zh-1"]);
+        assert!(fixture.search("自由", 10, &HashSet::new()).is_empty());
+    }
+
+    #[test]
+    fn keyword_search_only_returns_allowed_records() {
+        let fixture = sample();
+        let search_with = |allowed: &[Option<&RoaringBitmap>]| {
+            let segments: Vec<KeywordSegment<'_>> = fixture
+                .indexes
+                .iter()
+                .zip(&fixture.chunk_ids)
+                .zip(allowed)
+                .map(|((index, chunk_ids), allowed)| KeywordSegment {
+                    index,
+                    chunk_ids,
+                    committed: true,
+                    allowed: *allowed,
+                })
+                .collect();
+            let query = syntax::parse("learning", KeywordSyntax::Simple, &Analyzer::default()).unwrap();
+            keyword_search(&segments, &query, 10, &HashSet::new(), |i, c, r| fixture.is_latest(i, c, r), |_| Ok(Vec::new()))
+                .unwrap()
+        };
+        assert_eq!(ids(&search_with(&[None, None])), vec!["c-4", "c-2"]);
+
+        // Segment 1 only allows c-3, so c-4 drops out and c-2 remains.
+        let only_first: RoaringBitmap = [0].into_iter().collect();
+        assert_eq!(ids(&search_with(&[None, Some(&only_first)])), vec!["c-2"]);
+
+        let none = RoaringBitmap::new();
+        assert!(search_with(&[Some(&none), Some(&none)]).is_empty());
+    }
+
+    #[test]
+    fn keyword_search_includes_uncommitted_segment() {
+        let fixture = Fixture::new(&[
+            &[
+                ("c-1", "The quick brown fox jumps over the lazy dog"),
+                ("c-2", "Machine learning and artificial intelligence"),
+            ],
+            &[("c-5", "Reinforcement learning agents"), ("c-2", "Compilers and type systems")],
+        ]);
+        let segments: Vec<KeywordSegment<'_>> = fixture
+            .indexes
+            .iter()
+            .zip(&fixture.chunk_ids)
+            .enumerate()
+            .map(|(i, (index, chunk_ids))| KeywordSegment {
+                index,
+                chunk_ids,
+                committed: i == 0,
+                allowed: None,
+            })
+            .collect();
+        let search = |query: &str| {
+            let query = syntax::parse(query, KeywordSyntax::Simple, &Analyzer::default()).unwrap();
+            keyword_search(&segments, &query, 10, &HashSet::new(), |_, _, _| true, |_| Ok(Vec::new()))
+                .unwrap()
+                .into_iter()
+                .map(|r| (r.chunk_id, r.committed))
+                .collect::<Vec<_>>()
+        };
+
+        // The buffered c-2 supersedes the committed one, which no longer matches.
+        assert_eq!(search("learning"), vec![("c-5".to_string(), Some(false))]);
+        assert_eq!(search("compilers"), vec![("c-2".to_string(), Some(false))]);
+        assert_eq!(search("fox"), vec![("c-1".to_string(), Some(true))]);
+    }
+
+    #[test]
+    fn keyword_search_scores_only_latest_committed_version() {
+        let fixture = Fixture::new(&[
+            &[("c-1", "apple banana"), ("c-2", "cherry")],
+            &[("c-1", "apple pie recipe"), ("c-3", "banana bread")],
+        ]);
+        let none = HashSet::new();
+        let apple = fixture.search("apple", 10, &none);
+        assert_eq!(ids(&apple), vec!["c-1"]);
+        assert_eq!(apple[0].score, fixture.search("recipe", 10, &none)[0].score);
+        assert_eq!(ids(&fixture.search("banana", 10, &none)), vec!["c-3"]);
+    }
+
+    #[test]
+    fn advanced_query_combines_phrases_prefixes_and_exclusions() {
+        let fixture = sample();
+        assert_eq!(fixture.advanced("\"brown fox\""), vec!["c-1", "c-3"]);
+        assert_eq!(fixture.advanced("\"fox brown\""), Vec::<String>::new());
+        assert_eq!(fixture.advanced("learn*").len(), 2);
+        assert_eq!(fixture.advanced("neural OR artificial").len(), 2);
+        assert_eq!(fixture.advanced("learning -neural"), vec!["c-2"]);
+        assert_eq!(fixture.advanced("fox NOT (lazy OR today)"), Vec::<String>::new());
+        assert_eq!(fixture.advanced("(deep OR machine) learn* -\"artificial intelligence\""), vec!["c-4"]);
+    }
+
+    #[test]
+    fn advanced_query_near_bounds_token_distance() {
+        let fixture = sample();
+        // c-1: "quick" ... "dog" are 6 tokens apart; c-3: "brown" then "quick" 3 tokens later.
+        assert_eq!(fixture.advanced("NEAR(quick dog, 6)"), vec!["c-1"]);
+        assert!(fixture.advanced("NEAR(quick dog, 5)").is_empty());
+        assert_eq!(fixture.advanced("NEAR(quick brown, 0)"), vec!["c-1"]);
+        assert_eq!(fixture.advanced("NEAR(quick brown, 3)").len(), 2);
+        assert_eq!(fixture.advanced("NEAR(\"lazy dog\" fox)"), vec!["c-1"]);
+        // The largest distance matches any order without overflowing.
+        assert_eq!(fixture.advanced("NEAR(dog quick, 4294967295)"), vec!["c-1"]);
+    }
+
+    #[test]
+    fn advanced_query_falls_back_to_substrings() {
+        let fixture = Fixture::new(&[&[
+            ("ja-1", "人間は生れながらにして自由である"),
+            ("zh-1", "人人生而自由在尊严和权利上一律平等"),
+        ]]);
+        assert_eq!(fixture.advanced("人間は生れ OR 自由在尊严").len(), 2);
+        assert_eq!(fixture.advanced("\"而自由\" -人間は生"), vec!["zh-1"]);
     }
 }
@@ -1,35 +1,59 @@
 //! QueryEngine — manifest-bound search across segments.
 //!
 //! Supports three search modes:
-//!   - **Vector** (default): ANN search via HNSW index.
-//!   - **Keyword**: BM25 full-text search via FTS5.
+//!   - **Vector** (default): ANN search via the collection's HNSW or IVF-PQ index.
+//!   - **Keyword**: BM25 search over per-segment keyword indexes.
 //!   - **Hybrid**: Combines vector + keyword using Reciprocal Rank Fusion (RRF).
 //!
 //! For vector search, the engine:
 //!   1. Resolves the target manifest (latest or a specific version).
 //!   2. Loads every segment from storage.
-//!   3. Builds/deserializes indexes and performs ANN search.
-//!   4. Filters out tombstoned chunk_ids.
+//!   3. Builds/deserializes per-segment indexes (cached by segment ID) and
+//!      performs ANN search.
+//!   4. Skips tombstoned chunk_ids via a per-segment deleted-node bitmap.
 //!   5. Applies optional metadata filters.
 //!   6. Optionally scans the in-memory write buffer.
 //!   7. Merges and deduplicates results.
 //!   8. Returns top-K with deterministic tie-breaking.
 
+pub mod context;
+pub mod cursor;
+pub mod group;
+pub mod highlight;
 pub mod hybrid;
 pub mod keyword;
+pub mod rerank;
+pub mod syntax;
 
 use std::collections::{hash_map::DefaultHasher, HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 use std::sync::{Arc, Mutex};
 
+use rayon::prelude::*;
+use roaring::RoaringBitmap;
+
+use crate::binary::BinaryIndex;
 use crate::distance;
 use crate::error::{AkiDbError, Result};
 use crate::hnsw::HnswGraph;
-use crate::index::{ExplainInfo, SearchResult};
-use crate::metadata::{Collection, Manifest, MetadataStore};
+use crate::index::SearchResult;
+use crate::ivf_pq::IvfPqIndex;
+use crate::metadata::{Collection, Manifest};
+use crate::query::group::{Grouping, GROUP_OVERSAMPLING};
+use crate::segment::keyword::KeywordIndex;
 use crate::segment::reader::SegmentReader;
+use crate::segment::VectorEncoding;
+use crate::sq8::Sq8Vectors;
 use crate::storage::LocalFsBackend;
-use crate::write::{HnswParams, NativeRecord};
+use crate::text::Analyzer;
+use crate::write::{IndexKind, IndexParams, NativeRecord};
+
+pub use context::{AssembledContext, ContextBudget, ContextPassage};
+pub use cursor::SearchCursor;
+pub use group::{GroupBy, SearchGroup};
+pub use hybrid::Fusion;
+pub use rerank::{RerankCandidate, Reranker};
+pub use syntax::{KeywordQuery, KeywordSyntax};
 
 // ─── Types ───────────────────────────────────────────────────────────────────
 
@@ -66,41 +90,356 @@ pub struct SearchOptions {
     pub explain: bool,
     /// Per-query ef_search override. If None, uses the collection's default.
     pub ef_search: Option<usize>,
+    /// Per-query IVF probe count override (IVF-PQ collections only).
+    /// If None, uses the collection's default.
+    pub ivf_num_probes: Option<usize>,
+    /// Re-score approximate IVF-PQ and SQ8 candidates against full-precision
+    /// vectors before taking the top-K. Binary indexes always re-score; SQ8
+    /// segments re-score against the FP16 copies stored beside their codes.
+    pub rescore: bool,
+    /// Candidates fetched per requested result when re-scoring.
+    /// If None, uses `DEFAULT_OVERSAMPLING`.
+    pub oversampling: Option<f64>,
+    /// How `query_text` is parsed for keyword and hybrid search.
+    pub keyword_syntax: KeywordSyntax,
+    /// How hybrid search combines its vector and keyword results.
+    pub fusion: Fusion,
+    /// Return each hit's stored metadata, chunk text and decoded vector.
+    pub include_metadata: bool,
+    pub include_text: bool,
+    pub include_vector: bool,
+    /// Return the best `top_k` groups of results sharing this field instead
+    /// of the best `top_k` results.
+    pub group_by: Option<GroupBy>,
+    /// Results kept per group. If None, 1 (one result per group).
+    pub max_per_group: Option<usize>,
+    /// Continue after a previous page's `SearchResponse::cursor`, reading
+    /// the manifest version it pins.
+    pub search_after: Option<SearchCursor>,
+}
+
+impl Default for SearchOptions {
+    /// A top-10 vector search of the committed manifest, with equal hybrid
+    /// weights and nothing but scores returned.
+    fn default() -> Self {
+        Self {
+            collection_id: String::new(),
+            query_vector: Vec::new(),
+            top_k: 10,
+            filters: None,
+            manifest_version: None,
+            include_uncommitted: false,
+            mode: SearchMode::Vector,
+            query_text: None,
+            vector_weight: 1.0,
+            keyword_weight: 1.0,
+            explain: false,
+            ef_search: None,
+            ivf_num_probes: None,
+            rescore: false,
+            oversampling: None,
+            keyword_syntax: KeywordSyntax::Simple,
+            fusion: Fusion::default(),
+            include_metadata: false,
+            include_text: false,
+            include_vector: false,
+            group_by: None,
+            max_per_group: None,
+            search_after: None,
+        }
+    }
+}
+
+impl SearchOptions {
+    /// The manifest version to read: `manifest_version`, else the one
+    /// `search_after` pins.
+    pub fn pinned_manifest_version(&self) -> Option<i64> {
+        self.manifest_version.or(self.search_after.as_ref().map(|c| c.manifest_version))
+    }
+
+    /// Hash of the options that decide which results rank where, so a
+    /// cursor is only accepted by the query it was issued for. `top_k` and
+    /// the payload flags may change between pages.
+    pub fn query_fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        std::mem::discriminant(&self.mode).hash(&mut hasher);
+        for value in &self.query_vector {
+            value.to_bits().hash(&mut hasher);
+        }
+        self.query_text.hash(&mut hasher);
+        self.filters.as_ref().map(serde_json::Value::to_string).hash(&mut hasher);
+        self.include_uncommitted.hash(&mut hasher);
+        self.vector_weight.to_bits().hash(&mut hasher);
+        self.keyword_weight.to_bits().hash(&mut hasher);
+        std::mem::discriminant(&self.keyword_syntax).hash(&mut hasher);
+        self.fusion.name().hash(&mut hasher);
+        self.fusion.rrf_k().map(f64::to_bits).hash(&mut hasher);
+        self.ef_search.hash(&mut hasher);
+        self.ivf_num_probes.hash(&mut hasher);
+        self.rescore.hash(&mut hasher);
+        self.oversampling.map(f64::to_bits).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Results kept per group when grouping.
+    pub fn max_per_group(&self) -> usize {
+        self.max_per_group.unwrap_or(1)
+    }
+
+    /// Candidates a search leg collects before the final cut: `top_k`, or
+    /// enough for `top_k` groups of `max_per_group` when grouping.
+    pub fn candidate_depth(&self) -> usize {
+        match self.group_by {
+            Some(_) => self.top_k * self.max_per_group() * GROUP_OVERSAMPLING,
+            None => self.top_k,
+        }
+    }
+}
+
+/// One query of a batch search; all other options are shared by the batch.
+#[derive(Debug, Clone, Default)]
+pub struct BatchQuery {
+    pub query_vector: Vec<f32>,
+    pub query_text: Option<String>,
+}
+
+impl BatchQuery {
+    /// Pair up per-query vectors and texts. Either list may be omitted (e.g.
+    /// vectors for keyword search); when both are given they must be the
+    /// same length.
+    pub fn zip(
+        query_vectors: Option<Vec<Vec<f32>>>,
+        query_texts: Option<Vec<String>>,
+    ) -> Result<Vec<BatchQuery>> {
+        match (query_vectors, query_texts) {
+            (Some(vectors), Some(texts)) if vectors.len() != texts.len() => {
+                Err(AkiDbError::InvalidArgument(format!(
+                    "queryVectors and queryTexts must have the same length ({} vs {})",
+                    vectors.len(),
+                    texts.len()
+                )))
+            }
+            (Some(vectors), texts) => {
+                let texts: Vec<Option<String>> = match texts {
+                    Some(texts) => texts.into_iter().map(Some).collect(),
+                    None => vec![None; vectors.len()],
+                };
+                Ok(vectors
+                    .into_iter()
+                    .zip(texts)
+                    .map(|(query_vector, query_text)| BatchQuery { query_vector, query_text })
+                    .collect())
+            }
+            (None, Some(texts)) => Ok(texts
+                .into_iter()
+                .map(|text| BatchQuery { query_vector: Vec::new(), query_text: Some(text) })
+                .collect()),
+            (None, None) => Ok(Vec::new()),
+        }
+    }
 }
 
+/// Default candidates fetched per requested result when re-scoring.
+pub const DEFAULT_OVERSAMPLING: f64 = 4.0;
+
 pub struct SearchResponse {
     pub results: Vec<SearchResult>,
     pub manifest_version_used: i64,
+    /// `results` grouped, with `group_by`.
+    pub groups: Option<Vec<SearchGroup>>,
+    /// Encoded `SearchCursor` for the next page; None when this page holds
+    /// fewer than `top_k` results, or with grouping or re-ranking.
+    pub cursor: Option<String>,
+}
+
+/// A record fetched by chunk ID.
+#[derive(Debug, Clone)]
+pub struct StoredRecord {
+    pub chunk_id: String,
+    /// Empty for records flushed before doc IDs were stored in segments.
+    pub doc_id: String,
+    /// Position in the document, if one was written.
+    pub ordinal: Option<u32>,
+    pub vector: Vec<f32>,
+    pub metadata: serde_json::Value,
+    pub chunk_text: Option<String>,
+    /// False for a record still in the write buffer.
+    pub committed: bool,
 }
 
-pub struct VectorSearchSnapshot {
+/// The manifest, segments and tombstones a search (or search batch) reads,
+/// captured once so every search mode sees the same published state.
+pub struct ManifestSnapshot {
     pub collection: Collection,
     pub manifest: Manifest,
     pub segment_paths: Vec<(String, String)>,
     pub live_tombstone_set: HashSet<String>,
     pub live_tombstone_fingerprint: u64,
+    /// Write-buffer HNSW graph taken with the buffered records, if maintained.
+    pub buffer_index: Option<Arc<HnswGraph>>,
+    /// Write-buffer keyword index taken with the buffered records, if maintained.
+    pub buffer_keyword_index: Option<Arc<KeywordIndex>>,
 }
 
 // ─── LRU Index Cache ─────────────────────────────────────────────────────────
 
+/// Per-segment ANN index, selected by the collection's `index_type`.
+enum SegmentIndex {
+    Hnsw(HnswGraph),
+    IvfPq(IvfPqIndex),
+    Binary(BinaryIndex),
+}
+
+impl SegmentIndex {
+    fn search(&self, query: &[f32], top_k: usize, num_probes: usize) -> Vec<(u32, f32)> {
+        match self {
+            Self::Hnsw(graph) => graph.search(query, top_k),
+            Self::IvfPq(index) => index.search(query, top_k, num_probes),
+            Self::Binary(index) => index.search(query, top_k),
+        }
+    }
+
+    fn search_filtered<F>(&self, query: &[f32], top_k: usize, num_probes: usize, filter: F) -> Vec<(u32, f32)>
+    where
+        F: Fn(u32) -> bool,
+    {
+        match self {
+            Self::Hnsw(graph) => graph.search_filtered(query, top_k, filter),
+            Self::IvfPq(index) => index.search_filtered(query, top_k, num_probes, filter),
+            Self::Binary(index) => index.search_filtered(query, top_k, filter),
+        }
+    }
+
+    /// Whether candidates must be re-scored against full-precision vectors.
+    ///
+    /// Approximate IVF-PQ and SQ8 scores are re-scored on request; graphs
+    /// over FP16/FP32 vectors already score exactly. Hamming scores are not
+    /// comparable across segments, so binary indexes always re-score.
+    fn needs_rescore(&self, requested: bool) -> bool {
+        match self {
+            Self::Hnsw(graph) => requested && graph.is_quantized(),
+            Self::IvfPq(_) => requested,
+            Self::Binary(_) => true,
+        }
+    }
+
+    fn memory_size_bytes(&self, node_count: usize, dimension: usize, hnsw_m: usize) -> usize {
+        match self {
+            // SQ8 graphs hold one byte per element plus per-dimension bounds.
+            Self::Hnsw(graph) if graph.is_quantized() => {
+                node_count * dimension + dimension * 8 + node_count * hnsw_m * 4
+            }
+            Self::Hnsw(_) => node_count * dimension * 4 + node_count * hnsw_m * 4,
+            Self::IvfPq(index) => index.memory_size_bytes(),
+            Self::Binary(index) => index.memory_size_bytes(),
+        }
+    }
+}
+
+/// A segment's ANN index over all of its records (node id = segment position).
+///
+/// Segments are immutable, so the entry stays valid across publishes and
+/// deletes; tombstones are applied at search time via `deleted_nodes`.
 struct CachedIndex {
-    graph: HnswGraph,
-    /// Chunk IDs of the active (non-tombstoned) nodes in graph-node order.
-    active_chunk_ids: Vec<String>,
-    /// Maps graph node index → original segment position (for metadata filter closure).
-    node_to_segment: Vec<usize>,
+    index: SegmentIndex,
+    /// Chunk IDs in segment (= graph-node) order.
+    chunk_ids: Vec<String>,
+    /// Deleted-node bitmap memoized for the last tombstone-set fingerprint.
+    deleted: Mutex<Option<(u64, Arc<RoaringBitmap>)>>,
     memory_size_bytes: usize,
 }
 
-struct IndexCache {
-    entries: HashMap<String, Arc<CachedIndex>>,
+impl CachedIndex {
+    fn new(index: SegmentIndex, chunk_ids: Vec<String>, memory_size_bytes: usize) -> Self {
+        Self {
+            index,
+            chunk_ids,
+            deleted: Mutex::new(None),
+            memory_size_bytes,
+        }
+    }
+
+    /// Nodes whose chunk IDs are in `tombstone_set`. Recomputed only when the
+    /// tombstone fingerprint changes — a scan of this segment's chunk IDs, not
+    /// an index rebuild.
+    fn deleted_nodes(&self, tombstone_set: &HashSet<String>, fingerprint: u64) -> Arc<RoaringBitmap> {
+        let mut memo = self.deleted.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((memo_fingerprint, bitmap)) = memo.as_ref()
+            && *memo_fingerprint == fingerprint
+        {
+            return Arc::clone(bitmap);
+        }
+        let bitmap: Arc<RoaringBitmap> = Arc::new(
+            self.chunk_ids
+                .iter()
+                .enumerate()
+                .filter(|(_, chunk_id)| tombstone_set.contains(*chunk_id))
+                .map(|(i, _)| i as u32)
+                .collect(),
+        );
+        *memo = Some((fingerprint, Arc::clone(&bitmap)));
+        bitmap
+    }
+}
+
+/// A segment's BM25 keyword index. Like `CachedIndex`, it covers every record
+/// and stays valid across publishes; tombstones are applied per search.
+struct CachedKeywordIndex {
+    index: KeywordIndex,
+    /// Chunk IDs in segment (= keyword record) order.
+    chunk_ids: Vec<String>,
+}
+
+/// Where each chunk of a segment list lives: its latest `(segment, record)`
+/// position, with the segment indexing the list, and the chunks whose latest
+/// record belongs to each document. Tombstones are not applied, so one
+/// locator serves every tombstone state.
+struct ChunkLocator {
+    locations: HashMap<String, (usize, usize)>,
+    documents: HashMap<String, Vec<String>>,
+}
+
+/// Per-segment structures held in an `IndexCache`.
+trait CacheEntry {
+    fn memory_size_bytes(&self) -> usize;
+}
+
+impl CacheEntry for CachedIndex {
+    fn memory_size_bytes(&self) -> usize {
+        self.memory_size_bytes
+    }
+}
+
+impl CacheEntry for ChunkLocator {
+    fn memory_size_bytes(&self) -> usize {
+        let locations: usize =
+            self.locations.keys().map(|k| k.len() + std::mem::size_of::<(String, (usize, usize))>()).sum();
+        let documents: usize = self
+            .documents
+            .iter()
+            .map(|(doc_id, chunk_ids)| {
+                doc_id.len() + chunk_ids.iter().map(|c| c.len() + std::mem::size_of::<String>()).sum::<usize>()
+            })
+            .sum();
+        locations + documents
+    }
+}
+
+impl CacheEntry for CachedKeywordIndex {
+    fn memory_size_bytes(&self) -> usize {
+        self.index.memory_size_bytes() + self.chunk_ids.iter().map(String::len).sum::<usize>()
+    }
+}
+
+struct IndexCache<T> {
+    entries: HashMap<String, Arc<T>>,
     max_memory_bytes: usize,
     current_memory_bytes: usize,
     // LRU order tracking (Vec used as simplified LRU).
     order: Vec<String>,
 }
 
-impl IndexCache {
+impl<T: CacheEntry> IndexCache<T> {
     fn new(max_memory_bytes: usize) -> Self {
         Self {
             entries: HashMap::new(),
@@ -110,11 +449,7 @@ impl IndexCache {
         }
     }
 
-    fn cache_key(segment_id: &str, manifest_version: i64, tombstone_fingerprint: u64) -> String {
-        format!("{segment_id}:v{manifest_version}:t{tombstone_fingerprint:016x}")
-    }
-
-    fn get(&mut self, key: &str) -> Option<Arc<CachedIndex>> {
+    fn get(&mut self, key: &str) -> Option<Arc<T>> {
         if self.entries.contains_key(key) {
             // Move to end of LRU order.
             self.order.retain(|k| k != key);
@@ -125,31 +460,83 @@ impl IndexCache {
         }
     }
 
-    fn set(&mut self, key: String, entry: CachedIndex) {
+    fn set(&mut self, key: String, entry: T) -> Arc<T> {
         let entry = Arc::new(entry);
+        let entry_bytes = entry.memory_size_bytes();
         // Remove existing entry if present.
         if let Some(old) = self.entries.remove(&key) {
-            self.current_memory_bytes = self.current_memory_bytes.saturating_sub(old.memory_size_bytes);
+            self.current_memory_bytes = self.current_memory_bytes.saturating_sub(old.memory_size_bytes());
             self.order.retain(|k| k != &key);
         }
 
         // Evict LRU entries until we have room.
-        while self.current_memory_bytes + entry.memory_size_bytes > self.max_memory_bytes
+        while self.current_memory_bytes + entry_bytes > self.max_memory_bytes
             && !self.order.is_empty()
         {
             let oldest = self.order.remove(0);
             if let Some(removed) = self.entries.remove(&oldest) {
-                self.current_memory_bytes = self.current_memory_bytes.saturating_sub(removed.memory_size_bytes);
+                self.current_memory_bytes = self.current_memory_bytes.saturating_sub(removed.memory_size_bytes());
             }
         }
 
-        self.current_memory_bytes += entry.memory_size_bytes;
+        self.current_memory_bytes += entry_bytes;
         self.order.push(key.clone());
-        self.entries.insert(key, entry);
+        self.entries.insert(key, Arc::clone(&entry));
+        entry
     }
 
 }
 
+// ─── BufferKeywordIndex ──────────────────────────────────────────────────────
+
+/// Keyword index over uncommitted write-buffer records (record = buffer
+/// position). The write buffer maintains it as records are added; it is only
+/// built here for records recovered from the WAL before the next upsert.
+struct BufferKeywordIndex {
+    index: Arc<KeywordIndex>,
+    chunk_ids: Vec<String>,
+    /// Latest buffered version of each chunk that passes the filter; None
+    /// when every record qualifies.
+    allowed: Option<RoaringBitmap>,
+}
+
+impl BufferKeywordIndex {
+    /// None if nothing is buffered.
+    fn new(
+        records: &[NativeRecord],
+        maintained: Option<&Arc<KeywordIndex>>,
+        filters: Option<&serde_json::Value>,
+        analyzer: &Analyzer,
+    ) -> Option<Self> {
+        if records.is_empty() {
+            return None;
+        }
+        let index = match maintained {
+            Some(index) if index.record_count() == records.len() => Arc::clone(index),
+            _ => Arc::new(KeywordIndex::build(records.iter().map(|r| r.chunk_text.as_deref().unwrap_or("")), analyzer)),
+        };
+        let last_position: HashMap<&str, usize> =
+            records.iter().enumerate().map(|(i, r)| (r.chunk_id.as_str(), i)).collect();
+        let superseded = last_position.len() < records.len();
+        let allowed = (superseded || filters.is_some()).then(|| {
+            records
+                .iter()
+                .enumerate()
+                .filter(|(i, r)| {
+                    last_position[r.chunk_id.as_str()] == *i
+                        && filters.is_none_or(|f| matches_filter(&r.metadata, f))
+                })
+                .map(|(i, _)| i as u32)
+                .collect()
+        });
+        Some(Self {
+            index,
+            chunk_ids: records.iter().map(|r| r.chunk_id.clone()).collect(),
+            allowed,
+        })
+    }
+}
+
 // ─── SegmentSearchParams ─────────────────────────────────────────────────────
 
 struct SegmentSearchParams<'a> {
@@ -158,29 +545,64 @@ struct SegmentSearchParams<'a> {
     metric: &'a str,
     tombstone_set: &'a HashSet<String>,
     filters: Option<&'a serde_json::Value>,
-    hnsw: HnswParams,
-    manifest_version: i64,
+    index: IndexParams,
+    rescore: bool,
+    oversampling: f64,
     tombstone_fingerprint: u64,
 }
 
 // ─── QueryEngine ─────────────────────────────────────────────────────────────
 
+/// Memory budget for cached keyword indexes, separate from the ANN index cache.
+const KEYWORD_CACHE_MAX_MEMORY_BYTES: usize = 256 * 1024 * 1024;
+
+/// Memory budget for chunk locators used by point lookups and document deletes.
+const LOCATOR_CACHE_MAX_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+
 pub struct QueryEngine {
     // Mutex gives interior mutability so `search` can take `&self`, allowing the
     // caller to hold a read lock on EngineInner while concurrent searches run.
-    index_cache: Mutex<IndexCache>,
+    index_cache: Mutex<IndexCache<CachedIndex>>,
+    keyword_cache: Mutex<IndexCache<CachedKeywordIndex>>,
+    /// Chunk locators keyed by their segment list.
+    locator_cache: Mutex<IndexCache<ChunkLocator>>,
+    /// Workers for per-segment vector search. None searches segments serially
+    /// on the calling thread.
+    search_pool: Option<rayon::ThreadPool>,
 }
 
 impl QueryEngine {
-    pub fn new(cache_max_memory_bytes: Option<usize>) -> Self {
+    /// `search_threads` sizes the per-segment search pool; None uses the
+    /// available parallelism and 1 disables the pool.
+    pub fn new(cache_max_memory_bytes: Option<usize>, search_threads: Option<usize>) -> Self {
+        let search_threads = search_threads
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+        // A pool that fails to start (e.g. thread limits) degrades to serial search.
+        let search_pool = (search_threads > 1)
+            .then(|| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(search_threads)
+                    .thread_name(|i| format!("akidb-search-{i}"))
+                    .build()
+                    .ok()
+            })
+            .flatten();
         Self {
             index_cache: Mutex::new(IndexCache::new(
                 cache_max_memory_bytes.unwrap_or(512 * 1024 * 1024),
             )),
+            keyword_cache: Mutex::new(IndexCache::new(KEYWORD_CACHE_MAX_MEMORY_BYTES)),
+            locator_cache: Mutex::new(IndexCache::new(LOCATOR_CACHE_MAX_MEMORY_BYTES)),
+            search_pool,
         }
     }
 
-    /// Proactively load HNSW graphs for the given segment storage paths into the LRU cache.
+    /// Number of workers used for per-segment search (1 when serial).
+    pub fn search_threads(&self) -> usize {
+        self.search_pool.as_ref().map_or(1, |pool| pool.current_num_threads())
+    }
+
+    /// Proactively load segment indexes for the given storage paths into the LRU cache.
     ///
     /// Called after `publish()` so the first search query avoids the cold-start
     /// deserialization cost.  Errors are silently ignored — prewarm is best-effort.
@@ -188,126 +610,19 @@ impl QueryEngine {
         &self,
         storage: &LocalFsBackend,
         segment_paths: &[(String, String)],
-        manifest_version: i64,
-        tombstone_set: &HashSet<String>,
-        tombstone_fingerprint: u64,
-        hnsw: HnswParams,
+        index: IndexParams,
         metric: &str,
     ) {
         for (segment_id, storage_path) in segment_paths {
-            let Ok(buffer) = storage.get_object(storage_path) else { continue };
-            let Ok(reader) = SegmentReader::from_buffer(buffer) else { continue };
-            let Ok(chunk_ids) = reader.get_chunk_ids() else { continue };
-
-            let all_active_indices: Vec<usize> = (0..chunk_ids.len())
-                .filter(|i| !tombstone_set.contains(&chunk_ids[*i]))
-                .collect();
-            if all_active_indices.is_empty() {
-                continue;
-            }
-
-            let active_chunk_ids: Vec<String> =
-                all_active_indices.iter().map(|&i| chunk_ids[i].clone()).collect();
-            let cache_key =
-                IndexCache::cache_key(segment_id, manifest_version, tombstone_fingerprint);
-
             // Skip if already warm (brief lock, no I/O held).
             {
                 let mut cache = self.index_cache.lock().unwrap_or_else(|e| e.into_inner());
-                if cache.get(&cache_key).is_some() {
+                if cache.get(segment_id).is_some() {
                     continue;
                 }
             }
-
-            // Load vectors only after confirming a cache miss.
-            let Ok(vectors) = reader.get_vectors() else { continue };
-            let active_vectors: Vec<Vec<f32>> =
-                all_active_indices.iter().map(|&i| vectors[i].clone()).collect();
-            let node_to_segment: Vec<usize> = all_active_indices.clone();
-            let dimension = reader.dimension() as usize;
-            let mut graph = HnswGraph::new(metric, dimension, hnsw.m, hnsw.ef_construction, hnsw.ef_search);
-
-            let index_data = reader.get_index_data();
-            if !index_data.is_empty() && all_active_indices.len() == chunk_ids.len() {
-                if graph.deserialize(index_data).is_err() {
-                    graph.build(&active_vectors);
-                }
-            } else {
-                graph.build(&active_vectors);
-            }
-
-            let vector_bytes = active_vectors.len() * dimension * 4;
-            let graph_bytes = active_vectors.len() * hnsw.m * 4;
-            let mut cache = self.index_cache.lock().unwrap_or_else(|e| e.into_inner());
-            cache.set(
-                cache_key,
-                CachedIndex {
-                    graph,
-                    active_chunk_ids,
-                    node_to_segment,
-                    memory_size_bytes: vector_bytes + graph_bytes,
-                },
-            );
-        }
-    }
-
-    /// Search across all segments in a manifest.
-    ///
-    /// Routes to vector, keyword, or hybrid search based on `opts.mode`.
-    pub fn search(
-        &self,
-        metadata: &MetadataStore,
-        storage: &LocalFsBackend,
-        opts: &SearchOptions,
-        buffer_records: &[NativeRecord],
-    ) -> Result<SearchResponse> {
-        self.validate_search_opts(opts)?;
-
-        let manifest = self.resolve_manifest(metadata, &opts.collection_id, opts.manifest_version)?;
-
-        let mut response = match opts.mode {
-            SearchMode::Vector => {
-                self.search_vector(metadata, storage, opts, buffer_records, &manifest)
-            }
-            SearchMode::Keyword => {
-                self.search_keyword(metadata, opts, &manifest)
-            }
-            SearchMode::Hybrid => {
-                self.search_hybrid(metadata, storage, opts, buffer_records, &manifest)
-            }
-        }?;
-
-        // Populate explain info for vector/keyword-only modes (hybrid populates its own).
-        if opts.explain && opts.mode != SearchMode::Hybrid {
-            let query_text = opts.query_text.as_deref();
-            let preview_map = metadata
-                .fts_get_texts(
-                    &response
-                        .results
-                        .iter()
-                        .map(|r| r.chunk_id.clone())
-                        .collect::<Vec<_>>(),
-                )
-                .unwrap_or_default();
-            for (rank, r) in response.results.iter_mut().enumerate() {
-                let preview = preview_map
-                    .get(&r.chunk_id)
-                    .map(|t| t.chars().take(200).collect());
-                r.explain = Some(ExplainInfo {
-                    vector_score: if opts.mode == SearchMode::Vector { Some(r.score) } else { None },
-                    bm25_score: if opts.mode == SearchMode::Keyword { Some(r.score) } else { None },
-                    rrf_score: None,
-                    vector_rank: if opts.mode == SearchMode::Vector { Some(rank + 1) } else { None },
-                    bm25_rank: if opts.mode == SearchMode::Keyword { Some(rank + 1) } else { None },
-                    chunk_preview: preview,
-                    matched_terms: query_text
-                        .map(|qt| qt.split_whitespace().map(|s| s.to_string()).collect())
-                        .unwrap_or_default(),
-                });
-            }
+            let _ = self.load_cached_index(storage, segment_id, storage_path, index, metric);
         }
-
-        Ok(response)
     }
 
     pub fn search_vector_with_snapshot(
@@ -315,327 +630,430 @@ impl QueryEngine {
         storage: &LocalFsBackend,
         opts: &SearchOptions,
         buffer_records: &[NativeRecord],
-        snapshot: &VectorSearchSnapshot,
+        snapshot: &ManifestSnapshot,
     ) -> Result<SearchResponse> {
         let tombstone_set = &snapshot.live_tombstone_set;
         let tombstone_fingerprint = snapshot.live_tombstone_fingerprint;
         let collection = &snapshot.collection;
         let manifest = &snapshot.manifest;
 
-        let ef_search = opts.ef_search.unwrap_or(collection.hnsw_ef_search as usize);
-        let mut all_results: Vec<SearchResult> = Vec::new();
+        let params = SegmentSearchParams {
+            query_vector: &opts.query_vector,
+            top_k: opts.candidate_depth(),
+            metric: &collection.metric,
+            tombstone_set,
+            filters: opts.filters.as_ref(),
+            index: index_params(collection, opts),
+            rescore: opts.rescore,
+            oversampling: opts.oversampling.unwrap_or(DEFAULT_OVERSAMPLING),
+            tombstone_fingerprint,
+        };
+        let per_segment = self.map_segments(&snapshot.segment_paths, |segment_id, storage_path| {
+            self.search_segment_by_path(storage, segment_id, storage_path, &params)
+        });
 
-        for (segment_id, storage_path) in &snapshot.segment_paths {
-            let seg_results = self.search_segment_by_path(
-                storage,
-                segment_id,
-                storage_path,
-                &SegmentSearchParams {
-                    query_vector: &opts.query_vector,
-                    top_k: opts.top_k,
-                    metric: &collection.metric,
-                    tombstone_set,
-                    filters: opts.filters.as_ref(),
-                    hnsw: HnswParams {
-                        m: collection.hnsw_m as usize,
-                        ef_construction: collection.hnsw_ef_construction as usize,
-                        ef_search,
-                    },
-                    manifest_version: manifest.version,
-                    tombstone_fingerprint,
-                },
-            )?;
-            for mut r in seg_results {
+        // Per-segment results come back in manifest order, so the first error
+        // and the merged ranking do not depend on worker scheduling.
+        let mut all_results: Vec<SearchResult> = Vec::new();
+        for seg_results in per_segment {
+            for mut r in seg_results? {
                 r.committed = Some(true);
                 all_results.push(r);
             }
         }
 
         if opts.include_uncommitted && !buffer_records.is_empty() {
-            let buffer_results = self.search_buffer_brute_force(
-                buffer_records,
-                &opts.query_vector,
-                opts.top_k,
-                &collection.metric,
-                opts.filters.as_ref(),
-                tombstone_set,
-            );
+            let buffer_results = match &snapshot.buffer_index {
+                Some(graph) if graph.node_count() == buffer_records.len() => self
+                    .search_buffer_index(
+                        graph,
+                        buffer_records,
+                        &opts.query_vector,
+                        opts.candidate_depth(),
+                        opts.filters.as_ref(),
+                        tombstone_set,
+                    ),
+                _ => self.search_buffer_brute_force(
+                    buffer_records,
+                    &opts.query_vector,
+                    opts.candidate_depth(),
+                    &collection.metric,
+                    opts.filters.as_ref(),
+                    tombstone_set,
+                ),
+            };
             for mut r in buffer_results {
                 r.committed = Some(false);
                 all_results.push(r);
             }
         }
 
+        let uncommitted: &[NativeRecord] = if opts.include_uncommitted { buffer_records } else { &[] };
+        let grouping = self.grouping(storage, opts, snapshot, uncommitted, &all_results)?;
         Ok(SearchResponse {
-            results: Self::merge_and_dedup(all_results, opts.top_k),
+            results: Self::merge_and_dedup(all_results, opts.top_k, grouping.as_ref()),
             manifest_version_used: manifest.version,
+            groups: None,
+            cursor: None,
         })
     }
 
-    /// Vector-only search (original path).
-    fn search_vector(
+    /// The grouping `opts` asks for over `results`, None without `group_by`.
+    /// Each chunk's key comes from its latest record: the last of it in
+    /// `uncommitted`, else the snapshot's segments.
+    pub fn grouping<'a>(
         &self,
-        metadata: &MetadataStore,
         storage: &LocalFsBackend,
         opts: &SearchOptions,
-        buffer_records: &[NativeRecord],
-        manifest: &Manifest,
-    ) -> Result<SearchResponse> {
-        let collection = metadata
-            .get_collection(&opts.collection_id)?
-            .ok_or_else(|| AkiDbError::CollectionNotFound(opts.collection_id.clone()))?;
-
-        let tombstone_set: HashSet<String> = manifest.tombstone_ids.iter().cloned().collect();
-
-        // Resolve ef_search: per-query override > collection default.
-        let ef_search = opts.ef_search.unwrap_or(collection.hnsw_ef_search as usize);
-
-        let mut all_results: Vec<SearchResult> = Vec::new();
-
-        // Search each segment.
-        for segment_id in &manifest.segment_ids {
-            let seg_results = self.search_segment(
-                metadata,
-                storage,
-                segment_id,
-                &SegmentSearchParams {
-                    query_vector: &opts.query_vector,
-                    top_k: opts.top_k,
-                    metric: &collection.metric,
-                    tombstone_set: &tombstone_set,
-                    filters: opts.filters.as_ref(),
-                    hnsw: HnswParams {
-                        m: collection.hnsw_m as usize,
-                        ef_construction: collection.hnsw_ef_construction as usize,
-                        ef_search,
-                    },
-                    manifest_version: manifest.version,
-                    tombstone_fingerprint: tombstone_fingerprint(&tombstone_set),
-                },
-            )?;
-            for mut r in seg_results {
-                r.committed = Some(true);
-                all_results.push(r);
+        snapshot: &ManifestSnapshot,
+        uncommitted: &[NativeRecord],
+        results: impl IntoIterator<Item = &'a SearchResult>,
+    ) -> Result<Option<Grouping>> {
+        let Some(group_by) = &opts.group_by else {
+            return Ok(None);
+        };
+        let chunk_ids: Vec<String> = results
+            .into_iter()
+            .map(|r| r.chunk_id.as_str())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let key = |doc_id: &str, metadata: Option<&serde_json::Value>| match group_by {
+            GroupBy::DocId => serde_json::Value::String(doc_id.to_string()),
+            GroupBy::Metadata(field) => {
+                metadata.and_then(|m| m.get(field)).cloned().unwrap_or(serde_json::Value::Null)
+            }
+        };
+        let mut keys = self.chunk_fields(storage, snapshot, &chunk_ids, |reader, positions| {
+            let doc_ids = reader.get_doc_ids()?;
+            let metadata = match group_by {
+                GroupBy::DocId => Vec::new(),
+                GroupBy::Metadata(_) => reader.get_metadata()?,
+            };
+            Ok(positions.iter().map(|&i| Some(key(&doc_ids[i], metadata.get(i)))).collect())
+        })?;
+        let wanted: HashSet<&str> = chunk_ids.iter().map(String::as_str).collect();
+        for rec in uncommitted {
+            if wanted.contains(rec.chunk_id.as_str()) {
+                keys.insert(rec.chunk_id.clone(), key(&rec.doc_id, Some(&rec.metadata)));
             }
         }
+        Ok(Some(Grouping { keys, max_groups: opts.top_k, max_per_group: opts.max_per_group() }))
+    }
 
-        // Streaming search: scan buffer.
-        if opts.include_uncommitted && !buffer_records.is_empty() {
-            // Build a comprehensive tombstone set that covers both published tombstones
-            // (from the manifest) and live unpublished tombstones (from deleteChunks calls
-            // that haven't been included in a manifest yet).
-            let live_tombstone_set: HashSet<String> = metadata
-                .list_tombstone_chunk_ids(&opts.collection_id)
-                .map(|ids| ids.into_iter().collect())
-                .unwrap_or_else(|_| tombstone_set.clone());
-
-            let buffer_results = self.search_buffer_brute_force(
-                buffer_records,
-                &opts.query_vector,
-                opts.top_k,
-                &collection.metric,
-                opts.filters.as_ref(),
-                &live_tombstone_set,
-            );
-            for mut r in buffer_results {
-                r.committed = Some(false);
-                all_results.push(r);
-            }
+    /// BM25 keyword search over the segments of `snapshot`, so keyword
+    /// results are bound to the same manifest as vector results. Metadata
+    /// filters are evaluated per segment up front and restrict the scored
+    /// records, so `top_k` is filled from matching records only. Scores are
+    /// raw BM25; see `keyword::normalize_scores`.
+    /// `buffer_records` (empty unless uncommitted records are requested) are
+    /// scored alongside the segments through the snapshot's buffer keyword index.
+    pub fn search_keyword_with_snapshot(
+        &self,
+        storage: &LocalFsBackend,
+        query: &KeywordQuery,
+        top_k: usize,
+        filters: Option<&serde_json::Value>,
+        buffer_records: &[NativeRecord],
+        snapshot: &ManifestSnapshot,
+    ) -> Result<Vec<SearchResult>> {
+        let loaded = self
+            .map_segments(&snapshot.segment_paths, |segment_id, storage_path| {
+                let cached = self.cached_keyword_index(storage, segment_id, storage_path)?;
+                let allowed = match filters {
+                    Some(f) if cached.index.record_count() > 0 => {
+                        let reader = SegmentReader::from_mmap(storage.map_object(storage_path)?)?;
+                        let metadata_list = reader.get_metadata()?;
+                        let all_indices: Vec<usize> = (0..cached.chunk_ids.len()).collect();
+                        let matching = self.apply_metadata_filter(&reader, &all_indices, &metadata_list, f);
+                        Some(matching.into_iter().map(|i| i as u32).collect::<RoaringBitmap>())
+                    }
+                    _ => None,
+                };
+                Ok((cached, allowed))
+            })
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+        let mut segments: Vec<keyword::KeywordSegment<'_>> = loaded
+            .iter()
+            .map(|(cached, allowed)| keyword::KeywordSegment {
+                index: &cached.index,
+                chunk_ids: &cached.chunk_ids,
+                committed: true,
+                allowed: allowed.as_ref(),
+            })
+            .collect();
+
+        let buffer = BufferKeywordIndex::new(
+            buffer_records,
+            snapshot.buffer_keyword_index.as_ref(),
+            filters,
+            &Analyzer::from_collection(&snapshot.collection),
+        );
+        if let Some(buffer) = &buffer {
+            segments.push(keyword::KeywordSegment {
+                index: &buffer.index,
+                chunk_ids: &buffer.chunk_ids,
+                committed: false,
+                allowed: buffer.allowed.as_ref(),
+            });
         }
 
-        let deduped = Self::merge_and_dedup(all_results, opts.top_k);
+        let locator = self.chunk_locator(storage, &snapshot.segment_paths)?;
+        keyword::keyword_search(
+            &segments,
+            query,
+            top_k,
+            &snapshot.live_tombstone_set,
+            |i, chunk_id, record| locator.locations.get(chunk_id) == Some(&(i, record as usize)),
+            |i| match snapshot.segment_paths.get(i) {
+                Some((_, storage_path)) => {
+                    let reader = SegmentReader::from_mmap(storage.map_object(storage_path)?)?;
+                    Ok(reader.get_chunk_texts().unwrap_or_default())
+                }
+                None => Ok(buffer_records.iter().map(|r| r.chunk_text.clone().unwrap_or_default()).collect()),
+            },
+        )
+    }
 
-        Ok(SearchResponse {
-            results: deduped,
-            manifest_version_used: manifest.version,
+    /// Chunk texts for `chunk_ids`, read from the snapshot's segments. Only
+    /// segments holding one of the chunks are mapped; a chunk re-upserted
+    /// into a later segment resolves to its latest text.
+    pub fn chunk_texts(
+        &self,
+        storage: &LocalFsBackend,
+        snapshot: &ManifestSnapshot,
+        chunk_ids: &[String],
+    ) -> Result<HashMap<String, String>> {
+        self.chunk_fields(storage, snapshot, chunk_ids, |reader, positions| {
+            let texts = reader.get_chunk_texts().unwrap_or_default();
+            Ok(positions.iter().map(|&i| texts.get(i).filter(|t| !t.is_empty()).cloned()).collect())
         })
     }
 
-    /// Keyword-only search via FTS5 BM25.
-    fn search_keyword(
+    /// Record metadata for `chunk_ids`, read from the snapshot's segments
+    /// like `chunk_texts`.
+    pub fn chunk_metadata(
         &self,
-        metadata: &MetadataStore,
-        opts: &SearchOptions,
-        manifest: &Manifest,
-    ) -> Result<SearchResponse> {
-        let query_text = opts.query_text.as_deref().unwrap_or("");
-        let tombstone_set: HashSet<String> = manifest.tombstone_ids.iter().cloned().collect();
-
-        let results = keyword::keyword_search(
-            metadata,
-            &opts.collection_id,
-            query_text,
-            opts.top_k,
-            &tombstone_set,
-        )?;
-
-        Ok(SearchResponse {
-            results,
-            manifest_version_used: manifest.version,
+        storage: &LocalFsBackend,
+        snapshot: &ManifestSnapshot,
+        chunk_ids: &[String],
+    ) -> Result<HashMap<String, serde_json::Value>> {
+        self.chunk_fields(storage, snapshot, chunk_ids, |reader, positions| {
+            let metadata = reader.get_metadata()?;
+            Ok(positions.iter().map(|&i| metadata.get(i).cloned()).collect())
         })
     }
 
-    /// Hybrid search: vector + keyword fused via RRF.
-    fn search_hybrid(
+    /// Decoded vectors for `chunk_ids`, read from the snapshot's segments
+    /// like `chunk_texts`. Only the requested records are decoded.
+    pub fn chunk_vectors(
         &self,
-        metadata: &MetadataStore,
         storage: &LocalFsBackend,
-        opts: &SearchOptions,
-        buffer_records: &[NativeRecord],
-        manifest: &Manifest,
-    ) -> Result<SearchResponse> {
-        // Run vector search (over-fetch for better fusion).
-        let vector_response = self.search_vector(
-            metadata,
-            storage,
-            &SearchOptions {
-                collection_id: opts.collection_id.clone(),
-                query_vector: opts.query_vector.clone(),
-                top_k: opts.top_k * 2,
-                filters: opts.filters.clone(),
-                manifest_version: opts.manifest_version,
-                include_uncommitted: opts.include_uncommitted,
-                mode: SearchMode::Vector,
-                query_text: None,
-                vector_weight: opts.vector_weight,
-                keyword_weight: opts.keyword_weight,
-                explain: false,
-                ef_search: opts.ef_search,
-            },
-            buffer_records,
-            manifest,
-        )?;
-
-        // Run keyword search.
-        let query_text = opts.query_text.as_deref().unwrap_or("");
-        let tombstone_set: HashSet<String> = manifest.tombstone_ids.iter().cloned().collect();
-        let keyword_results = keyword::keyword_search(
-            metadata,
-            &opts.collection_id,
-            query_text,
-            opts.top_k * 2,
-            &tombstone_set,
-        )?;
-
-        // Fuse with RRF.
-        let mut fused = hybrid::rrf_fuse(
-            &vector_response.results,
-            &keyword_results,
-            opts.top_k,
-            opts.vector_weight,
-            opts.keyword_weight,
-        );
-
-        // Populate explain info for hybrid results.
-        if opts.explain {
-            let query_text = opts.query_text.as_deref();
-            let preview_map = metadata
-                .fts_get_texts(&fused.iter().map(|r| r.chunk_id.clone()).collect::<Vec<_>>())
-                .unwrap_or_default();
+        snapshot: &ManifestSnapshot,
+        chunk_ids: &[String],
+    ) -> Result<HashMap<String, Vec<f32>>> {
+        self.chunk_fields(storage, snapshot, chunk_ids, |reader, positions| {
+            positions.iter().map(|&i| reader.get_vector(i).map(Some)).collect()
+        })
+    }
 
-            // Build lookup maps for ranks and scores from each list.
-            let vector_map: HashMap<String, (usize, f64)> = vector_response
-                .results
-                .iter()
-                .enumerate()
-                .map(|(i, r)| (r.chunk_id.clone(), (i + 1, r.score)))
-                .collect();
-            let keyword_map: HashMap<String, (usize, f64)> = keyword_results
-                .iter()
-                .enumerate()
-                .map(|(i, r)| (r.chunk_id.clone(), (i + 1, r.score)))
-                .collect();
-
-            for r in &mut fused {
-                let v_info = vector_map.get(&r.chunk_id);
-                let k_info = keyword_map.get(&r.chunk_id);
-                let preview = preview_map
-                    .get(&r.chunk_id)
-                    .map(|t| t.chars().take(200).collect());
-
-                r.explain = Some(ExplainInfo {
-                    vector_score: v_info.map(|(_, s)| *s),
-                    bm25_score: k_info.map(|(_, s)| *s),
-                    rrf_score: Some(r.score),
-                    vector_rank: v_info.map(|(rank, _)| *rank),
-                    bm25_rank: k_info.map(|(rank, _)| *rank),
-                    chunk_preview: preview,
-                    matched_terms: query_text
-                        .map(|qt| qt.split_whitespace().map(|s| s.to_string()).collect())
-                        .unwrap_or_default(),
-                });
+    /// Read one per-record field for the latest committed version of each of
+    /// `chunk_ids`, mapping only the segments the chunk locator places them
+    /// in. `read` gets a segment's record positions and returns the field at
+    /// each, None where it is absent.
+    fn chunk_fields<T, F>(
+        &self,
+        storage: &LocalFsBackend,
+        snapshot: &ManifestSnapshot,
+        chunk_ids: &[String],
+        read: F,
+    ) -> Result<HashMap<String, T>>
+    where
+        F: Fn(&SegmentReader, &[usize]) -> Result<Vec<Option<T>>>,
+    {
+        let mut fields = HashMap::new();
+        if chunk_ids.is_empty() {
+            return Ok(fields);
+        }
+        let locator = self.chunk_locator(storage, &snapshot.segment_paths)?;
+        let mut by_segment: HashMap<usize, Vec<(&str, usize)>> = HashMap::new();
+        for chunk_id in chunk_ids {
+            if let Some(&(segment, offset)) = locator.locations.get(chunk_id) {
+                by_segment.entry(segment).or_default().push((chunk_id, offset));
             }
         }
-
-        Ok(SearchResponse {
-            results: fused,
-            manifest_version_used: manifest.version,
-        })
+        for (segment, wanted) in by_segment {
+            let (_, storage_path) = &snapshot.segment_paths[segment];
+            let reader = SegmentReader::from_mmap(storage.map_object(storage_path)?)?;
+            let positions: Vec<usize> = wanted.iter().map(|&(_, offset)| offset).collect();
+            let values = read(&reader, &positions)?;
+            for ((chunk_id, _), value) in wanted.into_iter().zip(values) {
+                if let Some(value) = value {
+                    fields.insert(chunk_id.to_string(), value);
+                }
+            }
+        }
+        Ok(fields)
     }
 
-    /// Merge, sort, and deduplicate results by descending score.
-    fn merge_and_dedup(mut results: Vec<SearchResult>, top_k: usize) -> Vec<SearchResult> {
-        results.sort_by(|a, b| {
-            b.score
-                .partial_cmp(&a.score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-                .then(a.chunk_id.cmp(&b.chunk_id))
-        });
-
-        let mut seen = HashSet::new();
-        let mut deduped = Vec::new();
-        for r in results {
-            if seen.contains(&r.chunk_id) {
+    /// Fetch the latest committed version of each of `chunk_ids` through the
+    /// snapshot's chunk locator, reading only the segments that hold them.
+    /// Tombstoned and unknown chunks are absent from the map.
+    pub fn get_records(
+        &self,
+        storage: &LocalFsBackend,
+        snapshot: &ManifestSnapshot,
+        chunk_ids: &[String],
+    ) -> Result<HashMap<String, StoredRecord>> {
+        let locator = self.chunk_locator(storage, &snapshot.segment_paths)?;
+        let mut by_segment: HashMap<usize, Vec<(&str, usize)>> = HashMap::new();
+        for chunk_id in chunk_ids {
+            if snapshot.live_tombstone_set.contains(chunk_id) {
                 continue;
             }
-            seen.insert(r.chunk_id.clone());
-            deduped.push(r);
-            if deduped.len() >= top_k {
-                break;
+            if let Some(&(segment, offset)) = locator.locations.get(chunk_id) {
+                by_segment.entry(segment).or_default().push((chunk_id, offset));
+            }
+        }
+
+        let mut records = HashMap::new();
+        for (segment, wanted) in by_segment {
+            let (_, storage_path) = &snapshot.segment_paths[segment];
+            let reader = SegmentReader::from_mmap(storage.map_object(storage_path)?)?;
+            let doc_ids = reader.get_doc_ids()?;
+            let ordinals = reader.get_ordinals()?;
+            let metadata = reader.get_metadata()?;
+            let texts = reader.get_chunk_texts().unwrap_or_default();
+            for (chunk_id, offset) in wanted {
+                records.insert(
+                    chunk_id.to_string(),
+                    StoredRecord {
+                        chunk_id: chunk_id.to_string(),
+                        doc_id: doc_ids[offset].clone(),
+                        ordinal: ordinals[offset],
+                        vector: reader.get_vector(offset)?,
+                        metadata: metadata.get(offset).cloned().unwrap_or_else(|| serde_json::json!({})),
+                        chunk_text: texts.get(offset).filter(|t| !t.is_empty()).cloned(),
+                        committed: true,
+                    },
+                );
             }
         }
-        deduped
+        Ok(records)
     }
 
-    fn resolve_manifest(
+    /// Chunks whose latest record in `segment_paths` (oldest first) belongs
+    /// to `doc_id`. Tombstones are not applied.
+    pub fn document_chunk_ids(
         &self,
-        metadata: &MetadataStore,
-        collection_id: &str,
-        version: Option<i64>,
-    ) -> Result<Manifest> {
-        if let Some(v) = version {
-            return metadata
-                .get_manifest_by_version(collection_id, v)?
-                .ok_or_else(|| {
-                AkiDbError::ManifestNotFound(format!(
-                    "version {} for collection \"{}\"",
-                    v, collection_id
-                ))
-            });
-        }
+        storage: &LocalFsBackend,
+        segment_paths: &[(String, String)],
+        doc_id: &str,
+    ) -> Result<Vec<String>> {
+        let locator = self.chunk_locator(storage, segment_paths)?;
+        Ok(locator.documents.get(doc_id).cloned().unwrap_or_default())
+    }
 
-        metadata
-            .get_latest_manifest(collection_id)?
-            .ok_or_else(|| {
-                AkiDbError::ManifestNotFound(format!(
-                    "no manifest published for collection \"{}\"",
-                    collection_id
-                ))
-            })
+    /// Get the chunk locator for `segment_paths` from the cache, building it
+    /// from the segments' ID maps on a miss.
+    fn chunk_locator(&self, storage: &LocalFsBackend, segment_paths: &[(String, String)]) -> Result<Arc<ChunkLocator>> {
+        // Keyed by the segment IDs themselves, so two lists can never share
+        // a locator. IDs are embedded in storage paths and hold no newlines.
+        let key = segment_paths.iter().map(|(segment_id, _)| segment_id.as_str()).collect::<Vec<_>>().join("\n");
+        {
+            let mut cache = self.locator_cache.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(cached) = cache.get(&key) {
+                return Ok(cached);
+            }
+        }
+        let mut locations = HashMap::new();
+        let mut chunk_docs: HashMap<String, String> = HashMap::new();
+        for (segment, (_, storage_path)) in segment_paths.iter().enumerate() {
+            let reader = SegmentReader::from_mmap(storage.map_object(storage_path)?)?;
+            let chunk_ids = reader.get_chunk_ids()?;
+            let doc_ids = reader.get_doc_ids()?;
+            for (offset, (chunk_id, doc_id)) in chunk_ids.into_iter().zip(doc_ids).enumerate() {
+                locations.insert(chunk_id.clone(), (segment, offset));
+                chunk_docs.insert(chunk_id, doc_id);
+            }
+        }
+        let mut documents: HashMap<String, Vec<String>> = HashMap::new();
+        for (chunk_id, doc_id) in chunk_docs {
+            documents.entry(doc_id).or_default().push(chunk_id);
+        }
+        for chunk_ids in documents.values_mut() {
+            chunk_ids.sort();
+        }
+        let mut cache = self.locator_cache.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(cache.set(key, ChunkLocator { locations, documents }))
     }
 
-    fn search_segment(
+    /// Get a segment's keyword index from the cache, loading it on a miss.
+    /// Segments without text get an empty index.
+    fn cached_keyword_index(
         &self,
-        metadata: &MetadataStore,
         storage: &LocalFsBackend,
         segment_id: &str,
-        p: &SegmentSearchParams<'_>,
-    ) -> Result<Vec<SearchResult>> {
-        let seg_meta = metadata
-            .get_segment(segment_id)?
-            .ok_or_else(|| AkiDbError::SegmentNotFound(segment_id.to_string()))?;
+        storage_path: &str,
+    ) -> Result<Arc<CachedKeywordIndex>> {
+        {
+            let mut cache = self.keyword_cache.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(cached) = cache.get(segment_id) {
+                return Ok(cached);
+            }
+        }
+        let reader = SegmentReader::from_mmap(storage.map_object(storage_path)?)?;
+        let entry = CachedKeywordIndex {
+            index: reader.get_keyword_index()?.unwrap_or_default(),
+            chunk_ids: reader.get_chunk_ids()?,
+        };
+        let mut cache = self.keyword_cache.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(cache.set(segment_id.to_string(), entry))
+    }
+
+    /// Run `f` over every `(segment_id, storage_path)`, on the search pool when
+    /// there is more than one segment. Output order matches `segment_paths`.
+    fn map_segments<T, F>(&self, segment_paths: &[(String, String)], f: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(&str, &str) -> T + Sync,
+    {
+        match &self.search_pool {
+            Some(pool) if segment_paths.len() > 1 => pool.install(|| {
+                segment_paths
+                    .par_iter()
+                    .map(|(segment_id, storage_path)| f(segment_id, storage_path))
+                    .collect()
+            }),
+            _ => segment_paths
+                .iter()
+                .map(|(segment_id, storage_path)| f(segment_id, storage_path))
+                .collect(),
+        }
+    }
+
+    /// Merge, sort, and deduplicate results by descending score, keeping the
+    /// best `top_k`, or with `grouping` the best results of its groups.
+    fn merge_and_dedup(
+        mut results: Vec<SearchResult>,
+        top_k: usize,
+        grouping: Option<&Grouping>,
+    ) -> Vec<SearchResult> {
+        results.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.chunk_id.cmp(&b.chunk_id))
+        });
 
-        self.search_segment_by_path(storage, segment_id, &seg_meta.storage_path, p)
+        let mut seen = HashSet::new();
+        let deduped = results.into_iter().filter(|r| seen.insert(r.chunk_id.clone()));
+        match grouping {
+            Some(grouping) => grouping.limit(deduped),
+            None => deduped.take(top_k).collect(),
+        }
     }
 
     fn search_segment_by_path(
@@ -651,70 +1069,49 @@ impl QueryEngine {
             metric,
             tombstone_set,
             filters,
-            hnsw,
-            manifest_version,
+            index,
+            rescore,
+            oversampling,
             tombstone_fingerprint,
         } = p;
-        let (top_k, hnsw, manifest_version, tombstone_fingerprint) =
-            (*top_k, *hnsw, *manifest_version, *tombstone_fingerprint);
-        let cache_key = IndexCache::cache_key(segment_id, manifest_version, tombstone_fingerprint);
-
-        // Fast path: unfiltered cache hit — no disk I/O required.
-        // The previous code only checked the cache inside `if let Some(f) = filters`,
-        // so every unfiltered query bypassed the cache and re-read the segment from disk.
-        if filters.is_none() {
-            let cached_arc: Option<Arc<CachedIndex>> = {
-                let mut cache = self.index_cache.lock().unwrap_or_else(|e| e.into_inner());
-                cache.get(&cache_key)
-            };
-            if let Some(cached) = cached_arc {
-                let results = cached.graph.search(query_vector, top_k);
-                return Ok(convert_search_results(&results, &cached.active_chunk_ids));
-            }
-        }
-
-        // Cache miss (or filtered query) — must read the segment from disk.
-        let buffer = storage.get_object(storage_path)?;
-        let reader = SegmentReader::from_buffer(buffer)?;
+        let (top_k, index, rescore, oversampling, tombstone_fingerprint) =
+            (*top_k, *index, *rescore, *oversampling, *tombstone_fingerprint);
+        let num_probes = match index.kind {
+            IndexKind::IvfPq(ivf) => ivf.num_probes,
+            _ => 0,
+        };
 
-        // Filtered cache hit: graph is cached, only need metadata for predicate evaluation.
-        if let Some(f) = filters {
-            let cached_arc: Option<Arc<CachedIndex>> = {
-                let mut cache = self.index_cache.lock().unwrap_or_else(|e| e.into_inner());
-                cache.get(&cache_key)
-            };
-            if let Some(cached) = cached_arc {
-                let metadata_list = reader.get_metadata()?;
-                let indices =
-                    self.apply_metadata_filter(&reader, &cached.node_to_segment, &metadata_list, f);
-                if indices.is_empty() {
-                    return Ok(Vec::new());
-                }
-                let filter_set: HashSet<usize> = indices.into_iter().collect();
-                let results = cached.graph.search_filtered(query_vector, top_k, |node_id| {
-                    let seg_idx = cached.node_to_segment[node_id as usize];
-                    filter_set.contains(&seg_idx)
-                });
-                return Ok(convert_search_results(&results, &cached.active_chunk_ids));
+        // The segment is only mapped when needed: on a cache miss, to evaluate
+        // metadata filters, or to re-score against full-precision vectors.
+        let mut reader: Option<SegmentReader> = None;
+        let cached_arc: Option<Arc<CachedIndex>> = {
+            let mut cache = self.index_cache.lock().unwrap_or_else(|e| e.into_inner());
+            cache.get(segment_id)
+        };
+        let cached = match cached_arc {
+            Some(cached) => cached,
+            None => {
+                let (cached, loaded) =
+                    self.load_cached_index(storage, segment_id, storage_path, index, metric)?;
+                reader = Some(loaded);
+                cached
             }
-        }
-
-        let chunk_ids = reader.get_chunk_ids()?;
-
-        // Active (non-tombstoned) indices.
-        let all_active_indices: Vec<usize> = (0..chunk_ids.len())
-            .filter(|i| !tombstone_set.contains(&chunk_ids[*i]))
-            .collect();
+        };
 
-        if all_active_indices.is_empty() {
+        let deleted = cached.deleted_nodes(tombstone_set, tombstone_fingerprint);
+        let node_count = cached.chunk_ids.len();
+        if deleted.len() as usize >= node_count {
             return Ok(Vec::new());
         }
 
         // Build filter set from metadata filters (bitmap-accelerated or brute-force).
         // Metadata is loaded lazily — only when a filter predicate is present.
         let filter_set: Option<HashSet<usize>> = if let Some(f) = filters {
+            let reader = open_segment(&mut reader, storage, storage_path)?;
             let metadata_list = reader.get_metadata()?;
-            let indices = self.apply_metadata_filter(&reader, &all_active_indices, &metadata_list, f);
+            let active_indices: Vec<usize> =
+                (0..node_count).filter(|&i| !deleted.contains(i as u32)).collect();
+            let indices = self.apply_metadata_filter(reader, &active_indices, &metadata_list, f);
             if indices.is_empty() {
                 return Ok(Vec::new());
             }
@@ -723,56 +1120,52 @@ impl QueryEngine {
             None
         };
 
-        let active_chunk_ids: Vec<String> = all_active_indices
-            .iter()
-            .map(|&i| chunk_ids[i].clone())
-            .collect();
-
-        // ── Cache miss path: load vectors and build / deserialize graph ───────
-        let vectors = reader.get_vectors()?;
-        let active_vectors: Vec<Vec<f32>> =
-            all_active_indices.iter().map(|&i| vectors[i].clone()).collect();
-        // Maps graph node index → original segment position (for filter closure).
-        let node_to_segment: Vec<usize> = all_active_indices.clone();
-
-        let dimension = reader.dimension() as usize;
-        let mut graph = HnswGraph::new(metric, dimension, hnsw.m, hnsw.ef_construction, hnsw.ef_search);
-
-        let index_data = reader.get_index_data();
-        if !index_data.is_empty() && all_active_indices.len() == chunk_ids.len() {
-            if graph.deserialize(index_data).is_err() {
-                graph.build(&active_vectors);
-            }
+        let rescore = cached.index.needs_rescore(rescore);
+        let candidate_k = fetch_k(top_k, rescore, oversampling);
+        let mut results = if filter_set.is_none() && deleted.is_empty() {
+            cached.index.search(query_vector, candidate_k, num_probes)
         } else {
-            graph.build(&active_vectors);
+            cached.index.search_filtered(query_vector, candidate_k, num_probes, |node_id| {
+                !deleted.contains(node_id)
+                    && filter_set.as_ref().is_none_or(|fs| fs.contains(&(node_id as usize)))
+            })
+        };
+        if rescore {
+            // Full-precision vectors live on disk only; mapping the segment
+            // touches just the candidates' pages.
+            let reader = open_segment(&mut reader, storage, storage_path)?;
+            results = rescore_candidates(reader, metric, query_vector, &results, top_k)?;
         }
 
-        let search_results = match &filter_set {
-            Some(fs) => graph.search_filtered(query_vector, top_k, |node_id| {
-                let seg_idx = node_to_segment[node_id as usize];
-                fs.contains(&seg_idx)
-            }),
-            None => graph.search(query_vector, top_k),
-        };
+        Ok(convert_search_results(&results, &cached.chunk_ids))
+    }
 
-        // Cache the graph with its active-set mapping, filter-agnostic and
-        // reusable across different filter predicates on the same active set.
-        let vector_bytes = active_vectors.len() * dimension * 4;
-        let graph_bytes = active_vectors.len() * hnsw.m * 4;
-        {
+    /// Load a segment's index over all of its records and insert it into the
+    /// cache under the segment ID. Returns the reader used to load it.
+    fn load_cached_index(
+        &self,
+        storage: &LocalFsBackend,
+        segment_id: &str,
+        storage_path: &str,
+        index: IndexParams,
+        metric: &str,
+    ) -> Result<(Arc<CachedIndex>, SegmentReader)> {
+        let reader = SegmentReader::from_mmap(storage.map_object(storage_path)?)?;
+        let chunk_ids = reader.get_chunk_ids()?;
+        let segment_index = load_segment_index(&reader, metric, index, chunk_ids.len())?;
+        let memory_size_bytes = segment_index.memory_size_bytes(
+            chunk_ids.len(),
+            reader.dimension() as usize,
+            index.hnsw.m,
+        );
+        let cached = {
             let mut cache = self.index_cache.lock().unwrap_or_else(|e| e.into_inner());
             cache.set(
-                cache_key,
-                CachedIndex {
-                    graph,
-                    active_chunk_ids: active_chunk_ids.clone(),
-                    node_to_segment,
-                    memory_size_bytes: vector_bytes + graph_bytes,
-                },
-            );
-        }
-
-        Ok(convert_search_results(&search_results, &active_chunk_ids))
+                segment_id.to_string(),
+                CachedIndex::new(segment_index, chunk_ids, memory_size_bytes),
+            )
+        };
+        Ok((cached, reader))
     }
 
     fn apply_metadata_filter(
@@ -845,6 +1238,36 @@ impl QueryEngine {
             .collect()
     }
 
+    /// ANN search over the write buffer's HNSW graph, whose node ids are
+    /// positions in `records`. Tombstones and filters are applied inline.
+    fn search_buffer_index(
+        &self,
+        graph: &HnswGraph,
+        records: &[NativeRecord],
+        query_vector: &[f32],
+        top_k: usize,
+        filters: Option<&serde_json::Value>,
+        tombstones: &HashSet<String>,
+    ) -> Vec<SearchResult> {
+        let hits = graph.search_filtered(query_vector, top_k, |node_id| {
+            let rec = &records[node_id as usize];
+            !tombstones.contains(&rec.chunk_id)
+                && filters.is_none_or(|f| matches_filter(&rec.metadata, f))
+        });
+        hits.into_iter()
+            .map(|(node_id, score)| SearchResult {
+                chunk_id: records[node_id as usize].chunk_id.clone(),
+                score: score as f64,
+                committed: Some(false),
+                explain: None,
+                metadata: None,
+                text: None,
+                vector: None,
+                group_key: None,
+            })
+            .collect()
+    }
+
     fn search_buffer_brute_force(
         &self,
         records: &[NativeRecord],
@@ -855,6 +1278,9 @@ impl QueryEngine {
         tombstones: &HashSet<String>,
     ) -> Vec<SearchResult> {
         let dist_fn = distance::get_distance_fn(metric);
+        // The cosine kernel expects unit vectors, like the graphs store.
+        let cosine = metric == "cosine";
+        let query_vector = if cosine { distance::normalize(query_vector) } else { query_vector.to_vec() };
         let mut results: Vec<SearchResult> = Vec::new();
 
         for rec in records {
@@ -868,13 +1294,21 @@ impl QueryEngine {
                 }
             }
 
-            let raw_distance = dist_fn(query_vector, &rec.vector);
+            let raw_distance = if cosine {
+                dist_fn(&query_vector, &distance::normalize(&rec.vector))
+            } else {
+                dist_fn(&query_vector, &rec.vector)
+            };
             let score = distance::distance_to_score(metric, raw_distance) as f64;
             results.push(SearchResult {
                 chunk_id: rec.chunk_id.clone(),
                 score,
                 committed: Some(false),
                 explain: None,
+                metadata: None,
+                text: None,
+                vector: None,
+                group_key: None,
             });
         }
 
@@ -888,7 +1322,7 @@ impl QueryEngine {
         results
     }
 
-    fn validate_search_opts(&self, opts: &SearchOptions) -> Result<()> {
+    pub fn validate_search_opts(&self, opts: &SearchOptions) -> Result<()> {
         if opts.collection_id.is_empty() {
             return Err(AkiDbError::InvalidArgument(
                 "collectionId is required".into(),
@@ -899,6 +1333,42 @@ impl QueryEngine {
                 "topK must be a positive integer".into(),
             ));
         }
+        if let Some(oversampling) = opts.oversampling
+            && !(oversampling.is_finite() && oversampling >= 1.0)
+        {
+            return Err(AkiDbError::InvalidArgument(
+                "oversampling must be a finite number >= 1".into(),
+            ));
+        }
+        if opts.group_by == Some(GroupBy::Metadata(String::new())) {
+            return Err(AkiDbError::InvalidArgument(
+                "groupBy must be \"doc_id\" or a metadata field name".into(),
+            ));
+        }
+        if opts.max_per_group == Some(0) {
+            return Err(AkiDbError::InvalidArgument(
+                "maxPerGroup must be a positive integer".into(),
+            ));
+        }
+        if let Some(cursor) = &opts.search_after {
+            if opts.group_by.is_some() {
+                return Err(AkiDbError::InvalidArgument(
+                    "searchAfter is not supported with groupBy".into(),
+                ));
+            }
+            if cursor.query != opts.query_fingerprint() {
+                return Err(AkiDbError::InvalidArgument(
+                    "searchAfter cursor was issued for a different query or search mode".into(),
+                ));
+            }
+            if opts.manifest_version.is_some_and(|version| version != cursor.manifest_version) {
+                return Err(AkiDbError::InvalidArgument(format!(
+                    "searchAfter continues manifest version {}, not {}",
+                    cursor.manifest_version,
+                    opts.manifest_version.unwrap_or_default()
+                )));
+            }
+        }
         match opts.mode {
             SearchMode::Vector => {
                 if opts.query_vector.is_empty() {
@@ -913,6 +1383,8 @@ impl QueryEngine {
                         "queryText is required for keyword search".into(),
                     ));
                 }
+                // Syntax errors do not depend on the collection's analyzer.
+                syntax::parse(opts.query_text.as_deref().unwrap_or(""), opts.keyword_syntax, &Analyzer::default())?;
             }
             SearchMode::Hybrid => {
                 if opts.query_vector.is_empty() {
@@ -925,12 +1397,33 @@ impl QueryEngine {
                         "queryText is required for hybrid search".into(),
                     ));
                 }
+                // Syntax errors do not depend on the collection's analyzer.
+                syntax::parse(opts.query_text.as_deref().unwrap_or(""), opts.keyword_syntax, &Analyzer::default())?;
+                if let Some(k) = opts.fusion.rrf_k()
+                    && !(k.is_finite() && k >= 0.0)
+                {
+                    return Err(AkiDbError::InvalidArgument(
+                        "rrfK must be a non-negative number".into(),
+                    ));
+                }
             }
         }
         Ok(())
     }
 }
 
+/// Map the segment into `slot` on first use.
+fn open_segment<'a>(
+    slot: &'a mut Option<SegmentReader>,
+    storage: &LocalFsBackend,
+    storage_path: &str,
+) -> Result<&'a SegmentReader> {
+    if slot.is_none() {
+        *slot = Some(SegmentReader::from_mmap(storage.map_object(storage_path)?)?);
+    }
+    Ok(slot.as_ref().expect("segment reader was just opened"))
+}
+
 fn convert_search_results(results: &[(u32, f32)], chunk_ids: &[String]) -> Vec<SearchResult> {
     results
         .iter()
@@ -941,11 +1434,156 @@ fn convert_search_results(results: &[(u32, f32)], chunk_ids: &[String]) -> Vec<S
                 score: *score as f64,
                 committed: None,
                 explain: None,
+                metadata: None,
+                text: None,
+                vector: None,
+                group_key: None,
             })
         })
         .collect()
 }
 
+/// Resolve the collection's index parameters with per-query overrides applied.
+fn index_params(collection: &Collection, opts: &SearchOptions) -> IndexParams {
+    let mut params = IndexParams::from_collection(collection);
+    if let Some(ef_search) = opts.ef_search {
+        params.hnsw.ef_search = ef_search;
+    }
+    if let (IndexKind::IvfPq(ivf), Some(num_probes)) = (&mut params.kind, opts.ivf_num_probes) {
+        ivf.num_probes = num_probes;
+    }
+    params
+}
+
+/// Number of candidates to pull from a segment index for `top_k` results.
+fn fetch_k(top_k: usize, rescore: bool, oversampling: f64) -> usize {
+    if rescore {
+        ((top_k as f64 * oversampling).ceil() as usize).max(top_k)
+    } else {
+        top_k
+    }
+}
+
+/// Full-precision vectors for the given segment positions.
+///
+/// An HNSW IndexBlock of an FP16 segment keeps f32 copies of every vector, so
+/// those are preferred over the VectorBlock, which is otherwise decoded one
+/// record at a time, so re-scoring a handful of candidates stays cheap.
+fn full_precision_vectors(reader: &SegmentReader, positions: &[usize]) -> Result<Vec<Vec<f32>>> {
+    let index_data = reader.get_index_data();
+    let from_index: Option<Vec<Vec<f32>>> = positions
+        .iter()
+        .map(|&i| HnswGraph::read_serialized_vector(index_data, i))
+        .collect();
+    if let Some(vectors) = from_index {
+        return Ok(vectors);
+    }
+    positions.iter().map(|&i| reader.get_vector(i)).collect()
+}
+
+/// Re-score approximate `(node_id, score)` candidates with exact distances
+/// and keep the best `top_k` (descending score, ascending node id for ties).
+fn rescore_candidates(
+    reader: &SegmentReader,
+    metric: &str,
+    query: &[f32],
+    candidates: &[(u32, f32)],
+    top_k: usize,
+) -> Result<Vec<(u32, f32)>> {
+    let query = if metric == "cosine" { distance::normalize(query) } else { query.to_vec() };
+
+    let mut rescored: Vec<(u32, f32)> = if reader.has_fp16_codes() {
+        // Score the stored FP16 codes (or an SQ8 segment's FP16 copies)
+        // directly, without a decode pass.
+        let dist_fn = distance::get_fp16_distance_fn(metric);
+        candidates
+            .iter()
+            .map(|&(node_id, _)| {
+                let codes = reader.get_fp16_codes(node_id as usize)?;
+                Ok((node_id, distance::distance_to_score(metric, dist_fn(&query, codes))))
+            })
+            .collect::<Result<_>>()?
+    } else {
+        let positions: Vec<usize> =
+            candidates.iter().map(|&(node_id, _)| node_id as usize).collect();
+        let vectors = full_precision_vectors(reader, &positions)?;
+        let dist_fn = distance::get_distance_fn(metric);
+        candidates
+            .iter()
+            .zip(&vectors)
+            .map(|(&(node_id, _), v)| {
+                let dist = if metric == "cosine" {
+                    dist_fn(&query, &distance::normalize(v))
+                } else {
+                    dist_fn(&query, v)
+                };
+                (node_id, distance::distance_to_score(metric, dist))
+            })
+            .collect()
+    };
+    rescored.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.0.cmp(&b.0))
+    });
+    rescored.truncate(top_k);
+    Ok(rescored)
+}
+
+/// Load a segment's ANN index over all `record_count` records.
+///
+/// The serialized IndexBlock is reused when it matches the collection's index
+/// type; otherwise the index is rebuilt from the segment's full-precision
+/// vectors. Tombstones are not applied here — the index is cached per segment
+/// and deleted nodes are skipped at search time. HNSW graphs over SQ8 segments
+/// search on the VectorBlock's codes.
+fn load_segment_index(
+    reader: &SegmentReader,
+    metric: &str,
+    index: IndexParams,
+    record_count: usize,
+) -> Result<SegmentIndex> {
+    let dimension = reader.dimension() as usize;
+    let index_data = reader.get_index_data();
+    let all_positions = || (0..record_count).collect::<Vec<usize>>();
+
+    match index.kind {
+        IndexKind::IvfPq(ivf) => {
+            let mut ivf_index =
+                IvfPqIndex::new(metric, dimension, ivf.num_clusters, ivf.num_subquantizers);
+            if !index_data.is_empty() && ivf_index.deserialize(index_data).is_ok() {
+                return Ok(SegmentIndex::IvfPq(ivf_index));
+            }
+            ivf_index.build(&full_precision_vectors(reader, &all_positions())?);
+            return Ok(SegmentIndex::IvfPq(ivf_index));
+        }
+        IndexKind::Binary => {
+            let mut binary_index = BinaryIndex::new(metric, dimension);
+            if !index_data.is_empty() && binary_index.deserialize(index_data).is_ok() {
+                return Ok(SegmentIndex::Binary(binary_index));
+            }
+            binary_index.build(&full_precision_vectors(reader, &all_positions())?);
+            return Ok(SegmentIndex::Binary(binary_index));
+        }
+        IndexKind::Hnsw => {}
+    }
+
+    let hnsw = index.hnsw;
+    let mut graph = HnswGraph::new(metric, dimension, hnsw.m, hnsw.ef_construction, hnsw.ef_search);
+    let sq8 = reader.vector_encoding() == VectorEncoding::Sq8;
+    let deserialized = !index_data.is_empty() && graph.deserialize(index_data).is_ok();
+    if !deserialized || (!sq8 && !graph.has_vectors()) || graph.node_count() != record_count {
+        graph.build(&full_precision_vectors(reader, &all_positions())?);
+    }
+    if sq8 {
+        let (codec, codes) = reader.get_sq8_codes()?;
+        graph
+            .use_sq8_vectors(Sq8Vectors::from_codes(metric, dimension, codec, codes.to_vec()))
+            .map_err(AkiDbError::Storage)?;
+    }
+    Ok(SegmentIndex::Hnsw(graph))
+}
+
 pub fn tombstone_fingerprint(tombstone_set: &HashSet<String>) -> u64 {
     let mut tombstone_ids: Vec<&String> = tombstone_set.iter().collect();
     tombstone_ids.sort_unstable();
@@ -1148,4 +1786,71 @@ mod tests {
         // Comparing number to string → no match (safe default).
         assert!(!matches_filter(&meta, &json!({"year": {"$gt": "2020"}})));
     }
+
+    #[test]
+    fn segment_index_cache_survives_delete_and_publish() {
+        use crate::engine::{EngineInner, EngineOptions};
+
+        let dir = tempfile::tempdir().unwrap();
+        let engine = EngineInner::open(EngineOptions {
+            storage_path: dir.path().to_path_buf(),
+            disable_wal: true,
+            search_threads: None,
+        })
+        .unwrap();
+        engine
+            .create_collection("docs", 8, "cosine", "model", "fp16", 16, 200, 100)
+            .unwrap();
+        let records: Vec<NativeRecord> = (0..50)
+            .map(|i| NativeRecord {
+                chunk_id: format!("chunk-{i}"),
+                doc_id: "doc".to_string(),
+                ordinal: None,
+                vector: (0..8).map(|d| (i as f32 * 0.37 + d as f32).sin()).collect(),
+                metadata: json!({"even": i % 2 == 0}),
+                chunk_text: None,
+            })
+            .collect();
+        engine.upsert_batch("docs", &records).unwrap();
+        engine.auto_publish("docs", "model", "sig").unwrap();
+
+        let search = |filters: Option<serde_json::Value>| {
+            engine
+                .search(SearchOptions {
+                    collection_id: "docs".to_string(),
+                    query_vector: records[10].vector.clone(),
+                    top_k: 5,
+                    filters,
+                    ..SearchOptions::default()
+                })
+                .unwrap()
+                .results
+        };
+        let cached = || {
+            let cache = engine.query_engine.index_cache.lock().unwrap();
+            assert_eq!(cache.entries.len(), 1);
+            Arc::clone(cache.entries.values().next().unwrap())
+        };
+
+        assert_eq!(search(None)[0].chunk_id, "chunk-10");
+        let before = cached();
+
+        engine
+            .delete_chunks("docs", &["chunk-10".to_string()], "manual_revoke")
+            .unwrap();
+        engine.auto_publish("docs", "model", "sig").unwrap();
+
+        let after = search(None);
+        assert_eq!(after.len(), 5);
+        assert!(after.iter().all(|r| r.chunk_id != "chunk-10"));
+        let even = search(Some(json!({"even": true})));
+        assert_eq!(even.len(), 5);
+        assert!(even.iter().all(|r| r.chunk_id != "chunk-10"));
+
+        // Same graph, with the delete applied as a deleted-node bitmap.
+        let after_cached = cached();
+        assert!(Arc::ptr_eq(&before, &after_cached));
+        let deleted = after_cached.deleted.lock().unwrap().as_ref().unwrap().1.clone();
+        assert_eq!(deleted.iter().collect::<Vec<_>>(), vec![10]);
+    }
 }
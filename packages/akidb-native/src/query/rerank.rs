@@ -0,0 +1,98 @@
+//! Re-ranking hook: re-score a search's top candidates with an external
+//! model (a cross-encoder, for example) before the final top-K cut.
+
+use crate::error::{AkiDbError, Result};
+use crate::index::{RerankInfo, SearchResult};
+
+/// A search result handed to a [`Reranker`].
+#[derive(Debug, Clone)]
+pub struct RerankCandidate {
+    pub chunk_id: String,
+    /// Score the search ranked the candidate by.
+    pub score: f64,
+    /// Chunk text, if the record stored one.
+    pub text: Option<String>,
+    /// Record metadata (`{}` when the record has none).
+    pub metadata: serde_json::Value,
+}
+
+/// Re-scores search candidates.
+pub trait Reranker {
+    /// Return one score per candidate, in candidate order; higher ranks first.
+    fn rerank(&self, query_text: Option<&str>, candidates: &[RerankCandidate]) -> Result<Vec<f64>>;
+}
+
+/// Reorder `results` by `scores` (one per result), keep the best `top_k` and
+/// record both ranks in any explain output. Ties keep the search order.
+pub fn apply(results: Vec<SearchResult>, scores: &[f64], top_k: usize) -> Result<Vec<SearchResult>> {
+    if scores.len() != results.len() {
+        return Err(AkiDbError::InvalidArgument(format!(
+            "reranker returned {} scores for {} candidates",
+            scores.len(),
+            results.len()
+        )));
+    }
+    if scores.iter().any(|s| !s.is_finite()) {
+        return Err(AkiDbError::InvalidArgument("reranker returned a non-finite score".into()));
+    }
+
+    let mut ranked: Vec<(usize, f64, SearchResult)> = results
+        .into_iter()
+        .zip(scores)
+        .enumerate()
+        .map(|(i, (r, &score))| (i, score, r))
+        .collect();
+    // Stable sort: equal scores keep their pre-rerank order.
+    ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    ranked.truncate(top_k);
+
+    Ok(ranked
+        .into_iter()
+        .enumerate()
+        .map(|(rank, (pre_rank, score, mut r))| {
+            if let Some(explain) = r.explain.as_mut() {
+                explain.rerank = Some(RerankInfo {
+                    pre_rerank_rank: pre_rank + 1,
+                    pre_rerank_score: r.score,
+                    post_rerank_rank: rank + 1,
+                });
+            }
+            r.score = score;
+            r
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_result(id: &str, score: f64) -> SearchResult {
+        SearchResult {
+            chunk_id: id.to_string(),
+            score,
+            committed: Some(true),
+            explain: None,
+            metadata: None,
+            text: None,
+            vector: None,
+            group_key: None,
+        }
+    }
+
+    #[test]
+    fn apply_reorders_and_truncates() {
+        let results = vec![make_result("a", 0.9), make_result("b", 0.8), make_result("c", 0.7)];
+        let reranked = apply(results, &[0.1, 0.5, 0.5], 2).unwrap();
+        let ranked: Vec<(&str, f64)> = reranked.iter().map(|r| (r.chunk_id.as_str(), r.score)).collect();
+        assert_eq!(ranked, vec![("b", 0.5), ("c", 0.5)]);
+    }
+
+    #[test]
+    fn apply_rejects_bad_scores() {
+        let results = || vec![make_result("a", 0.9), make_result("b", 0.8)];
+        let err = apply(results(), &[1.0], 2).unwrap_err();
+        assert!(err.to_string().contains("returned 1 scores for 2 candidates"), "{err}");
+        assert!(apply(results(), &[1.0, f64::NAN], 2).is_err());
+    }
+}
@@ -0,0 +1,511 @@
+//! Keyword query syntax — parses `queryText` into a `KeywordQuery`.
+//!
+//! The simple syntax (default) treats every whitespace-separated term as
+//! required. The advanced syntax follows the FTS5 query language:
+//!
+//!   quick "brown fox"   both required; quoted tokens must be adjacent
+//!   auth*               prefix match on the last token of a term or phrase
+//!   a OR b              either side
+//!   a AND b             same as `a b`
+//!   -draft, NOT draft   exclude records matching `draft`
+//!   NEAR(a b, 5)        every phrase within 5 tokens of each other (default 10)
+//!   ( ... )             grouping; AND binds tighter than OR
+//!
+//! As in FTS5, operators are case-sensitive (`or` is an ordinary term). A
+//! query is validated before any segment is read: unbalanced quotes or
+//! parentheses, empty groups, exclusions with nothing to exclude from and
+//! groups nested deeper than `MAX_GROUP_DEPTH` are rejected as invalid
+//! arguments.
+
+use crate::error::{AkiDbError, Result};
+use crate::text::Analyzer;
+
+/// Token gap allowed by `NEAR(...)` when no distance is given.
+pub const DEFAULT_NEAR_DISTANCE: u32 = 10;
+
+/// Deepest nesting of `( ... )` groups. Parsing and matching recurse per
+/// level, so this bounds their stack use.
+pub const MAX_GROUP_DEPTH: usize = 32;
+
+/// How `queryText` is interpreted for keyword and hybrid search.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum KeywordSyntax {
+    /// Whitespace-separated terms, all required. Operators are plain words.
+    #[default]
+    Simple,
+    /// FTS5-style phrases, prefixes, boolean operators and NEAR.
+    Advanced,
+}
+
+impl KeywordSyntax {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Self {
+        match s {
+            "advanced" => Self::Advanced,
+            _ => Self::Simple,
+        }
+    }
+}
+
+/// Tokens that must occur consecutively, e.g. `"brown fox"` or `e-mail`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Phrase {
+    /// Lowercased query text, matched as a substring by the trigram fallback.
+    pub text: String,
+    /// Tokens as the collection's analyzer produces them (see `Analyzer::tokenize`).
+    pub tokens: Vec<String>,
+    /// The last token matches any term it is a prefix of.
+    pub prefix: bool,
+}
+
+/// Parsed keyword query.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeywordQuery {
+    Phrase(Phrase),
+    /// Every phrase occurs, with at most `distance` tokens between the end of
+    /// the first and the start of the last.
+    Near { phrases: Vec<Phrase>, distance: u32 },
+    /// Records matching every `include` query and no `exclude` query.
+    /// `include` is only empty for a simple query without searchable terms,
+    /// which matches nothing.
+    And { include: Vec<KeywordQuery>, exclude: Vec<KeywordQuery> },
+    Or(Vec<KeywordQuery>),
+}
+
+impl KeywordQuery {
+    /// Phrases a matching record may contain, in query order: every phrase
+    /// outside an exclusion, including those of NEAR groups.
+    pub fn phrases(&self) -> Vec<&Phrase> {
+        let mut out = Vec::new();
+        self.collect_phrases(&mut out);
+        out
+    }
+
+    fn collect_phrases<'q>(&'q self, out: &mut Vec<&'q Phrase>) {
+        match self {
+            KeywordQuery::Phrase(phrase) => out.push(phrase),
+            KeywordQuery::Near { phrases, .. } => out.extend(phrases),
+            KeywordQuery::And { include, .. } => include.iter().for_each(|q| q.collect_phrases(out)),
+            KeywordQuery::Or(alternatives) => alternatives.iter().for_each(|q| q.collect_phrases(out)),
+        }
+    }
+}
+
+/// Parse and validate `text` as a keyword query, analyzing its terms with the
+/// collection's `analyzer`.
+pub fn parse(text: &str, syntax: KeywordSyntax, analyzer: &Analyzer) -> Result<KeywordQuery> {
+    match syntax {
+        KeywordSyntax::Simple => Ok(parse_simple(text, analyzer)),
+        KeywordSyntax::Advanced => {
+            let mut parser = Parser { tokens: lex(text)?, pos: 0, depth: 0, analyzer };
+            let query = parser.parse_or()?;
+            match parser.peek() {
+                None => Ok(query),
+                Some(token) => Err(invalid(format!("unexpected {}", token.describe()))),
+            }
+        }
+    }
+}
+
+fn parse_simple(text: &str, analyzer: &Analyzer) -> KeywordQuery {
+    let include = text
+        .split_whitespace()
+        .filter_map(|term| phrase(term, false, analyzer))
+        .map(KeywordQuery::Phrase)
+        .collect();
+    KeywordQuery::And { include, exclude: Vec::new() }
+}
+
+/// A phrase for `term`, or None if it has no word characters or only stop words.
+fn phrase(term: &str, prefix: bool, analyzer: &Analyzer) -> Option<Phrase> {
+    let tokens = if prefix { analyzer.tokenize_prefix(term) } else { analyzer.tokenize(term) };
+    (!tokens.is_empty()).then(|| Phrase { text: term.to_lowercase(), tokens, prefix })
+}
+
+fn invalid(message: String) -> AkiDbError {
+    AkiDbError::InvalidArgument(format!("Invalid keyword query: {message}"))
+}
+
+// ─── Lexer ──────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word { text: String, prefix: bool },
+    Quoted { text: String, prefix: bool },
+    Minus,
+    Near,
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Word { text, .. } => format!("'{text}'"),
+            Token::Quoted { text, .. } => format!("\"{text}\""),
+            Token::Minus => "'-'".to_string(),
+            Token::Near => "'NEAR'".to_string(),
+            Token::LParen => "'('".to_string(),
+            Token::RParen => "')'".to_string(),
+            Token::Comma => "','".to_string(),
+        }
+    }
+
+    fn is_operator(&self, op: &str) -> bool {
+        matches!(self, Token::Word { text, prefix: false } if text == op)
+    }
+}
+
+fn lex(text: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | ',' => {
+                chars.next();
+                tokens.push(match c {
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    _ => Token::Comma,
+                });
+            }
+            '"' => {
+                chars.next();
+                let mut quoted = String::new();
+                loop {
+                    match chars.next() {
+                        // FTS5 escapes a quote inside a phrase by doubling it.
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            quoted.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => quoted.push(c),
+                        None => return Err(invalid("unterminated quote".to_string())),
+                    }
+                }
+                let prefix = chars.next_if_eq(&'*').is_some();
+                tokens.push(Token::Quoted { text: quoted, prefix });
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"(),\"".contains(*c)) {
+                    word.push(c);
+                }
+                let mut word = word.as_str();
+                // A leading '-' excludes the term, group or phrase it is attached to.
+                if let Some(rest) = word.strip_prefix('-')
+                    && (!rest.is_empty() || matches!(chars.peek(), Some('(' | '"')))
+                {
+                    tokens.push(Token::Minus);
+                    word = rest;
+                }
+                if word.is_empty() {
+                    continue;
+                }
+                if word == "NEAR" && chars.peek() == Some(&'(') {
+                    tokens.push(Token::Near);
+                    continue;
+                }
+                let (word, prefix) = match word.strip_suffix('*') {
+                    Some(stem) => (stem, true),
+                    None => (word, false),
+                };
+                tokens.push(Token::Word { text: word.to_string(), prefix });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+// ─── Parser ─────────────────────────────────────────────────────────────────
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Groups open at the current position.
+    depth: usize,
+    analyzer: &'a Analyzer,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Whether the current group ends here (end of input, `)` or `OR`).
+    fn at_group_end(&self) -> bool {
+        self.peek().is_none_or(|t| *t == Token::RParen || t.is_operator("OR"))
+    }
+
+    /// or := and ("OR" and)*
+    fn parse_or(&mut self) -> Result<KeywordQuery> {
+        let mut alternatives = vec![self.parse_and()?];
+        while self.peek().is_some_and(|t| t.is_operator("OR")) {
+            self.next();
+            alternatives.push(self.parse_and()?);
+        }
+        Ok(if alternatives.len() == 1 {
+            alternatives.pop().expect("one alternative")
+        } else {
+            KeywordQuery::Or(alternatives)
+        })
+    }
+
+    /// and := (["AND"] ["-" | "NOT"] primary)+
+    fn parse_and(&mut self) -> Result<KeywordQuery> {
+        let mut include = Vec::new();
+        let mut exclude = Vec::new();
+        loop {
+            if self.at_group_end() {
+                let what = self.peek().map_or("end of query".to_string(), Token::describe);
+                return Err(invalid(format!("expected a term before {what}")));
+            }
+            if self.peek().is_some_and(|t| t.is_operator("AND")) {
+                self.next();
+                continue;
+            }
+            let negated = matches!(self.peek(), Some(Token::Minus))
+                || self.peek().is_some_and(|t| t.is_operator("NOT"));
+            if negated {
+                self.next();
+                exclude.push(self.parse_primary()?);
+            } else {
+                include.push(self.parse_primary()?);
+            }
+            if self.at_group_end() {
+                break;
+            }
+        }
+        if include.is_empty() {
+            return Err(invalid("an exclusion needs at least one term to exclude from".to_string()));
+        }
+        Ok(if include.len() == 1 && exclude.is_empty() {
+            include.pop().expect("one term")
+        } else {
+            KeywordQuery::And { include, exclude }
+        })
+    }
+
+    /// primary := "(" or ")" | NEAR "(" phrase+ ["," distance] ")" | phrase
+    fn parse_primary(&mut self) -> Result<KeywordQuery> {
+        match self.next() {
+            Some(Token::LParen) => {
+                if self.depth == MAX_GROUP_DEPTH {
+                    return Err(invalid(format!("groups nest deeper than {MAX_GROUP_DEPTH} levels")));
+                }
+                self.depth += 1;
+                let query = self.parse_or()?;
+                self.expect_rparen()?;
+                self.depth -= 1;
+                Ok(query)
+            }
+            Some(Token::Near) => self.parse_near(),
+            Some(token @ (Token::Word { .. } | Token::Quoted { .. })) => {
+                Ok(KeywordQuery::Phrase(self.phrase(&token)?))
+            }
+            Some(token) => Err(invalid(format!("unexpected {}", token.describe()))),
+            None => Err(invalid("expected a term before end of query".to_string())),
+        }
+    }
+
+    fn parse_near(&mut self) -> Result<KeywordQuery> {
+        self.next(); // the '(' the lexer saw after NEAR
+        let mut phrases = Vec::new();
+        while let Some(token @ (Token::Word { .. } | Token::Quoted { .. })) = self.peek().cloned() {
+            self.next();
+            phrases.push(self.phrase(&token)?);
+        }
+        if phrases.is_empty() {
+            return Err(invalid("NEAR needs at least one phrase".to_string()));
+        }
+        let mut distance = DEFAULT_NEAR_DISTANCE;
+        if self.peek() == Some(&Token::Comma) {
+            self.next();
+            distance = match self.next() {
+                Some(Token::Word { text, prefix: false }) => text
+                    .parse()
+                    .map_err(|_| invalid(format!("NEAR distance must be a non-negative integer, got '{text}'")))?,
+                other => {
+                    let what = other.map_or("end of query".to_string(), |t| t.describe());
+                    return Err(invalid(format!("expected a NEAR distance, got {what}")));
+                }
+            };
+        }
+        self.expect_rparen()?;
+        Ok(KeywordQuery::Near { phrases, distance })
+    }
+
+    fn expect_rparen(&mut self) -> Result<()> {
+        match self.next() {
+            Some(Token::RParen) => Ok(()),
+            Some(token) => Err(invalid(format!("expected ')', got {}", token.describe()))),
+            None => Err(invalid("missing ')'".to_string())),
+        }
+    }
+
+    fn phrase(&self, token: &Token) -> Result<Phrase> {
+        let (Token::Word { text, prefix } | Token::Quoted { text, prefix }) = token else {
+            unreachable!("phrase() is only called on words and quoted phrases");
+        };
+        phrase(text, *prefix, self.analyzer).ok_or_else(|| {
+            if text.chars().any(char::is_alphanumeric) {
+                invalid(format!("{} only contains stop words", token.describe()))
+            } else {
+                invalid(format!("{} has no searchable characters", token.describe()))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn advanced(text: &str) -> Result<KeywordQuery> {
+        parse(text, KeywordSyntax::Advanced, &Analyzer::default())
+    }
+
+    fn p(text: &str) -> KeywordQuery {
+        KeywordQuery::Phrase(phrase(text, false, &Analyzer::default()).unwrap())
+    }
+
+    fn error(text: &str) -> String {
+        advanced(text).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn simple_syntax_requires_every_term() {
+        let query = parse("auth* OR -draft", KeywordSyntax::Simple, &Analyzer::default()).unwrap();
+        let KeywordQuery::And { include, exclude } = query else {
+            panic!("expected AND");
+        };
+        assert!(exclude.is_empty());
+        let tokens: Vec<_> = include
+            .iter()
+            .map(|q| match q {
+                KeywordQuery::Phrase(p) => (p.tokens.join(" "), p.prefix),
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+        assert_eq!(tokens, vec![("auth".into(), false), ("or".into(), false), ("draft".into(), false)]);
+    }
+
+    #[test]
+    fn advanced_syntax_parses_operators() {
+        assert_eq!(advanced("fox").unwrap(), p("fox"));
+        assert_eq!(
+            advanced("quick \"Brown Fox\" AND dog").unwrap(),
+            KeywordQuery::And { include: vec![p("quick"), p("brown fox"), p("dog")], exclude: vec![] }
+        );
+        assert_eq!(
+            advanced("a b OR c").unwrap(),
+            KeywordQuery::Or(vec![KeywordQuery::And { include: vec![p("a"), p("b")], exclude: vec![] }, p("c")])
+        );
+        assert_eq!(
+            advanced("(a OR b) -draft NOT old").unwrap(),
+            KeywordQuery::And { include: vec![KeywordQuery::Or(vec![p("a"), p("b")])], exclude: vec![p("draft"), p("old")] }
+        );
+        assert_eq!(
+            advanced("a -(b OR c) -\"d e\"").unwrap(),
+            KeywordQuery::And { include: vec![p("a")], exclude: vec![KeywordQuery::Or(vec![p("b"), p("c")]), p("d e")] }
+        );
+        // Lowercase operators are terms.
+        assert_eq!(advanced("a or b").unwrap(), KeywordQuery::And { include: vec![p("a"), p("or"), p("b")], exclude: vec![] });
+    }
+
+    #[test]
+    fn advanced_syntax_parses_prefixes_and_near() {
+        let KeywordQuery::Phrase(auth) = advanced("auth*").unwrap() else {
+            panic!("expected phrase");
+        };
+        assert_eq!((auth.tokens, auth.prefix), (vec!["auth".to_string()], true));
+        let KeywordQuery::Phrase(quoted) = advanced("\"token refr\"*").unwrap() else {
+            panic!("expected phrase");
+        };
+        assert_eq!((quoted.tokens.len(), quoted.prefix), (2, true));
+
+        let KeywordQuery::Near { phrases, distance } = advanced("NEAR(fox \"lazy dog\", 3)").unwrap() else {
+            panic!("expected NEAR");
+        };
+        assert_eq!((phrases.len(), distance), (2, 3));
+        assert!(matches!(advanced("NEAR(a b)").unwrap(), KeywordQuery::Near { distance: DEFAULT_NEAR_DISTANCE, .. }));
+        // NEAR without a parenthesis is a term.
+        assert_eq!(advanced("NEAR").unwrap(), p("near"));
+    }
+
+    #[test]
+    fn phrases_skip_exclusions() {
+        let query = advanced("(a OR \"b c\") NEAR(d e*) -f").unwrap();
+        let texts: Vec<&str> = query.phrases().iter().map(|p| p.text.as_str()).collect();
+        assert_eq!(texts, vec!["a", "b c", "d", "e"]);
+    }
+
+    #[test]
+    fn advanced_syntax_rejects_malformed_queries() {
+        assert!(error("\"open phrase").contains("unterminated quote"));
+        assert!(error("(a OR b").contains("missing ')'"));
+        assert!(error("a)").contains("unexpected ')'"));
+        assert!(error("a OR").contains("expected a term"));
+        assert!(error("OR a").contains("expected a term"));
+        assert!(error("()").contains("expected a term"));
+        assert!(error("-draft").contains("at least one term"));
+        assert!(error("a (NOT b)").contains("at least one term"));
+        assert!(error("NEAR(, 2)").contains("at least one phrase"));
+        assert!(error("NEAR(a b, x)").contains("non-negative integer"));
+        assert!(error("a \"...\"").contains("no searchable characters"));
+        assert!(error("a -").contains("no searchable characters"));
+    }
+
+    #[test]
+    fn advanced_syntax_caps_group_depth() {
+        let nested = |depth: usize| format!("{}a{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(advanced(&nested(MAX_GROUP_DEPTH)).unwrap(), p("a"));
+        assert!(error(&nested(MAX_GROUP_DEPTH + 1)).contains("nest deeper than"));
+        assert!(error(&"(".repeat(200_000)).contains("nest deeper than"));
+        assert!(error(&"-(a ".repeat(200_000)).contains("nest deeper than"));
+    }
+
+    #[test]
+    fn terms_are_analyzed_with_the_collection_analyzer() {
+        use crate::text::{StopWords, Tokenizer};
+        use rust_stemmers::Algorithm;
+
+        let english = Analyzer {
+            tokenizer: Tokenizer::Unicode61,
+            stemmer: Some(Algorithm::English),
+            stop_words: Some(StopWords::English),
+            remove_diacritics: true,
+        };
+        let tokens = |text: &str, syntax| match parse(text, syntax, &english).unwrap() {
+            KeywordQuery::Phrase(p) => vec![(p.tokens, p.prefix)],
+            KeywordQuery::And { include, .. } => include
+                .into_iter()
+                .map(|q| match q {
+                    KeywordQuery::Phrase(p) => (p.tokens, p.prefix),
+                    other => panic!("unexpected {other:?}"),
+                })
+                .collect(),
+            other => panic!("unexpected {other:?}"),
+        };
+        assert_eq!(tokens("\"state of the art\"", KeywordSyntax::Advanced), vec![(vec!["state".into(), "art".into()], false)]);
+        assert_eq!(tokens("configured configuration*", KeywordSyntax::Advanced), vec![
+            (vec!["configur".into()], false),
+            (vec!["configuration".into()], true),
+        ]);
+        // Simple queries drop stop words; advanced ones reject a term made only of them.
+        assert_eq!(tokens("the tests", KeywordSyntax::Simple), vec![(vec!["test".into()], false)]);
+        let err = parse("fox AND the", KeywordSyntax::Advanced, &english).unwrap_err().to_string();
+        assert!(err.contains("only contains stop words"), "{err}");
+    }
+}
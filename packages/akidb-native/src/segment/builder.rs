@@ -1,17 +1,32 @@
 //! SegmentBuilder accumulates records and produces an immutable segment binary.
 //!
 //! Binary layout v2.1 (backward-compatible with v1 and v2):
-//!   [Header 64B][VectorBlock][IDMap][MetadataBlock][BitmapBlock][TextBlock][IndexBlock][Checksum 32B]
+//!   [Header 64B][VectorBlock][IDMap][MetadataBlock][BitmapBlock][TextBlock][KeywordBlock][IndexBlock][Checksum 32B]
 //!
 //! v1 segments have no BitmapBlock and bitmapOffset=0 in the header.
 //! v2 segments include a BitmapBlock with inverted bitmap indexes for metadata fields.
 //! v2.1 segments add a TextBlock with per-chunk text content.
 //!   Header bytes 62-63 are a flags field: bit 0 (0x0001) = TextBlock present.
+//!   Bit 1 (0x0002) = SQ8 VectorBlock: [min f32 × dim][max f32 × dim][u8 codes],
+//!   Bit 2 (0x0004) = FP32 VectorBlock (little-endian f32, lossless).
+//!   With neither bit set the VectorBlock holds FP16 vectors.
+//!   Bit 3 (0x0008) = KeywordBlock present: a BM25 index over the TextBlock
+//!   (see `segment::keyword`). Always written alongside a TextBlock.
+//!   Bit 4 (0x0010) = the SQ8 codes are followed by FP16 copies of every
+//!   vector, used to re-rank SQ8 candidates at full precision.
+//!
+//! The IDMap is JSON: `{"chunk_ids": [...], "doc_ids": [...], "ordinals": [...]}`,
+//! one entry per record; an ordinal is the chunk's position in its document,
+//! or null. Older segments lack `doc_ids` and `ordinals`.
 
 use crate::error::{AkiDbError, Result};
 use crate::fp16;
 use crate::segment::bitmap::BitmapIndex;
 use crate::segment::checksum::{compute_checksum, SHA256_BYTES};
+use crate::segment::keyword::KeywordIndex;
+use crate::segment::VectorEncoding;
+use crate::sq8::Sq8Codec;
+use crate::text::Analyzer;
 
 /// Magic bytes identifying an AkiDB segment file.
 const MAGIC: &[u8; 4] = b"AKDB";
@@ -21,12 +36,18 @@ const FORMAT_VERSION: u16 = 2;
 
 /// Header flags (bytes 62-63).
 const FLAG_TEXT_BLOCK: u16 = 0x0001;
+const FLAG_SQ8_VECTORS: u16 = 0x0002;
+const FLAG_FP32_VECTORS: u16 = 0x0004;
+const FLAG_KEYWORD_INDEX: u16 = 0x0008;
+const FLAG_SQ8_FP16_COPY: u16 = 0x0010;
 
 /// Fixed header size in bytes.
 const HEADER_SIZE: usize = 64;
 
 struct PendingRecord {
     chunk_id: String,
+    doc_id: String,
+    ordinal: Option<u32>,
     vector: Vec<f32>,
     metadata: serde_json::Value,
     chunk_text: Option<String>,
@@ -36,20 +57,37 @@ struct PendingRecord {
 pub struct SegmentBuilder {
     records: Vec<PendingRecord>,
     dimension: Option<usize>,
+    encoding: VectorEncoding,
+    analyzer: Analyzer,
 }
 
 impl SegmentBuilder {
     pub fn new() -> Self {
+        Self::with_encoding(VectorEncoding::Fp16)
+    }
+
+    /// Create a builder that writes the VectorBlock in the given encoding.
+    pub fn with_encoding(encoding: VectorEncoding) -> Self {
         Self {
             records: Vec::new(),
             dimension: None,
+            encoding,
+            analyzer: Analyzer::default(),
         }
     }
 
+    /// Analyze chunk text for the KeywordBlock with `analyzer` (default: unicode61).
+    pub fn with_analyzer(mut self, analyzer: Analyzer) -> Self {
+        self.analyzer = analyzer;
+        self
+    }
+
     /// Add a record with optional chunk text to the segment being built.
     pub fn add_record_with_text(
         &mut self,
         chunk_id: String,
+        doc_id: String,
+        ordinal: Option<u32>,
         vector: Vec<f32>,
         metadata: serde_json::Value,
         chunk_text: Option<String>,
@@ -67,6 +105,8 @@ impl SegmentBuilder {
         }
         self.records.push(PendingRecord {
             chunk_id,
+            doc_id,
+            ordinal,
             vector,
             metadata,
             chunk_text,
@@ -92,9 +132,19 @@ impl SegmentBuilder {
         let index_block = index_data.unwrap_or(&[]);
 
         let has_text = !text_block.is_empty();
+        let keyword_block = if has_text {
+            self.build_keyword_block()
+        } else {
+            Vec::new()
+        };
         let mut flags: u16 = 0;
         if has_text {
-            flags |= FLAG_TEXT_BLOCK;
+            flags |= FLAG_TEXT_BLOCK | FLAG_KEYWORD_INDEX;
+        }
+        match self.encoding {
+            VectorEncoding::Fp16 => {}
+            VectorEncoding::Sq8 => flags |= FLAG_SQ8_VECTORS | FLAG_SQ8_FP16_COPY,
+            VectorEncoding::Fp32 => flags |= FLAG_FP32_VECTORS,
         }
 
         let offsets = compute_offsets(
@@ -103,6 +153,7 @@ impl SegmentBuilder {
             metadata_block.len(),
             bitmap_block.len(),
             text_block.len(),
+            keyword_block.len(),
             index_block.len(),
         );
 
@@ -115,6 +166,7 @@ impl SegmentBuilder {
             + metadata_block.len()
             + bitmap_block.len()
             + text_block.len()
+            + keyword_block.len()
             + index_block.len();
         let mut body = Vec::with_capacity(body_len + SHA256_BYTES);
         body.extend_from_slice(&header);
@@ -123,6 +175,7 @@ impl SegmentBuilder {
         body.extend_from_slice(&metadata_block);
         body.extend_from_slice(&bitmap_block);
         body.extend_from_slice(&text_block);
+        body.extend_from_slice(&keyword_block);
         body.extend_from_slice(index_block);
 
         let checksum = compute_checksum(&body);
@@ -136,17 +189,44 @@ impl SegmentBuilder {
         })
     }
 
-    fn build_vector_block(&self, _dim: usize) -> Vec<u8> {
+    fn build_vector_block(&self, dim: usize) -> Vec<u8> {
         let mut parts = Vec::new();
-        for rec in &self.records {
-            parts.extend_from_slice(&fp16::encode_fp16_vector(&rec.vector));
+        match self.encoding {
+            VectorEncoding::Fp16 => {
+                for rec in &self.records {
+                    parts.extend_from_slice(&fp16::encode_fp16_vector(&rec.vector));
+                }
+            }
+            VectorEncoding::Sq8 => {
+                let codec = Sq8Codec::train(self.records.iter().map(|r| &r.vector), dim);
+                parts.reserve(Sq8Codec::serialized_size(dim) + self.records.len() * dim * 3);
+                parts.extend_from_slice(&codec.serialize());
+                for rec in &self.records {
+                    parts.extend_from_slice(&codec.encode(&rec.vector));
+                }
+                // FP16 copies follow the codes so candidates can be re-ranked
+                // at full precision; searches only touch the codes.
+                for rec in &self.records {
+                    parts.extend_from_slice(&fp16::encode_fp16_vector(&rec.vector));
+                }
+            }
+            VectorEncoding::Fp32 => {
+                parts.reserve(self.records.len() * dim * 4);
+                for rec in &self.records {
+                    for v in &rec.vector {
+                        parts.extend_from_slice(&v.to_le_bytes());
+                    }
+                }
+            }
         }
         parts
     }
 
     fn build_id_map_block(&self) -> Vec<u8> {
         let chunk_ids: Vec<&str> = self.records.iter().map(|r| r.chunk_id.as_str()).collect();
-        let json = serde_json::json!({ "chunk_ids": chunk_ids });
+        let doc_ids: Vec<&str> = self.records.iter().map(|r| r.doc_id.as_str()).collect();
+        let ordinals: Vec<Option<u32>> = self.records.iter().map(|r| r.ordinal).collect();
+        let json = serde_json::json!({ "chunk_ids": chunk_ids, "doc_ids": doc_ids, "ordinals": ordinals });
         serde_json::to_vec(&json).unwrap()
     }
 
@@ -184,6 +264,17 @@ impl SegmentBuilder {
 
         buf
     }
+
+    /// Build KeywordBlock: BM25 postings over the same texts as the TextBlock.
+    fn build_keyword_block(&self) -> Vec<u8> {
+        KeywordIndex::build(
+            self.records
+                .iter()
+                .map(|r| r.chunk_text.as_deref().unwrap_or("")),
+            &self.analyzer,
+        )
+        .serialize()
+    }
 }
 
 impl Default for SegmentBuilder {
@@ -215,13 +306,14 @@ fn compute_offsets(
     metadata_len: usize,
     bitmap_len: usize,
     text_len: usize,
+    keyword_len: usize,
     index_len: usize,
 ) -> BlockOffsets {
     let vector_block_offset = HEADER_SIZE as u64;
     let id_map_offset = vector_block_offset + vector_len as u64;
     let metadata_offset = id_map_offset + id_map_len as u64;
     let bitmap_offset = metadata_offset + metadata_len as u64;
-    let index_offset = bitmap_offset + bitmap_len as u64 + text_len as u64;
+    let index_offset = bitmap_offset + bitmap_len as u64 + text_len as u64 + keyword_len as u64;
     let checksum_offset = index_offset + index_len as u64;
 
     BlockOffsets {
@@ -259,7 +351,8 @@ fn build_header(record_count: usize, dimension: usize, offsets: &BlockOffsets, f
     // v2: bitmap offset at byte 54 (8 bytes)
     buf[54..62].copy_from_slice(&offsets.bitmap_offset.to_le_bytes());
 
-    // v2.1: flags (2 bytes) — bit 0 = TextBlock present
+    // v2.1: flags (2 bytes) — bit 0 = TextBlock present, bit 1 = SQ8 vectors,
+    // bit 2 = FP32 vectors, bit 3 = KeywordBlock present
     buf[62..64].copy_from_slice(&flags.to_le_bytes());
 
     buf
@@ -274,10 +367,10 @@ mod tests {
     fn build_segment() {
         let mut builder = SegmentBuilder::new();
         builder
-            .add_record_with_text("c-1".into(), vec![1.0, 0.0, 0.0, 0.0], json!({"source": "a.pdf"}), None)
+            .add_record_with_text("c-1".into(), "doc".into(), None, vec![1.0, 0.0, 0.0, 0.0], json!({"source": "a.pdf"}), None)
             .unwrap();
         builder
-            .add_record_with_text("c-2".into(), vec![0.0, 1.0, 0.0, 0.0], json!({"source": "b.pdf"}), None)
+            .add_record_with_text("c-2".into(), "doc".into(), None, vec![0.0, 1.0, 0.0, 0.0], json!({"source": "b.pdf"}), None)
             .unwrap();
 
         let result = builder.build(None).unwrap();
@@ -289,8 +382,8 @@ mod tests {
     #[test]
     fn dimension_mismatch_error() {
         let mut builder = SegmentBuilder::new();
-        builder.add_record_with_text("c-1".into(), vec![1.0, 0.0], json!({}), None).unwrap();
-        let result = builder.add_record_with_text("c-2".into(), vec![1.0, 0.0, 0.0], json!({}), None);
+        builder.add_record_with_text("c-1".into(), "doc".into(), None, vec![1.0, 0.0], json!({}), None).unwrap();
+        let result = builder.add_record_with_text("c-2".into(), "doc".into(), None, vec![1.0, 0.0, 0.0], json!({}), None);
         assert!(matches!(result, Err(AkiDbError::DimensionMismatch { .. })));
     }
 
@@ -304,10 +397,47 @@ mod tests {
     #[test]
     fn header_magic_and_version() {
         let mut builder = SegmentBuilder::new();
-        builder.add_record_with_text("c-1".into(), vec![1.0, 0.0], json!({}), None).unwrap();
+        builder.add_record_with_text("c-1".into(), "doc".into(), None, vec![1.0, 0.0], json!({}), None).unwrap();
         let result = builder.build(None).unwrap();
 
         assert_eq!(&result.buffer[0..4], b"AKDB");
         assert_eq!(u16::from_le_bytes([result.buffer[4], result.buffer[5]]), 2);
     }
+
+    #[test]
+    fn sq8_vector_block_holds_codes_and_fp16_copies() {
+        let mut fp16_builder = SegmentBuilder::new();
+        let mut sq8_builder = SegmentBuilder::with_encoding(VectorEncoding::Sq8);
+        for i in 0..10 {
+            let v = vec![i as f32, 0.5, -1.0, 2.0];
+            fp16_builder.add_record_with_text(format!("c-{i}"), "doc".into(), None, v.clone(), json!({}), None).unwrap();
+            sq8_builder.add_record_with_text(format!("c-{i}"), "doc".into(), None, v, json!({}), None).unwrap();
+        }
+        let fp16_buf = fp16_builder.build(None).unwrap().buffer;
+        let sq8_buf = sq8_builder.build(None).unwrap().buffer;
+
+        let vector_block_len = |buf: &[u8]| {
+            u64::from_le_bytes(buf[22..30].try_into().unwrap())
+                - u64::from_le_bytes(buf[14..22].try_into().unwrap())
+        };
+        assert_eq!(vector_block_len(&fp16_buf), 10 * 4 * 2);
+        assert_eq!(vector_block_len(&sq8_buf), (Sq8Codec::serialized_size(4) + 10 * 4 + 10 * 4 * 2) as u64);
+
+        let flags = |buf: &[u8]| u16::from_le_bytes([buf[62], buf[63]]);
+        assert_eq!(flags(&fp16_buf) & (FLAG_SQ8_VECTORS | FLAG_SQ8_FP16_COPY), 0);
+        assert_eq!(flags(&sq8_buf) & FLAG_SQ8_VECTORS, FLAG_SQ8_VECTORS);
+        assert_eq!(flags(&sq8_buf) & FLAG_SQ8_FP16_COPY, FLAG_SQ8_FP16_COPY);
+    }
+
+    #[test]
+    fn fp32_vector_block_is_four_bytes_per_element() {
+        let mut builder = SegmentBuilder::with_encoding(VectorEncoding::Fp32);
+        builder.add_record_with_text("c-1".into(), "doc".into(), None, vec![1.0, -2.5], json!({}), None).unwrap();
+        let buf = builder.build(None).unwrap().buffer;
+
+        let flags = u16::from_le_bytes([buf[62], buf[63]]);
+        assert_eq!(flags & (FLAG_FP32_VECTORS | FLAG_SQ8_VECTORS), FLAG_FP32_VECTORS);
+        assert_eq!(&buf[HEADER_SIZE..HEADER_SIZE + 4], &1.0_f32.to_le_bytes());
+        assert_eq!(&buf[HEADER_SIZE + 4..HEADER_SIZE + 8], &(-2.5_f32).to_le_bytes());
+    }
 }
@@ -0,0 +1,396 @@
+//! KeywordIndex — per-segment BM25 inverted index over chunk text.
+//!
+//! Built from the TextBlock when a segment is written and stored right after
+//! it (header flag bit 3). Word postings keep token positions so that a query
+//! term which tokenizes to several words ("e-mail") matches as a phrase, and
+//! terms are kept sorted so a prefix ("auth*") is a range scan.
+//! Trigram bitmaps narrow the candidates of the substring fallback used for
+//! scripts without word boundaries.
+//!
+//! Binary layout (KeywordBlock):
+//!   [4B  record_count (u32 LE)]
+//!   [4B  record_length (u32 LE) x record_count]  — word tokens per record
+//!   [4B  term_count (u32 LE)]
+//!   for each term (sorted):
+//!     [4B  term_length (u32 LE)]
+//!     [nB  term (UTF-8)]
+//!     [4B  posting_count (u32 LE)]
+//!     for each posting (ascending record):
+//!       [4B  record (u32 LE)]
+//!       [4B  position_count (u32 LE)]
+//!       [4B  position (u32 LE) x position_count]
+//!   [4B  trigram_count (u32 LE)]
+//!   for each trigram (sorted):
+//!     [4B  trigram_length (u32 LE)]
+//!     [nB  trigram (UTF-8)]
+//!     [4B  bitmap_length (u32 LE)]
+//!     [nB  RoaringBitmap of records (portable serialization)]
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
+
+use roaring::RoaringBitmap;
+
+use crate::text::{self, Analyzer};
+
+/// Occurrences of one term in one record.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Posting {
+    pub record: u32,
+    /// Token positions, ascending.
+    pub positions: Vec<u32>,
+}
+
+/// Inverted index over the chunk texts of one segment.
+#[derive(Debug, Default, Clone)]
+pub struct KeywordIndex {
+    record_lengths: Vec<u32>,
+    total_length: u64,
+    terms: BTreeMap<String, Vec<Posting>>,
+    trigrams: HashMap<String, RoaringBitmap>,
+}
+
+impl KeywordIndex {
+    /// Index `texts`, one per segment record in order, as `analyzer` tokenizes them.
+    pub fn build<'a>(texts: impl IntoIterator<Item = &'a str>, analyzer: &Analyzer) -> Self {
+        let mut index = Self::default();
+        for text in texts {
+            index.push(text, analyzer);
+        }
+        index
+    }
+
+    /// Index `text` as the next record.
+    pub fn push(&mut self, text: &str, analyzer: &Analyzer) {
+        let record = self.record_lengths.len() as u32;
+        let tokens = analyzer.tokenize(text);
+        self.record_lengths.push(tokens.len() as u32);
+        self.total_length += tokens.len() as u64;
+        for (position, token) in tokens.into_iter().enumerate() {
+            let postings = self.terms.entry(token).or_default();
+            match postings.last_mut() {
+                Some(last) if last.record == record => last.positions.push(position as u32),
+                _ => postings.push(Posting { record, positions: vec![position as u32] }),
+            }
+        }
+        for trigram in text::trigrams(text) {
+            self.trigrams.entry(trigram).or_default().insert(record);
+        }
+    }
+
+    /// Number of records (including those without text).
+    pub fn record_count(&self) -> usize {
+        self.record_lengths.len()
+    }
+
+    /// Word tokens in `record`.
+    pub fn record_length(&self, record: u32) -> u32 {
+        self.record_lengths.get(record as usize).copied().unwrap_or(0)
+    }
+
+    /// Word tokens across all records.
+    pub fn total_length(&self) -> u64 {
+        self.total_length
+    }
+
+    /// Records containing `terms` as consecutive tokens, with the number of
+    /// occurrences in each. With `prefix`, the last term matches any indexed
+    /// term it is a prefix of.
+    pub fn phrase_matches(&self, terms: &[String], prefix: bool) -> Vec<(u32, u32)> {
+        self.phrase_positions(terms, prefix)
+            .into_iter()
+            .map(|(record, starts)| (record, starts.len() as u32))
+            .collect()
+    }
+
+    /// Records containing `terms` as consecutive tokens, with the position of
+    /// the first token of each occurrence (ascending).
+    pub fn phrase_positions(&self, terms: &[String], prefix: bool) -> Vec<(u32, Vec<u32>)> {
+        let mut postings = Vec::with_capacity(terms.len());
+        for (i, term) in terms.iter().enumerate() {
+            let term_postings = self.term_postings(term, prefix && i + 1 == terms.len());
+            if term_postings.is_empty() {
+                return Vec::new();
+            }
+            postings.push(term_postings);
+        }
+        let Some((first_postings, rest_postings)) = postings.split_first() else {
+            return Vec::new();
+        };
+
+        first_postings
+            .iter()
+            .filter_map(|posting| {
+                let following: Vec<&Posting> = rest_postings
+                    .iter()
+                    .map(|postings| find_posting(postings, posting.record))
+                    .collect::<Option<_>>()?;
+                let starts: Vec<u32> = posting
+                    .positions
+                    .iter()
+                    .copied()
+                    .filter(|&start| {
+                        following.iter().enumerate().all(|(offset, p)| {
+                            p.positions.binary_search(&(start + offset as u32 + 1)).is_ok()
+                        })
+                    })
+                    .collect();
+                (!starts.is_empty()).then_some((posting.record, starts))
+            })
+            .collect()
+    }
+
+    /// Postings of `term`, or with `prefix` the merged postings of every term
+    /// starting with it.
+    fn term_postings(&self, term: &str, prefix: bool) -> Cow<'_, [Posting]> {
+        if !prefix {
+            return Cow::Borrowed(self.terms.get(term).map_or(&[], Vec::as_slice));
+        }
+        let expanded: Vec<&Vec<Posting>> = self
+            .terms
+            .range::<str, _>((Bound::Included(term), Bound::Unbounded))
+            .take_while(|(candidate, _)| candidate.starts_with(term))
+            .map(|(_, postings)| postings)
+            .collect();
+        if let [only] = expanded.as_slice() {
+            return Cow::Borrowed(only.as_slice());
+        }
+        let mut merged: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+        for posting in expanded.into_iter().flatten() {
+            merged.entry(posting.record).or_default().extend(&posting.positions);
+        }
+        Cow::Owned(
+            merged
+                .into_iter()
+                .map(|(record, mut positions)| {
+                    positions.sort_unstable();
+                    Posting { record, positions }
+                })
+                .collect(),
+        )
+    }
+
+    /// Records whose text contains `trigram` (lowercase), if any do.
+    pub fn trigram_records(&self, trigram: &str) -> Option<&RoaringBitmap> {
+        self.trigrams.get(trigram)
+    }
+
+    /// Approximate heap footprint, for cache accounting.
+    pub fn memory_size_bytes(&self) -> usize {
+        let terms: usize = self
+            .terms
+            .iter()
+            .map(|(term, postings)| {
+                term.len()
+                    + postings
+                        .iter()
+                        .map(|p| std::mem::size_of::<Posting>() + p.positions.len() * 4)
+                        .sum::<usize>()
+            })
+            .sum();
+        let trigrams: usize = self
+            .trigrams
+            .iter()
+            .map(|(trigram, bitmap)| trigram.len() + bitmap.serialized_size())
+            .sum();
+        self.record_lengths.len() * 4 + terms + trigrams
+    }
+
+    /// Serialize into the KeywordBlock layout. Terms and trigrams are written
+    /// in sorted order so identical input yields identical bytes.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_u32(&mut buf, self.record_lengths.len() as u32);
+        for len in &self.record_lengths {
+            write_u32(&mut buf, *len);
+        }
+
+        write_u32(&mut buf, self.terms.len() as u32);
+        for (term, postings) in &self.terms {
+            write_bytes(&mut buf, term.as_bytes());
+            write_u32(&mut buf, postings.len() as u32);
+            for posting in postings {
+                write_u32(&mut buf, posting.record);
+                write_u32(&mut buf, posting.positions.len() as u32);
+                for position in &posting.positions {
+                    write_u32(&mut buf, *position);
+                }
+            }
+        }
+
+        let trigrams: BTreeMap<&String, &RoaringBitmap> = self.trigrams.iter().collect();
+        write_u32(&mut buf, trigrams.len() as u32);
+        for (trigram, bitmap) in trigrams {
+            write_bytes(&mut buf, trigram.as_bytes());
+            let mut bytes = Vec::with_capacity(bitmap.serialized_size());
+            bitmap
+                .serialize_into(&mut bytes)
+                .expect("writing to a Vec cannot fail");
+            write_bytes(&mut buf, &bytes);
+        }
+        buf
+    }
+
+    /// Parse a KeywordBlock. Returns None if the block is truncated or malformed.
+    pub fn deserialize(data: &[u8]) -> Option<Self> {
+        let mut cursor = Cursor { data, offset: 0 };
+
+        let record_count = cursor.read_u32()? as usize;
+        let mut record_lengths = Vec::with_capacity(record_count.min(data.len() / 4));
+        for _ in 0..record_count {
+            record_lengths.push(cursor.read_u32()?);
+        }
+        let total_length = record_lengths.iter().map(|&l| l as u64).sum();
+
+        let term_count = cursor.read_u32()? as usize;
+        let mut terms = BTreeMap::new();
+        for _ in 0..term_count {
+            let term = cursor.read_str()?;
+            let posting_count = cursor.read_u32()? as usize;
+            let mut postings = Vec::with_capacity(posting_count.min(data.len() / 8));
+            for _ in 0..posting_count {
+                let record = cursor.read_u32()?;
+                let position_count = cursor.read_u32()? as usize;
+                let mut positions = Vec::with_capacity(position_count.min(data.len() / 4));
+                for _ in 0..position_count {
+                    positions.push(cursor.read_u32()?);
+                }
+                postings.push(Posting { record, positions });
+            }
+            terms.insert(term, postings);
+        }
+
+        let trigram_count = cursor.read_u32()? as usize;
+        let mut trigrams = HashMap::with_capacity(trigram_count.min(data.len()));
+        for _ in 0..trigram_count {
+            let trigram = cursor.read_str()?;
+            let bitmap = RoaringBitmap::deserialize_from(cursor.read_bytes()?).ok()?;
+            trigrams.insert(trigram, bitmap);
+        }
+
+        Some(Self {
+            record_lengths,
+            total_length,
+            terms,
+            trigrams,
+        })
+    }
+}
+
+// ─── Helpers ────────────────────────────────────────────────────────────────
+
+/// Postings are sorted by record, so a binary search finds a record's entry.
+fn find_posting(postings: &[Posting], record: u32) -> Option<&Posting> {
+    postings
+        .binary_search_by_key(&record, |p| p.record)
+        .ok()
+        .map(|i| &postings[i])
+}
+
+fn write_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    write_u32(buf, bytes.len() as u32);
+    buf.extend_from_slice(bytes);
+}
+
+struct Cursor<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn read_u32(&mut self) -> Option<u32> {
+        let bytes = self.data.get(self.offset..self.offset + 4)?;
+        self.offset += 4;
+        Some(u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn read_bytes(&mut self) -> Option<&'a [u8]> {
+        let len = self.read_u32()? as usize;
+        let bytes = self.data.get(self.offset..self.offset.checked_add(len)?)?;
+        self.offset += len;
+        Some(bytes)
+    }
+
+    fn read_str(&mut self) -> Option<String> {
+        std::str::from_utf8(self.read_bytes()?).ok().map(str::to_string)
+    }
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> KeywordIndex {
+        KeywordIndex::build([
+            "The quick brown fox jumps over the lazy dog",
+            "",
+            "Quick thinking: the fox was quick",
+            "人人生而自由",
+        ], &Analyzer::default())
+    }
+
+    #[test]
+    fn build_counts_tokens_and_positions() {
+        let index = sample();
+        assert_eq!(index.record_count(), 4);
+        assert_eq!(index.record_length(0), 9);
+        assert_eq!(index.record_length(1), 0);
+        assert_eq!(index.total_length(), 9 + 6 + 1);
+        assert_eq!(index.phrase_matches(&["quick".to_string()], false), vec![(0, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn phrase_matches_require_adjacent_tokens() {
+        let index = sample();
+        let phrase = |s: &str| index.phrase_matches(&Analyzer::default().tokenize(s), false);
+        assert_eq!(phrase("quick brown"), vec![(0, 1)]);
+        assert_eq!(phrase("the fox"), vec![(2, 1)]);
+        assert!(phrase("brown quick").is_empty());
+        assert!(phrase("missing").is_empty());
+    }
+
+    #[test]
+    fn prefix_matches_expand_the_last_term() {
+        let index = sample();
+        let phrase = |s: &str| index.phrase_matches(&Analyzer::default().tokenize(s), true);
+        assert_eq!(phrase("qui"), vec![(0, 1), (2, 2)]);
+        assert_eq!(phrase("th"), vec![(0, 2), (2, 2)]);
+        assert_eq!(phrase("brown f"), vec![(0, 1)]);
+        assert!(phrase("quickly").is_empty());
+        assert_eq!(index.phrase_positions(&Analyzer::default().tokenize("the f"), true), vec![(2, vec![2])]);
+    }
+
+    #[test]
+    fn trigram_records_cover_unsegmented_text() {
+        let index = sample();
+        let records = index.trigram_records("生而自").unwrap();
+        assert_eq!(records.iter().collect::<Vec<_>>(), vec![3]);
+        assert!(index.trigram_records("zzz").is_none());
+    }
+
+    #[test]
+    fn serialize_roundtrip() {
+        let index = sample();
+        let bytes = index.serialize();
+        let restored = KeywordIndex::deserialize(&bytes).unwrap();
+        assert_eq!(restored.record_count(), 4);
+        assert_eq!(restored.total_length(), index.total_length());
+        assert_eq!(restored.terms, index.terms);
+        assert_eq!(restored.trigrams, index.trigrams);
+        assert_eq!(restored.serialize(), bytes);
+    }
+
+    #[test]
+    fn deserialize_rejects_truncated_block() {
+        let bytes = sample().serialize();
+        assert!(KeywordIndex::deserialize(&bytes[..bytes.len() - 3]).is_none());
+        assert!(KeywordIndex::deserialize(&[]).is_none());
+    }
+}
@@ -1,4 +1,28 @@
 pub mod bitmap;
 pub mod builder;
 pub mod checksum;
+pub mod keyword;
 pub mod reader;
+
+/// Encoding of a segment's VectorBlock, recorded in the header flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VectorEncoding {
+    /// 2 bytes per element (IEEE 754 half precision).
+    #[default]
+    Fp16,
+    /// 1 byte per element plus per-dimension min/max bounds.
+    Sq8,
+    /// 4 bytes per element (lossless).
+    Fp32,
+}
+
+impl VectorEncoding {
+    /// Map a collection's `quantization` setting to a block encoding.
+    pub fn from_quantization(quantization: &str) -> Self {
+        match quantization {
+            "sq8" => Self::Sq8,
+            "fp32" => Self::Fp32,
+            _ => Self::Fp16,
+        }
+    }
+}
@@ -1,18 +1,34 @@
 //! SegmentReader parses and validates an immutable segment binary buffer.
 //!
 //! Binary layout v2.1 (backward-compatible with v1 and v2):
-//!   [Header 64B][VectorBlock][IDMap][MetadataBlock][BitmapBlock][TextBlock][IndexBlock][Checksum 32B]
+//!   [Header 64B][VectorBlock][IDMap][MetadataBlock][BitmapBlock][TextBlock][KeywordBlock][IndexBlock][Checksum 32B]
+//!
+//! The buffer is either owned or memory-mapped. Only the header is parsed up
+//! front; each block accessor slices and decodes its own byte range on demand,
+//! so a mapped segment only faults in the pages a query actually touches.
+
+use std::ops::Deref;
+
+use memmap2::Mmap;
 
 use crate::error::{AkiDbError, Result};
 use crate::fp16;
 use crate::segment::bitmap::BitmapIndex;
 use crate::segment::checksum::SHA256_BYTES;
+use crate::segment::keyword::KeywordIndex;
+use crate::segment::VectorEncoding;
+use crate::sq8::Sq8Codec;
+use crate::text::Analyzer;
 
 /// Fixed header size in bytes.
 const HEADER_SIZE: usize = 64;
 
 /// Header flags (bytes 62-63).
 const FLAG_TEXT_BLOCK: u16 = 0x0001;
+const FLAG_SQ8_VECTORS: u16 = 0x0002;
+const FLAG_FP32_VECTORS: u16 = 0x0004;
+const FLAG_KEYWORD_INDEX: u16 = 0x0008;
+const FLAG_SQ8_FP16_COPY: u16 = 0x0010;
 
 #[derive(Debug)]
 struct ParsedHeader {
@@ -27,22 +43,59 @@ struct ParsedHeader {
     flags: u16,
 }
 
+/// The ID map block. `doc_ids` and `ordinals` are absent from older segments.
+#[derive(serde::Deserialize)]
+struct IdMap {
+    chunk_ids: Vec<String>,
+    #[serde(default)]
+    doc_ids: Vec<String>,
+    #[serde(default)]
+    ordinals: Vec<Option<u32>>,
+}
+
+/// Backing bytes of a segment: an owned buffer or a read-only file mapping.
+enum SegmentBytes {
+    Owned(Vec<u8>),
+    Mapped(Mmap),
+}
+
+impl Deref for SegmentBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Self::Owned(data) => data,
+            Self::Mapped(map) => map,
+        }
+    }
+}
+
 /// Read-only view over a segment binary buffer.
 pub struct SegmentReader {
-    data: Vec<u8>,
+    data: SegmentBytes,
     header: ParsedHeader,
 }
 
 impl SegmentReader {
-    /// Parse a segment buffer and validate magic bytes.
+    /// Parse a segment buffer and validate magic bytes and block offsets.
     /// Does NOT verify the checksum automatically — call `validate_checksum()`.
     pub fn from_buffer(data: Vec<u8>) -> Result<Self> {
+        Self::from_bytes(SegmentBytes::Owned(data))
+    }
+
+    /// Parse a memory-mapped segment. Only the header page is read here.
+    pub fn from_mmap(map: Mmap) -> Result<Self> {
+        Self::from_bytes(SegmentBytes::Mapped(map))
+    }
+
+    fn from_bytes(data: SegmentBytes) -> Result<Self> {
         if data.len() < HEADER_SIZE + SHA256_BYTES {
             return Err(AkiDbError::Storage(
                 "Buffer too small to be a valid segment".into(),
             ));
         }
         let header = parse_header(&data)?;
+        validate_offsets(&header, data.len())?;
         Ok(Self { data, header })
     }
 
@@ -51,45 +104,147 @@ impl SegmentReader {
         self.header.dimension
     }
 
-    /// Decode all vectors from the FP16 vector block.
+    /// Encoding of the vector block (flags bits 1-2).
+    pub fn vector_encoding(&self) -> VectorEncoding {
+        if self.header.flags & FLAG_SQ8_VECTORS != 0 {
+            VectorEncoding::Sq8
+        } else if self.header.flags & FLAG_FP32_VECTORS != 0 {
+            VectorEncoding::Fp32
+        } else {
+            VectorEncoding::Fp16
+        }
+    }
+
+    /// Whether `get_fp16_codes` can serve this segment: FP16 segments and SQ8
+    /// segments that carry FP16 copies alongside their codes.
+    pub fn has_fp16_codes(&self) -> bool {
+        match self.vector_encoding() {
+            VectorEncoding::Fp16 => true,
+            VectorEncoding::Sq8 => self.header.flags & FLAG_SQ8_FP16_COPY != 0,
+            VectorEncoding::Fp32 => false,
+        }
+    }
+
+    /// Decode all vectors from the vector block (FP16, SQ8 or FP32). SQ8
+    /// segments with FP16 copies decode those rather than the codes.
     pub fn get_vectors(&self) -> Result<Vec<Vec<f32>>> {
+        let block = self.vector_block()?;
+        Ok((0..self.header.record_count as usize)
+            .map(|i| block.decode(i))
+            .collect())
+    }
+
+    /// Decode a single vector from the VectorBlock without touching the rest.
+    pub fn get_vector(&self, index: usize) -> Result<Vec<f32>> {
+        if index >= self.header.record_count as usize {
+            return Err(AkiDbError::InvalidArgument(format!(
+                "Vector index {index} out of range for {} records",
+                self.header.record_count
+            )));
+        }
+        Ok(self.vector_block()?.decode(index))
+    }
+
+    /// Borrow one record's raw FP16 codes (little-endian), for distance
+    /// kernels that score FP16 directly: the VectorBlock of an FP16 segment,
+    /// or the FP16 copies of an SQ8 segment. Errors when there are neither.
+    pub fn get_fp16_codes(&self, index: usize) -> Result<&[u8]> {
+        if !self.has_fp16_codes() {
+            return Err(AkiDbError::InvalidArgument(
+                "Raw FP16 codes requested from a segment without FP16 vectors".to_string(),
+            ));
+        }
+        if index >= self.header.record_count as usize {
+            return Err(AkiDbError::InvalidArgument(format!(
+                "Vector index {index} out of range for {} records",
+                self.header.record_count
+            )));
+        }
+        let block = self.vector_block()?;
+        let bytes = self.header.dimension as usize * 2;
+        let codes = block.fp16_copy.unwrap_or(block.codes);
+        Ok(&codes[index * bytes..(index + 1) * bytes])
+    }
+
+    /// The SQ8 codec and every record's codes, row-major. Errors for other
+    /// encodings.
+    pub fn get_sq8_codes(&self) -> Result<(Sq8Codec, &[u8])> {
+        let block = self.vector_block()?;
+        let Some(codec) = block.codec else {
+            return Err(AkiDbError::InvalidArgument(
+                "SQ8 codes requested from a non-SQ8 vector block".to_string(),
+            ));
+        };
+        let len = self.header.record_count as usize * block.bytes_per_vector;
+        Ok((codec, &block.codes[..len]))
+    }
+
+    /// Borrow the VectorBlock codes, validated against the record count.
+    fn vector_block(&self) -> Result<VectorBlock<'_>> {
         let start = self.header.vector_block_offset as usize;
         let end = self.header.id_map_offset as usize;
         let block = &self.data[start..end];
+        let dimension = self.header.dimension as usize;
+
+        // SQ8 blocks carry per-dimension bounds ahead of the codes.
+        let encoding = self.vector_encoding();
+        let (codec, codes, bytes_per_vector) = match encoding {
+            VectorEncoding::Fp16 => (None, block, dimension * 2),
+            VectorEncoding::Fp32 => (None, block, dimension * 4),
+            VectorEncoding::Sq8 => {
+                let params_len = Sq8Codec::serialized_size(dimension);
+                let codec = Sq8Codec::deserialize(block, dimension).ok_or_else(|| {
+                    AkiDbError::Storage(format!(
+                        "SQ8 vector block too small for bounds: need {params_len} bytes, have {}",
+                        block.len()
+                    ))
+                })?;
+                (Some(codec), &block[params_len..], dimension)
+            }
+        };
 
-        let bytes_per_vector = self.header.dimension as usize * 2; // FP16 = 2 bytes per element
-        let expected_bytes = self.header.record_count as usize * bytes_per_vector;
-        if block.len() < expected_bytes {
+        let record_count = self.header.record_count as usize;
+        let has_copy = encoding == VectorEncoding::Sq8 && self.header.flags & FLAG_SQ8_FP16_COPY != 0;
+        let copy_bytes = if has_copy { record_count * dimension * 2 } else { 0 };
+        let expected_bytes = record_count * bytes_per_vector + copy_bytes;
+        if codes.len() < expected_bytes {
             return Err(AkiDbError::Storage(format!(
                 "Vector block too small: need {expected_bytes} bytes, have {}",
-                block.len()
+                codes.len()
             )));
         }
+        let fp16_copy = has_copy.then(|| &codes[record_count * bytes_per_vector..expected_bytes]);
 
-        let mut results = Vec::with_capacity(self.header.record_count as usize);
-        for i in 0..self.header.record_count as usize {
-            let offset = i * bytes_per_vector;
-            let slice = &block[offset..offset + bytes_per_vector];
-            results.push(fp16::decode_fp16_vector(slice));
-        }
-
-        Ok(results)
+        Ok(VectorBlock { encoding, codec, codes, bytes_per_vector, fp16_copy })
     }
 
     /// Retrieve the ordered chunk ID list from the ID map block.
     pub fn get_chunk_ids(&self) -> Result<Vec<String>> {
+        Ok(self.read_id_map()?.chunk_ids)
+    }
+
+    /// Retrieve each record's document ID, in chunk ID order. Segments
+    /// written before doc IDs were stored yield empty strings.
+    pub fn get_doc_ids(&self) -> Result<Vec<String>> {
+        let IdMap { chunk_ids, mut doc_ids, .. } = self.read_id_map()?;
+        doc_ids.resize(chunk_ids.len(), String::new());
+        Ok(doc_ids)
+    }
+
+    /// Retrieve each record's position in its document, in chunk ID order.
+    /// None where the writer gave no ordinal or the segment predates them.
+    pub fn get_ordinals(&self) -> Result<Vec<Option<u32>>> {
+        let IdMap { chunk_ids, mut ordinals, .. } = self.read_id_map()?;
+        ordinals.resize(chunk_ids.len(), None);
+        Ok(ordinals)
+    }
+
+    fn read_id_map(&self) -> Result<IdMap> {
         let start = self.header.id_map_offset as usize;
         let end = self.header.metadata_offset as usize;
         let json_str = std::str::from_utf8(&self.data[start..end])
             .map_err(|e| AkiDbError::Serialization(e.to_string()))?;
-
-        #[derive(serde::Deserialize)]
-        struct IdMap {
-            chunk_ids: Vec<String>,
-        }
-
-        let parsed: IdMap = serde_json::from_str(json_str)?;
-        Ok(parsed.chunk_ids)
+        Ok(serde_json::from_str(json_str)?)
     }
 
     /// Retrieve per-record metadata from the metadata block.
@@ -183,6 +338,43 @@ impl SegmentReader {
         Some(texts)
     }
 
+    /// Whether this segment stores a KeywordBlock (flags bit 3).
+    pub fn has_keyword_index(&self) -> bool {
+        self.header.flags & FLAG_KEYWORD_INDEX != 0
+    }
+
+    /// Retrieve the BM25 keyword index. Segments written before the
+    /// KeywordBlock existed have it rebuilt from their TextBlock; those
+    /// predate per-collection analyzers, so the default analyzer applies.
+    /// Returns None if the segment has no text.
+    pub fn get_keyword_index(&self) -> Result<Option<KeywordIndex>> {
+        if !self.has_keyword_index() {
+            return Ok(self
+                .get_chunk_texts()
+                .map(|texts| KeywordIndex::build(texts.iter().map(String::as_str), &Analyzer::default())));
+        }
+        let start = self.text_block_end().ok_or_else(|| {
+            AkiDbError::Storage("TextBlock overruns the keyword index block".into())
+        })?;
+        let block = &self.data[start..self.header.index_offset as usize];
+        KeywordIndex::deserialize(block)
+            .map(Some)
+            .ok_or_else(|| AkiDbError::Storage("Corrupt keyword index block".into()))
+    }
+
+    /// End of the TextBlock, found by walking its length prefixes.
+    fn text_block_end(&self) -> Option<usize> {
+        let start = self.text_block_start();
+        let block = &self.data[start..self.header.index_offset as usize];
+        let count = u32::from_le_bytes(block.get(0..4)?.try_into().unwrap()) as usize;
+        let mut offset = 4;
+        for _ in 0..count {
+            let len = u32::from_le_bytes(block.get(offset..offset + 4)?.try_into().unwrap()) as usize;
+            offset = offset.checked_add(4 + len).filter(|&end| end <= block.len())?;
+        }
+        Some(start + offset)
+    }
+
     /// Compute the start of the TextBlock.
     /// TextBlock sits between BitmapBlock and IndexBlock.
     /// Uses BitmapIndex::serialized_size to find bitmap end.
@@ -201,6 +393,55 @@ impl SegmentReader {
 
 // ── Internal helpers ─────────────────────────────────────────────────────────
 
+/// Validated view over a VectorBlock's per-record codes.
+struct VectorBlock<'a> {
+    encoding: VectorEncoding,
+    codec: Option<Sq8Codec>,
+    codes: &'a [u8],
+    bytes_per_vector: usize,
+    /// FP16 copies trailing the codes of an SQ8 block, when present.
+    fp16_copy: Option<&'a [u8]>,
+}
+
+impl VectorBlock<'_> {
+    fn decode(&self, index: usize) -> Vec<f32> {
+        if let Some(copy) = self.fp16_copy {
+            let bytes = self.bytes_per_vector * 2;
+            return fp16::decode_fp16_vector(&copy[index * bytes..(index + 1) * bytes]);
+        }
+        let offset = index * self.bytes_per_vector;
+        let slice = &self.codes[offset..offset + self.bytes_per_vector];
+        match (&self.codec, self.encoding) {
+            (Some(codec), _) => codec.decode(slice),
+            (None, VectorEncoding::Fp32) => slice
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+                .collect(),
+            (None, _) => fp16::decode_fp16_vector(slice),
+        }
+    }
+}
+
+/// Reject headers whose block offsets are out of order or past the buffer end,
+/// so block accessors can slice without bounds panics.
+fn validate_offsets(header: &ParsedHeader, len: usize) -> Result<()> {
+    let mut offsets = vec![
+        header.vector_block_offset,
+        header.id_map_offset,
+        header.metadata_offset,
+    ];
+    if header.bitmap_offset > 0 {
+        offsets.push(header.bitmap_offset);
+    }
+    offsets.extend([header.index_offset, header.checksum_offset, len as u64]);
+    if header.vector_block_offset < HEADER_SIZE as u64 || offsets.windows(2).any(|w| w[0] > w[1]) {
+        return Err(AkiDbError::Storage(format!(
+            "Corrupt segment header: block offsets {offsets:?} are not ordered within the buffer"
+        )));
+    }
+    Ok(())
+}
+
 fn parse_header(data: &[u8]) -> Result<ParsedHeader> {
     if &data[0..4] != b"AKDB" {
         let magic = std::str::from_utf8(&data[0..4]).unwrap_or("????");
@@ -243,13 +484,13 @@ mod tests {
     fn build_test_segment() -> Vec<u8> {
         let mut builder = SegmentBuilder::new();
         builder
-            .add_record_with_text("c-1".into(), vec![1.0, 0.0, 0.0, 0.0], json!({"source": "a.pdf"}), None)
+            .add_record_with_text("c-1".into(), "doc-a".into(), Some(0), vec![1.0, 0.0, 0.0, 0.0], json!({"source": "a.pdf"}), None)
             .unwrap();
         builder
-            .add_record_with_text("c-2".into(), vec![0.0, 1.0, 0.0, 0.0], json!({"source": "b.pdf"}), None)
+            .add_record_with_text("c-2".into(), "doc-b".into(), None, vec![0.0, 1.0, 0.0, 0.0], json!({"source": "b.pdf"}), None)
             .unwrap();
         builder
-            .add_record_with_text("c-3".into(), vec![0.5, 0.5, 0.0, 0.0], json!({"source": "a.pdf"}), None)
+            .add_record_with_text("c-3".into(), "doc-a".into(), Some(1), vec![0.5, 0.5, 0.0, 0.0], json!({"source": "a.pdf"}), None)
             .unwrap();
         builder.build(None).unwrap().buffer
     }
@@ -261,12 +502,39 @@ mod tests {
         assert_eq!(reader.dimension(), 4);
     }
 
+    #[test]
+    fn from_mmap_matches_from_buffer() {
+        let buf = build_test_segment();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("seg.bin");
+        std::fs::write(&path, &buf).unwrap();
+        let file = std::fs::File::open(&path).unwrap();
+        let map = unsafe { Mmap::map(&file) }.unwrap();
+
+        let mapped = SegmentReader::from_mmap(map).unwrap();
+        let owned = SegmentReader::from_buffer(buf).unwrap();
+        assert_eq!(mapped.get_chunk_ids().unwrap(), owned.get_chunk_ids().unwrap());
+        assert_eq!(mapped.get_vectors().unwrap(), owned.get_vectors().unwrap());
+        assert_eq!(mapped.get_metadata().unwrap(), owned.get_metadata().unwrap());
+    }
+
+    #[test]
+    fn out_of_order_offsets_are_rejected() {
+        let mut buf = build_test_segment();
+        // Point the ID map past the checksum.
+        let bogus = (buf.len() as u64 + 1).to_le_bytes();
+        buf[22..30].copy_from_slice(&bogus);
+        assert!(SegmentReader::from_buffer(buf).is_err());
+    }
+
     #[test]
     fn get_chunk_ids() {
         let buf = build_test_segment();
         let reader = SegmentReader::from_buffer(buf).unwrap();
         let ids = reader.get_chunk_ids().unwrap();
         assert_eq!(ids, vec!["c-1", "c-2", "c-3"]);
+        assert_eq!(reader.get_doc_ids().unwrap(), vec!["doc-a", "doc-b", "doc-a"]);
+        assert_eq!(reader.get_ordinals().unwrap(), vec![Some(0), None, Some(1)]);
     }
 
     #[test]
@@ -281,6 +549,20 @@ mod tests {
         assert!((vectors[1][1] - 1.0).abs() < 0.01);
     }
 
+    #[test]
+    fn get_vector_decodes_single_record() {
+        let buf = build_test_segment();
+        let reader = SegmentReader::from_buffer(buf).unwrap();
+        let vectors = reader.get_vectors().unwrap();
+        assert_eq!(reader.get_vector(1).unwrap(), vectors[1]);
+        assert!(reader.get_vector(3).is_err());
+        assert_eq!(
+            fp16::decode_fp16_vector(reader.get_fp16_codes(1).unwrap()),
+            vectors[1]
+        );
+        assert!(reader.get_fp16_codes(3).is_err());
+    }
+
     #[test]
     fn get_metadata() {
         let buf = build_test_segment();
@@ -313,7 +595,7 @@ mod tests {
     fn build_with_index_data() {
         let mut builder = SegmentBuilder::new();
         builder
-            .add_record_with_text("c-1".into(), vec![1.0, 0.0], json!({}), None)
+            .add_record_with_text("c-1".into(), "doc".into(), None, vec![1.0, 0.0], json!({}), None)
             .unwrap();
 
         let fake_index = vec![1u8, 2, 3, 4, 5];
@@ -330,6 +612,8 @@ mod tests {
         builder
             .add_record_with_text(
                 "c-1".into(),
+                "doc".into(),
+                None,
                 vec![1.0, 0.0, 0.0, 0.0],
                 json!({"source": "a.pdf"}),
                 Some("Hello world from document A.".into()),
@@ -338,6 +622,8 @@ mod tests {
         builder
             .add_record_with_text(
                 "c-2".into(),
+                "doc".into(),
+                None,
                 vec![0.0, 1.0, 0.0, 0.0],
                 json!({"source": "b.pdf"}),
                 Some("Another chunk of text from B.".into()),
@@ -346,6 +632,8 @@ mod tests {
         builder
             .add_record_with_text(
                 "c-3".into(),
+                "doc".into(),
+                None,
                 vec![0.5, 0.5, 0.0, 0.0],
                 json!({"source": "a.pdf"}),
                 None, // no text for this record
@@ -386,6 +674,8 @@ mod tests {
         builder
             .add_record_with_text(
                 "c-1".into(),
+                "doc".into(),
+                None,
                 vec![1.0, 0.0],
                 json!({"type": "pdf"}),
                 Some("PDF content here".into()),
@@ -416,6 +706,8 @@ mod tests {
         builder
             .add_record_with_text(
                 "c-1".into(),
+                "doc".into(),
+                None,
                 vec![1.0, 0.0],
                 json!({}),
                 Some("日本語テスト 🎉".into()),
@@ -427,4 +719,94 @@ mod tests {
         let texts = reader.get_chunk_texts().unwrap();
         assert_eq!(texts[0], "日本語テスト 🎉");
     }
+
+    #[test]
+    fn keyword_index_stored_after_text_block() {
+        let buf = build_test_segment_with_text();
+        let reader = SegmentReader::from_buffer(buf.clone()).unwrap();
+        assert!(reader.has_keyword_index());
+        let index = reader.get_keyword_index().unwrap().unwrap();
+        assert_eq!(index.record_count(), 3);
+        assert_eq!(index.phrase_matches(&["from".to_string()], false), vec![(0, 1), (1, 1)]);
+        assert_eq!(reader.get_chunk_texts().unwrap().len(), 3);
+
+        // Segments written before the KeywordBlock rebuild it from the text.
+        let mut legacy = buf;
+        legacy[62] &= !(FLAG_KEYWORD_INDEX as u8);
+        let reader = SegmentReader::from_buffer(legacy).unwrap();
+        assert!(!reader.has_keyword_index());
+        let rebuilt = reader.get_keyword_index().unwrap().unwrap();
+        assert_eq!(rebuilt.phrase_matches(&["document".to_string()], false), vec![(0, 1)]);
+
+        let no_text = SegmentReader::from_buffer(build_test_segment()).unwrap();
+        assert!(!no_text.has_keyword_index());
+        assert!(no_text.get_keyword_index().unwrap().is_none());
+    }
+
+    // ── SQ8 vector block tests ──────────────────────────────────────────────
+
+    #[test]
+    fn sq8_vectors_roundtrip() {
+        let vectors = [
+            vec![1.0, -0.5, 0.25, 3.0],
+            vec![-2.0, 0.5, 0.75, 3.0],
+            vec![0.1, 0.0, -0.25, 3.0],
+        ];
+        let mut builder = SegmentBuilder::with_encoding(VectorEncoding::Sq8);
+        for (i, v) in vectors.iter().enumerate() {
+            builder
+                .add_record_with_text(format!("c-{i}"), "doc".into(), None, v.clone(), json!({"i": i}), Some(format!("text {i}")))
+                .unwrap();
+        }
+        let buf = builder.build(None).unwrap().buffer;
+        let reader = SegmentReader::from_buffer(buf.clone()).unwrap();
+
+        assert_eq!(reader.vector_encoding(), VectorEncoding::Sq8);
+        assert!(reader.has_text_block());
+        assert!(reader.has_fp16_codes());
+        // Vectors decode from the FP16 copies, not the 8-bit codes.
+        let decoded = reader.get_vectors().unwrap();
+        let expected: Vec<Vec<f32>> =
+            vectors.iter().map(|v| fp16::decode_fp16_vector(&fp16::encode_fp16_vector(v))).collect();
+        assert_eq!(decoded, expected);
+        assert_eq!(fp16::decode_fp16_vector(reader.get_fp16_codes(1).unwrap()), expected[1]);
+        let (_, codes) = reader.get_sq8_codes().unwrap();
+        assert_eq!(codes.len(), 3 * 4);
+        assert_eq!(reader.get_metadata().unwrap()[2]["i"], 2);
+
+        // Segments written before the FP16 copies decode the codes.
+        let mut legacy = buf;
+        legacy[62] &= !(FLAG_SQ8_FP16_COPY as u8);
+        let reader = SegmentReader::from_buffer(legacy).unwrap();
+        assert!(!reader.has_fp16_codes());
+        assert!(reader.get_fp16_codes(0).is_err());
+        let decoded = reader.get_vectors().unwrap();
+        assert_eq!(decoded.len(), 3);
+        for (orig, dec) in vectors.iter().zip(&decoded) {
+            for (o, d) in orig.iter().zip(dec) {
+                // Half a quantization step of the widest (3.0) range.
+                assert!((o - d).abs() < 3.0 / 255.0, "orig={o}, decoded={d}");
+            }
+        }
+    }
+
+    #[test]
+    fn fp32_vectors_are_lossless() {
+        let vectors = [vec![1e6_f32, -1.234_567_9, 1e-7, 65_519.5], vec![0.1, 0.2, 0.3, 0.4]];
+        let mut builder = SegmentBuilder::with_encoding(VectorEncoding::Fp32);
+        for (i, v) in vectors.iter().enumerate() {
+            builder.add_record_with_text(format!("c-{i}"), "doc".into(), None, v.clone(), json!({}), None).unwrap();
+        }
+        let reader = SegmentReader::from_buffer(builder.build(None).unwrap().buffer).unwrap();
+
+        assert_eq!(reader.vector_encoding(), VectorEncoding::Fp32);
+        assert_eq!(reader.get_vectors().unwrap(), vectors);
+        assert_eq!(reader.get_chunk_ids().unwrap(), vec!["c-0", "c-1"]);
+    }
+
+    #[test]
+    fn fp16_segments_report_fp16_encoding() {
+        let reader = SegmentReader::from_buffer(build_test_segment()).unwrap();
+        assert_eq!(reader.vector_encoding(), VectorEncoding::Fp16);
+    }
 }
@@ -0,0 +1,338 @@
+//! 8-bit scalar quantization (SQ8).
+//!
+//! Each dimension is mapped linearly from its `[min, max]` range onto the
+//! 256 code values, so a vector costs one byte per element instead of two
+//! (FP16) or four (FP32). Distances are computed asymmetrically: the query
+//! stays in full precision and codes are dequantized inline by the kernels.
+
+/// Number of quantization steps per dimension.
+const SQ8_LEVELS: f32 = 255.0;
+
+/// Per-dimension affine codec: `value = min + code * scale`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sq8Codec {
+    min: Vec<f32>,
+    scale: Vec<f32>,
+}
+
+impl Sq8Codec {
+    /// Derive per-dimension bounds from a batch of vectors.
+    pub fn train<'a, I>(vectors: I, dimension: usize) -> Self
+    where
+        I: IntoIterator<Item = &'a Vec<f32>>,
+    {
+        let mut min = vec![f32::INFINITY; dimension];
+        let mut max = vec![f32::NEG_INFINITY; dimension];
+        let mut count = 0usize;
+        for v in vectors {
+            for (d, &x) in v.iter().enumerate().take(dimension) {
+                min[d] = min[d].min(x);
+                max[d] = max[d].max(x);
+            }
+            count += 1;
+        }
+        if count == 0 {
+            min.fill(0.0);
+            max.fill(0.0);
+        }
+        Self::from_bounds(min, max)
+    }
+
+    /// Rebuild a codec from stored per-dimension bounds.
+    pub fn from_bounds(min: Vec<f32>, max: Vec<f32>) -> Self {
+        let scale = min
+            .iter()
+            .zip(&max)
+            .map(|(&lo, &hi)| if hi > lo { (hi - lo) / SQ8_LEVELS } else { 0.0 })
+            .collect();
+        Self { min, scale }
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.min.len()
+    }
+
+    /// Per-dimension upper bounds.
+    pub fn max(&self) -> Vec<f32> {
+        self.min
+            .iter()
+            .zip(&self.scale)
+            .map(|(&lo, &s)| lo + s * SQ8_LEVELS)
+            .collect()
+    }
+
+    /// Quantize a vector to one code per dimension (values outside the
+    /// trained range are clamped).
+    pub fn encode(&self, values: &[f32]) -> Vec<u8> {
+        values
+            .iter()
+            .zip(self.min.iter().zip(&self.scale))
+            .map(|(&x, (&lo, &s))| {
+                if s == 0.0 {
+                    0
+                } else {
+                    ((x - lo) / s).round().clamp(0.0, SQ8_LEVELS) as u8
+                }
+            })
+            .collect()
+    }
+
+    /// Dequantize codes back to approximate f32 values.
+    pub fn decode(&self, codes: &[u8]) -> Vec<f32> {
+        codes
+            .iter()
+            .zip(self.min.iter().zip(&self.scale))
+            .map(|(&c, (&lo, &s))| lo + c as f32 * s)
+            .collect()
+    }
+
+    /// Euclidean distance between a full-precision query and a code vector.
+    #[inline]
+    pub fn l2_distance(&self, query: &[f32], codes: &[u8]) -> f32 {
+        debug_assert_eq!(query.len(), codes.len());
+        let mut sum = 0.0_f32;
+        for (i, &c) in codes.iter().enumerate() {
+            let d = query[i] - (self.min[i] + c as f32 * self.scale[i]);
+            sum += d * d;
+        }
+        sum.sqrt()
+    }
+
+    /// Dot product between a full-precision query and a code vector.
+    #[inline]
+    pub fn dot_product(&self, query: &[f32], codes: &[u8]) -> f32 {
+        debug_assert_eq!(query.len(), codes.len());
+        let mut sum = 0.0_f32;
+        for (i, &c) in codes.iter().enumerate() {
+            sum += query[i] * (self.min[i] + c as f32 * self.scale[i]);
+        }
+        sum
+    }
+
+    /// Serialize bounds as `[min f32 × dim][max f32 × dim]` (little-endian).
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(Self::serialized_size(self.dimension()));
+        for v in self.min.iter().chain(self.max().iter()) {
+            buf.extend_from_slice(&v.to_le_bytes());
+        }
+        buf
+    }
+
+    /// Parse bounds written by [`Sq8Codec::serialize`].
+    pub fn deserialize(data: &[u8], dimension: usize) -> Option<Self> {
+        if data.len() < Self::serialized_size(dimension) {
+            return None;
+        }
+        let read = |i: usize| f32::from_le_bytes(data[i * 4..i * 4 + 4].try_into().unwrap());
+        let min = (0..dimension).map(read).collect();
+        let max = (dimension..dimension * 2).map(read).collect();
+        Some(Self::from_bounds(min, max))
+    }
+
+    /// Size of the serialized bounds for `dimension`.
+    pub fn serialized_size(dimension: usize) -> usize {
+        dimension * 2 * 4
+    }
+}
+
+/// Cosine distance on codes. Expects the query and the encoded vectors to be
+/// **pre-normalized**.
+#[inline]
+fn sq8_cosine_distance(codec: &Sq8Codec, query: &[f32], codes: &[u8]) -> f32 {
+    1.0 - codec.dot_product(query, codes)
+}
+
+#[inline]
+fn sq8_l2_distance(codec: &Sq8Codec, query: &[f32], codes: &[u8]) -> f32 {
+    codec.l2_distance(query, codes)
+}
+
+#[inline]
+fn sq8_dot_distance(codec: &Sq8Codec, query: &[f32], codes: &[u8]) -> f32 {
+    -codec.dot_product(query, codes)
+}
+
+/// Get the quantized distance kernel for a metric name. Matches the
+/// semantics of [`crate::distance::get_distance_fn`] (lower = closer).
+pub fn get_sq8_distance_fn(metric: &str) -> fn(&Sq8Codec, &[f32], &[u8]) -> f32 {
+    match metric {
+        "cosine" => sq8_cosine_distance,
+        "l2" => sq8_l2_distance,
+        "dot" => sq8_dot_distance,
+        _ => sq8_l2_distance,
+    }
+}
+
+// ─── Flat code store ────────────────────────────────────────────────────────
+
+/// A dense, row-major matrix of SQ8 codes sharing one codec.
+#[derive(Clone)]
+pub struct Sq8Vectors {
+    codec: Sq8Codec,
+    codes: Vec<u8>,
+    dimension: usize,
+    distance_fn: fn(&Sq8Codec, &[f32], &[u8]) -> f32,
+    /// Cosine over codes of unnormalized vectors: 1 / |decoded vector| per
+    /// row, applied to the dot product instead of normalizing before encoding.
+    inv_norms: Option<Vec<f32>>,
+}
+
+impl Sq8Vectors {
+    /// Train a codec on `vectors` and encode them.
+    pub fn encode(metric: &str, dimension: usize, vectors: &[Vec<f32>]) -> Self {
+        let codec = Sq8Codec::train(vectors, dimension);
+        let mut codes = Vec::with_capacity(vectors.len() * dimension);
+        for v in vectors {
+            codes.extend_from_slice(&codec.encode(v));
+        }
+        Self {
+            codec,
+            codes,
+            dimension,
+            distance_fn: get_sq8_distance_fn(metric),
+            inv_norms: None,
+        }
+    }
+
+    /// Wrap codes already encoded with `codec`, such as a segment's SQ8
+    /// VectorBlock. Those encode the vectors as stored, so cosine distances
+    /// divide by each decoded vector's norm.
+    pub fn from_codes(metric: &str, dimension: usize, codec: Sq8Codec, codes: Vec<u8>) -> Self {
+        let inv_norms = (metric == "cosine").then(|| {
+            codes
+                .chunks_exact(dimension.max(1))
+                .map(|row| {
+                    let norm = codec.decode(row).iter().map(|x| x * x).sum::<f32>().sqrt();
+                    if norm > 0.0 { 1.0 / norm } else { 0.0 }
+                })
+                .collect()
+        });
+        Self {
+            codec,
+            codes,
+            dimension,
+            distance_fn: get_sq8_distance_fn(metric),
+            inv_norms,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.codes.len().checked_div(self.dimension).unwrap_or(0)
+    }
+
+    /// Distance from a (pre-processed) query to vector `index`.
+    #[inline]
+    pub fn distance(&self, query: &[f32], index: usize) -> f32 {
+        let start = index * self.dimension;
+        let codes = &self.codes[start..start + self.dimension];
+        match &self.inv_norms {
+            Some(inv_norms) => 1.0 - self.codec.dot_product(query, codes) * inv_norms[index],
+            None => (self.distance_fn)(&self.codec, query, codes),
+        }
+    }
+
+    /// Dequantize vector `index` (normalized for cosine).
+    pub fn decode(&self, index: usize) -> Vec<f32> {
+        let start = index * self.dimension;
+        let decoded = self.codec.decode(&self.codes[start..start + self.dimension]);
+        match &self.inv_norms {
+            Some(inv_norms) => decoded.iter().map(|x| x * inv_norms[index]).collect(),
+            None => decoded,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distance;
+
+    /// Quantization error bound for a single dimension (half a step).
+    fn max_error(codec: &Sq8Codec, dim: usize) -> f32 {
+        codec.scale[dim] / 2.0 + 1e-6
+    }
+
+    fn sample_vectors() -> Vec<Vec<f32>> {
+        vec![
+            vec![1.0, -2.0, 0.5, 0.0],
+            vec![-1.0, 2.0, 0.25, 0.0],
+            vec![0.3, 0.7, -0.5, 0.0],
+        ]
+    }
+
+    #[test]
+    fn encode_decode_within_half_step() {
+        let vectors = sample_vectors();
+        let codec = Sq8Codec::train(&vectors, 4);
+        for v in &vectors {
+            let decoded = codec.decode(&codec.encode(v));
+            for d in 0..4 {
+                assert!(
+                    (decoded[d] - v[d]).abs() <= max_error(&codec, d),
+                    "dim={d}, orig={}, decoded={}",
+                    v[d],
+                    decoded[d]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn bounds_roundtrip() {
+        let codec = Sq8Codec::train(&sample_vectors(), 4);
+        assert_eq!(codec.min, vec![-1.0, -2.0, -0.5, 0.0]);
+        let restored = Sq8Codec::deserialize(&codec.serialize(), 4).unwrap();
+        assert_eq!(restored, codec);
+        assert!(Sq8Codec::deserialize(&[0u8; 8], 4).is_none());
+    }
+
+    #[test]
+    fn out_of_range_values_clamp() {
+        let codec = Sq8Codec::train(&sample_vectors(), 4);
+        assert_eq!(codec.encode(&[10.0, -10.0, 0.5, 3.0]), vec![255, 0, 255, 0]);
+    }
+
+    #[test]
+    fn kernels_match_decoded_distances() {
+        let vectors = sample_vectors();
+        let codec = Sq8Codec::train(&vectors, 4);
+        let query = [0.2_f32, -0.4, 0.9, 0.1];
+        for v in &vectors {
+            let codes = codec.encode(v);
+            let decoded = codec.decode(&codes);
+            let l2 = codec.l2_distance(&query, &codes);
+            assert!((l2 - distance::l2_distance(&query, &decoded)).abs() < 1e-5);
+            let dot = codec.dot_product(&query, &codes);
+            assert!((dot - distance::dot_product(&query, &decoded)).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn flat_store_ranks_like_full_precision() {
+        let vectors: Vec<Vec<f32>> = (0..20)
+            .map(|i| vec![i as f32, (20 - i) as f32 * 0.5, 1.0])
+            .collect();
+        let store = Sq8Vectors::encode("l2", 3, &vectors);
+        assert_eq!(store.len(), 20);
+        let query = [7.2_f32, 6.4, 1.0];
+        let nearest = (0..store.len())
+            .min_by(|&a, &b| store.distance(&query, a).total_cmp(&store.distance(&query, b)))
+            .unwrap();
+        assert_eq!(nearest, 7);
+    }
+
+    #[test]
+    fn cosine_over_stored_codes_matches_normalized_distance() {
+        let vectors: Vec<Vec<f32>> = (1..12).map(|i| vec![i as f32, 12.0 - i as f32, 3.0]).collect();
+        let codec = Sq8Codec::train(&vectors, 3);
+        let codes: Vec<u8> = vectors.iter().flat_map(|v| codec.encode(v)).collect();
+        let store = Sq8Vectors::from_codes("cosine", 3, codec, codes);
+        let query = distance::normalize(&[4.0, 8.0, 3.0]);
+        for (i, v) in vectors.iter().enumerate() {
+            let exact = distance::get_distance_fn("cosine")(&query, &distance::normalize(v));
+            assert!((store.distance(&query, i) - exact).abs() < 0.02, "vector {i}");
+            let norm: f32 = store.decode(i).iter().map(|x| x * x).sum();
+            assert!((norm - 1.0).abs() < 1e-4);
+        }
+    }
+}
@@ -1,11 +1,16 @@
 //! Storage backend — file-system backed object storage.
 //!
 //!   - `put_object` / `get_object` — store and retrieve blobs
+//!   - `map_object` — read-only memory map of a blob (no copy)
 //!   - Optional SHA-256 sidecar checksums on write/read
 
+use std::collections::HashSet;
 use std::fs;
+use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
+use memmap2::Mmap;
 use sha2::{Digest, Sha256};
 
 use crate::error::{AkiDbError, Result};
@@ -32,6 +37,8 @@ pub struct LocalFsBackend {
     root: PathBuf,
     checksum_on_write: bool,
     checksum_on_read: bool,
+    /// Keys whose sidecar checksum `map_object` has already verified.
+    verified_keys: Mutex<HashSet<String>>,
 }
 
 impl LocalFsBackend {
@@ -41,6 +48,7 @@ impl LocalFsBackend {
             root,
             checksum_on_write: opts.checksum_on_write,
             checksum_on_read: opts.checksum_on_read,
+            verified_keys: Mutex::new(HashSet::new()),
         }
     }
 
@@ -58,12 +66,18 @@ impl LocalFsBackend {
     // ── Public API ──────────────────────────────────────────────────────────
 
     /// Persist an opaque blob under `key`. Overwrites if the key already exists.
+    ///
+    /// The blob is written to a temporary file and renamed into place, so a
+    /// concurrent `map_object` of the old blob never sees it truncated.
     pub fn put_object(&self, key: &str, data: &[u8]) -> Result<()> {
         let abs_path = self.key_to_path(key)?;
         if let Some(parent) = abs_path.parent() {
             fs::create_dir_all(parent)?;
         }
-        fs::write(&abs_path, data)?;
+        let tmp_path = PathBuf::from(format!("{}.tmp", abs_path.display()));
+        fs::write(&tmp_path, data)?;
+        fs::rename(&tmp_path, &abs_path)?;
+        self.forget_verified(key);
 
         if self.checksum_on_write {
             let digest = sha256_hex(data);
@@ -76,6 +90,7 @@ impl LocalFsBackend {
     /// Also removes the SHA-256 sidecar file if present.
     pub fn delete_object(&self, key: &str) -> Result<()> {
         let abs_path = self.key_to_path(key)?;
+        self.forget_verified(key);
         if abs_path.exists() {
             fs::remove_file(&abs_path)?;
         }
@@ -90,9 +105,7 @@ impl LocalFsBackend {
     /// Retrieve the blob stored under `key`.
     pub fn get_object(&self, key: &str) -> Result<Vec<u8>> {
         let abs_path = self.key_to_path(key)?;
-        let data = fs::read(&abs_path).map_err(|_| {
-            AkiDbError::Storage(format!("Object not found: \"{key}\""))
-        })?;
+        let data = fs::read(&abs_path).map_err(|e| read_error(key, e))?;
 
         if self.checksum_on_read {
             self.validate_checksum(&abs_path, &data, key)?;
@@ -100,6 +113,28 @@ impl LocalFsBackend {
         Ok(data)
     }
 
+    /// Memory-map the blob stored under `key` read-only.
+    ///
+    /// With `checksum_on_read`, the first map of a key reads the whole blob to
+    /// verify its SHA-256 sidecar; later maps of the same key skip the check
+    /// until `put_object` or `delete_object` touches it.
+    pub fn map_object(&self, key: &str) -> Result<Mmap> {
+        let abs_path = self.key_to_path(key)?;
+        let file = fs::File::open(&abs_path).map_err(|e| read_error(key, e))?;
+        // SAFETY: stored objects are immutable once written — `put_object`
+        // replaces files by rename and `delete_object` unlinks them, neither of
+        // which modifies the mapped inode.
+        let map = unsafe { Mmap::map(&file) }.map_err(|e| {
+            AkiDbError::Storage(format!("Failed to map object \"{key}\": {e}"))
+        })?;
+
+        if self.checksum_on_read && !self.lock_verified_keys().contains(key) {
+            self.validate_checksum(&abs_path, &map, key)?;
+            self.lock_verified_keys().insert(key.to_string());
+        }
+        Ok(map)
+    }
+
     // ── Internal helpers ────────────────────────────────────────────────────
 
     /// Convert a logical key to an absolute path, guarding against path traversal.
@@ -117,6 +152,14 @@ impl LocalFsBackend {
         Ok(resolved)
     }
 
+    fn lock_verified_keys(&self) -> std::sync::MutexGuard<'_, HashSet<String>> {
+        self.verified_keys.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn forget_verified(&self, key: &str) {
+        self.lock_verified_keys().remove(key);
+    }
+
     fn validate_checksum(&self, abs_path: &Path, data: &[u8], _key: &str) -> Result<()> {
         let sidecar = format!("{}.sha256", abs_path.display());
         let expected = match fs::read_to_string(&sidecar) {
@@ -134,6 +177,15 @@ impl LocalFsBackend {
 
 // ── Free functions ──────────────────────────────────────────────────────────
 
+/// `Object not found` for a missing file; any other IO error as is.
+fn read_error(key: &str, err: std::io::Error) -> AkiDbError {
+    if err.kind() == ErrorKind::NotFound {
+        AkiDbError::Storage(format!("Object not found: \"{key}\""))
+    } else {
+        AkiDbError::Io(err)
+    }
+}
+
 fn sha256_hex(data: &[u8]) -> String {
     let mut hasher = Sha256::new();
     hasher.update(data);
@@ -191,6 +243,39 @@ mod tests {
 
         let result = backend.get_object("chk.bin");
         assert!(matches!(result, Err(AkiDbError::ChecksumMismatch { .. })));
+        let result = backend.map_object("chk.bin");
+        assert!(matches!(result, Err(AkiDbError::ChecksumMismatch { .. })));
+
+        // A verified key is checked again once it is rewritten.
+        backend.put_object("chk.bin", b"rewritten").unwrap();
+        assert_eq!(&backend.map_object("chk.bin").unwrap()[..], b"rewritten");
+        backend.put_object("chk.bin", b"rewritten again").unwrap();
+        fs::write(&path, b"tampered").unwrap();
+        assert!(matches!(backend.map_object("chk.bin"), Err(AkiDbError::ChecksumMismatch { .. })));
+    }
+
+    #[test]
+    fn read_errors_other_than_missing_are_not_reported_as_missing() {
+        let (_dir, backend) = test_backend();
+        let err = backend.map_object("nope.bin").unwrap_err().to_string();
+        assert!(err.contains("Object not found"), "{err}");
+
+        // Reading a directory fails with an IO error, not "not found".
+        fs::create_dir(backend.root.join("dir.bin")).unwrap();
+        let err = backend.get_object("dir.bin").unwrap_err();
+        assert!(matches!(err, AkiDbError::Io(_)), "{err}");
+    }
+
+    #[test]
+    fn map_object_survives_overwrite_and_delete() {
+        let (_dir, backend) = test_backend();
+        backend.put_object("seg.bin", b"first version").unwrap();
+        let mapped = backend.map_object("seg.bin").unwrap();
+
+        backend.put_object("seg.bin", b"second").unwrap();
+        backend.delete_object("seg.bin").unwrap();
+        assert_eq!(&mapped[..], b"first version");
+        assert!(backend.map_object("seg.bin").is_err());
     }
 
     #[test]
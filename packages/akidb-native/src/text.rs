@@ -0,0 +1,297 @@
+//! Text analysis shared by segment keyword indexes and keyword queries.
+//!
+//! The default analyzer follows SQLite's `unicode61` tokenizer defaults, which
+//! the keyword index replaces: runs of alphanumeric characters form tokens,
+//! tokens are lowercased, and diacritics are removed ("Café" → "cafe").
+//! Scripts without whitespace word boundaries (CJK, Thai) produce long
+//! tokens; keyword search falls back to character trigrams for those.
+//!
+//! Collections can choose a different analyzer at creation: the `cjk_bigram`
+//! tokenizer (overlapping character pairs for Han, kana and Hangul, like
+//! Lucene's CJKAnalyzer), a Snowball stemmer, an English stop-word list, and
+//! whether diacritics are folded. Segments and queries of a collection are
+//! always analyzed the same way.
+
+use std::ops::Range;
+
+use rust_stemmers::{Algorithm, Stemmer};
+use unicode_normalization::UnicodeNormalization;
+
+use crate::metadata::Collection;
+
+/// Stemmers accepted by a collection's `stemmer` setting. "porter" is the
+/// Snowball English (Porter2) stemmer.
+pub const STEMMERS: &[&str] = &[
+    "porter", "arabic", "danish", "dutch", "english", "finnish", "french", "german", "greek", "hungarian",
+    "italian", "norwegian", "portuguese", "romanian", "russian", "spanish", "swedish", "tamil", "turkish",
+];
+
+/// Lucene's English stop words (`EnglishAnalyzer.ENGLISH_STOP_WORDS_SET`).
+const ENGLISH_STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "if", "in", "into", "is", "it", "no", "not",
+    "of", "on", "or", "such", "that", "the", "their", "then", "there", "these", "they", "this", "to", "was",
+    "will", "with",
+];
+
+/// How text is split into words, from the collection's `tokenizer`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Tokenizer {
+    /// Runs of alphanumeric characters, like SQLite's `unicode61`.
+    #[default]
+    Unicode61,
+    /// `unicode61`, except CJK runs become overlapping two-character tokens.
+    CjkBigram,
+}
+
+/// Stop-word lists, from the collection's `stop_words`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StopWords {
+    English,
+}
+
+/// Text analysis settings of one collection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Analyzer {
+    pub tokenizer: Tokenizer,
+    pub stemmer: Option<Algorithm>,
+    pub stop_words: Option<StopWords>,
+    pub remove_diacritics: bool,
+}
+
+impl Default for Analyzer {
+    fn default() -> Self {
+        Self { tokenizer: Tokenizer::Unicode61, stemmer: None, stop_words: None, remove_diacritics: true }
+    }
+}
+
+impl Analyzer {
+    /// The analyzer a collection's segments and queries use. Settings are
+    /// validated when the collection is created.
+    pub fn from_collection(collection: &Collection) -> Self {
+        Self {
+            tokenizer: match collection.tokenizer.as_str() {
+                "cjk_bigram" => Tokenizer::CjkBigram,
+                _ => Tokenizer::Unicode61,
+            },
+            stemmer: collection.stemmer.as_deref().and_then(stemmer_algorithm),
+            stop_words: match collection.stop_words.as_deref() {
+                Some("english") => Some(StopWords::English),
+                _ => None,
+            },
+            remove_diacritics: collection.remove_diacritics,
+        }
+    }
+
+    /// Split `text` into analyzed tokens, in order. Stop words are dropped, so
+    /// the words around them become adjacent.
+    pub fn tokenize(&self, text: &str) -> Vec<String> {
+        self.tokenize_with_offsets(text).into_iter().map(|(token, _)| token).collect()
+    }
+
+    /// Like `tokenize`, with the character (code point) range each token spans
+    /// in `text`. Used to locate matches for highlighting.
+    pub fn tokenize_with_offsets(&self, text: &str) -> Vec<(String, Range<usize>)> {
+        self.words(text)
+            .into_iter()
+            .filter_map(|(word, range)| self.filter(word).map(|token| (token, range)))
+            .collect()
+    }
+
+    /// Tokens of a prefix query term: the last word is kept as typed (not
+    /// stemmed or stop-word filtered), since it is only the start of a word.
+    pub fn tokenize_prefix(&self, text: &str) -> Vec<String> {
+        let mut words = self.words(text);
+        let Some((last, _)) = words.pop() else {
+            return Vec::new();
+        };
+        let mut tokens: Vec<String> = words.into_iter().filter_map(|(word, _)| self.filter(word)).collect();
+        tokens.push(last);
+        tokens
+    }
+
+    /// Normalized words before stop-word removal and stemming.
+    fn words(&self, text: &str) -> Vec<(String, Range<usize>)> {
+        let mut words = Vec::new();
+        let mut run: Vec<char> = Vec::new();
+        let mut run_is_cjk = false;
+        let mut start = 0;
+        for (i, c) in text.chars().enumerate() {
+            let cjk = self.tokenizer == Tokenizer::CjkBigram && is_cjk(c);
+            if c.is_alphanumeric() && (run.is_empty() || cjk == run_is_cjk) {
+                if run.is_empty() {
+                    start = i;
+                    run_is_cjk = cjk;
+                }
+                run.push(c);
+                continue;
+            }
+            self.flush_run(&mut words, &mut run, run_is_cjk, start);
+            if c.is_alphanumeric() {
+                start = i;
+                run_is_cjk = cjk;
+                run.push(c);
+            }
+        }
+        self.flush_run(&mut words, &mut run, run_is_cjk, start);
+        words
+    }
+
+    fn flush_run(&self, words: &mut Vec<(String, Range<usize>)>, run: &mut Vec<char>, cjk: bool, start: usize) {
+        if run.is_empty() {
+            return;
+        }
+        if cjk && run.len() > 1 {
+            for (i, pair) in run.windows(2).enumerate() {
+                let pair: String = pair.iter().collect();
+                words.push((self.normalize(&pair), start + i..start + i + 2));
+            }
+        } else {
+            let word: String = run.iter().collect();
+            words.push((self.normalize(&word), start..start + run.len()));
+        }
+        run.clear();
+    }
+
+    /// Drop stop words and stem what is left.
+    fn filter(&self, word: String) -> Option<String> {
+        if self.stop_words == Some(StopWords::English) && ENGLISH_STOP_WORDS.contains(&word.as_str()) {
+            return None;
+        }
+        match self.stemmer {
+            Some(algorithm) => Some(Stemmer::create(algorithm).stem(&word).into_owned()),
+            None => Some(word),
+        }
+    }
+
+    /// Lowercase a single word and, with `remove_diacritics`, strip its
+    /// diacritics. Only the generic combining diacritics block is removed;
+    /// everything else is recomposed, so e.g. Hangul syllables come back intact.
+    fn normalize(&self, word: &str) -> String {
+        if !self.remove_diacritics {
+            return word.nfc().flat_map(char::to_lowercase).collect();
+        }
+        word.nfd()
+            .filter(|c| !('\u{0300}'..='\u{036F}').contains(c))
+            .nfc()
+            .flat_map(char::to_lowercase)
+            .collect()
+    }
+}
+
+/// The Snowball algorithm for a `stemmer` setting, or None if unknown.
+fn stemmer_algorithm(name: &str) -> Option<Algorithm> {
+    Some(match name {
+        "porter" | "english" => Algorithm::English,
+        "arabic" => Algorithm::Arabic,
+        "danish" => Algorithm::Danish,
+        "dutch" => Algorithm::Dutch,
+        "finnish" => Algorithm::Finnish,
+        "french" => Algorithm::French,
+        "german" => Algorithm::German,
+        "greek" => Algorithm::Greek,
+        "hungarian" => Algorithm::Hungarian,
+        "italian" => Algorithm::Italian,
+        "norwegian" => Algorithm::Norwegian,
+        "portuguese" => Algorithm::Portuguese,
+        "romanian" => Algorithm::Romanian,
+        "russian" => Algorithm::Russian,
+        "spanish" => Algorithm::Spanish,
+        "swedish" => Algorithm::Swedish,
+        "tamil" => Algorithm::Tamil,
+        "turkish" => Algorithm::Turkish,
+        _ => return None,
+    })
+}
+
+/// Han ideographs, kana and Hangul: scripts written without spaces between words.
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{1100}'..='\u{11FF}'     // Hangul Jamo
+        | '\u{3040}'..='\u{30FF}'   // Hiragana, Katakana
+        | '\u{3130}'..='\u{318F}'   // Hangul Compatibility Jamo
+        | '\u{3400}'..='\u{4DBF}'   // CJK Extension A
+        | '\u{4E00}'..='\u{9FFF}'   // CJK Unified Ideographs
+        | '\u{AC00}'..='\u{D7AF}'   // Hangul Syllables
+        | '\u{F900}'..='\u{FAFF}'   // CJK Compatibility Ideographs
+        | '\u{FF66}'..='\u{FF9F}'   // Halfwidth Katakana
+        | '\u{20000}'..='\u{2FFFF}' // CJK Extensions B-F
+    )
+}
+
+/// Distinct lowercase character trigrams of `text`, like SQLite's `trigram`
+/// tokenizer. Text shorter than three characters has none.
+pub fn trigrams(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let mut out: Vec<String> = chars.windows(3).map(|w| w.iter().collect()).collect();
+    out.sort_unstable();
+    out.dedup();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analyzer(tokenizer: Tokenizer, stemmer: Option<Algorithm>, stop_words: Option<StopWords>) -> Analyzer {
+        Analyzer { tokenizer, stemmer, stop_words, remove_diacritics: true }
+    }
+
+    #[test]
+    fn tokenize_splits_lowercases_and_folds_diacritics() {
+        let analyzer = Analyzer::default();
+        assert_eq!(
+            analyzer.tokenize("The Café's crème-brûlée, 2nd edition!"),
+            vec!["the", "cafe", "s", "creme", "brulee", "2nd", "edition"]
+        );
+        assert!(analyzer.tokenize("  ... ").is_empty());
+        assert_eq!(analyzer.tokenize("人人生而自由"), vec!["人人生而自由"]);
+
+        let keep = Analyzer { remove_diacritics: false, ..Analyzer::default() };
+        assert_eq!(keep.tokenize("Crème Brûlée"), vec!["crème", "brûlée"]);
+    }
+
+    #[test]
+    fn tokenize_with_offsets_reports_character_ranges() {
+        assert_eq!(
+            Analyzer::default().tokenize_with_offsets("Où est-il?"),
+            vec![("ou".to_string(), 0..2), ("est".to_string(), 3..6), ("il".to_string(), 7..9)]
+        );
+    }
+
+    #[test]
+    fn stemming_and_stop_words() {
+        let english = analyzer(Tokenizer::Unicode61, Some(Algorithm::English), Some(StopWords::English));
+        assert_eq!(english.tokenize("Configure the configuration"), vec!["configur", "configur"]);
+        assert_eq!(
+            english.tokenize_with_offsets("state of the art"),
+            vec![("state".to_string(), 0..5), ("art".to_string(), 13..16)]
+        );
+        // A prefix keeps its last word as typed.
+        assert_eq!(english.tokenize_prefix("running config"), vec!["run", "config"]);
+        assert_eq!(english.tokenize_prefix("the"), vec!["the"]);
+    }
+
+    #[test]
+    fn cjk_bigrams() {
+        let cjk = analyzer(Tokenizer::CjkBigram, None, None);
+        assert_eq!(
+            cjk.tokenize_with_offsets("東京タワー v2"),
+            vec![
+                ("東京".to_string(), 0..2),
+                ("京タ".to_string(), 1..3),
+                ("タワ".to_string(), 2..4),
+                ("ワー".to_string(), 3..5),
+                ("v2".to_string(), 6..8),
+            ]
+        );
+        // CJK and Latin runs split at the script boundary; lone characters stay unigrams.
+        assert_eq!(cjk.tokenize("AI人工智能和ML"), vec!["ai", "人工", "工智", "智能", "能和", "ml"]);
+        assert_eq!(cjk.tokenize("猫 cat"), vec!["猫", "cat"]);
+    }
+
+    #[test]
+    fn trigrams_are_lowercase_and_distinct() {
+        assert_eq!(trigrams("AbAbA"), vec!["aba", "bab"]);
+        assert!(trigrams("ab").is_empty());
+    }
+}
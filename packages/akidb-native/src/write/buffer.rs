@@ -1,16 +1,35 @@
 //! WriteBuffer — accumulates records in memory until a flush threshold is reached.
 //!
-//! Uncommitted records are searchable via brute-force scan in query/mod.rs.
-//! There is no in-memory HNSW index for the write buffer by design: HNSW build
-//! cost is amortized over segments, not individual writes.
+//! For HNSW collections the buffer maintains an incrementally built graph over
+//! its records (node id = buffer position), so uncommitted records are searched
+//! by ANN instead of a brute-force scan, and flush serializes the graph as the
+//! segment's IndexBlock instead of rebuilding it. Other index types fall back
+//! to the brute-force scan in query/mod.rs.
+//!
+//! Likewise a BM25 keyword index over the buffered chunk texts is kept up to
+//! date as records are added, so keyword queries don't re-tokenize the buffer.
+
+use std::sync::Arc;
+
+use crate::hnsw::HnswGraph;
+use crate::segment::keyword::KeywordIndex;
+use crate::text::Analyzer;
 
-use super::NativeRecord;
+use super::{HnswParams, NativeRecord};
 
 const DEFAULT_MAX_RECORDS: usize = 1000;
 const DEFAULT_MAX_BYTES: usize = 10 * 1024 * 1024;
 
 pub struct WriteBuffer {
     records: Vec<NativeRecord>,
+    /// Graph over `records`, shared copy-on-write with in-flight searches.
+    index: Option<Arc<HnswGraph>>,
+    /// Keyword index over `records` (record = buffer position) and the
+    /// analyzer it was built with, shared copy-on-write like `index`.
+    keyword_index: Option<(Arc<KeywordIndex>, Analyzer)>,
+    /// Bumped by every `drain`. Records are only appended in between, so a
+    /// generation and a record count identify a prefix of the buffer.
+    generation: u64,
     estimated_bytes: usize,
     max_records: usize,
     max_bytes: usize,
@@ -20,13 +39,52 @@ impl WriteBuffer {
     pub fn new(max_records: Option<usize>, max_bytes: Option<usize>) -> Self {
         Self {
             records: Vec::new(),
+            index: None,
+            keyword_index: None,
+            generation: 0,
             estimated_bytes: 0,
             max_records: max_records.unwrap_or(DEFAULT_MAX_RECORDS),
             max_bytes: max_bytes.unwrap_or(DEFAULT_MAX_BYTES),
         }
     }
 
+    /// Start maintaining an HNSW graph over the buffer, indexing any records
+    /// already present (e.g. recovered from the WAL). No-op once enabled.
+    pub fn enable_index(&mut self, metric: &str, dimension: usize, hnsw: HnswParams) {
+        if self.index.is_some() || self.records.iter().any(|r| r.vector.len() != dimension) {
+            return;
+        }
+        let mut graph = HnswGraph::new(metric, dimension, hnsw.m, hnsw.ef_construction, hnsw.ef_search);
+        for record in &self.records {
+            graph.insert(&record.vector);
+        }
+        self.index = Some(Arc::new(graph));
+    }
+
+    /// Start maintaining a keyword index over the buffer's chunk texts,
+    /// indexing any records already present. No-op once enabled.
+    pub fn enable_keyword_index(&mut self, analyzer: Analyzer) {
+        if self.keyword_index.is_some() {
+            return;
+        }
+        let index = KeywordIndex::build(self.records.iter().map(chunk_text), &analyzer);
+        self.keyword_index = Some((Arc::new(index), analyzer));
+    }
+
     pub fn add_batch(&mut self, records: &[NativeRecord]) -> bool {
+        if let Some(index) = &mut self.index {
+            // Clones the graph only if a search still holds the previous snapshot.
+            let graph = Arc::make_mut(index);
+            for record in records {
+                graph.insert(&record.vector);
+            }
+        }
+        if let Some((index, analyzer)) = &mut self.keyword_index {
+            let index = Arc::make_mut(index);
+            for record in records {
+                index.push(chunk_text(record), analyzer);
+            }
+        }
         for record in records {
             self.estimated_bytes = self.estimated_bytes.saturating_add(estimate_record_bytes(record));
             self.records.push(record.clone());
@@ -34,11 +92,17 @@ impl WriteBuffer {
         self.should_flush()
     }
 
-    /// Drain all records from the buffer.
-    pub fn drain(&mut self) -> Vec<NativeRecord> {
+    /// Drain all records from the buffer, along with the graph over them if
+    /// one is maintained. The next `enable_index` starts a fresh graph; the
+    /// keyword index is reset and keeps being maintained.
+    pub fn drain(&mut self) -> (Vec<NativeRecord>, Option<Arc<HnswGraph>>) {
         let drained = std::mem::take(&mut self.records);
+        self.generation += 1;
         self.estimated_bytes = 0;
-        drained
+        if let Some((index, _)) = &mut self.keyword_index {
+            *index = Arc::new(KeywordIndex::default());
+        }
+        (drained, self.index.take())
     }
 
     pub fn should_flush(&self) -> bool {
@@ -56,6 +120,24 @@ impl WriteBuffer {
     pub fn peek(&self) -> &[NativeRecord] {
         &self.records
     }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Snapshot of the graph over `peek()`, if one is maintained.
+    pub fn peek_index(&self) -> Option<Arc<HnswGraph>> {
+        self.index.clone()
+    }
+
+    /// Snapshot of the keyword index over `peek()`, if one is maintained.
+    pub fn peek_keyword_index(&self) -> Option<Arc<KeywordIndex>> {
+        self.keyword_index.as_ref().map(|(index, _)| Arc::clone(index))
+    }
+}
+
+fn chunk_text(record: &NativeRecord) -> &str {
+    record.chunk_text.as_deref().unwrap_or("")
 }
 
 fn estimate_record_bytes(record: &NativeRecord) -> usize {
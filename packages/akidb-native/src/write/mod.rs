@@ -6,11 +6,17 @@ use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
 use crate::error::{AkiDbError, Result};
+use crate::binary::BinaryIndex;
+use crate::collection::DEFAULT_IVF_NUM_PROBES;
 use crate::hnsw::HnswGraph;
-use crate::metadata::MetadataStore;
+use crate::ivf_pq::IvfPqIndex;
+use crate::metadata::{Collection, MetadataStore};
 use crate::segment::builder::SegmentBuilder;
 use crate::segment::checksum::checksum_hex;
+use crate::segment::keyword::KeywordIndex;
+use crate::segment::VectorEncoding;
 use crate::storage::LocalFsBackend;
+use crate::text::Analyzer;
 use crate::wal::reader::WalReader;
 use crate::wal::writer::WalWriter;
 
@@ -26,6 +32,95 @@ pub struct HnswParams {
     pub ef_search: usize,
 }
 
+/// IVF-PQ construction and search parameters, sourced from the collection.
+#[derive(Clone, Copy)]
+pub struct IvfPqParams {
+    /// Coarse clusters per segment; 0 derives √n from the segment size.
+    pub num_clusters: usize,
+    pub num_probes: usize,
+    pub num_subquantizers: usize,
+}
+
+/// Per-segment ANN index type, from the collection's `index_type`.
+#[derive(Clone, Copy)]
+pub enum IndexKind {
+    Hnsw,
+    IvfPq(IvfPqParams),
+    /// 1-bit-per-dimension codes searched by Hamming distance.
+    Binary,
+}
+
+/// Vector index selection, VectorBlock encoding and keyword analysis for a
+/// collection's segments.
+#[derive(Clone, Copy)]
+pub struct IndexParams {
+    pub hnsw: HnswParams,
+    pub kind: IndexKind,
+    /// VectorBlock encoding derived from the collection's `quantization`.
+    pub encoding: VectorEncoding,
+    /// Text analyzer for the KeywordBlock, from the collection's analyzer settings.
+    pub analyzer: Analyzer,
+}
+
+impl IndexParams {
+    pub fn from_collection(collection: &Collection) -> Self {
+        let kind = match collection.index_type.as_str() {
+            "ivf_pq" => IndexKind::IvfPq(IvfPqParams {
+                num_clusters: collection.ivf_num_clusters.unwrap_or(0) as usize,
+                num_probes: collection.ivf_num_probes.unwrap_or(DEFAULT_IVF_NUM_PROBES) as usize,
+                num_subquantizers: collection
+                    .pq_num_subquantizers
+                    .unwrap_or(collection.dimension) as usize,
+            }),
+            "binary" => IndexKind::Binary,
+            _ => IndexKind::Hnsw,
+        };
+        Self {
+            hnsw: HnswParams {
+                m: collection.hnsw_m as usize,
+                ef_construction: collection.hnsw_ef_construction as usize,
+                ef_search: collection.hnsw_ef_search as usize,
+            },
+            kind,
+            encoding: VectorEncoding::from_quantization(&collection.quantization),
+            analyzer: Analyzer::from_collection(collection),
+        }
+    }
+
+    /// Build the segment IndexBlock (serialized HNSW graph, IVF-PQ or binary index).
+    pub fn build_index_data(&self, metric: &str, dimension: usize, vectors: &[Vec<f32>]) -> Vec<u8> {
+        match self.kind {
+            IndexKind::IvfPq(ivf) => {
+                let mut index =
+                    IvfPqIndex::new(metric, dimension, ivf.num_clusters, ivf.num_subquantizers);
+                index.build(vectors);
+                index.serialize()
+            }
+            IndexKind::Binary => {
+                let mut index = BinaryIndex::new(metric, dimension);
+                index.build(vectors);
+                index.serialize()
+            }
+            IndexKind::Hnsw => {
+                let hnsw = self.hnsw;
+                let mut graph =
+                    HnswGraph::new(metric, dimension, hnsw.m, hnsw.ef_construction, hnsw.ef_search);
+                graph.build(vectors);
+                self.serialize_hnsw(&graph)
+            }
+        }
+    }
+
+    /// Serialize an HNSW IndexBlock. SQ8 segments search on the VectorBlock's
+    /// codes, so their IndexBlock holds the graph without f32 vectors.
+    pub fn serialize_hnsw(&self, graph: &HnswGraph) -> Vec<u8> {
+        match self.encoding {
+            VectorEncoding::Sq8 => graph.serialize_graph(),
+            _ => graph.serialize(),
+        }
+    }
+}
+
 // ─── WritePath ───────────────────────────────────────────────────────────────
 
 pub struct WritePathOptions {
@@ -43,6 +138,8 @@ pub struct UpsertResult {
 pub struct NativeRecord {
     pub chunk_id: String,
     pub doc_id: String,
+    /// Position of the chunk in its document, if the caller tracks one.
+    pub ordinal: Option<u32>,
     pub vector: Vec<f32>,
     pub metadata: serde_json::Value,
     pub chunk_text: Option<String>,
@@ -60,6 +157,7 @@ impl NativeRecord {
                     .collect()
             })
             .unwrap_or_default();
+        let ordinal = value.get("ordinal").and_then(|v| v.as_u64()).map(|v| v as u32);
         let metadata = value.get("metadata").cloned().unwrap_or(serde_json::json!({}));
         let chunk_text = value
             .get("chunk_text")
@@ -69,6 +167,7 @@ impl NativeRecord {
         Ok(Self {
             chunk_id,
             doc_id,
+            ordinal,
             vector,
             metadata,
             chunk_text,
@@ -79,6 +178,7 @@ impl NativeRecord {
         serde_json::json!({
             "chunk_id": self.chunk_id,
             "doc_id": self.doc_id,
+            "ordinal": self.ordinal,
             "vector": self.vector,
             "metadata": self.metadata,
             "chunk_text": self.chunk_text,
@@ -167,7 +267,7 @@ impl WritePath {
         records: &[NativeRecord],
         dimension: usize,
         metric: &str,
-        hnsw: HnswParams,
+        index: IndexParams,
     ) -> Result<UpsertResult> {
         if records.is_empty() {
             return Ok(UpsertResult {
@@ -183,11 +283,15 @@ impl WritePath {
             self.last_wal_sequence = wal.append_batch(&wal_records)?;
         }
 
+        if let IndexKind::Hnsw = index.kind {
+            self.buffer.enable_index(metric, dimension, index.hnsw);
+        }
+        self.buffer.enable_keyword_index(index.analyzer);
         let should_flush = self.buffer.add_batch(records);
         let mut segment_ids = Vec::new();
 
         if should_flush {
-            let flushed = self.flush(collection_id, dimension, metric, hnsw)?;
+            let flushed = self.flush(collection_id, dimension, metric, index)?;
             segment_ids.extend(flushed);
         }
 
@@ -203,14 +307,21 @@ impl WritePath {
         collection_id: &str,
         dimension: usize,
         metric: &str,
-        hnsw: HnswParams,
+        index: IndexParams,
     ) -> Result<Vec<String>> {
         if self.buffer.is_empty() {
             return Ok(Vec::new());
         }
 
-        let records = self.buffer.drain();
-        let segment_id = self.build_and_store(collection_id, &records, dimension, metric, hnsw)?;
+        let (records, buffer_index) = self.buffer.drain();
+        let segment_id = self.build_and_store(
+            collection_id,
+            &records,
+            buffer_index.as_deref(),
+            dimension,
+            metric,
+            index,
+        )?;
 
         // WAL: mark flushed and truncate.
         if let Some(wal) = &mut self.wal {
@@ -225,6 +336,21 @@ impl WritePath {
         self.buffer.peek()
     }
 
+    /// Drains of the write buffer so far; see `WriteBuffer::generation`.
+    pub fn buffer_generation(&self) -> u64 {
+        self.buffer.generation()
+    }
+
+    /// Snapshot of the write buffer's HNSW graph (node id = `peek_buffer` position).
+    pub fn peek_buffer_index(&self) -> Option<Arc<HnswGraph>> {
+        self.buffer.peek_index()
+    }
+
+    /// Snapshot of the write buffer's keyword index (record = `peek_buffer` position).
+    pub fn peek_buffer_keyword_index(&self) -> Option<Arc<KeywordIndex>> {
+        self.buffer.peek_keyword_index()
+    }
+
     pub fn close(&mut self) {
         if let Some(wal) = &mut self.wal {
             wal.close();
@@ -235,9 +361,10 @@ impl WritePath {
         &self,
         collection_id: &str,
         records: &[NativeRecord],
+        buffer_index: Option<&HnswGraph>,
         dimension: usize,
         metric: &str,
-        hnsw: HnswParams,
+        index: IndexParams,
     ) -> Result<String> {
         let metadata = self
             .metadata
@@ -245,7 +372,7 @@ impl WritePath {
             .map_err(|_| AkiDbError::InvalidArgument("Metadata lock poisoned".to_string()))?;
         let storage = &*self.storage;
 
-        let mut builder = SegmentBuilder::new();
+        let mut builder = SegmentBuilder::with_encoding(index.encoding).with_analyzer(index.analyzer);
 
         // Validate dimensions and collect vectors for HNSW in one pass (one clone per vector).
         let mut vectors: Vec<Vec<f32>> = Vec::with_capacity(records.len());
@@ -260,16 +387,24 @@ impl WritePath {
             vectors.push(rec.vector.clone());
         }
 
-        // Build HNSW index before consuming vectors into the builder.
-        let mut graph = HnswGraph::new(metric, dimension, hnsw.m, hnsw.ef_construction, hnsw.ef_search);
-        graph.build(&vectors);
-        let index_data = graph.serialize();
+        // Build the vector index before consuming vectors into the builder,
+        // reusing the buffer's graph when it covers exactly these records.
+        let index_data = match buffer_index {
+            Some(graph)
+                if matches!(index.kind, IndexKind::Hnsw) && graph.node_count() == records.len() =>
+            {
+                index.serialize_hnsw(graph)
+            }
+            _ => index.build_index_data(metric, dimension, &vectors),
+        };
 
         // Feed the segment builder by moving vectors from the pre-collected Vec,
         // eliminating the second clone per record that was present in the original loop.
-        for (rec, vector) in records.iter().zip(vectors.into_iter()) {
+        for (rec, vector) in records.iter().zip(vectors) {
             builder.add_record_with_text(
                 rec.chunk_id.clone(),
+                rec.doc_id.clone(),
+                rec.ordinal,
                 vector, // moved — no extra clone
                 rec.metadata.clone(),
                 rec.chunk_text.clone(),
@@ -297,35 +432,6 @@ impl WritePath {
             created_at: now,
         })?;
 
-        // FTS5: index chunk text for keyword/hybrid search — all in one transaction.
-        let fts_owned: Vec<(String, String, String)> = records
-            .iter()
-            .filter_map(|rec| {
-                rec.chunk_text
-                    .as_deref()
-                    .filter(|t| !t.is_empty())
-                    .map(|text| (rec.chunk_id.clone(), collection_id.to_string(), text.to_string()))
-            })
-            .collect();
-        if !fts_owned.is_empty() {
-            let fts_refs: Vec<(&str, &str, &str)> = fts_owned
-                .iter()
-                .map(|(a, b, c)| (a.as_str(), b.as_str(), c.as_str()))
-                .collect();
-            // The segment is already stored and registered as "ready"; we cannot
-            // atomically roll it back here. Surface the failure as a warning so
-            // operators can detect partial FTS coverage rather than silently losing
-            // keyword/hybrid search results for these chunks.
-            if let Err(e) = metadata.fts_insert_batch(&fts_refs) {
-                eprintln!(
-                    "[WARN] akidb: fts_insert_batch failed for segment {} — keyword search will miss {} chunks: {}",
-                    result.segment_id,
-                    fts_owned.len(),
-                    e,
-                );
-            }
-        }
-
         Ok(result.segment_id)
     }
 }
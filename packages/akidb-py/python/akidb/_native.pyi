@@ -2,6 +2,7 @@
 
 from __future__ import annotations
 
+from collections.abc import Callable, Sequence
 from typing import Any, NotRequired, TypedDict
 
 class _CollectionDict(TypedDict):
@@ -16,6 +17,14 @@ class _CollectionDict(TypedDict):
     hnsw_m: int
     hnsw_ef_construction: int
     hnsw_ef_search: int
+    index_type: str
+    ivf_num_clusters: int | None
+    ivf_num_probes: int | None
+    pq_num_subquantizers: int | None
+    tokenizer: str
+    stemmer: str | None
+    stop_words: str | None
+    remove_diacritics: bool
 
 class _ManifestDict(TypedDict):
     manifest_id: str
@@ -32,6 +41,25 @@ class _UpsertResultDict(TypedDict):
     segment_ids: list[str]
     buffered_count: int
 
+class _TermMatchDict(TypedDict):
+    term: str
+    start: int
+    end: int
+
+class _FusionDict(TypedDict, total=False):
+    method: str
+    rrf_k: float
+    vector_weight: float
+    keyword_weight: float
+    vector_normalized: float
+    keyword_normalized: float
+    score: float
+
+class _RerankDict(TypedDict):
+    pre_rerank_rank: int
+    pre_rerank_score: float
+    post_rerank_rank: int
+
 class _ExplainDict(TypedDict, total=False):
     vector_score: float
     bm25_score: float
@@ -40,12 +68,32 @@ class _ExplainDict(TypedDict, total=False):
     bm25_rank: int
     chunk_preview: str
     matched_terms: list[str]
+    term_matches: list[_TermMatchDict]
+    snippet: str
+    snippet_start: int
+    fusion: _FusionDict
+    rerank: _RerankDict
+
+class _RerankCandidateDict(TypedDict):
+    chunk_id: str
+    score: float
+    text: str | None
+    metadata: dict[str, Any]
 
 class _SearchResultDict(TypedDict):
     chunk_id: str
     score: float
     committed: NotRequired[bool | None]
     explain: NotRequired[_ExplainDict | None]
+    metadata: NotRequired[dict[str, Any]]
+    text: NotRequired[str]
+    vector: NotRequired[list[float]]
+    group_key: NotRequired[Any]
+
+class _SearchGroupDict(TypedDict):
+    key: Any
+    score: float
+    results: list[_SearchResultDict]
 
 class _TelemetryDict(TypedDict):
     segments_scanned: int
@@ -61,6 +109,8 @@ class _SearchResponseDict(TypedDict):
     results: list[_SearchResultDict]
     manifest_version_used: int
     telemetry: NotRequired[_TelemetryDict | None]
+    groups: NotRequired[list[_SearchGroupDict]]
+    cursor: NotRequired[str]
 
 class _CompactResultDict(TypedDict):
     records_kept: int
@@ -76,19 +126,47 @@ class _CompactionStatusDict(TypedDict):
 class _RecordDict(TypedDict, total=False):
     chunk_id: str
     doc_id: str
+    ordinal: int | None
     vector: list[float]
     metadata: dict[str, Any]
     chunk_text: str
 
+class _StoredRecordDict(TypedDict):
+    chunk_id: str
+    doc_id: str
+    ordinal: int | None
+    vector: list[float]
+    metadata: dict[str, Any]
+    chunk_text: str | None
+    committed: bool
+
+class _ContextPassageDict(TypedDict):
+    doc_id: str
+    chunk_ids: list[str]
+    text: str
+    score: float
+
+class _AssembledContextDict(TypedDict):
+    passages: list[_ContextPassageDict]
+    total_chars: int
+    manifest_version_used: int
+
 class AkiDB:
     """Embedded vector database engine (Rust-powered).
 
     Args:
         storage_path: Path to the database directory.
         disable_wal: Disable write-ahead log (default: False).
+        search_threads: Worker threads for per-segment vector search
+            (default: available parallelism; 1 searches serially).
     """
 
-    def __init__(self, storage_path: str, disable_wal: bool = False) -> None: ...
+    def __init__(
+        self,
+        storage_path: str,
+        disable_wal: bool = False,
+        search_threads: int | None = None,
+    ) -> None: ...
     def __enter__(self) -> AkiDB: ...
     def __exit__(
         self,
@@ -107,8 +185,33 @@ class AkiDB:
         hnsw_m: int = 16,
         hnsw_ef_construction: int = 200,
         hnsw_ef_search: int = 100,
+        index_type: str = "hnsw",
+        ivf_num_clusters: int | None = None,
+        ivf_num_probes: int | None = None,
+        pq_num_subquantizers: int | None = None,
+        tokenizer: str = "unicode61",
+        stemmer: str | None = None,
+        stop_words: str | None = None,
+        remove_diacritics: bool = True,
     ) -> _CollectionDict:
-        """Create a new vector collection."""
+        """Create a new vector collection.
+
+        ``quantization`` selects the segment vector encoding: ``"fp16"``,
+        ``"sq8"`` (8-bit scalar) or ``"fp32"`` (lossless).
+        ``index_type="ivf_pq"`` builds an IVF-PQ index per segment instead of
+        HNSW; unset IVF-PQ parameters fall back to √n clusters, 8 probes and
+        dimension / 8 subquantizers. ``index_type="binary"`` keeps one bit per
+        dimension and always re-scores Hamming candidates against the stored
+        vectors.
+
+        The remaining options choose how chunk text is analyzed for keyword
+        search; they are fixed for the collection's lifetime.
+        ``tokenizer="cjk_bigram"`` indexes CJK text as overlapping character
+        pairs. ``stemmer`` enables a Snowball stemmer (``"porter"`` for
+        English, or a language such as ``"french"``), ``stop_words="english"``
+        drops common English words, and ``remove_diacritics=False`` keeps
+        accents significant.
+        """
         ...
 
     def get_collection(self, collection_id: str) -> _CollectionDict | None:
@@ -156,6 +259,20 @@ class AkiDB:
         keyword_weight: float = 1.0,
         explain: bool = False,
         ef_search: int | None = None,
+        ivf_num_probes: int | None = None,
+        rescore: bool = False,
+        oversampling: float | None = None,
+        keyword_syntax: str = "simple",
+        fusion: str = "rrf",
+        rrf_k: float | None = None,
+        include_metadata: bool = False,
+        include_text: bool = False,
+        include_vector: bool = False,
+        group_by: str | None = None,
+        max_per_group: int | None = None,
+        search_after: str | None = None,
+        reranker: Callable[[str | None, list[_RerankCandidateDict]], Sequence[float]] | None = None,
+        rerank_depth: int | None = None,
     ) -> _SearchResponseDict:
         """Search a collection.
 
@@ -168,10 +285,149 @@ class AkiDB:
             include_uncommitted: Include unflushed buffer records (default: True).
             mode: Search mode — "vector", "keyword", or "hybrid".
             query_text: Text query for keyword/hybrid search.
-            vector_weight: Weight for vector results in hybrid RRF fusion.
-            keyword_weight: Weight for keyword results in hybrid RRF fusion.
+            vector_weight: Weight for vector results in hybrid fusion.
+            keyword_weight: Weight for keyword results in hybrid fusion.
             explain: Include per-result scoring breakdown.
             ef_search: Per-query ef_search override.
+            ivf_num_probes: Per-query IVF probe count override (IVF-PQ collections).
+            rescore: Re-score approximate IVF-PQ and sq8 candidates with
+                full-precision vectors. sq8 segments re-score against FP16
+                copies of their vectors.
+            oversampling: Candidates fetched per result when re-scoring, >= 1 (default: 4).
+            keyword_syntax: How ``query_text`` is parsed — "simple" (every term
+                required) or "advanced" ("exact phrase", prefix*, -exclude, OR,
+                NEAR(a b, 5), parentheses). Malformed advanced queries raise.
+            fusion: How hybrid search combines the two result lists — "rrf"
+                (reciprocal rank fusion), "min_max", "z_score", or "dbsf"
+                (distribution-based score fusion). The score-based methods
+                scale the weights to sum to 1.
+            rrf_k: RRF constant k, >= 0 (default: 60). Only used by "rrf".
+            include_metadata: Return each hit's stored ``metadata`` dict.
+            include_text: Return each hit's stored chunk ``text``, if any.
+            include_vector: Return each hit's stored ``vector``, decoded from
+                the collection's quantization (lossy for fp16 and sq8).
+            group_by: Group results by ``"doc_id"`` or a top-level metadata
+                field. ``top_k`` then counts groups, each result carries its
+                ``group_key`` and the response adds ``groups`` in rank order.
+                Not supported with ``reranker``.
+            max_per_group: Results kept per group, >= 1 (default: 1).
+            search_after: ``cursor`` of the previous page, to fetch the next
+                ``top_k`` results. Pages search the manifest version and
+                buffered records the first page read, so writes in between
+                do not shift them; a cursor fails once the buffer has been
+                flushed. A page carries a ``cursor`` while results follow
+                it; the last page has none. A cursor only continues the same
+                query and mode (``top_k`` may change); hybrid pages split the
+                candidates fused for the first page, then those deeper
+                fusions add. Not supported with ``group_by`` or ``reranker``.
+            reranker: Optional ``reranker(query_text, candidates)`` returning
+                one score per candidate (higher first), e.g. from a
+                cross-encoder. Results are re-ordered by these scores and
+                ``explain`` records ranks before and after re-ranking. An
+                exception it raises propagates from ``search``.
+            rerank_depth: Candidates passed to ``reranker``, >= top_k
+                (default: top_k).
+        """
+        ...
+
+    def search_batch(
+        self,
+        collection_id: str,
+        query_vectors: list[list[float]] | None = None,
+        query_texts: list[str] | None = None,
+        top_k: int = 10,
+        filters: dict[str, Any] | str | None = None,
+        manifest_version: int | None = None,
+        include_uncommitted: bool = True,
+        mode: str = "vector",
+        vector_weight: float = 1.0,
+        keyword_weight: float = 1.0,
+        explain: bool = False,
+        ef_search: int | None = None,
+        ivf_num_probes: int | None = None,
+        rescore: bool = False,
+        oversampling: float | None = None,
+        keyword_syntax: str = "simple",
+        fusion: str = "rrf",
+        rrf_k: float | None = None,
+        include_metadata: bool = False,
+        include_text: bool = False,
+        include_vector: bool = False,
+        group_by: str | None = None,
+        max_per_group: int | None = None,
+    ) -> list[_SearchResponseDict]:
+        """Run many queries against one collection in a single call.
+
+        The manifest and tombstone snapshot are resolved once, so every
+        response shares the same ``manifest_version_used``. Options other than
+        the queries are shared by the whole batch (see ``search``).
+
+        Args:
+            collection_id: Collection to search.
+            query_vectors: One query embedding per query (vector/hybrid modes).
+            query_texts: One text query per query (keyword/hybrid modes); must
+                match ``query_vectors`` in length when both are given.
+        """
+        ...
+
+    def assemble_context(
+        self,
+        collection_id: str,
+        query_vector: list[float],
+        budget: int,
+        budget_unit: str = "chars",
+        top_k: int = 10,
+        filters: dict[str, Any] | str | None = None,
+        manifest_version: int | None = None,
+        include_uncommitted: bool = True,
+        mode: str = "vector",
+        query_text: str | None = None,
+        vector_weight: float = 1.0,
+        keyword_weight: float = 1.0,
+        keyword_syntax: str = "simple",
+        fusion: str = "rrf",
+        rrf_k: float | None = None,
+    ) -> _AssembledContextDict:
+        """Search, then assemble the hits' chunk text into passages for a
+        prompt.
+
+        Passages fit within ``budget`` characters, or approximate tokens
+        (4 characters each) with ``budget_unit="tokens"``. Duplicate or
+        contained texts are dropped, adjacent chunks of a document (by
+        ``ordinal``) are merged without their overlap, and hits that do not
+        fit are skipped. Passages are in rank order; the search options are
+        as for ``search``.
+        """
+        ...
+
+    def get_records(
+        self,
+        collection_id: str,
+        chunk_ids: list[str],
+        manifest_version: int | None = None,
+    ) -> list[_StoredRecordDict]:
+        """Fetch records by chunk ID, in request order.
+
+        Missing and deleted chunks are omitted. Without ``manifest_version``
+        this reads the live state, including buffered writes (``committed``
+        False) and pending deletes; with one, only that manifest's records.
+        """
+        ...
+
+    def get_neighbors(
+        self,
+        collection_id: str,
+        chunk_id: str,
+        before: int = 1,
+        after: int = 1,
+        manifest_version: int | None = None,
+    ) -> list[_StoredRecordDict]:
+        """Fetch a chunk and up to ``before`` / ``after`` neighbors in its
+        document, ordered by the ``ordinal`` given at upsert.
+
+        Reads the same state as ``get_records``. Returns an empty list if the
+        chunk is missing or deleted, and raises if it has no ordinal; other
+        chunks without one are skipped.
         """
         ...
 
@@ -184,6 +440,23 @@ class AkiDB:
         """Delete chunks by ID. Returns number of tombstones created."""
         ...
 
+    def delete_document(self, collection_id: str, doc_id: str) -> int:
+        """Delete every chunk of a document. Returns the number of chunks deleted."""
+        ...
+
+    def replace_document(
+        self,
+        collection_id: str,
+        doc_id: str,
+        records: list[_RecordDict],
+    ) -> _UpsertResultDict:
+        """Replace a document's chunks with ``records``.
+
+        Every record must carry ``doc_id``. Old chunks missing from
+        ``records`` are deleted; the rest are upserted.
+        """
+        ...
+
     def compact(self, collection_id: str) -> _CompactResultDict:
         """Compact a collection: merge segments, apply tombstones."""
         ...
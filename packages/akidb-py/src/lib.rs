@@ -5,8 +5,9 @@ use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList};
 
 use akidb_native::{
-    AkiDbError, Collection, EngineInner, EngineOptions, Manifest, NativeRecord, SearchMode,
-    SearchOptions,
+    AkiDbError, BatchQuery, Collection, ContextBudget, CreateCollectionOptions, EngineInner,
+    EngineOptions, Fusion, GroupBy, KeywordSyntax, Manifest, NativeRecord, RerankCandidate, Reranker,
+    SearchCursor, SearchMode, SearchOptions, SearchResponse, SearchResult, StoredRecord,
 };
 
 // ─── Error conversion ────────────────────────────────────────────────────────
@@ -36,11 +37,12 @@ struct AkiDB {
 #[pymethods]
 impl AkiDB {
     #[new]
-    #[pyo3(signature = (storage_path, disable_wal=false))]
-    fn new(storage_path: String, disable_wal: bool) -> PyResult<Self> {
+    #[pyo3(signature = (storage_path, disable_wal=false, search_threads=None))]
+    fn new(storage_path: String, disable_wal: bool, search_threads: Option<usize>) -> PyResult<Self> {
         let engine = EngineInner::open(EngineOptions {
             storage_path: storage_path.into(),
             disable_wal,
+            search_threads,
         })
         .map_err(to_py_err)?;
 
@@ -62,6 +64,14 @@ impl AkiDB {
         hnsw_m=16,
         hnsw_ef_construction=200,
         hnsw_ef_search=100,
+        index_type="hnsw",
+        ivf_num_clusters=None,
+        ivf_num_probes=None,
+        pq_num_subquantizers=None,
+        tokenizer="unicode61",
+        stemmer=None,
+        stop_words=None,
+        remove_diacritics=true,
     ))]
     fn create_collection<'py>(
         &self,
@@ -74,19 +84,36 @@ impl AkiDB {
         hnsw_m: i64,
         hnsw_ef_construction: i64,
         hnsw_ef_search: i64,
+        index_type: &str,
+        ivf_num_clusters: Option<i64>,
+        ivf_num_probes: Option<i64>,
+        pq_num_subquantizers: Option<i64>,
+        tokenizer: &str,
+        stemmer: Option<String>,
+        stop_words: Option<String>,
+        remove_diacritics: bool,
     ) -> PyResult<Py<PyDict>> {
         let inner = self.inner.borrow();
         let c = inner
-            .create_collection(
-                collection_id,
+            .create_collection_with_options(&CreateCollectionOptions {
+                collection_id: collection_id.to_string(),
                 dimension,
-                metric,
-                embedding_model_id,
-                quantization,
+                metric: metric.to_string(),
+                embedding_model_id: embedding_model_id.to_string(),
+                schema_version: "1".to_string(),
+                quantization: quantization.to_string(),
                 hnsw_m,
                 hnsw_ef_construction,
                 hnsw_ef_search,
-            )
+                index_type: index_type.to_string(),
+                ivf_num_clusters,
+                ivf_num_probes,
+                pq_num_subquantizers,
+                tokenizer: tokenizer.to_string(),
+                stemmer,
+                stop_words,
+                remove_diacritics,
+            })
             .map_err(to_py_err)?;
         collection_to_dict(py, c)
     }
@@ -132,59 +159,7 @@ impl AkiDB {
     ) -> PyResult<Py<PyDict>> {
         let mut inner = self.inner.borrow_mut();
 
-        let mut native_records: Vec<NativeRecord> = Vec::with_capacity(records.len());
-        for (i, rec_obj) in records.iter().enumerate() {
-            let rec = rec_obj.bind(py).downcast::<PyDict>().map_err(|_| {
-                PyRuntimeError::new_err("Each record must be a dict")
-            })?;
-
-            let chunk_id: String = rec.get_item("chunk_id")
-                .map_err(|_| PyKeyError::new_err("Record missing 'chunk_id'"))?
-                .ok_or_else(|| PyKeyError::new_err("Record missing 'chunk_id'"))?
-                .extract()?;
-            let doc_id: String = rec.get_item("doc_id")
-                .map_err(|_| PyKeyError::new_err("Record missing 'doc_id'"))?
-                .ok_or_else(|| PyKeyError::new_err("Record missing 'doc_id'"))?
-                .extract()?;
-            let vector: Vec<f64> = rec.get_item("vector")
-                .map_err(|_| PyKeyError::new_err("Record missing 'vector'"))?
-                .ok_or_else(|| PyKeyError::new_err("Record missing 'vector'"))?
-                .extract()?;
-
-            let metadata = match rec.get_item("metadata")? {
-                Some(m) => {
-                    if m.is_instance_of::<PyDict>() {
-                        let json_mod = py.import("json")?;
-                        let py_str: String = json_mod.call_method1("dumps", (m,))?.extract()?;
-                        serde_json::from_str(&py_str).map_err(|e| {
-                            PyValueError::new_err(format!("record[{i}] metadata is not valid JSON: {e}"))
-                        })?
-                    } else {
-                        let s: String = m.str()?.extract()?;
-                        serde_json::from_str(&s).map_err(|e| {
-                            PyValueError::new_err(format!("record[{i}] metadata string is not valid JSON: {e}"))
-                        })?
-                    }
-                }
-                None => serde_json::json!({}),
-            };
-
-            let chunk_text = match rec.get_item("chunk_text")? {
-                Some(text_obj) => {
-                    let text: String = text_obj.extract()?;
-                    if text.is_empty() { None } else { Some(text) }
-                }
-                None => None,
-            };
-
-            native_records.push(NativeRecord {
-                chunk_id,
-                doc_id,
-                vector: vector.into_iter().map(|v| v as f32).collect(),
-                metadata,
-                chunk_text,
-            });
-        }
+        let native_records = records_from_py(py, &records)?;
 
         let result = inner.upsert_batch(collection_id, &native_records).map_err(to_py_err)?;
 
@@ -233,6 +208,20 @@ impl AkiDB {
         keyword_weight=1.0,
         explain=false,
         ef_search=None,
+        ivf_num_probes=None,
+        rescore=false,
+        oversampling=None,
+        keyword_syntax="simple",
+        fusion="rrf",
+        rrf_k=None,
+        include_metadata=false,
+        include_text=false,
+        include_vector=false,
+        group_by=None,
+        max_per_group=None,
+        search_after=None,
+        reranker=None,
+        rerank_depth=None,
     ))]
     fn search<'py>(
         &self,
@@ -249,27 +238,24 @@ impl AkiDB {
         keyword_weight: f64,
         explain: bool,
         ef_search: Option<usize>,
+        ivf_num_probes: Option<usize>,
+        rescore: bool,
+        oversampling: Option<f64>,
+        keyword_syntax: &str,
+        fusion: &str,
+        rrf_k: Option<f64>,
+        include_metadata: bool,
+        include_text: bool,
+        include_vector: bool,
+        group_by: Option<&str>,
+        max_per_group: Option<usize>,
+        search_after: Option<String>,
+        reranker: Option<Bound<'py, PyAny>>,
+        rerank_depth: Option<usize>,
     ) -> PyResult<Py<PyDict>> {
         let inner = self.inner.borrow();
 
-        let parsed_filters = match filters {
-            Some(obj) => {
-                let bound = obj.bind(py);
-                let json_str = if bound.is_instance_of::<PyDict>() {
-                    // Accept a Python dict — serialize it to JSON for the engine
-                    let json_mod = py.import("json")?;
-                    json_mod.call_method1("dumps", (bound,))?.extract::<String>()?
-                } else {
-                    // Accept a pre-serialized JSON string
-                    bound.extract::<String>()?
-                };
-                Some(
-                    serde_json::from_str(&json_str)
-                        .map_err(|e| PyRuntimeError::new_err(format!("Invalid filters JSON: {e}")))?,
-                )
-            }
-            None => None,
-        };
+        let parsed_filters = parse_filters(py, filters)?;
 
         let opts = SearchOptions {
             collection_id,
@@ -284,38 +270,249 @@ impl AkiDB {
             keyword_weight,
             explain,
             ef_search,
+            ivf_num_probes,
+            rescore,
+            oversampling,
+            keyword_syntax: KeywordSyntax::from_str(keyword_syntax),
+            fusion: Fusion::from_options(fusion, rrf_k),
+            include_metadata,
+            include_text,
+            include_vector,
+            group_by: group_by.map(GroupBy::from_str),
+            max_per_group,
+            search_after: search_after.as_deref().map(SearchCursor::decode).transpose().map_err(to_py_err)?,
         };
 
-        let response = inner.search(opts).map_err(to_py_err)?;
-
-        let dict = PyDict::new(py);
-        let results = PyList::empty(py);
-        for r in &response.results {
-            let rd = PyDict::new(py);
-            rd.set_item("chunk_id", &r.chunk_id)?;
-            rd.set_item("score", r.score)?;
-            if let Some(committed) = r.committed {
-                rd.set_item("committed", committed)?;
-            }
-            if let Some(ref e) = r.explain {
-                let ed = PyDict::new(py);
-                if let Some(vs) = e.vector_score { ed.set_item("vector_score", vs)?; }
-                if let Some(bs) = e.bm25_score { ed.set_item("bm25_score", bs)?; }
-                if let Some(rs) = e.rrf_score { ed.set_item("rrf_score", rs)?; }
-                if let Some(vr) = e.vector_rank { ed.set_item("vector_rank", vr)?; }
-                if let Some(br) = e.bm25_rank { ed.set_item("bm25_rank", br)?; }
-                if let Some(ref cp) = e.chunk_preview { ed.set_item("chunk_preview", cp)?; }
-                ed.set_item("matched_terms", &e.matched_terms)?;
-                rd.set_item("explain", ed)?;
+        let response = match reranker {
+            Some(callable) => {
+                let reranker = PyReranker { callable, error: RefCell::new(None) };
+                let response = inner.search_reranked(opts, &reranker, rerank_depth);
+                // Re-raise the reranker's own exception rather than a wrapped one.
+                if let Some(err) = reranker.error.into_inner() {
+                    return Err(err);
+                }
+                response.map_err(to_py_err)?
             }
-            results.append(rd)?;
+            None => inner.search(opts).map_err(to_py_err)?,
+        };
+        search_response_to_dict(py, &response)
+    }
+
+    /// Run many queries against one collection under a single manifest and
+    /// tombstone snapshot. Returns one response dict per query, in order.
+    #[pyo3(signature = (
+        collection_id,
+        query_vectors=None,
+        query_texts=None,
+        top_k=10,
+        filters=None,
+        manifest_version=None,
+        include_uncommitted=true,
+        mode="vector",
+        vector_weight=1.0,
+        keyword_weight=1.0,
+        explain=false,
+        ef_search=None,
+        ivf_num_probes=None,
+        rescore=false,
+        oversampling=None,
+        keyword_syntax="simple",
+        fusion="rrf",
+        rrf_k=None,
+        include_metadata=false,
+        include_text=false,
+        include_vector=false,
+        group_by=None,
+        max_per_group=None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn search_batch<'py>(
+        &self,
+        py: Python<'py>,
+        collection_id: String,
+        query_vectors: Option<Vec<Vec<f32>>>,
+        query_texts: Option<Vec<String>>,
+        top_k: usize,
+        filters: Option<PyObject>,
+        manifest_version: Option<i64>,
+        include_uncommitted: bool,
+        mode: &str,
+        vector_weight: f64,
+        keyword_weight: f64,
+        explain: bool,
+        ef_search: Option<usize>,
+        ivf_num_probes: Option<usize>,
+        rescore: bool,
+        oversampling: Option<f64>,
+        keyword_syntax: &str,
+        fusion: &str,
+        rrf_k: Option<f64>,
+        include_metadata: bool,
+        include_text: bool,
+        include_vector: bool,
+        group_by: Option<&str>,
+        max_per_group: Option<usize>,
+    ) -> PyResult<Py<PyList>> {
+        let inner = self.inner.borrow();
+        let queries = BatchQuery::zip(query_vectors, query_texts).map_err(to_py_err)?;
+
+        let opts = SearchOptions {
+            collection_id,
+            query_vector: Vec::new(),
+            top_k,
+            filters: parse_filters(py, filters)?,
+            manifest_version,
+            include_uncommitted,
+            mode: SearchMode::from_str(mode),
+            query_text: None,
+            vector_weight,
+            keyword_weight,
+            explain,
+            ef_search,
+            ivf_num_probes,
+            rescore,
+            oversampling,
+            keyword_syntax: KeywordSyntax::from_str(keyword_syntax),
+            fusion: Fusion::from_options(fusion, rrf_k),
+            include_metadata,
+            include_text,
+            include_vector,
+            group_by: group_by.map(GroupBy::from_str),
+            max_per_group,
+            search_after: None,
+        };
+
+        let responses = inner.search_batch(opts, queries).map_err(to_py_err)?;
+        let list = PyList::empty(py);
+        for response in &responses {
+            list.append(search_response_to_dict(py, response)?)?;
         }
-        dict.set_item("results", results)?;
-        dict.set_item("manifest_version_used", response.manifest_version_used)?;
+        Ok(list.into())
+    }
 
+    /// Search, then assemble the hits' chunk text into passages within
+    /// `budget` characters (or approximate tokens with budget_unit="tokens").
+    #[pyo3(signature = (
+        collection_id,
+        query_vector,
+        budget,
+        budget_unit="chars",
+        top_k=10,
+        filters=None,
+        manifest_version=None,
+        include_uncommitted=true,
+        mode="vector",
+        query_text=None,
+        vector_weight=1.0,
+        keyword_weight=1.0,
+        keyword_syntax="simple",
+        fusion="rrf",
+        rrf_k=None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn assemble_context<'py>(
+        &self,
+        py: Python<'py>,
+        collection_id: String,
+        query_vector: Vec<f32>,
+        budget: usize,
+        budget_unit: &str,
+        top_k: usize,
+        filters: Option<PyObject>,
+        manifest_version: Option<i64>,
+        include_uncommitted: bool,
+        mode: &str,
+        query_text: Option<String>,
+        vector_weight: f64,
+        keyword_weight: f64,
+        keyword_syntax: &str,
+        fusion: &str,
+        rrf_k: Option<f64>,
+    ) -> PyResult<Py<PyDict>> {
+        let inner = self.inner.borrow();
+        let opts = SearchOptions {
+            collection_id,
+            query_vector,
+            top_k,
+            filters: parse_filters(py, filters)?,
+            manifest_version,
+            include_uncommitted,
+            mode: SearchMode::from_str(mode),
+            query_text,
+            vector_weight,
+            keyword_weight,
+            explain: false,
+            ef_search: None,
+            ivf_num_probes: None,
+            rescore: false,
+            oversampling: None,
+            keyword_syntax: KeywordSyntax::from_str(keyword_syntax),
+            fusion: Fusion::from_options(fusion, rrf_k),
+            include_metadata: false,
+            include_text: false,
+            include_vector: false,
+            group_by: None,
+            max_per_group: None,
+            search_after: None,
+        };
+        let context = inner
+            .assemble_context(opts, ContextBudget::from_options(budget_unit, budget))
+            .map_err(to_py_err)?;
+
+        let dict = PyDict::new(py);
+        let passages = PyList::empty(py);
+        for p in context.passages {
+            let pd = PyDict::new(py);
+            pd.set_item("doc_id", p.doc_id)?;
+            pd.set_item("chunk_ids", p.chunk_ids)?;
+            pd.set_item("text", p.text)?;
+            pd.set_item("score", p.score)?;
+            passages.append(pd)?;
+        }
+        dict.set_item("passages", passages)?;
+        dict.set_item("total_chars", context.total_chars)?;
+        dict.set_item("manifest_version_used", context.manifest_version_used)?;
         Ok(dict.into())
     }
 
+    // ─── Point Lookup ───────────────────────────────────────────────────
+
+    /// Fetch records by chunk ID, in request order; missing and deleted
+    /// chunks are omitted.
+    #[pyo3(signature = (collection_id, chunk_ids, manifest_version=None))]
+    fn get_records<'py>(
+        &self,
+        py: Python<'py>,
+        collection_id: &str,
+        chunk_ids: Vec<String>,
+        manifest_version: Option<i64>,
+    ) -> PyResult<Py<PyList>> {
+        let inner = self.inner.borrow();
+        let records = inner
+            .get_records(collection_id, &chunk_ids, manifest_version)
+            .map_err(to_py_err)?;
+        stored_records_to_list(py, records)
+    }
+
+    /// Fetch a chunk and up to `before` / `after` neighbors in its document,
+    /// ordered by ordinal. Empty if the chunk is missing or deleted.
+    #[pyo3(signature = (collection_id, chunk_id, before=1, after=1, manifest_version=None))]
+    fn get_neighbors<'py>(
+        &self,
+        py: Python<'py>,
+        collection_id: &str,
+        chunk_id: &str,
+        before: usize,
+        after: usize,
+        manifest_version: Option<i64>,
+    ) -> PyResult<Py<PyList>> {
+        let inner = self.inner.borrow();
+        let records = inner
+            .get_neighbors(collection_id, chunk_id, before, after, manifest_version)
+            .map_err(to_py_err)?;
+        stored_records_to_list(py, records)
+    }
+
     // ─── Delete ─────────────────────────────────────────────────────────
 
     /// Delete chunks by ID. Returns number of tombstones created.
@@ -332,6 +529,33 @@ impl AkiDB {
             .map_err(to_py_err)
     }
 
+    /// Delete every chunk of a document. Returns the number of chunks deleted.
+    fn delete_document(&self, collection_id: &str, doc_id: &str) -> PyResult<usize> {
+        let inner = self.inner.borrow();
+        inner.delete_document(collection_id, doc_id).map_err(to_py_err)
+    }
+
+    /// Replace a document's chunks with `records` (all with `doc_id`): old
+    /// chunks missing from `records` are deleted, the rest upserted.
+    fn replace_document<'py>(
+        &self,
+        py: Python<'py>,
+        collection_id: &str,
+        doc_id: &str,
+        records: Vec<PyObject>,
+    ) -> PyResult<Py<PyDict>> {
+        let native_records = records_from_py(py, &records)?;
+        let inner = self.inner.borrow();
+        let result = inner
+            .replace_document(collection_id, doc_id, &native_records)
+            .map_err(to_py_err)?;
+
+        let dict = PyDict::new(py);
+        dict.set_item("segment_ids", &result.segment_ids)?;
+        dict.set_item("buffered_count", result.buffered_count)?;
+        Ok(dict.into())
+    }
+
     // ─── Compact ────────────────────────────────────────────────────────
 
     /// Compact a collection: merge segments, apply tombstones.
@@ -429,6 +653,232 @@ impl AkiDB {
 
 // ─── Conversion helpers ──────────────────────────────────────────────────────
 
+/// Accept filters as a Python dict or a pre-serialized JSON string.
+/// Calls a Python `reranker(query_text, candidates) -> list[float]`, keeping
+/// any exception it raises so `search` can re-raise it.
+struct PyReranker<'py> {
+    callable: Bound<'py, PyAny>,
+    error: RefCell<Option<PyErr>>,
+}
+
+impl Reranker for PyReranker<'_> {
+    fn rerank(&self, query_text: Option<&str>, candidates: &[RerankCandidate]) -> Result<Vec<f64>, AkiDbError> {
+        let py = self.callable.py();
+        let call = || -> PyResult<Vec<f64>> {
+            let json_mod = py.import("json")?;
+            let list = PyList::empty(py);
+            for c in candidates {
+                let cd = PyDict::new(py);
+                cd.set_item("chunk_id", &c.chunk_id)?;
+                cd.set_item("score", c.score)?;
+                cd.set_item("text", &c.text)?;
+                cd.set_item("metadata", json_mod.call_method1("loads", (c.metadata.to_string(),))?)?;
+                list.append(cd)?;
+            }
+            self.callable.call1((query_text, list))?.extract()
+        };
+        call().map_err(|e| {
+            let message = format!("reranker failed: {e}");
+            *self.error.borrow_mut() = Some(e);
+            AkiDbError::InvalidArgument(message)
+        })
+    }
+}
+
+/// Convert record dicts (see `upsert_batch`) to native records.
+fn records_from_py(py: Python<'_>, records: &[PyObject]) -> PyResult<Vec<NativeRecord>> {
+    let mut native_records: Vec<NativeRecord> = Vec::with_capacity(records.len());
+    for (i, rec_obj) in records.iter().enumerate() {
+        let rec = rec_obj.bind(py).downcast::<PyDict>().map_err(|_| {
+            PyRuntimeError::new_err("Each record must be a dict")
+        })?;
+
+        let chunk_id: String = rec.get_item("chunk_id")
+            .map_err(|_| PyKeyError::new_err("Record missing 'chunk_id'"))?
+            .ok_or_else(|| PyKeyError::new_err("Record missing 'chunk_id'"))?
+            .extract()?;
+        let doc_id: String = rec.get_item("doc_id")
+            .map_err(|_| PyKeyError::new_err("Record missing 'doc_id'"))?
+            .ok_or_else(|| PyKeyError::new_err("Record missing 'doc_id'"))?
+            .extract()?;
+        let vector: Vec<f64> = rec.get_item("vector")
+            .map_err(|_| PyKeyError::new_err("Record missing 'vector'"))?
+            .ok_or_else(|| PyKeyError::new_err("Record missing 'vector'"))?
+            .extract()?;
+
+        let metadata = match rec.get_item("metadata")? {
+            Some(m) => {
+                if m.is_instance_of::<PyDict>() {
+                    let json_mod = py.import("json")?;
+                    let py_str: String = json_mod.call_method1("dumps", (m,))?.extract()?;
+                    serde_json::from_str(&py_str).map_err(|e| {
+                        PyValueError::new_err(format!("record[{i}] metadata is not valid JSON: {e}"))
+                    })?
+                } else {
+                    let s: String = m.str()?.extract()?;
+                    serde_json::from_str(&s).map_err(|e| {
+                        PyValueError::new_err(format!("record[{i}] metadata string is not valid JSON: {e}"))
+                    })?
+                }
+            }
+            None => serde_json::json!({}),
+        };
+
+        let ordinal: Option<u32> = rec.get_item("ordinal")?
+            .map(|o| o.extract::<Option<u32>>())
+            .transpose()?
+            .flatten();
+
+        let chunk_text = match rec.get_item("chunk_text")? {
+            Some(text_obj) => {
+                let text: String = text_obj.extract()?;
+                if text.is_empty() { None } else { Some(text) }
+            }
+            None => None,
+        };
+
+        native_records.push(NativeRecord {
+            chunk_id,
+            doc_id,
+            ordinal,
+            vector: vector.into_iter().map(|v| v as f32).collect(),
+            metadata,
+            chunk_text,
+        });
+    }
+    Ok(native_records)
+}
+
+fn stored_records_to_list(py: Python<'_>, records: Vec<StoredRecord>) -> PyResult<Py<PyList>> {
+    let json_mod = py.import("json")?;
+    let list = PyList::empty(py);
+    for r in records {
+        let d = PyDict::new(py);
+        d.set_item("chunk_id", r.chunk_id)?;
+        d.set_item("doc_id", r.doc_id)?;
+        d.set_item("ordinal", r.ordinal)?;
+        d.set_item("vector", r.vector)?;
+        d.set_item("metadata", json_mod.call_method1("loads", (r.metadata.to_string(),))?)?;
+        d.set_item("chunk_text", r.chunk_text)?;
+        d.set_item("committed", r.committed)?;
+        list.append(d)?;
+    }
+    Ok(list.into())
+}
+
+fn parse_filters(py: Python<'_>, filters: Option<PyObject>) -> PyResult<Option<serde_json::Value>> {
+    let Some(obj) = filters else {
+        return Ok(None);
+    };
+    let bound = obj.bind(py);
+    let json_str = if bound.is_instance_of::<PyDict>() {
+        // Accept a Python dict — serialize it to JSON for the engine
+        let json_mod = py.import("json")?;
+        json_mod.call_method1("dumps", (bound,))?.extract::<String>()?
+    } else {
+        // Accept a pre-serialized JSON string
+        bound.extract::<String>()?
+    };
+    serde_json::from_str(&json_str)
+        .map(Some)
+        .map_err(|e| PyRuntimeError::new_err(format!("Invalid filters JSON: {e}")))
+}
+
+fn search_response_to_dict(py: Python<'_>, response: &SearchResponse) -> PyResult<Py<PyDict>> {
+    let dict = PyDict::new(py);
+    let results = PyList::empty(py);
+    for r in &response.results {
+        results.append(search_result_to_dict(py, r)?)?;
+    }
+    dict.set_item("results", results)?;
+    dict.set_item("manifest_version_used", response.manifest_version_used)?;
+    if let Some(ref groups) = response.groups {
+        let json_mod = py.import("json")?;
+        let group_list = PyList::empty(py);
+        for g in groups {
+            let gd = PyDict::new(py);
+            gd.set_item("key", json_mod.call_method1("loads", (g.key.to_string(),))?)?;
+            gd.set_item("score", g.score)?;
+            let group_results = PyList::empty(py);
+            for r in &g.results {
+                group_results.append(search_result_to_dict(py, r)?)?;
+            }
+            gd.set_item("results", group_results)?;
+            group_list.append(gd)?;
+        }
+        dict.set_item("groups", group_list)?;
+    }
+    if let Some(ref cursor) = response.cursor {
+        dict.set_item("cursor", cursor)?;
+    }
+
+    Ok(dict.into())
+}
+
+fn search_result_to_dict<'py>(py: Python<'py>, r: &SearchResult) -> PyResult<Bound<'py, PyDict>> {
+    let rd = PyDict::new(py);
+    rd.set_item("chunk_id", &r.chunk_id)?;
+    rd.set_item("score", r.score)?;
+    if let Some(committed) = r.committed {
+        rd.set_item("committed", committed)?;
+    }
+    if let Some(ref e) = r.explain {
+        let ed = PyDict::new(py);
+        if let Some(vs) = e.vector_score { ed.set_item("vector_score", vs)?; }
+        if let Some(bs) = e.bm25_score { ed.set_item("bm25_score", bs)?; }
+        if let Some(rs) = e.rrf_score { ed.set_item("rrf_score", rs)?; }
+        if let Some(vr) = e.vector_rank { ed.set_item("vector_rank", vr)?; }
+        if let Some(br) = e.bm25_rank { ed.set_item("bm25_rank", br)?; }
+        if let Some(ref cp) = e.chunk_preview { ed.set_item("chunk_preview", cp)?; }
+        ed.set_item("matched_terms", &e.matched_terms)?;
+        let term_matches = PyList::empty(py);
+        for m in &e.term_matches {
+            let md = PyDict::new(py);
+            md.set_item("term", &m.term)?;
+            md.set_item("start", m.start)?;
+            md.set_item("end", m.end)?;
+            term_matches.append(md)?;
+        }
+        ed.set_item("term_matches", term_matches)?;
+        if let Some(ref sn) = e.snippet { ed.set_item("snippet", sn)?; }
+        if let Some(ss) = e.snippet_start { ed.set_item("snippet_start", ss)?; }
+        if let Some(ref f) = e.fusion {
+            let fd = PyDict::new(py);
+            fd.set_item("method", &f.method)?;
+            if let Some(k) = f.rrf_k { fd.set_item("rrf_k", k)?; }
+            fd.set_item("vector_weight", f.vector_weight)?;
+            fd.set_item("keyword_weight", f.keyword_weight)?;
+            if let Some(vn) = f.vector_normalized { fd.set_item("vector_normalized", vn)?; }
+            if let Some(kn) = f.keyword_normalized { fd.set_item("keyword_normalized", kn)?; }
+            fd.set_item("score", f.score)?;
+            ed.set_item("fusion", fd)?;
+        }
+        if let Some(ref rk) = e.rerank {
+            let kd = PyDict::new(py);
+            kd.set_item("pre_rerank_rank", rk.pre_rerank_rank)?;
+            kd.set_item("pre_rerank_score", rk.pre_rerank_score)?;
+            kd.set_item("post_rerank_rank", rk.post_rerank_rank)?;
+            ed.set_item("rerank", kd)?;
+        }
+        rd.set_item("explain", ed)?;
+    }
+    if let Some(ref metadata) = r.metadata {
+        let json_mod = py.import("json")?;
+        rd.set_item("metadata", json_mod.call_method1("loads", (metadata.to_string(),))?)?;
+    }
+    if let Some(ref text) = r.text {
+        rd.set_item("text", text)?;
+    }
+    if let Some(ref vector) = r.vector {
+        rd.set_item("vector", vector)?;
+    }
+    if let Some(ref key) = r.group_key {
+        let json_mod = py.import("json")?;
+        rd.set_item("group_key", json_mod.call_method1("loads", (key.to_string(),))?)?;
+    }
+    Ok(rd)
+}
+
 fn collection_to_dict(py: Python<'_>, c: Collection) -> PyResult<Py<PyDict>> {
     let d = PyDict::new(py);
     d.set_item("collection_id", &c.collection_id)?;
@@ -442,6 +892,14 @@ fn collection_to_dict(py: Python<'_>, c: Collection) -> PyResult<Py<PyDict>> {
     d.set_item("hnsw_m", c.hnsw_m)?;
     d.set_item("hnsw_ef_construction", c.hnsw_ef_construction)?;
     d.set_item("hnsw_ef_search", c.hnsw_ef_search)?;
+    d.set_item("index_type", &c.index_type)?;
+    d.set_item("ivf_num_clusters", c.ivf_num_clusters)?;
+    d.set_item("ivf_num_probes", c.ivf_num_probes)?;
+    d.set_item("pq_num_subquantizers", c.pq_num_subquantizers)?;
+    d.set_item("tokenizer", &c.tokenizer)?;
+    d.set_item("stemmer", &c.stemmer)?;
+    d.set_item("stop_words", &c.stop_words)?;
+    d.set_item("remove_diacritics", c.remove_diacritics)?;
     Ok(d.into())
 }
 
@@ -67,17 +67,38 @@ class TestCollections:
         )
         assert coll["quantization"] == "sq8"
 
-    def test_create_collection_rejects_unsupported_index_params(self, db):
-        with pytest.raises(TypeError):
-            db.create_collection(
-                "ivf_coll",
-                8,
-                "cosine",
-                "model",
-                index_type="ivf_pq",
-                ivf_num_clusters=4,
-                pq_num_subquantizers=2,
-            )
+    def test_create_collection_with_text_analyzer(self, db):
+        coll = db.create_collection(
+            "english", 4, "cosine", "model", stemmer="porter", stop_words="english"
+        )
+        assert coll["tokenizer"] == "unicode61"
+        assert coll["stemmer"] == "porter"
+        assert coll["stop_words"] == "english"
+        assert coll["remove_diacritics"] is True
+        db.upsert_batch("english", [{
+            "chunk_id": "c1",
+            "doc_id": "d1",
+            "vector": [1.0, 0.0, 0.0, 0.0],
+            "metadata": {},
+            "chunk_text": "Configuration of the build",
+        }])
+        db.publish("english", "model", "v1")
+        response = db.search("english", [0.0, 0.0, 0.0, 0.0], top_k=5, mode="keyword", query_text="configure")
+        assert [r["chunk_id"] for r in response["results"]] == ["c1"]
+
+    def test_create_collection_with_ivf_pq_index(self, db):
+        coll = db.create_collection(
+            "ivf_coll",
+            8,
+            "cosine",
+            "model",
+            index_type="ivf_pq",
+            ivf_num_clusters=4,
+            pq_num_subquantizers=2,
+        )
+        assert coll["index_type"] == "ivf_pq"
+        assert coll["ivf_num_clusters"] == 4
+        assert coll["pq_num_subquantizers"] == 2
 
 
 class TestUpsertAndSearch:
@@ -159,6 +180,126 @@ class TestUpsertAndSearch:
         assert len(response["results"]) == 1
         assert response["results"][0]["chunk_id"] == "chunk_3"
 
+    def test_search_include_payloads(self, db):
+        db.create_collection("test", 4, "cosine", "model")
+        db.upsert_batch("test", self._make_records(2))
+        db.publish("test", "model", "v1")
+
+        response = db.search(
+            "test",
+            [1.0, 0.0, 0.0, 0.0],
+            top_k=1,
+            include_metadata=True,
+            include_text=True,
+            include_vector=True,
+        )
+        hit = response["results"][0]
+        assert hit["chunk_id"] == "chunk_0"
+        assert hit["metadata"] == {"idx": 0}
+        assert hit["text"] == "This is chunk number 0"
+        assert hit["vector"] == [1.0, 0.0, 0.0, 0.0]
+
+        plain = db.search("test", [1.0, 0.0, 0.0, 0.0], top_k=1)["results"][0]
+        assert "metadata" not in plain and "text" not in plain and "vector" not in plain
+
+    def test_search_group_by(self, db):
+        db.create_collection("test", 4, "cosine", "model")
+        db.upsert_batch("test", [
+            {"chunk_id": "a1", "doc_id": "a", "vector": [1.0, 0.0, 0.0, 0.0], "metadata": {"dept": "sales"}},
+            {"chunk_id": "a2", "doc_id": "a", "vector": [0.9, 0.1, 0.0, 0.0], "metadata": {"dept": "sales"}},
+            {"chunk_id": "b1", "doc_id": "b", "vector": [0.8, 0.2, 0.0, 0.0], "metadata": {"dept": "legal"}},
+        ])
+        db.publish("test", "model", "v1")
+
+        response = db.search("test", [1.0, 0.0, 0.0, 0.0], top_k=2, group_by="doc_id")
+        assert [r["chunk_id"] for r in response["results"]] == ["a1", "b1"]
+        assert [r["group_key"] for r in response["results"]] == ["a", "b"]
+
+        response = db.search("test", [1.0, 0.0, 0.0, 0.0], top_k=1, group_by="dept", max_per_group=2)
+        [group] = response["groups"]
+        assert group["key"] == "sales"
+        assert [r["chunk_id"] for r in group["results"]] == ["a1", "a2"]
+        assert group["score"] == group["results"][0]["score"]
+
+        assert "groups" not in db.search("test", [1.0, 0.0, 0.0, 0.0], top_k=1)
+        with pytest.raises(RuntimeError, match="maxPerGroup"):
+            db.search("test", [1.0, 0.0, 0.0, 0.0], group_by="doc_id", max_per_group=0)
+
+    def test_search_after_cursor(self, db):
+        db.create_collection("test", 4, "cosine", "model")
+        db.upsert_batch("test", self._make_records(5))
+        db.publish("test", "model", "v1")
+        query = [1.0, 0.5, 0.0, 0.0]
+        full = db.search("test", query, top_k=5, include_uncommitted=False)
+
+        page = db.search("test", query, top_k=2, include_uncommitted=False)
+        chunk_ids = [r["chunk_id"] for r in page["results"]]
+        # Later pages stay on the first page's manifest version.
+        db.upsert_batch("test", [{"chunk_id": "late", "doc_id": "late", "vector": query}])
+        db.publish("test", "model", "v2")
+        while "cursor" in page:
+            page = db.search("test", query, top_k=2, include_uncommitted=False, search_after=page["cursor"])
+            assert page["manifest_version_used"] == full["manifest_version_used"]
+            chunk_ids += [r["chunk_id"] for r in page["results"]]
+        assert chunk_ids == [r["chunk_id"] for r in full["results"]]
+
+        with pytest.raises(RuntimeError, match="Invalid search cursor"):
+            db.search("test", query, search_after="not a cursor")
+        first = db.search("test", query, top_k=2, include_uncommitted=False)
+        with pytest.raises(RuntimeError, match="different query"):
+            db.search("test", [0.0, 1.0, 0.0, 0.0], top_k=2, include_uncommitted=False, search_after=first["cursor"])
+
+    def test_get_records(self, db):
+        db.create_collection("test", 4, "cosine", "model")
+        db.upsert_batch("test", self._make_records(2))
+        manifest = db.publish("test", "model", "v1")
+        db.delete_chunks("test", ["chunk_1"])
+        db.upsert_batch("test", [{"chunk_id": "chunk_2", "doc_id": "doc_2", "vector": [0.0, 0.0, 1.0, 0.0]}])
+
+        records = db.get_records("test", ["chunk_2", "chunk_0", "chunk_1", "missing"])
+        assert [(r["chunk_id"], r["committed"]) for r in records] == [("chunk_2", False), ("chunk_0", True)]
+        assert records[1]["metadata"] == {"idx": 0}
+        assert records[1]["chunk_text"] == "This is chunk number 0"
+        assert records[1]["vector"] == [1.0, 0.0, 0.0, 0.0]
+
+        pinned = db.get_records("test", ["chunk_1", "chunk_2"], manifest_version=manifest["version"])
+        assert [r["chunk_id"] for r in pinned] == ["chunk_1"]
+
+    def test_assemble_context(self, db):
+        db.create_collection("test", 4, "cosine", "model")
+        db.upsert_batch("test", [
+            {"chunk_id": "w0", "doc_id": "d1", "ordinal": 0, "vector": [1.0, 0.0, 0.0, 0.0], "chunk_text": "alpha beta gamma delta"},
+            {"chunk_id": "w1", "doc_id": "d1", "ordinal": 1, "vector": [0.9, 0.1, 0.0, 0.0], "chunk_text": "gamma delta epsilon"},
+            {"chunk_id": "o0", "doc_id": "d2", "vector": [0.8, 0.2, 0.0, 0.0], "chunk_text": "another document"},
+        ])
+        db.publish("test", "model", "v1")
+
+        context = db.assemble_context("test", [1.0, 0.0, 0.0, 0.0], budget=100)
+        assert [(p["doc_id"], p["chunk_ids"], p["text"]) for p in context["passages"]] == [
+            ("d1", ["w0", "w1"], "alpha beta gamma delta epsilon"),
+            ("d2", ["o0"], "another document"),
+        ]
+        assert context["total_chars"] == 46
+
+        small = db.assemble_context("test", [1.0, 0.0, 0.0, 0.0], budget=8, budget_unit="tokens")
+        assert [p["chunk_ids"] for p in small["passages"]] == [["w0", "w1"]]
+
+    def test_get_neighbors(self, db):
+        db.create_collection("test", 4, "cosine", "model")
+        db.upsert_batch("test", [
+            {"chunk_id": f"p{i}", "doc_id": "d1", "ordinal": i, "vector": [1.0, 0.0, 0.0, 0.0], "chunk_text": f"part {i}"}
+            for i in range(4)
+        ] + [{"chunk_id": "x", "doc_id": "d1", "vector": [0.0, 1.0, 0.0, 0.0]}])
+        db.publish("test", "model", "v1")
+
+        neighbors = db.get_neighbors("test", "p1", before=1, after=1)
+        assert [(r["chunk_id"], r["ordinal"]) for r in neighbors] == [("p0", 0), ("p1", 1), ("p2", 2)]
+        assert neighbors[2]["chunk_text"] == "part 2"
+        assert [r["chunk_id"] for r in db.get_neighbors("test", "p3", before=2, after=5)] == ["p1", "p2", "p3"]
+        assert db.get_neighbors("test", "missing") == []
+        with pytest.raises(RuntimeError, match="has no ordinal"):
+            db.get_neighbors("test", "x")
+
 
 class TestFlushAndPublish:
     def test_flush_writes(self, db):
@@ -192,6 +333,25 @@ class TestDeleteAndCompact:
         assert count == 1
         assert db.get_tombstone_count("test") >= 1
 
+    def test_delete_and_replace_document(self, db):
+        db.create_collection("test", 4, "cosine", "model")
+        db.upsert_batch("test", [
+            {"chunk_id": "c1", "doc_id": "d1", "vector": [1.0, 0.0, 0.0, 0.0]},
+            {"chunk_id": "c2", "doc_id": "d1", "vector": [0.0, 1.0, 0.0, 0.0]},
+            {"chunk_id": "c3", "doc_id": "d2", "vector": [0.0, 0.0, 1.0, 0.0]},
+        ])
+        db.publish("test", "model", "v1")
+
+        db.replace_document("test", "d1", [
+            {"chunk_id": "c2", "doc_id": "d1", "vector": [0.0, 0.0, 0.0, 1.0]},
+        ])
+        records = db.get_records("test", ["c1", "c2", "c3"])
+        assert [(r["chunk_id"], r["doc_id"]) for r in records] == [("c2", "d1"), ("c3", "d2")]
+        assert records[0]["vector"] == [0.0, 0.0, 0.0, 1.0]
+
+        assert db.delete_document("test", "d1") == 1
+        assert [r["chunk_id"] for r in db.get_records("test", ["c1", "c2", "c3"])] == ["c3"]
+
     def test_compact(self, db):
         db.create_collection("test", 4, "cosine", "model")
         # Upsert two batches to create multiple segments
@@ -367,7 +527,7 @@ class TestKeywordAndHybridSearch:
             query_text="document",
         )
         assert "results" in response
-        # Keyword search may return 0 results if FTS5 index is empty, but must not error
+        # Keyword search may return 0 results if no text is indexed, but must not error
 
     def test_hybrid_search_returns_results(self, db):
         """mode='hybrid' fuses vector + BM25 via RRF."""
@@ -381,6 +541,38 @@ class TestKeywordAndHybridSearch:
         )
         assert "results" in response
 
+    def test_search_with_reranker(self, db):
+        """A reranker callable re-scores the top rerank_depth candidates."""
+        self._setup(db)
+        seen = []
+
+        def by_topic(query_text, candidates):
+            seen.extend(candidates)
+            return [float(c["text"].split()[-1]) for c in candidates]
+
+        response = db.search(
+            "test",
+            [0.0, 0.0, 0.0, 0.0],
+            top_k=2,
+            mode="keyword",
+            query_text="document",
+            explain=True,
+            reranker=by_topic,
+            rerank_depth=4,
+        )
+        assert len(seen) == 4
+        assert all(c["metadata"] == {} for c in seen)
+        results = response["results"]
+        assert [r["chunk_id"] for r in results] == ["c3", "c2"]
+        assert [r["score"] for r in results] == [3.0, 2.0]
+        assert [r["explain"]["rerank"]["post_rerank_rank"] for r in results] == [1, 2]
+
+        def failing(query_text, candidates):
+            raise KeyError("model unavailable")
+
+        with pytest.raises(KeyError):
+            db.search("test", [1.0, 0.0, 0.0, 0.0], reranker=failing)
+
 
 class TestErrorHandling:
     def test_upsert_record_missing_chunk_id_raises(self, db):
@@ -48,8 +48,8 @@ export interface CreateCollectionOptions {
   dimension: number;
   metric: DistanceMetric;
   embeddingModelId: string;
-  /** Vector quantization: "fp16" (default) or "sq8". */
-  quantization?: "fp16" | "sq8";
+  /** Vector quantization: "fp16" (default), "sq8", or "fp32" (lossless). */
+  quantization?: "fp16" | "sq8" | "fp32";
   /** HNSW M parameter (max connections per node). Default: 16, range: 4-64. */
   hnswM?: number;
   /** HNSW efConstruction parameter. Default: 200, range: 50-800. */
@@ -27,7 +27,7 @@ export function registerAkiDbTools(server: McpServer, db: AkiDB): void {
       dimension: z.number().int().positive().describe("Vector dimension"),
       metric: z.enum(["cosine", "l2", "dot"]).default("cosine").describe("Distance metric"),
       embedding_model_id: z.string().describe("Embedding model identifier"),
-      quantization: z.enum(["fp16", "sq8"]).default("fp16").optional().describe("Vector quantization"),
+      quantization: z.enum(["fp16", "sq8", "fp32"]).default("fp16").optional().describe("Vector quantization"),
       hnsw_m: z.number().int().min(4).max(64).default(16).optional().describe("HNSW M parameter"),
       hnsw_ef_construction: z.number().int().min(50).max(800).default(200).optional().describe("HNSW efConstruction"),
       hnsw_ef_search: z.number().int().min(10).max(500).default(100).optional().describe("HNSW efSearch"),
@@ -39,7 +39,7 @@ export function registerAkiDbTools(server: McpServer, db: AkiDB): void {
           dimension: args.dimension,
           metric: args.metric,
           embeddingModelId: args.embedding_model_id,
-          quantization: args.quantization as "fp16" | "sq8" | undefined,
+          quantization: args.quantization as "fp16" | "sq8" | "fp32" | undefined,
           hnswM: args.hnsw_m,
           hnswEfConstruction: args.hnsw_ef_construction,
           hnswEfSearch: args.hnsw_ef_search,